@@ -340,6 +340,91 @@ fn nivc_prove_benchmarks(c: &mut Criterion) {
     }
 }
 
+fn sha256_nivc_prove_compressed<M: measurement::Measurement>(
+    prove_params: ProveParams,
+    c: &mut BenchmarkGroup<'_, M>,
+    state: &Rc<RefCell<State>>,
+) {
+    let ProveParams {
+        arity,
+        n: _,
+        reduction_count,
+    } = prove_params;
+
+    let limit = 10000;
+
+    let store = &Store::<Fr>::default();
+    let cproc_sym = user_sym(&format!("sha256_ivc_{arity}"));
+
+    let mut lang = Lang::<Fr, Sha256Coproc<Fr>>::new();
+    lang.add_coprocessor(cproc_sym, Sha256Coprocessor::new(arity));
+    let lang_rc = Arc::new(lang.clone());
+
+    let lurk_step = make_eval_step_from_config(&EvalConfig::new_nivc(&lang));
+
+    // use cached public params
+    let instance = Instance::new(
+        reduction_count,
+        lang_rc.clone(),
+        true,
+        Kind::SuperNovaAuxParams,
+    );
+    let pp = supernova_public_params::<_, _, MultiFrame<'_, _, _>>(&instance).unwrap();
+
+    c.bench_with_input(
+        BenchmarkId::new(prove_params.name(), arity),
+        &prove_params,
+        |b, prove_params| {
+            let ptr = sha256_ivc(
+                store,
+                state.clone(),
+                black_box(prove_params.arity),
+                black_box(prove_params.n),
+                &(0..prove_params.n).collect(),
+            );
+
+            let prover = SuperNovaProver::new(prove_params.reduction_count, lang.clone());
+
+            let frames = &evaluate(Some((&lurk_step, &lang)), ptr, store, limit)
+                .unwrap()
+                .0;
+
+            b.iter_batched(
+                || (frames, lang_rc.clone()),
+                |(frames, lang_rc)| {
+                    let (proof, _, _, _, _) = prover.prove(&pp, frames, store, lang_rc).unwrap();
+                    let compressed_result = proof.compress(&pp).unwrap();
+
+                    let _ = black_box(compressed_result);
+                },
+                BatchSize::LargeInput,
+            )
+        },
+    );
+}
+
+fn nivc_prove_compressed_benchmarks(c: &mut Criterion) {
+    set_bench_config();
+    tracing::debug!("{:?}", &lurk::config::LURK_CONFIG);
+    let reduction_counts = [10, 100];
+    let batch_sizes = [1, 2, 5, 10, 20];
+    let mut group: BenchmarkGroup<'_, _> = c.benchmark_group("prove_compressed");
+    group.sampling_mode(SamplingMode::Flat); // This can take a *while*
+    group.sample_size(10);
+    let state = State::init_lurk_state().rccell();
+
+    for &n in batch_sizes.iter() {
+        for &reduction_count in reduction_counts.iter() {
+            let prove_params = ProveParams {
+                arity: 1,
+                n,
+                reduction_count,
+            };
+            sha256_nivc_prove_compressed(prove_params, &mut group, &state);
+        }
+    }
+}
+
 cfg_if::cfg_if! {
     if #[cfg(feature = "flamegraph")] {
         criterion_group! {
@@ -359,10 +444,8 @@ cfg_if::cfg_if! {
             .sample_size(10)
             .with_profiler(pprof::criterion::PProfProfiler::new(100, pprof::criterion::Output::Flamegraph(None)));
             targets =
-                nivc_prove_benchmarks
-                // TODO: Add when compressed SNARK is implemented for SuperNova
-                // https://github.com/lurk-lab/arecibo/issues/27https://github.com/lurk-lab/arecibo/issues/27
-                // nivc_prove_compressed_benchmarks
+                nivc_prove_benchmarks,
+                nivc_prove_compressed_benchmarks
          }
     } else {
         criterion_group! {
@@ -380,10 +463,8 @@ cfg_if::cfg_if! {
              .measurement_time(Duration::from_secs(120))
              .sample_size(10);
              targets =
-                 nivc_prove_benchmarks
-                 // TODO: Add when compressed SNARK is implemented for SuperNova
-                 // https://github.com/lurk-lab/arecibo/issues/27https://github.com/lurk-lab/arecibo/issues/27
-                 // nivc_prove_compressed_benchmarks
+                 nivc_prove_benchmarks,
+                 nivc_prove_compressed_benchmarks
           }
     }
 }
@@ -126,30 +126,32 @@ pub(crate) fn add_to_lc<F: PrimeField, CS: ConstraintSystem<F>>(
     }
 }
 
-/// If premise is true, enforce `a` fits into 64 bits. It shows a non-deterministic
-/// partial bit decomposition in order to constraint correct behavior.
-pub(crate) fn implies_u64<F: LurkField, CS: ConstraintSystem<F>>(
+/// If premise is true, enforce `a` fits into `n` bits, up to 128. It shows a
+/// non-deterministic partial bit decomposition in order to constraint correct behavior.
+pub(crate) fn implies_uint<F: LurkField, CS: ConstraintSystem<F>>(
     mut cs: CS,
     premise: &Boolean,
     a: &AllocatedNum<F>,
+    n: u32,
 ) -> Result<(), SynthesisError> {
-    let mut a_u64 = a.get_value().and_then(|a| a.to_u64()).unwrap_or(0);
+    assert!(n <= 128);
+    let mut a_uint = a.get_value().and_then(|a| a.to_u128()).unwrap_or(0);
 
-    let mut bits: Vec<Boolean> = Vec::with_capacity(64);
-    for i in 0..64 {
-        let b = a_u64 & 1;
+    let mut bits: Vec<Boolean> = Vec::with_capacity(n as usize);
+    for i in 0..n {
+        let b = a_uint & 1;
         let b_bool = Boolean::Is(AllocatedBit::alloc(
             &mut cs.namespace(|| format!("b.{i}")),
             Some(b == 1),
         )?);
         bits.push(b_bool);
 
-        a_u64 /= 2;
+        a_uint /= 2;
     }
 
     // premise -> a = sum(bits)
     implies_pack(
-        &mut cs.namespace(|| "u64 bit decomposition check"),
+        &mut cs.namespace(|| "uint bit decomposition check"),
         premise,
         &bits,
         a,
@@ -1277,10 +1279,24 @@ mod tests {
         .unwrap();
 
         let t = Boolean::Constant(true);
-        implies_u64(&mut cs.namespace(|| "enforce u64"), &t, &alloc_num).unwrap();
+        implies_uint(&mut cs.namespace(|| "enforce u64"), &t, &alloc_num, 64).unwrap();
         assert!(!cs.is_satisfied());
     }
 
+    #[test]
+    fn test_implies_uint_widths() {
+        for n in [8, 16, 32, 128] {
+            let max = Fr::from_u128(u128::MAX >> (128 - n));
+            for (x, fits) in [(max, true), (max + Fr::ONE, false)] {
+                let mut cs = TestConstraintSystem::<Fr>::new();
+                let num = AllocatedNum::alloc_infallible(cs.namespace(|| "num"), || x);
+                let t = Boolean::Constant(true);
+                implies_uint(&mut cs.namespace(|| "enforce uint"), &t, &num, n).unwrap();
+                assert_eq!(fits, cs.is_satisfied());
+            }
+        }
+    }
+
     proptest! {
         #[test]
         fn test_implies_u64(f in any::<FWrap<Fr>>()) {
@@ -1289,7 +1305,7 @@ mod tests {
             let num = AllocatedNum::alloc_infallible(cs.namespace(|| "num"), || f.0);
 
             let t = Boolean::Constant(true);
-            implies_u64(&mut cs.namespace(|| "enforce u64"), &t, &num).unwrap();
+            implies_uint(&mut cs.namespace(|| "enforce u64"), &t, &num, 64).unwrap();
 
             let f_u64_roundtrip: Fr = f.0.to_u64_unchecked().into();
             let was_u64 = f_u64_roundtrip == f.0;
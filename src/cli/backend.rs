@@ -8,21 +8,24 @@ use crate::field::LanguageField;
 pub(crate) enum Backend {
     #[default]
     Nova,
+    #[value(name = "supernova")]
+    SuperNova,
 }
 
 impl std::fmt::Display for Backend {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Self::Nova => write!(f, "Nova"),
+            Self::SuperNova => write!(f, "SuperNova"),
         }
     }
 }
 
 impl Backend {
     fn compatible_fields(&self) -> Vec<LanguageField> {
-        use LanguageField::{Pallas, Vesta};
+        use LanguageField::{Pallas, Vesta, BN256};
         match self {
-            Self::Nova => vec![Pallas, Vesta],
+            Self::Nova | Self::SuperNova => vec![Pallas, Vesta, BN256],
         }
     }
 
@@ -1,7 +1,11 @@
-use anyhow::Result;
+use anyhow::{bail, Result};
 use camino::Utf8PathBuf;
+use nova::provider::bn256_grumpkin::{bn256, grumpkin};
+use pasta_curves::{pallas, vesta};
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 
+use crate::field::{LanguageField, LurkField};
+
 // This module implements a 2-step serde protocol for data that is parametrized
 // on an arithmetic field in order to be properly deserialized.
 //
@@ -36,6 +40,28 @@ pub(crate) fn load<T: DeserializeOwned + HasFieldModulus>(path: &Utf8PathBuf) ->
     de(&std::fs::read(path)?)
 }
 
+/// Reads the field modulus recorded in a file written with `dump`, without
+/// deserializing its contents, and returns the corresponding `LanguageField`
+pub(crate) fn load_field(path: &Utf8PathBuf) -> Result<LanguageField> {
+    de_field(&std::fs::read(path)?)
+}
+
+fn de_field(bytes: &[u8]) -> Result<LanguageField> {
+    let fdw: FieldDataWrap = bincode::deserialize(bytes)?;
+    let modulus = fdw.field_modulus.as_str();
+    if modulus == pallas::Scalar::MODULUS {
+        Ok(LanguageField::Pallas)
+    } else if modulus == vesta::Scalar::MODULUS {
+        Ok(LanguageField::Vesta)
+    } else if modulus == bn256::Scalar::MODULUS {
+        Ok(LanguageField::BN256)
+    } else if modulus == grumpkin::Scalar::MODULUS {
+        Ok(LanguageField::Grumpkin)
+    } else {
+        bail!("Unknown field modulus {modulus}")
+    }
+}
+
 #[derive(Debug, PartialEq, Eq)]
 struct FieldData<T>(T);
 
@@ -75,12 +101,13 @@ impl<T: Serialize + HasFieldModulus> Serialize for FieldData<T> {
 
 #[cfg(test)]
 mod tests {
-    use crate::field::LurkField;
+    use crate::field::{LanguageField, LurkField};
     use ff::Field;
+    use nova::provider::bn256_grumpkin::bn256;
     use pasta_curves::Fq;
     use serde::{Deserialize, Serialize};
 
-    use super::{de, ser, HasFieldModulus};
+    use super::{de, de_field, ser, HasFieldModulus};
 
     #[derive(PartialEq, Debug, Clone, Serialize, Deserialize)]
     struct Struct<F: LurkField> {
@@ -158,4 +185,16 @@ mod tests {
             assert_eq!(e1, de(&ser(e2).unwrap()).unwrap());
         }
     }
+
+    #[test]
+    fn field_is_detected() {
+        let s = Struct {
+            str: "hi".into(),
+            int: 42,
+            ff: Fq::ONE,
+        };
+        assert_eq!(de_field(&ser(s).unwrap()).unwrap(), LanguageField::Pallas);
+        let e = Enum1::CaseFF(bn256::Scalar::ONE);
+        assert_eq!(de_field(&ser(e).unwrap()).unwrap(), LanguageField::BN256);
+    }
 }
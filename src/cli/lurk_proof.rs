@@ -15,12 +15,12 @@ use crate::{
     lem::{pointers::ZPtr, store::Store},
     proof::{
         nova::{self, CurveCycleEquipped, E1, E2},
-        supernova::C2,
+        supernova::{self, C2},
         MultiFrameTrait,
     },
     public_parameters::{
         instance::{Instance, Kind},
-        public_params,
+        public_params, supernova_public_params,
     },
     state::{initial_lurk_state, State},
 };
@@ -155,7 +155,7 @@ pub(crate) enum LurkProof<
     'a,
     F: CurveCycleEquipped,
     C: Coprocessor<F> + Serialize + DeserializeOwned,
-    M: MultiFrameTrait<'a, F, C>,
+    M: MultiFrameTrait<'a, F, C> + SuperStepCircuit<F>,
 > where
     <<E1<F> as Engine>::Scalar as ff::PrimeField>::Repr: Abomonation,
     <<E2<F> as Engine>::Scalar as ff::PrimeField>::Repr: Abomonation,
@@ -168,13 +168,22 @@ pub(crate) enum LurkProof<
         rc: usize,
         lang: Lang<F, C>,
     },
+    SuperNova {
+        proof: supernova::Proof<'a, F, C, M>,
+        public_inputs: Vec<F>,
+        public_outputs: Vec<F>,
+        num_steps: usize,
+        last_circuit_index: usize,
+        rc: usize,
+        lang: Lang<F, C>,
+    },
 }
 
 impl<
         'a,
         F: CurveCycleEquipped,
         C: Coprocessor<F> + 'a + Serialize + DeserializeOwned,
-        M: MultiFrameTrait<'a, F, C>,
+        M: MultiFrameTrait<'a, F, C> + SuperStepCircuit<F>,
     > HasFieldModulus for LurkProof<'a, F, C, M>
 where
     <<E1<F> as Engine>::Scalar as ff::PrimeField>::Repr: Abomonation,
@@ -189,7 +198,7 @@ impl<
         'a,
         F: CurveCycleEquipped,
         C: Coprocessor<F> + Serialize + DeserializeOwned,
-        M: MultiFrameTrait<'a, F, C>,
+        M: MultiFrameTrait<'a, F, C> + SuperStepCircuit<F>,
     > LurkProof<'a, F, C, M>
 where
     <<E1<F> as Engine>::Scalar as ff::PrimeField>::Repr: Abomonation,
@@ -203,6 +212,12 @@ where
                 public_inputs,
                 public_outputs,
                 ..
+            }
+            | Self::SuperNova {
+                proof: _,
+                public_inputs,
+                public_outputs,
+                ..
             } => (public_inputs, public_outputs),
         }
     }
@@ -242,7 +257,7 @@ impl<
         'a,
         F: CurveCycleEquipped + Serialize,
         C: Coprocessor<F> + Serialize + DeserializeOwned,
-        M: MultiFrameTrait<'a, F, C>,
+        M: MultiFrameTrait<'a, F, C> + SuperStepCircuit<F>,
     > LurkProof<'a, F, C, M>
 where
     <<E1<F> as Engine>::Scalar as ff::PrimeField>::Repr: Abomonation,
@@ -297,6 +312,27 @@ where
                 let pp = public_params(&instance)?;
                 Ok(proof.verify(&pp, *num_steps, public_inputs, public_outputs)?)
             }
+            Self::SuperNova {
+                proof,
+                public_inputs,
+                public_outputs,
+                num_steps,
+                last_circuit_index,
+                rc,
+                lang,
+            } => {
+                tracing::info!("Loading public parameters");
+                let instance =
+                    Instance::new(*rc, Arc::new(lang.clone()), true, Kind::SuperNovaAuxParams);
+                let pp = supernova_public_params(&instance)?;
+                Ok(proof.verify(
+                    &pp,
+                    *last_circuit_index,
+                    *num_steps,
+                    public_inputs,
+                    public_outputs,
+                )?)
+            }
         }
     }
 }
@@ -307,7 +343,7 @@ pub(crate) struct PackedLurkProof<
     'a,
     F: CurveCycleEquipped,
     C: Coprocessor<F> + Serialize + DeserializeOwned,
-    M: MultiFrameTrait<'a, F, C>,
+    M: MultiFrameTrait<'a, F, C> + SuperStepCircuit<F>,
 > where
     <<E1<F> as Engine>::Scalar as ff::PrimeField>::Repr: Abomonation,
     <<E2<F> as Engine>::Scalar as ff::PrimeField>::Repr: Abomonation,
@@ -321,7 +357,7 @@ impl<
         'a,
         F: CurveCycleEquipped,
         C: Coprocessor<F> + 'a + Serialize + DeserializeOwned,
-        M: MultiFrameTrait<'a, F, C>,
+        M: MultiFrameTrait<'a, F, C> + SuperStepCircuit<F>,
     > HasFieldModulus for PackedLurkProof<'a, F, C, M>
 where
     <<E1<F> as Engine>::Scalar as ff::PrimeField>::Repr: Abomonation,
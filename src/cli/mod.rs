@@ -11,7 +11,8 @@ mod zstore;
 use anyhow::{bail, Context, Result};
 use camino::Utf8PathBuf;
 use clap::{Args, Parser, Subcommand};
-use pasta_curves::pallas;
+use nova::provider::bn256_grumpkin::bn256;
+use pasta_curves::{pallas, vesta};
 
 use std::{
     collections::HashMap,
@@ -23,7 +24,7 @@ use std::{
 use crate::{
     eval::lang::Coproc,
     field::{LanguageField, LurkField},
-    lem::{multiframe::MultiFrame, store::Store},
+    lem::{eval::eval_step, multiframe::MultiFrame, store::Store, Func},
     public_parameters::disk_cache::public_params_dir,
     public_parameters::instance::Metadata,
 };
@@ -36,7 +37,11 @@ use crate::cli::{
     zstore::ZStore,
 };
 
-use self::{field_data::load, lurk_proof::PackedLurkProof};
+use self::{
+    field_data::{load, load_field},
+    lurk_proof::PackedLurkProof,
+    paths::{proof_meta_path, proof_path},
+};
 
 #[derive(Parser, Debug)]
 #[clap(version)]
@@ -65,6 +70,9 @@ enum Command {
     Pack(PackArgs),
     /// Unpacks a proof into Lurk's internal data storage
     Unpack(UnpackArgs),
+    /// Breaks down the number of constraints of a LEM function (defaults to
+    /// Lurk's step function)
+    Profile(ProfileArgs),
 }
 
 #[derive(Args, Debug)]
@@ -120,6 +128,14 @@ struct LoadArgs {
     /// Flag to load the file in demo mode
     #[arg(long)]
     demo: bool,
+
+    /// Flag to report the branches of the step function never taken while
+    /// loading the file. The branches are those of the step function as
+    /// written, which is the one the REPL evaluates and proves with, not of an
+    /// optimized one. Every evaluation then memoizes its full frames, so
+    /// loading takes longer and uses more memory
+    #[arg(long)]
+    coverage: bool,
 }
 
 #[derive(Parser, Debug)]
@@ -162,6 +178,9 @@ struct LoadCli {
 
     #[arg(long)]
     demo: bool,
+
+    #[arg(long)]
+    coverage: bool,
 }
 
 impl LoadArgs {
@@ -180,6 +199,7 @@ impl LoadArgs {
             commits_dir: self.commits_dir,
             circom_dir: self.circom_dir,
             demo: self.demo,
+            coverage: self.coverage,
         }
     }
 }
@@ -358,9 +378,8 @@ impl ReplCli {
         backend.validate_field(field)?;
         match field {
             LanguageField::Pallas => repl!(rc, limit, pallas::Scalar, backend.clone()),
-            LanguageField::Vesta => todo!(),
-            LanguageField::BN256 => todo!(),
-            LanguageField::Grumpkin => todo!(),
+            LanguageField::BN256 => repl!(rc, limit, bn256::Scalar, backend.clone()),
+            field => bail!("Unsupported field {field}"),
         }
     }
 }
@@ -370,7 +389,12 @@ impl LoadCli {
         macro_rules! load {
             ( $rc: expr, $limit: expr, $field: path, $backend: expr ) => {{
                 let mut repl = new_repl!(self, $rc, $limit, $field, $backend);
-                repl.load_file(&self.lurk_file, self.demo)?;
+                if self.coverage {
+                    repl.enable_coverage();
+                }
+                let loaded = repl.load_file(&self.lurk_file, self.demo);
+                repl.print_coverage();
+                loaded?;
                 if self.prove {
                     repl.prove_last_frames()?;
                 }
@@ -412,9 +436,8 @@ impl LoadCli {
         backend.validate_field(field)?;
         match field {
             LanguageField::Pallas => load!(rc, limit, pallas::Scalar, backend.clone()),
-            LanguageField::Vesta => todo!(),
-            LanguageField::BN256 => todo!(),
-            LanguageField::Grumpkin => todo!(),
+            LanguageField::BN256 => load!(rc, limit, bn256::Scalar, backend.clone()),
+            field => bail!("Unsupported field {field}"),
         }
     }
 }
@@ -627,6 +650,92 @@ struct UnpackArgs {
     config: Option<Utf8PathBuf>,
 }
 
+#[derive(Args, Debug)]
+struct ProfileArgs {
+    /// File with a LEM function in the textual format (defaults to Lurk's step
+    /// function)
+    #[clap(value_parser)]
+    lem_file: Option<Utf8PathBuf>,
+
+    /// Only lists the blocks reached by taking at most this many branches
+    #[clap(long, value_parser)]
+    depth: Option<usize>,
+
+    /// Arithmetic field (defaults to Pallas)
+    #[clap(long, value_enum)]
+    field: Option<LanguageField>,
+
+    /// Flag to output the profile as JSON
+    #[arg(long)]
+    json: bool,
+}
+
+impl ProfileArgs {
+    fn run(&self) -> Result<()> {
+        let parsed;
+        let func = match &self.lem_file {
+            Some(path) => {
+                let src =
+                    fs::read_to_string(path).with_context(|| format!("Couldn't read {path}"))?;
+                parsed = src.parse::<Func>()?;
+                &parsed
+            }
+            None => eval_step(),
+        };
+        let mut profile = match self.field.unwrap_or(LanguageField::Pallas) {
+            LanguageField::Pallas => func.cost_profile(&Store::<pallas::Scalar>::default()),
+            LanguageField::Vesta => func.cost_profile(&Store::<vesta::Scalar>::default()),
+            LanguageField::BN256 => func.cost_profile(&Store::<bn256::Scalar>::default()),
+            field => bail!("Unsupported field {field}"),
+        };
+        if let Some(depth) = self.depth {
+            profile.blocks.retain(|cost| cost.depth <= depth);
+        }
+        if self.json {
+            println!("{}", serde_json::to_string_pretty(&profile)?);
+            return Ok(());
+        }
+        println!(
+            "{} constraints: {} on slots, {} on global constants",
+            profile.total, profile.slots, profile.globals
+        );
+        println!();
+        println!("{: >8} {: >8}  {: <16} path", "total", "own", "function");
+        for cost in &profile.blocks {
+            let path = cost.path.to_string();
+            let path = if path.is_empty() { "-" } else { path.as_str() };
+            println!(
+                "{: >8} {: >8}  {: <16} {path}",
+                cost.total, cost.own, cost.func
+            );
+        }
+        println!();
+        println!("{: >8} {: >8}  kind", "total", "count");
+        for cost in &profile.kinds {
+            println!("{: >8} {: >8}  {}", cost.constraints, cost.count, cost.kind);
+        }
+        Ok(())
+    }
+}
+
+/// Calls `$run` with `$field` bound to the scalar type of the field recorded
+/// in the file at `$path`
+macro_rules! with_field {
+    ( $path: expr, $field: ident, $run: expr ) => {{
+        match load_field($path)? {
+            LanguageField::Pallas => {
+                type $field = pallas::Scalar;
+                $run
+            }
+            LanguageField::BN256 => {
+                type $field = bn256::Scalar;
+                $run
+            }
+            field => bail!("Unsupported field {field}"),
+        }
+    }};
+}
+
 impl Cli {
     fn run(self) -> Result<()> {
         match self.command {
@@ -644,8 +753,13 @@ impl Cli {
                 }
                 cli_config(verify_args.config.as_ref(), Some(&cli_settings));
 
-                LurkProof::<_, _, MultiFrame<'_, _, Coproc<pallas::Scalar>>>::verify_proof(
-                    &verify_args.proof_key,
+                let path = proof_path(&verify_args.proof_key);
+                with_field!(
+                    &path,
+                    F,
+                    LurkProof::<_, _, MultiFrame<'_, _, Coproc<F>>>::verify_proof(
+                        &verify_args.proof_key,
+                    )
                 )
             }
             #[allow(unused_variables)]
@@ -657,10 +771,15 @@ impl Cli {
                 }
                 cli_config(None, Some(&cli_settings));
 
-                LurkProofMeta::<pallas::Scalar>::inspect_proof(
-                    &inspect_args.proof_key,
-                    None,
-                    inspect_args.full,
+                let path = proof_meta_path(&inspect_args.proof_key);
+                with_field!(
+                    &path,
+                    F,
+                    LurkProofMeta::<F>::inspect_proof(
+                        &inspect_args.proof_key,
+                        None,
+                        inspect_args.full,
+                    )
                 )
             }
             Command::Circom(circom_args) => {
@@ -693,11 +812,16 @@ impl Cli {
                     cli_settings.insert("proofs_dir", dir.to_string());
                 }
                 cli_config(pack_args.config.as_ref(), Some(&cli_settings));
-                PackedLurkProof::<_, _, MultiFrame<'_, _, Coproc<pallas::Scalar>>>::pack(
-                    pack_args.proof_key,
-                    &pack_args.output,
-                    pack_args.exclude_meta,
-                    pack_args.include_envs,
+                let path = proof_path(&pack_args.proof_key);
+                with_field!(
+                    &path,
+                    F,
+                    PackedLurkProof::<_, _, MultiFrame<'_, _, Coproc<F>>>::pack(
+                        pack_args.proof_key,
+                        &pack_args.output,
+                        pack_args.exclude_meta,
+                        pack_args.include_envs,
+                    )
                 )
             }
             Command::Unpack(unpack_args) => {
@@ -710,10 +834,15 @@ impl Cli {
                 }
                 cli_config(unpack_args.config.as_ref(), Some(&cli_settings));
                 create_lurk_dirs()?;
-                PackedLurkProof::<_, _, MultiFrame<'_, _, Coproc<pallas::Scalar>>>::unpack(
+                with_field!(
                     &unpack_args.proof_path,
+                    F,
+                    PackedLurkProof::<_, _, MultiFrame<'_, _, Coproc<F>>>::unpack(
+                        &unpack_args.proof_path,
+                    )
                 )
             }
+            Command::Profile(profile_args) => profile_args.run(),
         }
     }
 }
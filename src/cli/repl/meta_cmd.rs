@@ -25,9 +25,23 @@ pub(super) struct MetaCmd<F: LurkField> {
     pub(super) run: fn(repl: &mut Repl<F>, args: &Ptr<F>) -> Result<()>,
 }
 
-type F = pasta_curves::pallas::Scalar; // TODO: generalize this
+/// Implements `MetaCmd<F>` items for the fields that can be used for proving,
+/// which are the ones most meta commands need. The bounds can't be gathered in
+/// a helper trait because `where` clauses on projections aren't implied by a
+/// trait bound, so they're written once here instead
+macro_rules! impl_meta_cmd {
+    ( $($body:tt)* ) => {
+        impl<F: CurveCycleEquipped + Serialize + DeserializeOwned> MetaCmd<F>
+        where
+            <<E1<F> as Engine>::Scalar as ff::PrimeField>::Repr: Abomonation,
+            <<E2<F> as Engine>::Scalar as ff::PrimeField>::Repr: Abomonation,
+        {
+            $($body)*
+        }
+    };
+}
 
-impl MetaCmd<F> {
+impl_meta_cmd! {
     const LOAD: MetaCmd<F> = MetaCmd {
         name: "load",
         summary: "Load lurk expressions from a file.",
@@ -45,7 +59,7 @@ impl MetaCmd<F> {
     };
 }
 
-impl MetaCmd<F> {
+impl_meta_cmd! {
     const DEF: MetaCmd<F> = MetaCmd {
         name: "def",
         summary: "Extends env with a non-recursive binding.",
@@ -53,6 +67,7 @@ impl MetaCmd<F> {
         description: &[
             "Gets macroexpanded to this: (let ((foo (lambda () 123))) (current-env))",
             "The state's env is set to the result.",
+            "A macro of the same name is forgotten.",
         ],
         example: &["!(def foo (lambda () 123))"],
         run: |repl: &mut Repl<F>, args: &Ptr<F>| {
@@ -66,43 +81,93 @@ impl MetaCmd<F> {
             let expanded = repl.store.list(vec![l, bindings, current_env_call]);
             let (expanded_io, ..) = repl.eval_expr(expanded)?;
             repl.env = expanded_io[0];
+            repl.forget_macro(&first);
             println!("{new_name}");
             Ok(())
         },
     };
 }
 
-impl MetaCmd<F> {
+impl_meta_cmd! {
     const DEFREC: MetaCmd<F> = MetaCmd {
         name: "defrec",
-        summary: "Extends the env with a recursive binding.",
-        format: "!(defrec <binding> <body>)",
+        summary: "Extends the env with recursive bindings.",
+        format: "!(defrec <binding> <body>) | !(defrec (<binding> <body>) ...)",
         description: &[
             "Gets macroexpanded to this: (letrec ((foo (lambda () 123))) (current-env))",
+            "Several bindings can be provided at once, in which case they end up in the",
+            "same `letrec` and can refer to each other (mutual recursion).",
             "The state's env is set ot the result.",
+            "Macros of the same names are forgotten.",
         ],
         example: &[
             "!(defrec sum (lambda (l) (if (eq l nil) 0 (+ (car l) (sum (cdr l))))))",
             "(sum '(1 2 3))",
+            "!(defrec (even? (lambda (n) (if (= n 0) t (odd? (- n 1)))))",
+            "         (odd? (lambda (n) (if (= n 0) nil (even? (- n 1))))))",
+            "(odd? 3)",
         ],
         run: |repl, args| {
-            let (first, second) = repl.peek2(args)?;
-            let new_name = first.fmt_to_string(&repl.store, &repl.state.borrow());
+            let (first, _) = repl.store.car_cdr(args)?;
+            let (bindings, names) = if first.tag() == &Tag::Expr(ExprTag::Cons) {
+                let Some((bindings_vec, None)) = repl.store.fetch_list(args) else {
+                    bail!("Bindings must be a proper list")
+                };
+                let mut names = Vec::with_capacity(bindings_vec.len());
+                for binding in &bindings_vec {
+                    let (name, _) = repl.store.car_cdr(binding)?;
+                    names.push(name);
+                }
+                (*args, names)
+            } else {
+                let (first, second) = repl.peek2(args)?;
+                let binding = repl.store.list(vec![first, second]);
+                (repl.store.list(vec![binding]), vec![first])
+            };
             let l = repl.store.intern_lurk_symbol("letrec");
             let current_env = repl.store.intern_lurk_symbol("current-env");
-            let binding = repl.store.list(vec![first, second]);
-            let bindings = repl.store.list(vec![binding]);
             let current_env_call = repl.store.list(vec![current_env]);
             let expanded = repl.store.list(vec![l, bindings, current_env_call]);
             let (expanded_io, ..) = repl.eval_expr(expanded)?;
             repl.env = expanded_io[0];
-            println!("{new_name}");
+            for name in names {
+                repl.forget_macro(&name);
+                println!("{}", name.fmt_to_string(&repl.store, &repl.state.borrow()));
+            }
             Ok(())
         },
     };
 }
 
-impl MetaCmd<F> {
+impl_meta_cmd! {
+    const DEFMACRO: MetaCmd<F> = MetaCmd {
+        name: "defmacro",
+        summary: "Defines a macro.",
+        format: "!(defmacro <name> <args> <body>)",
+        description: &[
+            "The body is closed over the current env as in (lambda <args> <body>).",
+            "Before evaluation, calls to the macro in each form are replaced by the",
+            "result of applying that closure to the call's unevaluated arguments.",
+        ],
+        example: &[
+            "!(defmacro when (c x) `(if ,c ,x nil))",
+            "(when (= 1 1) 42)",
+        ],
+        run: |repl, args| {
+            let (name, rest) = repl.store.car_cdr(args)?;
+            let (params, body) = repl.peek2(&rest)?;
+            let symbol = repl.get_symbol(&name)?;
+            let lambda = repl.store.intern_lurk_symbol("lambda");
+            let expr = repl.store.list(vec![lambda, params, body]);
+            let (output, ..) = repl.eval_expr(expr)?;
+            repl.macros.insert(symbol, output[0]);
+            println!("{}", name.fmt_to_string(&repl.store, &repl.state.borrow()));
+            Ok(())
+        },
+    };
+}
+
+impl_meta_cmd! {
     const ASSERT: MetaCmd<F> = MetaCmd {
         name: "assert",
         summary: "Assert that an expression evaluates to true.",
@@ -124,7 +189,7 @@ impl MetaCmd<F> {
     };
 }
 
-impl MetaCmd<F> {
+impl_meta_cmd! {
     const ASSERT_EQ: MetaCmd<F> = MetaCmd {
         name: "assert-eq",
         summary: "Assert that two expressions evaluate to the same value.",
@@ -155,7 +220,7 @@ impl MetaCmd<F> {
     };
 }
 
-impl MetaCmd<F> {
+impl_meta_cmd! {
     const ASSERT_EMITTED: MetaCmd<F> = MetaCmd {
         name:
             "assert-emitted",
@@ -194,7 +259,7 @@ impl MetaCmd<F> {
     };
 }
 
-impl MetaCmd<F> {
+impl_meta_cmd! {
     const ASSERT_ERROR: MetaCmd<F> = MetaCmd {
         name: "assert-error",
         summary: "Assert that a evaluation of <expr> fails.",
@@ -216,7 +281,7 @@ impl MetaCmd<F> {
     };
 }
 
-impl MetaCmd<F> {
+impl_meta_cmd! {
     const COMMIT: MetaCmd<F> = MetaCmd {
         name:
             "commit",
@@ -237,7 +302,7 @@ impl MetaCmd<F> {
     };
 }
 
-impl MetaCmd<F> {
+impl_meta_cmd! {
     const HIDE: MetaCmd<F> = MetaCmd {
         name: "hide",
         summary: "Return and persist the commitment of <exp> using secret <secret>.",
@@ -268,7 +333,7 @@ impl MetaCmd<F> {
     };
 }
 
-impl MetaCmd<F> {
+impl_meta_cmd! {
     const FETCH: MetaCmd<F> = MetaCmd {
         name: "fetch",
         summary: "Add data from a commitment to the repl store.",
@@ -285,7 +350,7 @@ impl MetaCmd<F> {
     };
 }
 
-impl MetaCmd<F> {
+impl_meta_cmd! {
     const OPEN: MetaCmd<F> = MetaCmd {
         name: "open",
         summary: "Open a commitment.",
@@ -316,7 +381,7 @@ impl<F: LurkField> MetaCmd<F> {
     };
 }
 
-impl MetaCmd<F> {
+impl_meta_cmd! {
     const SET_ENV: MetaCmd<F> = MetaCmd {
         name: "set-env",
         summary: "Set the env to the result of evaluating the first argument.",
@@ -332,7 +397,7 @@ impl MetaCmd<F> {
     };
 }
 
-impl MetaCmd<F> {
+impl_meta_cmd! {
     const PROVE: MetaCmd<F> = MetaCmd {
         name:
             "prove",
@@ -357,11 +422,7 @@ impl MetaCmd<F> {
     };
 }
 
-impl<F: CurveCycleEquipped + Serialize + DeserializeOwned> MetaCmd<F>
-where
-    <<E1<F> as Engine>::Scalar as ff::PrimeField>::Repr: Abomonation,
-    <<E2<F> as Engine>::Scalar as ff::PrimeField>::Repr: Abomonation,
-{
+impl_meta_cmd! {
     const VERIFY: MetaCmd<F> = MetaCmd {
         name: "verify",
         summary: "Verify a proof",
@@ -474,7 +535,7 @@ impl<F: LurkField> MetaCmd<F> {
     };
 }
 
-impl<F: LurkField> MetaCmd<F> {
+impl_meta_cmd! {
     const HELP: MetaCmd<F> = MetaCmd {
         name: "help",
         summary: "Print help message.",
@@ -529,7 +590,7 @@ impl<F: LurkField> MetaCmd<F> {
     }
 }
 
-impl MetaCmd<F> {
+impl_meta_cmd! {
     fn call(repl: &mut Repl<F>, args: &Ptr<F>) -> Result<()> {
         let (hash_ptr, args) = repl.store.car_cdr(args)?;
         let hash_expr = match hash_ptr.tag() {
@@ -565,7 +626,7 @@ impl MetaCmd<F> {
     };
 }
 
-impl MetaCmd<F> {
+impl_meta_cmd! {
     const CHAIN: MetaCmd<F> = MetaCmd {
         name: "chain",
         summary: "Chain a functional commitment by applying the provided arguments to it",
@@ -647,11 +708,12 @@ impl<F: LurkField + DeserializeOwned> MetaCmd<F> {
     };
 }
 
-impl MetaCmd<F> {
-    const CMDS: [MetaCmd<F>; 23] = [
+impl_meta_cmd! {
+    const CMDS: [MetaCmd<F>; 24] = [
         MetaCmd::LOAD,
         MetaCmd::DEF,
         MetaCmd::DEFREC,
+        MetaCmd::DEFMACRO,
         MetaCmd::ASSERT,
         MetaCmd::ASSERT_EQ,
         MetaCmd::ASSERT_EMITTED,
@@ -1,7 +1,9 @@
 mod meta_cmd;
 
+use abomonation::Abomonation;
 use anyhow::{anyhow, bail, Context, Result};
 use camino::{Utf8Path, Utf8PathBuf};
+use nova::traits::Engine;
 use rustyline::{
     error::ReadlineError,
     history::DefaultHistory,
@@ -9,27 +11,43 @@ use rustyline::{
     Config, Editor,
 };
 use rustyline_derive::{Completer, Helper, Highlighter, Hinter};
-use std::{cell::RefCell, collections::HashMap, fs::read_to_string, io::Write, rc::Rc, sync::Arc};
+use serde::{de::DeserializeOwned, Serialize};
+use std::{
+    cell::RefCell,
+    collections::{HashMap, HashSet},
+    fs::read_to_string,
+    io::Write,
+    rc::Rc,
+    sync::Arc,
+};
 use tracing::info;
 
 use crate::{
+    coprocessor::trie::install_map,
     eval::lang::{Coproc, Lang},
     field::LurkField,
     lem::{
-        eval::{evaluate_simple_with_env, evaluate_with_env},
+        eval::{
+            evaluate_simple_with_env, evaluate_with_env, make_eval_step_from_config, EvalConfig,
+        },
         interpreter::Frame,
         multiframe::MultiFrame,
+        path::Coverage,
         pointers::Ptr,
         store::Store,
-        Tag,
+        Func, Tag,
     },
     parser,
-    proof::{nova::NovaProver, Prover},
+    proof::{
+        nova::{CurveCycleEquipped, NovaProver, E1, E2},
+        supernova::SuperNovaProver,
+        Prover,
+    },
     public_parameters::{
         instance::{Instance, Kind},
-        public_params,
+        public_params, supernova_public_params,
     },
-    state::State,
+    state::{lurk_sym, State},
     tag::{ContTag, ExprTag},
     Symbol,
 };
@@ -75,12 +93,15 @@ pub(crate) struct Repl<F: LurkField> {
     state: Rc<RefCell<State>>,
     env: Ptr<F>,
     lang: Arc<Lang<F, Coproc<F>>>,
+    lurk_step: Func,
     rc: usize,
     limit: usize,
     backend: Backend,
     evaluation: Option<Evaluation<F>>,
     pwd_path: Utf8PathBuf,
     meta: HashMap<&'static str, MetaCmd<F>>,
+    macros: HashMap<Symbol, Ptr<F>>,
+    coverage: Option<Coverage>,
 }
 
 pub(crate) fn validate_non_zero(name: &str, x: usize) -> Result<()> {
@@ -141,9 +162,11 @@ impl<F: LurkField> Repl<F> {
     }
 }
 
-type F = pasta_curves::pallas::Scalar; // TODO: generalize this
-
-impl Repl<F> {
+impl<F: CurveCycleEquipped + Serialize + DeserializeOwned> Repl<F>
+where
+    <<E1<F> as Engine>::Scalar as ff::PrimeField>::Repr: Abomonation,
+    <<E2<F> as Engine>::Scalar as ff::PrimeField>::Repr: Abomonation,
+{
     pub(crate) fn new(store: Store<F>, rc: usize, limit: usize, backend: Backend) -> Repl<F> {
         let limit = pad(limit, rc);
         info!(
@@ -154,17 +177,28 @@ impl Repl<F> {
         let pwd_path =
             Utf8PathBuf::from_path_buf(current_dir).expect("path contains invalid Unicode");
         let env = store.intern_nil();
+        let state = State::init_lurk_state().rccell();
+        let mut lang = Lang::new();
+        install_map(&state, &mut lang);
+        let lang = Arc::new(lang);
+        let lurk_step = match backend {
+            Backend::Nova => make_eval_step_from_config(&EvalConfig::new_ivc(&lang)),
+            Backend::SuperNova => make_eval_step_from_config(&EvalConfig::new_nivc(&lang)),
+        };
         Repl {
             store,
-            state: State::init_lurk_state().rccell(),
+            state,
             env,
-            lang: Arc::new(Lang::new()),
+            lang,
+            lurk_step,
             rc,
             limit,
             backend,
             evaluation: None,
             pwd_path,
             meta: MetaCmd::cmds(),
+            macros: HashMap::new(),
+            coverage: None,
         }
     }
 
@@ -211,83 +245,136 @@ impl Repl<F> {
     pub(crate) fn prove_last_frames(&mut self) -> Result<()> {
         match self.evaluation.as_ref() {
             None => bail!("No evaluation to prove"),
-            Some(Evaluation { frames, iterations }) => match self.backend {
-                Backend::Nova => {
-                    info!("Hydrating the store");
-                    self.store.hydrate_z_cache();
-
-                    let n_frames = frames.len();
-
-                    // saving to avoid clones
-                    let input = &frames[0].input;
-                    let output = &frames[n_frames - 1].output;
-                    let mut z_dag = ZDag::<F>::default();
-                    let mut cache = HashMap::default();
-                    let expr = z_dag.populate_with(&input[0], &self.store, &mut cache);
-                    let env = z_dag.populate_with(&input[1], &self.store, &mut cache);
-                    let cont = z_dag.populate_with(&input[2], &self.store, &mut cache);
-                    let expr_out = z_dag.populate_with(&output[0], &self.store, &mut cache);
-                    let env_out = z_dag.populate_with(&output[1], &self.store, &mut cache);
-                    let cont_out = z_dag.populate_with(&output[2], &self.store, &mut cache);
-
-                    let claim = Self::proof_claim(
-                        &self.store,
-                        (input[0], output[0]),
-                        (input[1], output[1]),
-                        (cont.parts(), cont_out.parts()),
-                    );
-
-                    let claim_comm = Commitment::new(None, claim, &self.store);
-                    let claim_hash = &claim_comm.hash.hex_digits();
-                    let proof_key = &Self::proof_key(&self.backend, &self.rc, claim_hash);
-
-                    let lurk_proof_meta = LurkProofMeta {
-                        iterations: *iterations,
-                        expr_io: (expr, expr_out),
-                        env_io: Some((env, env_out)),
-                        cont_io: (cont, cont_out),
-                        z_dag,
+            Some(Evaluation { frames, iterations }) => {
+                info!("Hydrating the store");
+                self.store.hydrate_z_cache();
+
+                let n_frames = frames.len();
+
+                // saving to avoid clones
+                let input = &frames[0].input;
+                let output = &frames[n_frames - 1].output;
+                let mut z_dag = ZDag::<F>::default();
+                let mut cache = HashMap::default();
+                let expr = z_dag.populate_with(&input[0], &self.store, &mut cache);
+                let env = z_dag.populate_with(&input[1], &self.store, &mut cache);
+                let cont = z_dag.populate_with(&input[2], &self.store, &mut cache);
+                let expr_out = z_dag.populate_with(&output[0], &self.store, &mut cache);
+                let env_out = z_dag.populate_with(&output[1], &self.store, &mut cache);
+                let cont_out = z_dag.populate_with(&output[2], &self.store, &mut cache);
+
+                let claim = Self::proof_claim(
+                    &self.store,
+                    (input[0], output[0]),
+                    (input[1], output[1]),
+                    (cont.parts(), cont_out.parts()),
+                );
+
+                let claim_comm = Commitment::new(None, claim, &self.store);
+                let claim_hash = &claim_comm.hash.hex_digits();
+                let proof_key = &Self::proof_key(&self.backend, &self.rc, claim_hash);
+
+                let lurk_proof_meta = LurkProofMeta {
+                    iterations: *iterations,
+                    expr_io: (expr, expr_out),
+                    env_io: Some((env, env_out)),
+                    cont_io: (cont, cont_out),
+                    z_dag,
+                };
+
+                if LurkProof::<_, _, MultiFrame<'_, _, Coproc<F>>>::is_cached(proof_key) {
+                    info!("Proof already cached");
+                } else {
+                    info!("Proof not cached. Loading public parameters");
+                    let lurk_proof = match self.backend {
+                        Backend::Nova => {
+                            let instance = Instance::new(
+                                self.rc,
+                                self.lang.clone(),
+                                true,
+                                Kind::NovaPublicParams,
+                            );
+                            let pp = public_params(&instance)?;
+
+                            let prover = NovaProver::<_, _, MultiFrame<'_, F, Coproc<F>>>::new(
+                                self.rc,
+                                (*self.lang).clone(),
+                            );
+
+                            info!("Proving");
+                            let (proof, public_inputs, public_outputs, num_steps) =
+                                prover.prove(&pp, frames, &self.store, &self.lang)?;
+                            info!("Compressing proof");
+                            let proof = proof.compress(&pp)?;
+                            assert_eq!(self.rc * num_steps, pad(n_frames, self.rc));
+                            assert!(proof.verify(
+                                &pp,
+                                num_steps,
+                                &public_inputs,
+                                &public_outputs
+                            )?);
+
+                            LurkProof::Nova {
+                                proof,
+                                public_inputs,
+                                public_outputs,
+                                num_steps,
+                                rc: self.rc,
+                                lang: (*self.lang).clone(),
+                            }
+                        }
+                        Backend::SuperNova => {
+                            let instance = Instance::new(
+                                self.rc,
+                                self.lang.clone(),
+                                true,
+                                Kind::SuperNovaAuxParams,
+                            );
+                            let pp = supernova_public_params(&instance)?;
+
+                            let prover = SuperNovaProver::<_, _, MultiFrame<'_, F, Coproc<F>>>::new(
+                                self.rc,
+                                (*self.lang).clone(),
+                            );
+
+                            info!("Proving");
+                            let (
+                                proof,
+                                public_inputs,
+                                public_outputs,
+                                num_steps,
+                                last_circuit_index,
+                            ) = prover.prove(&pp, frames, &self.store, self.lang.clone())?;
+                            info!("Compressing proof");
+                            let proof = proof.compress(&pp)?;
+                            assert!(proof.verify(
+                                &pp,
+                                last_circuit_index,
+                                num_steps,
+                                &public_inputs,
+                                &public_outputs
+                            )?);
+
+                            LurkProof::SuperNova {
+                                proof,
+                                public_inputs,
+                                public_outputs,
+                                num_steps,
+                                last_circuit_index,
+                                rc: self.rc,
+                                lang: (*self.lang).clone(),
+                            }
+                        }
                     };
 
-                    if LurkProof::<_, _, MultiFrame<'_, _, Coproc<F>>>::is_cached(proof_key) {
-                        info!("Proof already cached");
-                    } else {
-                        info!("Proof not cached. Loading public parameters");
-                        let instance =
-                            Instance::new(self.rc, self.lang.clone(), true, Kind::NovaPublicParams);
-                        let pp = public_params(&instance)?;
-
-                        let prover = NovaProver::<_, _, MultiFrame<'_, F, Coproc<F>>>::new(
-                            self.rc,
-                            (*self.lang).clone(),
-                        );
-
-                        info!("Proving");
-                        let (proof, public_inputs, public_outputs, num_steps) =
-                            prover.prove(&pp, frames, &self.store, &self.lang)?;
-                        info!("Compressing proof");
-                        let proof = proof.compress(&pp)?;
-                        assert_eq!(self.rc * num_steps, pad(n_frames, self.rc));
-                        assert!(proof.verify(&pp, num_steps, &public_inputs, &public_outputs)?);
-
-                        let lurk_proof = LurkProof::Nova {
-                            proof,
-                            public_inputs,
-                            public_outputs,
-                            num_steps,
-                            rc: self.rc,
-                            lang: (*self.lang).clone(),
-                        };
-
-                        lurk_proof.persist(proof_key)?;
-                    }
-                    lurk_proof_meta.persist(proof_key)?;
-                    claim_comm.persist()?;
-                    println!("Claim hash: 0x{claim_hash}");
-                    println!("Proof key: \"{proof_key}\"");
-                    Ok(())
+                    lurk_proof.persist(proof_key)?;
                 }
-            },
+                lurk_proof_meta.persist(proof_key)?;
+                claim_comm.persist()?;
+                println!("Claim hash: 0x{claim_hash}");
+                println!("Proof key: \"{proof_key}\"");
+                Ok(())
+            }
         }
     }
 
@@ -331,20 +418,91 @@ impl Repl<F> {
         }
     }
 
-    fn eval_expr(&mut self, expr_ptr: Ptr<F>) -> Result<(Vec<Ptr<F>>, usize, Vec<Ptr<F>>)> {
-        let (ptrs, iterations, emitted) = evaluate_simple_with_env::<F, Coproc<F>>(
-            None,
+    fn error_msg(&self, cont: &Ptr<F>, iterations_display: &str) -> String {
+        match self.store.fetch_cont_error(cont) {
+            Some((code, expr)) => {
+                let state = self.state.borrow();
+                format!(
+                    "Evaluation encountered error {} on {} after {iterations_display}",
+                    code.fmt_to_string(&self.store, &state),
+                    expr.fmt_to_string(&self.store, &state)
+                )
+            }
+            None => format!("Evaluation encountered an error after {iterations_display}"),
+        }
+    }
+
+    /// Starts recording which branches of the step function are taken by the
+    /// evaluations. The paths are only kept in the frames, so from now on
+    /// every evaluation memoizes its full frames, even when the REPL wouldn't
+    /// otherwise need them
+    pub(crate) fn enable_coverage(&mut self) {
+        self.coverage = Some(Coverage::new(&self.lurk_step));
+    }
+
+    /// Prints how many branches of the step function were taken and which
+    /// ones never were. The REPL's step function isn't optimized, so these
+    /// are the branches of the function as written
+    pub(crate) fn print_coverage(&self) {
+        if let Some(coverage) = &self.coverage {
+            println!(
+                "Coverage: {} of {} branches of `{}` taken",
+                coverage.num_taken(),
+                coverage.num_branches(),
+                self.lurk_step.name
+            );
+            for branch in coverage.untaken() {
+                println!("  never taken: {branch}");
+            }
+        }
+    }
+
+    fn record_coverage(&mut self, frames: &[Frame<F>]) -> Result<()> {
+        if let Some(coverage) = &mut self.coverage {
+            // frames with a non-zero program counter come from coprocessors
+            for frame in frames.iter().filter(|frame| frame.pc == 0) {
+                coverage.record(&self.lurk_step, &frame.path)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Evaluates `expr_ptr` without memoizing the frames, unless they're needed
+    /// to record the coverage
+    fn eval_expr_simple(&mut self, expr_ptr: Ptr<F>) -> Result<(Vec<Ptr<F>>, usize, Vec<Ptr<F>>)> {
+        if self.coverage.is_none() {
+            return evaluate_simple_with_env::<F, Coproc<F>>(
+                Some((&self.lurk_step, &*self.lang)),
+                expr_ptr,
+                self.env,
+                &self.store,
+                self.limit,
+            );
+        }
+        let (frames, iterations) = evaluate_with_env::<F, Coproc<F>>(
+            Some((&self.lurk_step, &*self.lang)),
             expr_ptr,
             self.env,
             &self.store,
             self.limit,
         )?;
+        self.record_coverage(&frames)?;
+        let output = frames[frames.len() - 1].output.clone();
+        let emitted = frames
+            .iter()
+            .flat_map(|frame| frame.emitted.iter().copied())
+            .collect();
+        Ok((output, iterations, emitted))
+    }
+
+    fn eval_expr(&mut self, expr_ptr: Ptr<F>) -> Result<(Vec<Ptr<F>>, usize, Vec<Ptr<F>>)> {
+        let (ptrs, iterations, emitted) = self.eval_expr_simple(expr_ptr)?;
         match ptrs[2].tag() {
             Tag::Cont(ContTag::Terminal) => Ok((ptrs, iterations, emitted)),
             t => {
                 let iterations_display = Self::pretty_iterations_display(iterations);
                 if t == &Tag::Cont(ContTag::Error) {
-                    bail!("Evaluation encountered an error after {iterations_display}")
+                    bail!("{}", self.error_msg(&ptrs[2], &iterations_display))
                 } else {
                     bail!("Limit reached after {iterations_display}")
                 }
@@ -356,13 +514,7 @@ impl Repl<F> {
         &mut self,
         expr_ptr: Ptr<F>,
     ) -> Result<(Vec<Ptr<F>>, usize, Vec<Ptr<F>>)> {
-        let (ptrs, iterations, emitted) = evaluate_simple_with_env::<F, Coproc<F>>(
-            None,
-            expr_ptr,
-            self.env,
-            &self.store,
-            self.limit,
-        )?;
+        let (ptrs, iterations, emitted) = self.eval_expr_simple(expr_ptr)?;
         if matches!(ptrs[2].tag(), Tag::Cont(ContTag::Terminal | ContTag::Error)) {
             Ok((ptrs, iterations, emitted))
         } else {
@@ -374,8 +526,14 @@ impl Repl<F> {
     }
 
     fn eval_expr_and_memoize(&mut self, expr_ptr: Ptr<F>) -> Result<(Vec<Ptr<F>>, usize)> {
-        let (frames, iterations) =
-            evaluate_with_env::<F, Coproc<F>>(None, expr_ptr, self.env, &self.store, self.limit)?;
+        let (frames, iterations) = evaluate_with_env::<F, Coproc<F>>(
+            Some((&self.lurk_step, &*self.lang)),
+            expr_ptr,
+            self.env,
+            &self.store,
+            self.limit,
+        )?;
+        self.record_coverage(&frames)?;
         let output = frames[frames.len() - 1].output.clone();
         self.evaluation = Some(Evaluation { frames, iterations });
         Ok((output, iterations))
@@ -394,7 +552,184 @@ impl Repl<F> {
         Ok(hash)
     }
 
+    /// Expands the calls to macros defined with `defmacro` in `expr_ptr`.
+    ///
+    /// A call is expanded by applying the macro's closure to its quoted
+    /// arguments and the result is expanded again, until no calls are left.
+    fn expand_macros(&mut self, expr_ptr: &Ptr<F>) -> Result<Ptr<F>> {
+        if self.macros.is_empty() {
+            return Ok(*expr_ptr);
+        }
+        self.expand_form(expr_ptr, &HashSet::new())
+    }
+
+    /// Forgets the macro named by `name`, which is now bound to a value that
+    /// calls to it must refer to
+    fn forget_macro(&mut self, name: &Ptr<F>) {
+        if let Some(symbol) = self.store.fetch_sym(name) {
+            self.macros.remove(&symbol);
+        }
+    }
+
+    /// The symbols in `names` that are bound to macros
+    fn macro_names(&self, names: &[Ptr<F>]) -> Vec<Symbol> {
+        names
+            .iter()
+            .filter_map(|name| self.store.fetch_sym(name))
+            .filter(|symbol| self.macros.contains_key(symbol))
+            .collect()
+    }
+
+    /// Expands the macro calls of an evaluated form. Only lists in operator
+    /// position are considered calls, so quoted expressions, lambda parameters
+    /// and the variables of `let`/`letrec` bindings are kept as they are.
+    /// Variables bound by the form shadow the macros of the same name, which
+    /// are tracked in `shadowed`
+    fn expand_form(&mut self, expr_ptr: &Ptr<F>, shadowed: &HashSet<Symbol>) -> Result<Ptr<F>> {
+        let Some((elts, None)) = self.store.fetch_list(expr_ptr) else {
+            return Ok(*expr_ptr);
+        };
+        let Some(head) = elts.first() else {
+            return Ok(*expr_ptr);
+        };
+        let Some(symbol) = self.store.fetch_sym(head) else {
+            return self.expand_forms(&elts, shadowed);
+        };
+        if symbol == lurk_sym("quote") {
+            Ok(*expr_ptr)
+        } else if symbol == lurk_sym("lambda") && elts.len() > 1 {
+            let params = self
+                .store
+                .fetch_list(&elts[1])
+                .map(|(params, _)| params)
+                .unwrap_or_default();
+            let mut shadowed = shadowed.clone();
+            shadowed.extend(self.macro_names(&params));
+            let body = self.expand_forms(&elts[2..], &shadowed)?;
+            Ok(self.store.list([&elts[..2], &body].concat()))
+        } else if (symbol == lurk_sym("let") || symbol == lurk_sym("letrec")) && elts.len() > 1 {
+            let Some((bindings, None)) = self.store.fetch_list(&elts[1]) else {
+                return Ok(*expr_ptr);
+            };
+            let mut shadowed = shadowed.clone();
+            let recursive = symbol == lurk_sym("letrec");
+            let bindings = self.expand_bindings(&bindings, recursive, &mut shadowed)?;
+            let body = self.expand_forms(&elts[2..], &shadowed)?;
+            let bindings = self.store.list(bindings);
+            Ok(self.store.list([&[elts[0], bindings], &body[..]].concat()))
+        } else if let Some(closure) = self
+            .macros
+            .get(&symbol)
+            .filter(|_| !shadowed.contains(&symbol))
+        {
+            let quote = self.store.intern_lurk_symbol("quote");
+            let mut call = Vec::with_capacity(elts.len());
+            call.push(*closure);
+            for arg in &elts[1..] {
+                call.push(self.store.list(vec![quote, *arg]));
+            }
+            let call = self.store.list(call);
+            let (output, ..) = self.eval_expr(call).with_context(|| {
+                format!(
+                    "expanding {}",
+                    expr_ptr.fmt_to_string(&self.store, &self.state.borrow())
+                )
+            })?;
+            self.expand_form(&output[0], shadowed)
+        } else {
+            self.expand_forms(&elts, shadowed)
+        }
+    }
+
+    fn expand_forms(
+        &mut self,
+        forms: &[Ptr<F>],
+        shadowed: &HashSet<Symbol>,
+    ) -> Result<Vec<Ptr<F>>> {
+        forms
+            .iter()
+            .map(|form| self.expand_form(form, shadowed))
+            .collect()
+    }
+
+    /// Expands the values of `let` (or `letrec`, if `recursive`) bindings,
+    /// adding the variables they bind to `shadowed`
+    fn expand_bindings(
+        &mut self,
+        bindings: &[Ptr<F>],
+        recursive: bool,
+        shadowed: &mut HashSet<Symbol>,
+    ) -> Result<Vec<Ptr<F>>> {
+        if recursive {
+            let names = bindings
+                .iter()
+                .filter_map(|binding| self.store.fetch_list(binding))
+                .filter_map(|(binding, _)| binding.first().copied())
+                .collect::<Vec<_>>();
+            shadowed.extend(self.macro_names(&names));
+        }
+        let mut expanded = Vec::with_capacity(bindings.len());
+        for binding in bindings {
+            match self.store.fetch_list(binding) {
+                Some((binding, None)) if !binding.is_empty() => {
+                    // `let` is sequential, so a value is only in the scope of
+                    // the previous bindings
+                    let value = self.expand_forms(&binding[1..], shadowed)?;
+                    shadowed.extend(self.macro_names(&binding[..1]));
+                    expanded.push(self.store.list([&binding[..1], &value].concat()));
+                }
+                _ => expanded.push(*binding),
+            }
+        }
+        Ok(expanded)
+    }
+
+    /// Expands the macro calls in the arguments of the meta command `cmd`.
+    /// Only the arguments that the command evaluates are expanded, so the
+    /// names and parameters of definitions are kept as they are
+    fn expand_meta_args(&mut self, cmd: &str, args: &Ptr<F>) -> Result<Ptr<F>> {
+        if self.macros.is_empty() {
+            return Ok(*args);
+        }
+        let Some((args_vec, None)) = self.store.fetch_list(args) else {
+            return Ok(*args);
+        };
+        let mut shadowed = HashSet::new();
+        match cmd {
+            "def" if !args_vec.is_empty() => {
+                let value = self.expand_forms(&args_vec[1..], &shadowed)?;
+                Ok(self.store.list([&args_vec[..1], &value].concat()))
+            }
+            "defrec" if !args_vec.is_empty() => {
+                if args_vec[0].tag() == &Tag::Expr(ExprTag::Cons) {
+                    let bindings = self.expand_bindings(&args_vec, true, &mut shadowed)?;
+                    Ok(self.store.list(bindings))
+                } else {
+                    let mut binding = self.expand_bindings(&[*args], true, &mut shadowed)?;
+                    Ok(binding.pop().expect("one binding was expanded"))
+                }
+            }
+            "defmacro" if args_vec.len() > 1 => {
+                let params = self
+                    .store
+                    .fetch_list(&args_vec[1])
+                    .map(|(params, _)| params)
+                    .unwrap_or_default();
+                shadowed.extend(self.macro_names(&params));
+                let body = self.expand_forms(&args_vec[2..], &shadowed)?;
+                Ok(self.store.list([&args_vec[..2], &body].concat()))
+            }
+            "assert" | "assert-eq" | "assert-emitted" | "assert-error" | "commit" | "hide"
+            | "set-env" | "prove" | "call" | "chain" => {
+                let args_vec = self.expand_forms(&args_vec, &shadowed)?;
+                Ok(self.store.list(args_vec))
+            }
+            _ => Ok(*args),
+        }
+    }
+
     pub(crate) fn handle_non_meta(&mut self, expr_ptr: Ptr<F>) -> Result<()> {
+        let expr_ptr = self.expand_macros(&expr_ptr)?;
         let (output, iterations) = self.eval_expr_and_memoize(expr_ptr)?;
         let iterations_display = Self::pretty_iterations_display(iterations);
         match output[2].tag() {
@@ -406,7 +741,7 @@ impl Repl<F> {
                 Ok(())
             }
             Tag::Cont(ContTag::Error) => {
-                bail!("Evaluation encountered an error after {iterations_display}")
+                bail!("{}", self.error_msg(&output[2], &iterations_display))
             }
             _ => bail!("Limit reached after {iterations_display}"),
         }
@@ -418,10 +753,16 @@ impl Repl<F> {
             Some(symbol) => {
                 let cmdstr = symbol.name()?;
                 match self.meta.get(cmdstr) {
-                    Some(cmd) => match (cmd.run)(self, &cdr) {
-                        Ok(()) => (),
-                        Err(e) => bail!("Meta command failed with {}", e),
-                    },
+                    Some(cmd) => {
+                        let run = cmd.run;
+                        match self
+                            .expand_meta_args(cmdstr, &cdr)
+                            .and_then(|cdr| run(self, &cdr))
+                        {
+                            Ok(()) => (),
+                            Err(e) => bail!("Meta command failed with {}", e),
+                        }
+                    }
                     None => bail!("Unsupported meta command: {cmdstr}"),
                 }
             }
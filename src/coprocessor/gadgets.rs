@@ -14,7 +14,7 @@ use crate::{
         pointers::{Ptr, ZPtr},
         store::Store,
     },
-    tag::{ExprTag, Tag},
+    tag::{ContTag, ExprTag, Tag},
 };
 
 /// Constructs an `AllocatedPtr` compound by two others
@@ -104,6 +104,44 @@ pub(crate) fn construct_tuple4<F: LurkField, CS: ConstraintSystem<F>, T: Tag>(
     Ok(AllocatedPtr::from_parts(tag, hash))
 }
 
+/// Constructs the error continuation that Lurk's step function builds for the
+/// keyword `code` and the offending expression `expr`, as interned by
+/// `Store::intern_cont_error`
+pub(crate) fn construct_cont_error<F: LurkField, CS: ConstraintSystem<F>>(
+    mut cs: CS,
+    store: &Store<F>,
+    code: &str,
+    expr: &AllocatedPtr<F>,
+) -> Result<AllocatedPtr<F>, SynthesisError> {
+    let code = AllocatedPtr::alloc_constant(
+        &mut cs.namespace(|| "code"),
+        store.hash_ptr(&store.key(code)),
+    )?;
+    let dummy =
+        AllocatedPtr::alloc_constant(&mut cs.namespace(|| "dummy"), store.hash_ptr(&Ptr::dummy()))?;
+
+    let hash = hash_poseidon(
+        &mut cs.namespace(|| "hash"),
+        vec![
+            code.tag().clone(),
+            code.hash().clone(),
+            expr.tag().clone(),
+            expr.hash().clone(),
+            dummy.tag().clone(),
+            dummy.hash().clone(),
+            dummy.tag().clone(),
+            dummy.hash().clone(),
+        ],
+        store.poseidon_cache.constants.c8(),
+    )?;
+
+    AllocatedPtr::alloc_tag(
+        &mut cs.namespace(|| "error"),
+        ContTag::Error.to_field(),
+        hash,
+    )
+}
+
 /// Constructs a `Cons` pointer
 #[allow(dead_code)]
 #[inline]
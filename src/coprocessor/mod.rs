@@ -113,6 +113,7 @@ pub(crate) mod test {
 
     use super::*;
     use crate::circuit::gadgets::constraints::{alloc_equal, mul};
+    use crate::coprocessor::gadgets::construct_cont_error;
     use crate::lem::Tag as LEMTag;
     use crate::tag::{ExprTag, Tag};
     use std::marker::PhantomData;
@@ -127,11 +128,11 @@ pub(crate) mod test {
         fn synthesize_aux<CS: ConstraintSystem<F>>(
             &self,
             cs: &mut CS,
+            s: &Store<F>,
             input_exprs: &[AllocatedPtr<F>],
             input_env: &AllocatedPtr<F>,
             input_cont: &AllocatedPtr<F>,
             num_tag: &AllocatedNum<F>,
-            cont_error: &AllocatedPtr<F>,
         ) -> Result<(AllocatedPtr<F>, AllocatedPtr<F>, AllocatedPtr<F>), SynthesisError> {
             let a = &input_exprs[0];
             let b = &input_exprs[1];
@@ -161,11 +162,18 @@ pub(crate) mod test {
                 a,
             )?;
 
+            // the offending expression of a type error is the argument that isn't a `Num`
+            let cont_error = construct_cont_error(
+                &mut cs.namespace(|| "cont_error"),
+                s,
+                "type-mismatch",
+                &result_expr,
+            )?;
             let result_cont = AllocatedPtr::pick(
                 &mut cs.namespace(|| "result_cont"),
                 &types_are_correct,
                 input_cont,
-                cont_error,
+                &cont_error,
             )?;
 
             Ok((result_expr, input_env.clone(), result_cont))
@@ -189,10 +197,8 @@ pub(crate) mod test {
         ) -> Result<Vec<AllocatedPtr<F>>, SynthesisError> {
             let num_tag = g.get_tag(&ExprTag::Num)?;
 
-            let cont_err = g.get_allocated_ptr_from_ptr(&s.cont_error(), s)?;
-
             let (expr, env, cont) =
-                self.synthesize_aux(cs, input_exprs, input_env, input_cont, num_tag, &cont_err)?;
+                self.synthesize_aux(cs, s, input_exprs, input_env, input_cont, num_tag)?;
 
             Ok(vec![expr, env, cont])
         }
@@ -216,10 +222,10 @@ pub(crate) mod test {
             cont: &Ptr<F>,
         ) -> Vec<Ptr<F>> {
             let Ptr::Atom(LEMTag::Expr(ExprTag::Num), a) = &args[0] else {
-                return vec![args[0], *env, s.cont_error()];
+                return vec![args[0], *env, s.intern_cont_error("type-mismatch", args[0])];
             };
             let Ptr::Atom(LEMTag::Expr(ExprTag::Num), b) = &args[1] else {
-                return vec![args[1], *env, s.cont_error()];
+                return vec![args[1], *env, s.intern_cont_error("type-mismatch", args[1])];
             };
             vec![Ptr::num((*a * *a) + *b), *env, *cont]
         }
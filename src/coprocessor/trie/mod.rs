@@ -28,18 +28,25 @@ use crate::package::Package;
 use crate::state::State;
 use crate::{self as lurk, Symbol};
 
-use crate::circuit::gadgets::constraints::{enforce_equal, implies_equal, select};
+use crate::circuit::gadgets::constraints::{
+    alloc_equal_const, alloc_is_zero, enforce_equal, enforce_equal_zero, implies_equal, select,
+};
+use crate::circuit::gadgets::data::allocate_constant;
 use crate::circuit::gadgets::pointer::AllocatedPtr;
-use crate::coprocessor::{CoCircuit, Coprocessor};
+use crate::coprocessor::{gadgets::construct_cont_error, CoCircuit, Coprocessor};
 use crate::eval::lang::Lang;
 use crate::field::{FWrap, LurkField};
 use crate::hash::{HashArity, HashConstants, InversePoseidonCache, PoseidonCache};
-use crate::lem::{pointers::Ptr, store::Store};
+use crate::lem::{pointers::Ptr, store::Store, Tag};
 use crate::tag::ExprTag;
 
 #[derive(Debug)]
 pub enum Error<F> {
     MissingPreimage(F),
+    /// The key has no value in the trie, so it can't be updated.
+    MissingKey(F),
+    /// The key has a value in the trie, so its absence can't be proved.
+    KeyPresent(F),
 }
 
 // TODO: As an optimization, PreimagePath and HashPreimagePath only actually need to hold the ARITY - 1 sibling hashes
@@ -55,6 +62,10 @@ pub enum TrieCoproc<F: LurkField> {
     New(NewCoprocessor<F>),
     Lookup(LookupCoprocessor<F>),
     Insert(InsertCoprocessor<F>),
+    Remove(RemoveCoprocessor<F>),
+    Update(UpdateCoprocessor<F>),
+    MapGet(MapGetCoprocessor<F>),
+    MapAssoc(MapAssocCoprocessor<F>),
 }
 
 #[derive(Clone, Debug, Serialize, Default, Deserialize)]
@@ -134,7 +145,7 @@ impl<F: LurkField> Coprocessor<F> for LookupCoprocessor<F> {
 fn synthesize_lookup_aux<F: LurkField, CS: ConstraintSystem<F>>(
     cs: &mut CS,
     root_ptr: &AllocatedPtr<F>,
-    key_ptr: &AllocatedPtr<F>,
+    key_val: &AllocatedNum<F>,
     not_dummy: &Boolean,
     poseidon_cache: &PoseidonCache<F>,
     inverse_poseidon_cache: &InversePoseidonCache<F>,
@@ -142,7 +153,6 @@ fn synthesize_lookup_aux<F: LurkField, CS: ConstraintSystem<F>>(
     // TODO: Check tags.
     let supplied_root_value = root_ptr.hash();
     let root_value = supplied_root_value.get_value();
-    let key_val = key_ptr.hash();
     let trie: StandardTrie<'_, F> = if not_dummy.get_value() == Some(true) {
         Trie::new_with_root(
             poseidon_cache,
@@ -192,7 +202,7 @@ impl<F: LurkField> CoCircuit<F> for LookupCoprocessor<F> {
         let result_commitment_val = synthesize_lookup_aux(
             cs,
             root_ptr,
-            key_ptr,
+            key_ptr.hash(),
             not_dummy,
             &s.poseidon_cache,
             &s.inverse_poseidon_cache,
@@ -245,11 +255,31 @@ fn synthesize_insert_aux<F: LurkField, CS: ConstraintSystem<F>>(
     poseidon_cache: &PoseidonCache<F>,
     inverse_poseidon_cache: &InversePoseidonCache<F>,
 ) -> Result<AllocatedNum<F>, SynthesisError> {
+    let (_old_val, new_root_val) = synthesize_modify_aux(
+        cs,
+        root_ptr,
+        key_ptr.hash(),
+        val_ptr.hash().clone(),
+        not_dummy,
+        poseidon_cache,
+        inverse_poseidon_cache,
+    )?;
+    Ok(new_root_val)
+}
+
+/// Sets the value at `key_val` to `new_val`, returning the old value and the new root.
+fn synthesize_modify_aux<F: LurkField, CS: ConstraintSystem<F>>(
+    cs: &mut CS,
+    root_ptr: &AllocatedPtr<F>,
+    key_val: &AllocatedNum<F>,
+    new_val: AllocatedNum<F>,
+    not_dummy: &Boolean,
+    poseidon_cache: &PoseidonCache<F>,
+    inverse_poseidon_cache: &InversePoseidonCache<F>,
+) -> Result<(AllocatedNum<F>, AllocatedNum<F>), SynthesisError> {
     // TODO: Check tags.
     let supplied_root_value = root_ptr.hash();
     let root_value = supplied_root_value.get_value();
-    let key_val = key_ptr.hash();
-    let new_val = val_ptr.hash();
     let trie: StandardTrie<'_, F> = if not_dummy.get_value() == Some(true) {
         Trie::new_with_root(
             poseidon_cache,
@@ -272,12 +302,12 @@ fn synthesize_insert_aux<F: LurkField, CS: ConstraintSystem<F>>(
         &allocated_root_value,
     );
 
-    trie.synthesize_insert(
+    trie.synthesize_update(
         cs,
         &poseidon_cache.constants,
         &allocated_root_value,
         key_val,
-        new_val.clone(),
+        new_val,
     )
     .map_err(|_e| SynthesisError::Unsatisfiable)
 }
@@ -314,16 +344,490 @@ impl<F: LurkField> CoCircuit<F> for InsertCoprocessor<F> {
     }
 }
 
+#[derive(Clone, Debug, Serialize, Default, Deserialize)]
+pub struct RemoveCoprocessor<F: LurkField> {
+    _p: PhantomData<F>,
+}
+
+impl<F: LurkField> Coprocessor<F> for RemoveCoprocessor<F> {
+    fn eval_arity(&self) -> usize {
+        2
+    }
+
+    fn evaluate_simple(&self, s: &Store<F>, args: &[Ptr<F>]) -> Ptr<F> {
+        let root_ptr = &args[0];
+        let key_ptr = &args[1];
+        let root_scalar = *s.hash_ptr(root_ptr).value();
+        let key_scalar = *s.hash_ptr(key_ptr).value();
+        let mut trie: StandardTrie<'_, F> =
+            Trie::new_with_root(&s.poseidon_cache, &s.inverse_poseidon_cache, root_scalar);
+        trie.remove(key_scalar).unwrap();
+
+        Ptr::num(trie.root)
+    }
+
+    fn has_circuit(&self) -> bool {
+        true
+    }
+}
+
+impl<F: LurkField> CoCircuit<F> for RemoveCoprocessor<F> {
+    fn arity(&self) -> usize {
+        2
+    }
+
+    fn synthesize_simple<CS: ConstraintSystem<F>>(
+        &self,
+        cs: &mut CS,
+        g: &lurk::lem::circuit::GlobalAllocator<F>,
+        s: &Store<F>,
+        not_dummy: &Boolean,
+        args: &[AllocatedPtr<F>],
+    ) -> Result<AllocatedPtr<F>, SynthesisError> {
+        let root_ptr = &args[0];
+        let key_ptr = &args[1];
+
+        // Removing is inserting the empty element.
+        let empty = AllocatedNum::alloc(&mut cs.namespace(|| "empty"), || {
+            Ok(StandardTrie::<'_, F>::empty_element())
+        })?;
+        enforce_equal_zero(cs, || "empty is zero", &empty);
+
+        let (_old_val, new_root_val) = synthesize_modify_aux(
+            cs,
+            root_ptr,
+            key_ptr.hash(),
+            empty,
+            not_dummy,
+            &s.poseidon_cache,
+            &s.inverse_poseidon_cache,
+        )?;
+
+        let num_tag = g.get_tag(&ExprTag::Num)?;
+        Ok(AllocatedPtr::from_parts(num_tag.clone(), new_root_val))
+    }
+}
+
+/// Like `InsertCoprocessor`, but the key must already have a value. Updating a missing key is an error, which leaves
+/// the root unchanged.
+#[derive(Clone, Debug, Serialize, Default, Deserialize)]
+pub struct UpdateCoprocessor<F: LurkField> {
+    _p: PhantomData<F>,
+}
+
+impl<F: LurkField> Coprocessor<F> for UpdateCoprocessor<F> {
+    fn eval_arity(&self) -> usize {
+        3
+    }
+
+    fn evaluate(&self, s: &Store<F>, args: &[Ptr<F>], env: &Ptr<F>, cont: &Ptr<F>) -> Vec<Ptr<F>> {
+        let root_ptr = &args[0];
+        let key_ptr = &args[1];
+        let val_ptr = &args[2];
+        let root_scalar = *s.hash_ptr(root_ptr).value();
+        let key_scalar = *s.hash_ptr(key_ptr).value();
+        let val_scalar = *s.hash_ptr(val_ptr).value();
+        let mut trie: StandardTrie<'_, F> =
+            Trie::new_with_root(&s.poseidon_cache, &s.inverse_poseidon_cache, root_scalar);
+
+        match trie.update(key_scalar, val_scalar) {
+            Ok(_old_value) => vec![Ptr::num(trie.root), *env, *cont],
+            Err(Error::MissingKey(_)) => vec![
+                *root_ptr,
+                *env,
+                s.intern_cont_error("missing-key", *key_ptr),
+            ],
+            Err(e) => panic!("{e:?}"),
+        }
+    }
+
+    fn evaluate_simple(&self, _s: &Store<F>, _args: &[Ptr<F>]) -> Ptr<F> {
+        unreachable!()
+    }
+
+    fn has_circuit(&self) -> bool {
+        true
+    }
+}
+
+impl<F: LurkField> CoCircuit<F> for UpdateCoprocessor<F> {
+    fn arity(&self) -> usize {
+        3
+    }
+
+    fn synthesize<CS: ConstraintSystem<F>>(
+        &self,
+        cs: &mut CS,
+        g: &lurk::lem::circuit::GlobalAllocator<F>,
+        s: &Store<F>,
+        not_dummy: &Boolean,
+        args: &[AllocatedPtr<F>],
+        env: &AllocatedPtr<F>,
+        cont: &AllocatedPtr<F>,
+    ) -> Result<Vec<AllocatedPtr<F>>, SynthesisError> {
+        let root_ptr = &args[0];
+        let key_ptr = &args[1];
+        let val_ptr = &args[2];
+
+        let (old_val, new_root_val) = synthesize_modify_aux(
+            cs,
+            root_ptr,
+            key_ptr.hash(),
+            val_ptr.hash().clone(),
+            not_dummy,
+            &s.poseidon_cache,
+            &s.inverse_poseidon_cache,
+        )?;
+
+        let missing = alloc_is_zero(cs.namespace(|| "missing"), &old_val)?;
+
+        let num_tag = g.get_tag(&ExprTag::Num)?;
+        let new_root = AllocatedPtr::from_parts(num_tag.clone(), new_root_val);
+        let result = AllocatedPtr::pick(
+            &mut cs.namespace(|| "result"),
+            &missing,
+            root_ptr,
+            &new_root,
+        )?;
+
+        let cont_err =
+            construct_cont_error(&mut cs.namespace(|| "cont_err"), s, "missing-key", key_ptr)?;
+        let result_cont = AllocatedPtr::pick(
+            &mut cs.namespace(|| "result_cont"),
+            &missing,
+            &cont_err,
+            cont,
+        )?;
+
+        Ok(vec![result, env.clone(), result_cont])
+    }
+}
+
+/// Synthesizes the key under which `key_ptr` is bound in the trie of a map, see `Store::map_key`.
+fn synthesize_map_key<F: LurkField, CS: ConstraintSystem<F>>(
+    cs: &mut CS,
+    key_ptr: &AllocatedPtr<F>,
+    hash_constants: &HashConstants<F>,
+) -> Result<AllocatedNum<F>, SynthesisError> {
+    let zero = allocate_constant(&mut cs.namespace(|| "zero"), F::ZERO);
+    hash_constants.constants(HashArity::A3).hash(
+        &mut cs.namespace(|| "key hash"),
+        vec![zero, key_ptr.tag().clone(), key_ptr.hash().clone()],
+        None,
+    )
+}
+
+/// Synthesizes the value stored in the trie of a map for the binding of `key_ptr` to `val_ptr`, see
+/// `Store::map_entry`.
+fn synthesize_map_entry<F: LurkField, CS: ConstraintSystem<F>>(
+    cs: &mut CS,
+    key_ptr: &AllocatedPtr<F>,
+    val_ptr: &AllocatedPtr<F>,
+    hash_constants: &HashConstants<F>,
+) -> Result<AllocatedNum<F>, SynthesisError> {
+    let binding = hash_constants.constants(HashArity::A4).hash(
+        &mut cs.namespace(|| "binding hash"),
+        vec![
+            key_ptr.tag().clone(),
+            key_ptr.hash().clone(),
+            val_ptr.tag().clone(),
+            val_ptr.hash().clone(),
+        ],
+        None,
+    )?;
+    let zero = allocate_constant(&mut cs.namespace(|| "zero"), F::ZERO);
+    let cons_tag = allocate_constant(
+        &mut cs.namespace(|| "cons tag"),
+        Tag::Expr(ExprTag::Cons).to_field(),
+    );
+    hash_constants.constants(HashArity::A3).hash(
+        &mut cs.namespace(|| "entry hash"),
+        vec![zero, cons_tag, binding],
+        None,
+    )
+}
+
+/// Looks a key up in a map, returning `nil` if it's unbound. The map is a `StandardTrie` whose values are
+/// commitments to the bindings, see `Store::map_assoc`.
+#[derive(Clone, Debug, Serialize, Default, Deserialize)]
+pub struct MapGetCoprocessor<F: LurkField> {
+    _p: PhantomData<F>,
+}
+
+impl<F: LurkField> Coprocessor<F> for MapGetCoprocessor<F> {
+    fn eval_arity(&self) -> usize {
+        2
+    }
+
+    fn evaluate(&self, s: &Store<F>, args: &[Ptr<F>], env: &Ptr<F>, cont: &Ptr<F>) -> Vec<Ptr<F>> {
+        let map_ptr = &args[0];
+        let key_ptr = &args[1];
+        if map_ptr.tag() != &Tag::Expr(ExprTag::Map) {
+            return vec![
+                *map_ptr,
+                *env,
+                s.intern_cont_error("type-mismatch", *map_ptr),
+            ];
+        }
+        match s.map_get(map_ptr, key_ptr) {
+            Some(val) => vec![val, *env, *cont],
+            // the store doesn't have the preimages of the map's trie
+            None => vec![
+                *map_ptr,
+                *env,
+                s.intern_cont_error("missing-preimage", *map_ptr),
+            ],
+        }
+    }
+
+    fn evaluate_simple(&self, _s: &Store<F>, _args: &[Ptr<F>]) -> Ptr<F> {
+        unreachable!()
+    }
+
+    fn has_circuit(&self) -> bool {
+        true
+    }
+}
+
+impl<F: LurkField> CoCircuit<F> for MapGetCoprocessor<F> {
+    fn arity(&self) -> usize {
+        2
+    }
+
+    fn synthesize<CS: ConstraintSystem<F>>(
+        &self,
+        cs: &mut CS,
+        _g: &lurk::lem::circuit::GlobalAllocator<F>,
+        s: &Store<F>,
+        not_dummy: &Boolean,
+        args: &[AllocatedPtr<F>],
+        env: &AllocatedPtr<F>,
+        cont: &AllocatedPtr<F>,
+    ) -> Result<Vec<AllocatedPtr<F>>, SynthesisError> {
+        let map_ptr = &args[0];
+        let key_ptr = &args[1];
+        let hash_constants = &s.poseidon_cache.constants;
+
+        let is_map = alloc_equal_const(
+            &mut cs.namespace(|| "is_map"),
+            map_ptr.tag(),
+            Tag::Expr(ExprTag::Map).to_field(),
+        )?;
+        let lookup = Boolean::and(&mut cs.namespace(|| "lookup"), not_dummy, &is_map)?;
+
+        let key = synthesize_map_key(&mut cs.namespace(|| "key"), key_ptr, hash_constants)?;
+        let entry = synthesize_lookup_aux(
+            &mut cs.namespace(|| "entry"),
+            map_ptr,
+            &key,
+            &lookup,
+            &s.poseidon_cache,
+            &s.inverse_poseidon_cache,
+        )?;
+        let found = alloc_is_zero(&mut cs.namespace(|| "missing"), &entry)?.not();
+
+        // the value is a witness, which is bound by recomputing the entry
+        let nil = s.intern_nil();
+        let val_ptr = AllocatedPtr::alloc_infallible(&mut cs.namespace(|| "val"), || {
+            let val = match (
+                lookup.get_value(),
+                map_ptr.hash().get_value(),
+                key.get_value(),
+            ) {
+                (Some(true), Some(root), Some(key)) => {
+                    let trie: StandardTrie<'_, F> =
+                        Trie::new_with_root(&s.poseidon_cache, &s.inverse_poseidon_cache, root);
+                    trie.lookup(key)
+                        .ok()
+                        .flatten()
+                        .and_then(|entry| s.car_cdr(&s.open(entry)?.1).ok())
+                        .map(|(_, val)| val)
+                }
+                _ => None,
+            };
+            s.hash_ptr(&val.unwrap_or(nil))
+        });
+        let bound_entry = synthesize_map_entry(
+            &mut cs.namespace(|| "bound_entry"),
+            key_ptr,
+            &val_ptr,
+            hash_constants,
+        )?;
+        let found_and_lookup =
+            Boolean::and(&mut cs.namespace(|| "found_and_lookup"), &found, &lookup)?;
+        implies_equal(
+            &mut cs.namespace(|| "enforce_entry"),
+            &found_and_lookup,
+            &bound_entry,
+            &entry,
+        );
+
+        let nil = AllocatedPtr::alloc_constant(&mut cs.namespace(|| "nil"), s.hash_ptr(&nil))?;
+        let val = AllocatedPtr::pick(&mut cs.namespace(|| "found_val"), &found, &val_ptr, &nil)?;
+        let result = AllocatedPtr::pick(&mut cs.namespace(|| "result"), &is_map, &val, map_ptr)?;
+
+        let cont_err = construct_cont_error(
+            &mut cs.namespace(|| "cont_err"),
+            s,
+            "type-mismatch",
+            map_ptr,
+        )?;
+        let result_cont = AllocatedPtr::pick(
+            &mut cs.namespace(|| "result_cont"),
+            &is_map,
+            cont,
+            &cont_err,
+        )?;
+
+        Ok(vec![result, env.clone(), result_cont])
+    }
+}
+
+/// Binds a key to a value in a map, returning the new map.
+#[derive(Clone, Debug, Serialize, Default, Deserialize)]
+pub struct MapAssocCoprocessor<F: LurkField> {
+    _p: PhantomData<F>,
+}
+
+impl<F: LurkField> Coprocessor<F> for MapAssocCoprocessor<F> {
+    fn eval_arity(&self) -> usize {
+        3
+    }
+
+    fn evaluate(&self, s: &Store<F>, args: &[Ptr<F>], env: &Ptr<F>, cont: &Ptr<F>) -> Vec<Ptr<F>> {
+        let map_ptr = &args[0];
+        let key_ptr = args[1];
+        let val_ptr = args[2];
+        if map_ptr.tag() != &Tag::Expr(ExprTag::Map) {
+            return vec![
+                *map_ptr,
+                *env,
+                s.intern_cont_error("type-mismatch", *map_ptr),
+            ];
+        }
+        match s.map_assoc(map_ptr, key_ptr, val_ptr) {
+            Some(map) => vec![map, *env, *cont],
+            // the store doesn't have the preimages of the map's trie
+            None => vec![
+                *map_ptr,
+                *env,
+                s.intern_cont_error("missing-preimage", *map_ptr),
+            ],
+        }
+    }
+
+    fn evaluate_simple(&self, _s: &Store<F>, _args: &[Ptr<F>]) -> Ptr<F> {
+        unreachable!()
+    }
+
+    fn has_circuit(&self) -> bool {
+        true
+    }
+}
+
+impl<F: LurkField> CoCircuit<F> for MapAssocCoprocessor<F> {
+    fn arity(&self) -> usize {
+        3
+    }
+
+    fn synthesize<CS: ConstraintSystem<F>>(
+        &self,
+        cs: &mut CS,
+        _g: &lurk::lem::circuit::GlobalAllocator<F>,
+        s: &Store<F>,
+        not_dummy: &Boolean,
+        args: &[AllocatedPtr<F>],
+        env: &AllocatedPtr<F>,
+        cont: &AllocatedPtr<F>,
+    ) -> Result<Vec<AllocatedPtr<F>>, SynthesisError> {
+        let map_ptr = &args[0];
+        let key_ptr = &args[1];
+        let val_ptr = &args[2];
+        let hash_constants = &s.poseidon_cache.constants;
+
+        let is_map = alloc_equal_const(
+            &mut cs.namespace(|| "is_map"),
+            map_ptr.tag(),
+            Tag::Expr(ExprTag::Map).to_field(),
+        )?;
+        let modify = Boolean::and(&mut cs.namespace(|| "modify"), not_dummy, &is_map)?;
+
+        let key = synthesize_map_key(&mut cs.namespace(|| "key"), key_ptr, hash_constants)?;
+        let entry = synthesize_map_entry(
+            &mut cs.namespace(|| "entry"),
+            key_ptr,
+            val_ptr,
+            hash_constants,
+        )?;
+        let (_old_entry, new_root_val) = synthesize_modify_aux(
+            &mut cs.namespace(|| "modify_aux"),
+            map_ptr,
+            &key,
+            entry,
+            &modify,
+            &s.poseidon_cache,
+            &s.inverse_poseidon_cache,
+        )?;
+
+        let new_map = AllocatedPtr::alloc_tag(
+            &mut cs.namespace(|| "new_map"),
+            Tag::Expr(ExprTag::Map).to_field(),
+            new_root_val,
+        )?;
+        let result =
+            AllocatedPtr::pick(&mut cs.namespace(|| "result"), &is_map, &new_map, map_ptr)?;
+
+        let cont_err = construct_cont_error(
+            &mut cs.namespace(|| "cont_err"),
+            s,
+            "type-mismatch",
+            map_ptr,
+        )?;
+        let result_cont = AllocatedPtr::pick(
+            &mut cs.namespace(|| "result_cont"),
+            &is_map,
+            cont,
+            &cont_err,
+        )?;
+
+        Ok(vec![result, env.clone(), result_cont])
+    }
+}
+
 /// Add the `Trie`-associated functions to a `Lang` with standard bindings.
 // TODO: define standard patterns for such modularity.
 pub fn install<F: LurkField>(state: &Rc<RefCell<State>>, lang: &mut Lang<F, TrieCoproc<F>>) {
     lang.add_coprocessor(".lurk.trie.new", NewCoprocessor::default());
     lang.add_coprocessor(".lurk.trie.lookup", LookupCoprocessor::default());
     lang.add_coprocessor(".lurk.trie.insert", InsertCoprocessor::default());
+    lang.add_coprocessor(".lurk.trie.remove", RemoveCoprocessor::default());
+    lang.add_coprocessor(".lurk.trie.update", UpdateCoprocessor::default());
 
     let trie_package_name: Symbol = ".lurk.trie".into();
     let mut package = Package::new(trie_package_name.into());
-    ["new", "lookup", "insert"].into_iter().for_each(|name| {
+    ["new", "lookup", "insert", "remove", "update"]
+        .into_iter()
+        .for_each(|name| {
+            package.intern(name);
+        });
+    state.borrow_mut().add_package(package);
+
+    install_map(state, lang);
+}
+
+/// Add the map functions to a `Lang` with standard bindings. The default `Coproc` also includes them,
+/// so that maps can be used in the REPL.
+pub fn install_map<F, C>(state: &Rc<RefCell<State>>, lang: &mut Lang<F, C>)
+where
+    F: LurkField,
+    C: Coprocessor<F> + From<MapGetCoprocessor<F>> + From<MapAssocCoprocessor<F>>,
+{
+    lang.add_coprocessor(".lurk.map.get", MapGetCoprocessor::default());
+    lang.add_coprocessor(".lurk.map.assoc", MapAssocCoprocessor::default());
+
+    let map_package_name: Symbol = ".lurk.map".into();
+    let mut package = Package::new(map_package_name.into());
+    ["get", "assoc"].into_iter().for_each(|name| {
         package.intern(name);
     });
     state.borrow_mut().add_package(package);
@@ -429,6 +933,74 @@ impl<F: LurkField, const ARITY: usize, const HEIGHT: usize> InsertProof<F, ARITY
     }
 }
 
+#[derive(Debug)]
+pub struct RemoveProof<F: LurkField, const ARITY: usize, const HEIGHT: usize> {
+    insert_proof: InsertProof<F, ARITY, HEIGHT>,
+}
+
+impl<F: LurkField, const ARITY: usize, const HEIGHT: usize> RemoveProof<F, ARITY, HEIGHT> {
+    /// Verify a `RemoveProof`. A removal is an insertion of the empty element over a non-empty `old_value`.
+    pub fn verify(
+        &self,
+        old_root: F,
+        new_root: F,
+        key: F,
+        old_value: F,
+        hash_cache: &PoseidonCache<F>,
+    ) -> bool {
+        let empty = Trie::<'_, F, ARITY, HEIGHT>::empty_element();
+        old_value != empty
+            && self
+                .insert_proof
+                .verify(old_root, new_root, key, Some(old_value), empty, hash_cache)
+    }
+}
+
+#[derive(Debug)]
+pub struct UpdateProof<F: LurkField, const ARITY: usize, const HEIGHT: usize> {
+    insert_proof: InsertProof<F, ARITY, HEIGHT>,
+}
+
+impl<F: LurkField, const ARITY: usize, const HEIGHT: usize> UpdateProof<F, ARITY, HEIGHT> {
+    /// Verify an `UpdateProof`. An update is an insertion over a non-empty `old_value`.
+    pub fn verify(
+        &self,
+        old_root: F,
+        new_root: F,
+        key: F,
+        old_value: F,
+        new_value: F,
+        hash_cache: &PoseidonCache<F>,
+    ) -> bool {
+        old_value != Trie::<'_, F, ARITY, HEIGHT>::empty_element()
+            && self.insert_proof.verify(
+                old_root,
+                new_root,
+                key,
+                Some(old_value),
+                new_value,
+                hash_cache,
+            )
+    }
+}
+
+#[derive(Debug)]
+pub struct NonMembershipProof<F: LurkField, const ARITY: usize, const HEIGHT: usize> {
+    lookup_proof: LookupProof<F, ARITY, HEIGHT>,
+}
+
+impl<F: LurkField, const ARITY: usize, const HEIGHT: usize> NonMembershipProof<F, ARITY, HEIGHT> {
+    /// Verify a `NonMembershipProof`, which is a lookup of the empty element.
+    pub fn verify(&self, root: F, key: F, hash_cache: &PoseidonCache<F>) -> bool {
+        self.lookup_proof.verify(
+            root,
+            key,
+            Trie::<'_, F, ARITY, HEIGHT>::empty_element(),
+            hash_cache,
+        )
+    }
+}
+
 impl<'a, F: LurkField, const ARITY: usize, const HEIGHT: usize> Trie<'a, F, ARITY, HEIGHT> {
     /// The empty element is specified to be zero. This is a natural choice. Crucially, the chosen value must have no known
     /// preimage.
@@ -568,7 +1140,7 @@ impl<'a, F: LurkField, const ARITY: usize, const HEIGHT: usize> Trie<'a, F, ARIT
     }
 
     /// Create a new `Trie` with specified root.
-    fn new_with_root(
+    pub(crate) fn new_with_root(
         poseidon_cache: &'a PoseidonCache<F>,
         inverse_poseidon_cache: &'a InversePoseidonCache<F>,
         root: F,
@@ -722,6 +1294,28 @@ impl<'a, F: LurkField, const ARITY: usize, const HEIGHT: usize> Trie<'a, F, ARIT
         Ok((found, preimage_path))
     }
 
+    /// Returns the non-empty payloads, in the order of their paths.
+    pub fn values(&self) -> Result<Vec<F>, Error<F>> {
+        let mut values = Vec::new();
+        self.collect_values(self.root, HEIGHT, &mut values)?;
+        Ok(values)
+    }
+
+    fn collect_values(&self, node: F, height: usize, values: &mut Vec<F>) -> Result<(), Error<F>> {
+        if node == self.empty_root_for_height(height) {
+            return Ok(());
+        }
+        if height == 0 {
+            values.push(node);
+            return Ok(());
+        }
+        let preimage =
+            *Self::get_hash_preimage(self.children, node).ok_or(Error::MissingPreimage(node))?;
+        preimage
+            .into_iter()
+            .try_for_each(|child| self.collect_values(child, height - 1, values))
+    }
+
     /// Returns a slice of preimages, corresponding to the path.
     /// Final preimage contains payloads.
     pub fn prove_lookup(&self, key: F) -> Result<LookupProof<F, ARITY, HEIGHT>, Error<F>> {
@@ -784,6 +1378,68 @@ impl<'a, F: LurkField, const ARITY: usize, const HEIGHT: usize> Trie<'a, F, ARIT
         Ok((InsertProof::new(old_proof, new_proof), inserted))
     }
 
+    /// Removes the value associated with `key`, returning it if there was one. Removing a missing key leaves the trie
+    /// unchanged.
+    pub fn remove(&mut self, key: F) -> Result<Option<F>, Error<F>> {
+        match self.prove_remove(key) {
+            Ok((_remove_proof, old_value)) => Ok(Some(old_value)),
+            Err(Error::MissingKey(_)) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Removes the value associated with `key` and returns it with a proof of the removal. Since empty subtrees hash
+    /// to the precomputed empty roots, the resulting root is the same as if `key` had never been inserted.
+    pub fn prove_remove(&mut self, key: F) -> Result<(RemoveProof<F, ARITY, HEIGHT>, F), Error<F>> {
+        let (insert_proof, old_value) = self.replace_existing(key, Self::empty_element())?;
+        Ok((RemoveProof { insert_proof }, old_value))
+    }
+
+    /// Replaces the value associated with `key`, returning the old one. Unlike `insert`, `key` must already have a
+    /// value.
+    pub fn update(&mut self, key: F, value: F) -> Result<F, Error<F>> {
+        let (_update_proof, old_value) = self.prove_update(key, value)?;
+
+        Ok(old_value)
+    }
+
+    pub fn prove_update(
+        &mut self,
+        key: F,
+        value: F,
+    ) -> Result<(UpdateProof<F, ARITY, HEIGHT>, F), Error<F>> {
+        let (insert_proof, old_value) = self.replace_existing(key, value)?;
+        Ok((UpdateProof { insert_proof }, old_value))
+    }
+
+    fn replace_existing(
+        &mut self,
+        key: F,
+        value: F,
+    ) -> Result<(InsertProof<F, ARITY, HEIGHT>, F), Error<F>> {
+        let old_value = self.lookup_aux(key)?;
+        if old_value == Self::empty_element() {
+            return Err(Error::MissingKey(key));
+        }
+        let path = Self::path(key);
+        let (insert_proof, _inserted) = self.insert_at_path(&path, value)?;
+
+        Ok((insert_proof, old_value))
+    }
+
+    /// Proves that `key` has no value in the trie.
+    pub fn prove_non_membership(
+        &self,
+        key: F,
+    ) -> Result<NonMembershipProof<F, ARITY, HEIGHT>, Error<F>> {
+        if self.lookup_aux(key)? != Self::empty_element() {
+            return Err(Error::KeyPresent(key));
+        }
+        let lookup_proof = self.prove_lookup(key)?;
+
+        Ok(NonMembershipProof { lookup_proof })
+    }
+
     fn modify_value_at_path(
         &mut self,
         path: &[usize],
@@ -817,6 +1473,21 @@ impl<'a, F: LurkField, const ARITY: usize, const HEIGHT: usize> Trie<'a, F, ARIT
         key: &AllocatedNum<F>,
         value: AllocatedNum<F>,
     ) -> Result<AllocatedNum<F>, SynthesisError> {
+        let (_old_value, new_root) =
+            self.synthesize_update(cs, hash_constants, allocated_root, key, value)?;
+        Ok(new_root)
+    }
+
+    /// Like `synthesize_insert`, but also returns the value previously found at `key`, which is the empty element if
+    /// there was none.
+    pub fn synthesize_update<CS: ConstraintSystem<F>>(
+        &self,
+        cs: &mut CS,
+        hash_constants: &HashConstants<F>,
+        allocated_root: &AllocatedNum<F>,
+        key: &AllocatedNum<F>,
+        value: AllocatedNum<F>,
+    ) -> Result<(AllocatedNum<F>, AllocatedNum<F>), SynthesisError> {
         let path = Self::synthesize_path(&mut cs.namespace(|| "path"), key)?;
         self.synthesize_insert_at_path(
             &mut cs.namespace(|| "insert_aux"),
@@ -834,23 +1505,23 @@ impl<'a, F: LurkField, const ARITY: usize, const HEIGHT: usize> Trie<'a, F, ARIT
         allocated_root: &AllocatedNum<F>,
         path: &[Vec<Boolean>],
         value: AllocatedNum<F>,
-    ) -> Result<AllocatedNum<F>, SynthesisError> {
-        let preimage_path = self
-            .synthesize_lookup_at_path(
-                &mut cs.namespace(|| "found_value"),
-                hash_constants,
-                allocated_root,
-                path,
-            )?
-            .1;
+    ) -> Result<(AllocatedNum<F>, AllocatedNum<F>), SynthesisError> {
+        let (found, preimage_path) = self.synthesize_lookup_at_path(
+            &mut cs.namespace(|| "found_value"),
+            hash_constants,
+            allocated_root,
+            path,
+        )?;
 
-        Self::synthesize_modify_value_at_path(
+        let new_root = Self::synthesize_modify_value_at_path(
             &mut cs.namespace(|| "new_root_value"),
             hash_constants,
             path,
             &preimage_path,
             value,
-        )
+        )?;
+
+        Ok((found, new_root))
     }
 
     fn synthesize_modify_value_at_path<CS: ConstraintSystem<F>>(
@@ -1234,4 +1905,91 @@ mod test {
             }
         }
     }
+
+    #[test]
+    fn test_remove_proof() {
+        let mut t3: Trie<'_, Fr, 8, 3> =
+            Trie::new_with_capacity(poseidon_cache(), inverse_poseidon_cache(), 512);
+        let key = Fr::from_u64(500);
+        let val = Fr::from_u64(123);
+        let key2 = Fr::from_u64(127);
+        let val2 = Fr::from_u64(987);
+
+        let empty_root = t3.root;
+        t3.insert(key, val).unwrap();
+        let root_with_key = t3.root;
+        t3.insert(key2, val2).unwrap();
+
+        assert!(matches!(
+            t3.prove_remove(Fr::from_u64(1)),
+            Err(Error::MissingKey(_))
+        ));
+
+        let old_root = t3.root;
+        let (remove_proof, old_value) = t3.prove_remove(key2).unwrap();
+        assert_eq!(val2, old_value);
+        assert_eq!(root_with_key, t3.root);
+        assert_eq!(None, t3.lookup(key2).unwrap());
+        assert_eq!(Some(val), t3.lookup(key).unwrap());
+
+        let fresh_p = PoseidonCache::<Fr>::default();
+        assert!(remove_proof.verify(old_root, t3.root, key2, val2, &fresh_p));
+        assert!(!remove_proof.verify(old_root, t3.root, key2, val, &fresh_p));
+        assert!(!remove_proof.verify(old_root, t3.root, key, val2, &fresh_p));
+
+        assert_eq!(Some(val), t3.remove(key).unwrap());
+        assert_eq!(None, t3.remove(key).unwrap());
+        assert_eq!(empty_root, t3.root);
+    }
+
+    #[test]
+    fn test_update_proof() {
+        let mut t3: Trie<'_, Fr, 8, 3> =
+            Trie::new_with_capacity(poseidon_cache(), inverse_poseidon_cache(), 512);
+        let key = Fr::from_u64(500);
+        let val = Fr::from_u64(123);
+        let val2 = Fr::from_u64(987);
+
+        let empty_root = t3.root;
+        assert!(matches!(t3.update(key, val), Err(Error::MissingKey(_))));
+        assert_eq!(empty_root, t3.root);
+
+        t3.insert(key, val).unwrap();
+
+        let old_root = t3.root;
+        let (update_proof, old_value) = t3.prove_update(key, val2).unwrap();
+        assert_eq!(val, old_value);
+        assert_eq!(Some(val2), t3.lookup(key).unwrap());
+
+        let fresh_p = PoseidonCache::<Fr>::default();
+        assert!(update_proof.verify(old_root, t3.root, key, val, val2, &fresh_p));
+        assert!(!update_proof.verify(old_root, t3.root, key, val2, val2, &fresh_p));
+
+        // An insertion into an empty slot is not an update.
+        let mut t3b: Trie<'_, Fr, 8, 3> =
+            Trie::new_with_capacity(poseidon_cache(), inverse_poseidon_cache(), 512);
+        let (insert_proof, _) = t3b.prove_insert(key, val).unwrap();
+        let update_proof = UpdateProof { insert_proof };
+        assert!(!update_proof.verify(empty_root, t3b.root, key, Fr::zero(), val, &fresh_p));
+    }
+
+    #[test]
+    fn test_non_membership_proof() {
+        let mut t3: Trie<'_, Fr, 8, 3> =
+            Trie::new_with_capacity(poseidon_cache(), inverse_poseidon_cache(), 512);
+        let key = Fr::from_u64(500);
+        let key2 = Fr::from_u64(127);
+
+        t3.insert(key, Fr::from_u64(123)).unwrap();
+
+        let fresh_p = PoseidonCache::<Fr>::default();
+        let proof = t3.prove_non_membership(key2).unwrap();
+        assert!(proof.verify(t3.root, key2, &fresh_p));
+        assert!(!proof.verify(t3.root, key, &fresh_p));
+
+        assert!(matches!(
+            t3.prove_non_membership(key),
+            Err(Error::KeyPresent(_))
+        ));
+    }
 }
@@ -1,13 +1,15 @@
 use crate::store;
 
 use bellpepper_core::SynthesisError;
-use nova::errors::NovaError;
+use nova::{errors::NovaError, supernova::error::SuperNovaError};
 use thiserror::Error;
 
 #[derive(Error, Debug)]
 pub enum ProofError {
     #[error("Nova error")]
     Nova(#[from] NovaError),
+    #[error("SuperNova error")]
+    SuperNova(#[from] SuperNovaError),
     #[error("Synthesis error: {0}")]
     Synthesis(#[from] SynthesisError),
     #[error("Reduction error: {0}")]
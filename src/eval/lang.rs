@@ -5,7 +5,10 @@ use std::{fmt::Debug, marker::PhantomData};
 
 use crate::{
     self as lurk,
-    coprocessor::{CoCircuit, Coprocessor},
+    coprocessor::{
+        trie::{MapAssocCoprocessor, MapGetCoprocessor},
+        CoCircuit, Coprocessor,
+    },
     field::LurkField,
     lem::{pointers::Ptr, store::Store},
     symbol::Symbol,
@@ -59,6 +62,8 @@ impl<F: LurkField> DummyCoprocessor<F> {
 #[derive(Clone, Debug, Deserialize, Serialize, Coproc)]
 pub enum Coproc<F: LurkField> {
     Dummy(DummyCoprocessor<F>),
+    MapGet(MapGetCoprocessor<F>),
+    MapAssoc(MapAssocCoprocessor<F>),
 }
 
 /// `Lang` is a struct that represents a language with coprocessors.
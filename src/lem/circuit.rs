@@ -38,16 +38,16 @@ use std::{
 use crate::{
     circuit::gadgets::{
         constraints::{
-            alloc_equal, alloc_is_zero, div, enforce_product_and_sum,
+            add_to_lc, alloc_equal, alloc_is_zero, div, enforce_product_and_sum,
             enforce_selector_with_premise, implies_equal, implies_equal_const, implies_pack,
-            implies_u64, implies_unequal_const, mul, or, pick, sub,
+            implies_uint, implies_unequal_const, mul, or, pick, sub,
         },
         data::{allocate_constant, hash_poseidon},
         pointer::AllocatedPtr,
     },
     coprocessor::Coprocessor,
     eval::lang::Lang,
-    field::{FWrap, LanguageField, LurkField},
+    field::{FWrap, LurkField},
     tag::{
         ExprTag::{Comm, Num, Sym},
         Tag,
@@ -436,10 +436,13 @@ impl Block {
                 | Op::Mul(..)
                 | Op::Lt(..)
                 | Op::Trunc(..)
-                | Op::DivRem64(..) => {
+                | Op::DivRem(..)
+                | Op::BitAnd(..)
+                | Op::BitOr(..)
+                | Op::BitXor(..) => {
                     g.new_const_from_tag(cs, &Num);
                 }
-                Op::Div(..) => {
+                Op::Div(..) | Op::Pow2(..) => {
                     g.new_const_from_tag(cs, &Num);
                     g.new_const(cs, F::ONE);
                 }
@@ -922,7 +925,7 @@ fn synthesize_block<F: LurkField, CS: ConstraintSystem<F>, C: Coprocessor<F>>(
                 bound_allocations.insert_bool(tgt.clone(), lt.clone());
             }
             Op::Trunc(tgt, a, n) => {
-                assert!(*n <= 64);
+                assert!(*n <= 128);
                 let a = bound_allocations.get_ptr(a)?;
                 let (preallocated_preimg, trunc_bits) =
                     &ctx.bit_decomp_slots[next_slot.consume_bit_decomp()];
@@ -937,10 +940,10 @@ fn synthesize_block<F: LurkField, CS: ConstraintSystem<F>, C: Coprocessor<F>>(
                 };
                 let trunc_bits = &trunc_bits[0..*n as usize];
                 let trunc = AllocatedNum::alloc(cs.namespace(|| "trunc"), || {
-                    let b = if *n < 64 { (1 << *n) - 1 } else { u64::MAX };
+                    let b = if *n < 128 { (1 << *n) - 1 } else { u128::MAX };
                     a.hash()
                         .get_value()
-                        .map(|a| F::from_u64(a.to_u64_unchecked() & b))
+                        .map(|a| F::from_u128(a.to_u128_unchecked() & b))
                         .ok_or(SynthesisError::AssignmentMissing)
                 })?;
                 implies_pack(
@@ -953,15 +956,15 @@ fn synthesize_block<F: LurkField, CS: ConstraintSystem<F>, C: Coprocessor<F>>(
                 let c = AllocatedPtr::from_parts(tag, trunc);
                 bound_allocations.insert_ptr(tgt.clone(), c);
             }
-            Op::DivRem64(tgt, a, b) => {
+            Op::DivRem(tgt, a, b, n) => {
                 let a = bound_allocations.get_ptr(a)?.hash();
                 let b = bound_allocations.get_ptr(b)?.hash();
                 let div_rem = a.get_value().and_then(|a| {
                     b.get_value().map(|b| {
                         if not_dummy.get_value().unwrap() {
-                            let a = a.to_u64_unchecked();
-                            let b = b.to_u64_unchecked();
-                            (F::from_u64(a / b), F::from_u64(a % b))
+                            let a = a.to_u128_unchecked();
+                            let b = b.to_u128_unchecked();
+                            (F::from_u128(a / b), F::from_u128(a % b))
                         } else {
                             (F::ZERO, a)
                         }
@@ -973,9 +976,32 @@ fn synthesize_block<F: LurkField, CS: ConstraintSystem<F>, C: Coprocessor<F>>(
                     AllocatedNum::alloc_infallible(cs.namespace(|| "rem"), || div_rem.unwrap().1);
 
                 let diff = sub(cs.namespace(|| "diff for slot {slot}"), b, &rem)?;
-                implies_u64(cs.namespace(|| "div_u64"), not_dummy, &div)?;
-                implies_u64(cs.namespace(|| "rem_u64"), not_dummy, &rem)?;
-                implies_u64(cs.namespace(|| "diff_u64"), not_dummy, &diff)?;
+                implies_uint(cs.namespace(|| "div_uint"), not_dummy, &div, *n)?;
+                implies_uint(cs.namespace(|| "rem_uint"), not_dummy, &rem, *n)?;
+                implies_uint(cs.namespace(|| "diff_uint"), not_dummy, &diff, *n)?;
+
+                if *n > 64 {
+                    // With operands over 64 bits, `b * div` could wrap around the
+                    // field modulus. A correct division has `min(b, div)^2 <= a`,
+                    // so we also require one of the factors to fit in 64 bits
+                    let min = AllocatedNum::alloc_infallible(cs.namespace(|| "min"), || {
+                        let div = div_rem.unwrap().0;
+                        if not_dummy.get_value().unwrap() {
+                            let b = b.get_value().unwrap();
+                            if b.to_u128_unchecked() < div.to_u128_unchecked() {
+                                return b;
+                            }
+                        }
+                        div
+                    });
+                    cs.enforce(
+                        || "min is either b or div",
+                        |lc| lc + min.get_variable() - b.get_variable(),
+                        |lc| lc + min.get_variable() - div.get_variable(),
+                        |lc| lc,
+                    );
+                    implies_uint(cs.namespace(|| "min_u64"), not_dummy, &min, 64)?;
+                }
 
                 enforce_product_and_sum(&mut cs, || "enforce a = b * div + rem", b, &div, &rem, a);
                 let tag = ctx.global_allocator.get_tag_cloned(&Num)?;
@@ -984,6 +1010,101 @@ fn synthesize_block<F: LurkField, CS: ConstraintSystem<F>, C: Coprocessor<F>>(
                 bound_allocations.insert_ptr(tgt[0].clone(), div_ptr);
                 bound_allocations.insert_ptr(tgt[1].clone(), rem_ptr);
             }
+            Op::BitAnd(tgt, a, b, n) | Op::BitOr(tgt, a, b, n) | Op::BitXor(tgt, a, b, n) => {
+                assert!(*n <= 128);
+                let a = bound_allocations.get_ptr(a)?;
+                let b = bound_allocations.get_ptr(b)?;
+                let (a_preimg, a_bits) = &ctx.bit_decomp_slots[next_slot.consume_bit_decomp()];
+                let (b_preimg, b_bits) = &ctx.bit_decomp_slots[next_slot.consume_bit_decomp()];
+                implies_equal(
+                    &mut cs.namespace(|| "implies equal for a_preimg"),
+                    not_dummy,
+                    a.hash(),
+                    &a_preimg[0],
+                );
+                implies_equal(
+                    &mut cs.namespace(|| "implies equal for b_preimg"),
+                    not_dummy,
+                    b.hash(),
+                    &b_preimg[0],
+                );
+                let (AllocatedVal::Bits(a_bits), AllocatedVal::Bits(b_bits)) = (a_bits, b_bits)
+                else {
+                    panic!("Expected bits")
+                };
+                let bits = a_bits[0..*n as usize]
+                    .iter()
+                    .zip(&b_bits[0..*n as usize])
+                    .enumerate()
+                    .map(|(i, (a_bit, b_bit))| {
+                        let cs = cs.namespace(|| format!("bit {i}"));
+                        match op {
+                            Op::BitAnd(..) => Boolean::and(cs, a_bit, b_bit),
+                            Op::BitOr(..) => or(cs, a_bit, b_bit),
+                            _ => Boolean::xor(cs, a_bit, b_bit),
+                        }
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+                let c = AllocatedNum::alloc(cs.namespace(|| "bitwise"), || {
+                    let mask = if *n < 128 { (1 << *n) - 1 } else { u128::MAX };
+                    let f = a
+                        .hash()
+                        .get_value()
+                        .ok_or(SynthesisError::AssignmentMissing)?;
+                    let g = b
+                        .hash()
+                        .get_value()
+                        .ok_or(SynthesisError::AssignmentMissing)?;
+                    let f = f.to_u128_unchecked() & mask;
+                    let g = g.to_u128_unchecked() & mask;
+                    let c = match op {
+                        Op::BitAnd(..) => f & g,
+                        Op::BitOr(..) => f | g,
+                        _ => f ^ g,
+                    };
+                    Ok(F::from_u128(c))
+                })?;
+                implies_pack(cs.namespace(|| "implies_bitwise"), not_dummy, &bits, &c);
+                let tag = ctx.global_allocator.get_tag_cloned(&Num)?;
+                let c = AllocatedPtr::from_parts(tag, c);
+                bound_allocations.insert_ptr(tgt.clone(), c);
+            }
+            Op::Pow2(tgt, a, n) => {
+                assert!(*n <= 7);
+                let a = bound_allocations.get_ptr(a)?;
+                let (preallocated_preimg, a_bits) =
+                    &ctx.bit_decomp_slots[next_slot.consume_bit_decomp()];
+                implies_equal(
+                    &mut cs.namespace(|| "implies equal component pow2"),
+                    not_dummy,
+                    a.hash(),
+                    &preallocated_preimg[0],
+                );
+                let AllocatedVal::Bits(a_bits) = a_bits else {
+                    panic!("Expected bits")
+                };
+                // `2^k` is the product of the `2^(2^i)` for every bit `i` set in `k`
+                let mut pow = ctx.global_allocator.get_const_cloned(F::ONE)?;
+                for (i, bit) in a_bits[0..*n as usize].iter().enumerate() {
+                    let factor = F::from_u128(1u128 << (1 << i));
+                    let next = AllocatedNum::alloc(cs.namespace(|| format!("pow {i}")), || {
+                        let pow = pow.get_value().ok_or(SynthesisError::AssignmentMissing)?;
+                        let bit = bit.get_value().ok_or(SynthesisError::AssignmentMissing)?;
+                        Ok(if bit { pow * factor } else { pow })
+                    })?;
+                    // next = pow * (1 + (factor - 1) * bit)
+                    cs.enforce(
+                        || format!("enforce pow {i}"),
+                        |lc| lc + pow.get_variable(),
+                        |lc| add_to_lc::<F, CS>(bit, lc + CS::one(), factor - F::ONE),
+                        |lc| lc + next.get_variable(),
+                    );
+                    pow = next;
+                }
+                let tag = ctx.global_allocator.get_tag_cloned(&Num)?;
+                let c = AllocatedPtr::from_parts(tag, pow);
+                bound_allocations.insert_ptr(tgt.clone(), c);
+            }
             Op::Emit(_) | Op::Unit(_) => (),
             Op::Hide(tgt, sec, pay) => {
                 let sec = bound_allocations.get_ptr(sec)?;
@@ -1437,169 +1558,225 @@ impl Func {
         ) -> usize {
             let mut num_constraints = 0;
             for op in &block.ops {
-                match op {
-                    Op::Call(_, func, _) => {
-                        num_constraints += recurse(&func.body, globals, store, is_nested);
-                    }
-                    Op::Zero(_, tag) => {
-                        // constrain tag and hash
-                        globals.insert(FWrap(tag.to_field()));
-                        globals.insert(FWrap(F::ZERO));
-                    }
-                    Op::Hash3Zeros(_, tag) => {
-                        // constrain tag and hash
-                        globals.insert(FWrap(tag.to_field()));
-                        globals.insert(FWrap(store.hash3zeros));
-                    }
-                    Op::Hash4Zeros(_, tag) => {
-                        // constrain tag and hash
-                        globals.insert(FWrap(tag.to_field()));
-                        globals.insert(FWrap(store.hash4zeros));
-                    }
-                    Op::Hash6Zeros(_, tag) => {
-                        // constrain tag and hash
-                        globals.insert(FWrap(tag.to_field()));
-                        globals.insert(FWrap(store.hash6zeros));
-                    }
-                    Op::Hash8Zeros(_, tag) => {
-                        // constrain tag and hash
-                        globals.insert(FWrap(tag.to_field()));
-                        globals.insert(FWrap(store.hash8zeros));
-                    }
-                    Op::Lit(_, lit) => {
-                        let lit_ptr = lit.to_ptr(store);
-                        let lit_z_ptr = store.hash_ptr(&lit_ptr);
-                        globals.insert(FWrap(lit_z_ptr.tag_field()));
-                        globals.insert(FWrap(*lit_z_ptr.value()));
-                    }
-                    Op::Cast(_, tag, _) => {
-                        globals.insert(FWrap(tag.to_field()));
-                    }
-                    Op::EqTag(..) | Op::EqVal(..) => {
-                        num_constraints += 3;
-                    }
-                    Op::Add(..) | Op::Sub(..) | Op::Mul(..) => {
-                        globals.insert(FWrap(Num.to_field()));
-                        num_constraints += 1;
-                    }
-                    Op::Div(..) => {
-                        globals.insert(FWrap(Num.to_field()));
-                        globals.insert(FWrap(F::ONE));
-                        num_constraints += 5;
-                    }
-                    Op::Lt(..) => {
-                        globals.insert(FWrap(Num.to_field()));
-                        num_constraints += 11;
-                    }
-                    Op::Trunc(..) => {
-                        globals.insert(FWrap(Num.to_field()));
-                        // 1 implies_equal, 1 implies_pack
-                        num_constraints += 2;
-                    }
-                    Op::DivRem64(..) => {
-                        globals.insert(FWrap(Num.to_field()));
-                        // three implies_u64, one sub and one linear
-                        num_constraints += 197;
-                    }
-                    Op::Not(..) | Op::Emit(_) | Op::Cproc(..) | Op::Copy(..) | Op::Unit(_) => (),
-                    Op::Cons2(_, tag, _) => {
-                        // tag for the image
-                        globals.insert(FWrap(tag.to_field()));
-                        // tag and hash for 2 preimage pointers
-                        num_constraints += 4;
-                    }
-                    Op::Cons3(_, tag, _) => {
-                        // tag for the image
-                        globals.insert(FWrap(tag.to_field()));
-                        // tag and hash for 3 preimage pointers
-                        num_constraints += 6;
-                    }
-                    Op::Cons4(_, tag, _) => {
-                        // tag for the image
-                        globals.insert(FWrap(tag.to_field()));
-                        // tag and hash for 4 preimage pointers
-                        num_constraints += 8;
-                    }
-                    Op::And(..)
-                    | Op::Or(..)
-                    | Op::Decons2(..)
-                    | Op::Decons3(..)
-                    | Op::Decons4(..) => {
-                        // one constraint for the image's hash
-                        num_constraints += 1;
-                    }
-                    Op::Hide(..) => {
-                        num_constraints += 4;
-                        globals.insert(FWrap(Num.to_field()));
-                        globals.insert(FWrap(Comm.to_field()));
-                    }
-                    Op::Open(..) => {
-                        num_constraints += 2;
-                        globals.insert(FWrap(Num.to_field()));
-                        globals.insert(FWrap(Comm.to_field()));
-                    }
-                }
+                num_constraints += match op {
+                    Op::Call(_, func, _) => recurse(&func.body, globals, store, is_nested),
+                    _ => op.num_constraints(globals, store),
+                };
             }
+            num_constraints += block.ctrl.num_constraints(globals, is_nested);
             match &block.ctrl {
-                Ctrl::Return(vars) => num_constraints + 2 * vars.len(),
+                Ctrl::Return(..) => (),
                 Ctrl::If(_, true_block, false_block) => {
-                    num_constraints
-                        + if is_nested { 2 } else { 0 }
-                        + recurse(true_block, globals, store, true)
-                        + recurse(false_block, globals, store, true)
+                    num_constraints += recurse(true_block, globals, store, true);
+                    num_constraints += recurse(false_block, globals, store, true);
                 }
                 Ctrl::MatchTag(_, cases, def) => {
-                    // We allocate one boolean per case and constrain it once
-                    // per case. Then we add 1 constraint to enforce only one
-                    // case was selected
-                    num_constraints += 2 * cases.len() + 1;
-
                     for block in cases.values() {
                         num_constraints += recurse(block, globals, store, true);
                     }
                     if let Some(def) = def {
-                        // constraints for the boolean, the unequalities and the default case
-                        num_constraints += 1 + cases.len();
                         num_constraints += recurse(def, globals, store, true);
                     }
-                    num_constraints
                 }
                 Ctrl::MatchSymbol(_, cases, def) => {
-                    // First we enforce that the tag of the pointer being matched on
-                    // is Sym
-                    num_constraints += 1;
-                    globals.insert(FWrap(Sym.to_field()));
-                    // We allocate one boolean per case and constrain it once
-                    // per case. Then we add 1 constraint to enforce only one
-                    // case was selected
-                    num_constraints += 2 * cases.len() + 1;
-
                     for block in cases.values() {
                         num_constraints += recurse(block, globals, store, true);
                     }
                     if let Some(def) = def {
-                        // constraints for the boolean, the unequalities and the default case
-                        num_constraints += 1 + cases.len();
                         num_constraints += recurse(def, globals, store, true);
                     }
-                    num_constraints
                 }
             }
+            num_constraints
         }
         let globals = &mut HashSet::default();
-        let bit_decomp_cost = match F::FIELD {
-            LanguageField::Pallas => 298,
-            LanguageField::Vesta => 301,
-            _ => todo!(),
-        };
-
-        // fixed cost for each slot
-        let slot_constraints = store.hash4_cost() * self.slots_count.hash4
+        let num_constraints = recurse(&self.body, globals, store, false);
+        self.slot_constraints(store) + num_constraints + globals.len()
+    }
+
+    /// The fixed number of constraints spent on the slots of the function
+    pub(crate) fn slot_constraints<F: LurkField>(&self, store: &Store<F>) -> usize {
+        store.hash4_cost() * self.slots_count.hash4
             + store.hash6_cost() * self.slots_count.hash6
             + store.hash8_cost() * self.slots_count.hash8
             + store.hash3_cost() * self.slots_count.commitment
-            + bit_decomp_cost * self.slots_count.bit_decomp;
-        let num_constraints = recurse(&self.body, globals, store, false);
-        slot_constraints + num_constraints + globals.len()
+            + bit_decomp_cost::<F>() * self.slots_count.bit_decomp
+    }
+}
+
+impl Op {
+    /// The number of constraints that synthesizing the operation creates,
+    /// besides the ones for slots. The constants it allocates are added to
+    /// `globals`, each of which costs one constraint for the whole function.
+    /// Called functions are accounted for by their bodies, so `Call`s cost
+    /// nothing on their own
+    pub(crate) fn num_constraints<F: LurkField>(
+        &self,
+        globals: &mut HashSet<FWrap<F>>,
+        store: &Store<F>,
+    ) -> usize {
+        let mut num_constraints = 0;
+        match self {
+            Op::Zero(_, tag) => {
+                // constrain tag and hash
+                globals.insert(FWrap(tag.to_field()));
+                globals.insert(FWrap(F::ZERO));
+            }
+            Op::Hash3Zeros(_, tag) => {
+                // constrain tag and hash
+                globals.insert(FWrap(tag.to_field()));
+                globals.insert(FWrap(store.hash3zeros));
+            }
+            Op::Hash4Zeros(_, tag) => {
+                // constrain tag and hash
+                globals.insert(FWrap(tag.to_field()));
+                globals.insert(FWrap(store.hash4zeros));
+            }
+            Op::Hash6Zeros(_, tag) => {
+                // constrain tag and hash
+                globals.insert(FWrap(tag.to_field()));
+                globals.insert(FWrap(store.hash6zeros));
+            }
+            Op::Hash8Zeros(_, tag) => {
+                // constrain tag and hash
+                globals.insert(FWrap(tag.to_field()));
+                globals.insert(FWrap(store.hash8zeros));
+            }
+            Op::Lit(_, lit) => {
+                let lit_ptr = lit.to_ptr(store);
+                let lit_z_ptr = store.hash_ptr(&lit_ptr);
+                globals.insert(FWrap(lit_z_ptr.tag_field()));
+                globals.insert(FWrap(*lit_z_ptr.value()));
+            }
+            Op::Cast(_, tag, _) => {
+                globals.insert(FWrap(tag.to_field()));
+            }
+            Op::EqTag(..) | Op::EqVal(..) => {
+                num_constraints += 3;
+            }
+            Op::Add(..) | Op::Sub(..) | Op::Mul(..) => {
+                globals.insert(FWrap(Num.to_field()));
+                num_constraints += 1;
+            }
+            Op::Div(..) => {
+                globals.insert(FWrap(Num.to_field()));
+                globals.insert(FWrap(F::ONE));
+                num_constraints += 5;
+            }
+            Op::Lt(..) => {
+                globals.insert(FWrap(Num.to_field()));
+                num_constraints += 11;
+            }
+            Op::Trunc(..) => {
+                globals.insert(FWrap(Num.to_field()));
+                // 1 implies_equal, 1 implies_pack
+                num_constraints += 2;
+            }
+            Op::DivRem(_, _, _, n) => {
+                globals.insert(FWrap(Num.to_field()));
+                // three implies_uint, one sub and one linear
+                num_constraints += 3 * (*n as usize + 1) + 2;
+                if *n > 64 {
+                    // the factor selection and its 64 bits implies_uint
+                    num_constraints += 66;
+                }
+            }
+            Op::BitAnd(_, _, _, n) | Op::BitOr(_, _, _, n) | Op::BitXor(_, _, _, n) => {
+                globals.insert(FWrap(Num.to_field()));
+                // 2 implies_equal, one per bit and 1 implies_pack
+                num_constraints += *n as usize + 3;
+            }
+            Op::Pow2(_, _, n) => {
+                globals.insert(FWrap(Num.to_field()));
+                globals.insert(FWrap(F::ONE));
+                // 1 implies_equal and one per bit
+                num_constraints += *n as usize + 1;
+            }
+            Op::Not(..)
+            | Op::Emit(_)
+            | Op::Cproc(..)
+            | Op::Copy(..)
+            | Op::Unit(_)
+            | Op::Call(..) => (),
+            Op::Cons2(_, tag, _) => {
+                // tag for the image
+                globals.insert(FWrap(tag.to_field()));
+                // tag and hash for 2 preimage pointers
+                num_constraints += 4;
+            }
+            Op::Cons3(_, tag, _) => {
+                // tag for the image
+                globals.insert(FWrap(tag.to_field()));
+                // tag and hash for 3 preimage pointers
+                num_constraints += 6;
+            }
+            Op::Cons4(_, tag, _) => {
+                // tag for the image
+                globals.insert(FWrap(tag.to_field()));
+                // tag and hash for 4 preimage pointers
+                num_constraints += 8;
+            }
+            Op::And(..) | Op::Or(..) | Op::Decons2(..) | Op::Decons3(..) | Op::Decons4(..) => {
+                // one constraint for the image's hash
+                num_constraints += 1;
+            }
+            Op::Hide(..) => {
+                num_constraints += 4;
+                globals.insert(FWrap(Num.to_field()));
+                globals.insert(FWrap(Comm.to_field()));
+            }
+            Op::Open(..) => {
+                num_constraints += 2;
+                globals.insert(FWrap(Num.to_field()));
+                globals.insert(FWrap(Comm.to_field()));
+            }
+        }
+        num_constraints
+    }
+}
+
+impl Ctrl {
+    /// The number of constraints that synthesizing the control statement
+    /// creates, not counting the ones from its nested blocks
+    pub(crate) fn num_constraints<F: LurkField>(
+        &self,
+        globals: &mut HashSet<FWrap<F>>,
+        is_nested: bool,
+    ) -> usize {
+        match self {
+            Ctrl::Return(vars) => 2 * vars.len(),
+            Ctrl::If(..) => {
+                if is_nested {
+                    2
+                } else {
+                    0
+                }
+            }
+            Ctrl::MatchTag(_, cases, def) => {
+                // We allocate one boolean per case and constrain it once
+                // per case. Then we add 1 constraint to enforce only one
+                // case was selected
+                let mut num_constraints = 2 * cases.len() + 1;
+                if def.is_some() {
+                    // constraints for the boolean and the unequalities
+                    num_constraints += 1 + cases.len();
+                }
+                num_constraints
+            }
+            Ctrl::MatchSymbol(_, cases, def) => {
+                // First we enforce that the tag of the pointer being matched on
+                // is Sym
+                globals.insert(FWrap(Sym.to_field()));
+                // We allocate one boolean per case and constrain it once
+                // per case. Then we add 1 constraint to enforce only one
+                // case was selected
+                let mut num_constraints = 1 + 2 * cases.len() + 1;
+                if def.is_some() {
+                    // constraints for the boolean and the unequalities
+                    num_constraints += 1 + cases.len();
+                }
+                num_constraints
+            }
+        }
     }
 }
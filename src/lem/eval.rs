@@ -239,17 +239,35 @@ pub fn evaluate_simple<F: LurkField, C: Coprocessor<F>>(
 pub struct EvalConfig<'a, F: LurkField, C: Coprocessor<F>> {
     lang: &'a Lang<F, C>,
     ivc: bool,
+    optimize: bool,
 }
 
 impl<'a, F: LurkField, C: Coprocessor<F>> EvalConfig<'a, F, C> {
     #[inline]
     pub fn new_ivc(lang: &'a Lang<F, C>) -> Self {
-        Self { lang, ivc: true }
+        Self {
+            lang,
+            ivc: true,
+            optimize: false,
+        }
     }
 
     #[inline]
     pub fn new_nivc(lang: &'a Lang<F, C>) -> Self {
-        Self { lang, ivc: false }
+        Self {
+            lang,
+            ivc: false,
+            optimize: false,
+        }
+    }
+
+    /// Makes the step function go through `Func::optimized`. It's off by
+    /// default, so `eval_step` and the step functions of the provers are the
+    /// functions as written
+    #[inline]
+    pub fn with_optimizations(mut self) -> Self {
+        self.optimize = true;
+        self
     }
 
     #[inline]
@@ -264,18 +282,25 @@ impl<'a, F: LurkField, C: Coprocessor<F>> EvalConfig<'a, F, C> {
 /// coprocessors present in `Lang` and their circuit will go in the circuit of
 /// the step function. In the NIVC case, the step function won't be able to reduce
 /// calls to coprocessors and sets up a loop via the `Expr::Cproc` tag, meaning
-/// that the reduction must be done from outside.
+/// that the reduction must be done from outside. The optimization passes only
+/// run if the config asks for them, see `EvalConfig::with_optimizations`.
 pub fn make_eval_step_from_config<F: LurkField, C: Coprocessor<F>>(
     ec: &EvalConfig<'_, F, C>,
 ) -> Func {
-    make_eval_step(
+    let func = make_eval_step(
         &ec.lang
             .coprocessors()
             .iter()
             .map(|(s, c)| (s, c.arity()))
             .collect::<Vec<_>>(),
         ec.ivc,
-    )
+    );
+    if ec.optimize {
+        func.optimized()
+            .expect("Optimizations preserve the validity of the step function")
+    } else {
+        func
+    }
 }
 
 fn make_eval_step(cprocs: &[(&Symbol, usize)], ivc: bool) -> Func {
@@ -321,7 +346,8 @@ fn car_cdr() -> Func {
 ///
 /// ```ignore
 /// run_cproc(cproc, env, cont): 3 {
-///     let err: Cont::Error = HASH_8_ZEROS;
+///     let arity_mismatch = Keyword("arity-mismatch");
+///     let foo: Expr::Nil;
 ///     let nil = Symbol("nil");
 ///     let nil = cast(nil, Expr::Nil);
 ///     match cproc.tag {
@@ -346,10 +372,13 @@ fn car_cdr() -> Func {
 ///                                 Op::Cproc([expr, env, cont], x, [x0, x1, ..., x{n-1}, env, cont]);
 ///                                 return (expr, env, cont);
 ///                             }
+///                             let err: Cont::Error = cons4(arity_mismatch, evaluated_args_cp, foo, foo);
 ///                             return (evaluated_args_cp, env, err);
 ///                         }
+///                         let err: Cont::Error = cons4(arity_mismatch, evaluated_args_cp, foo, foo);
 ///                         return (evaluated_args_cp, env, err);
 ///                     }
+///                     let err: Cont::Error = cons4(arity_mismatch, evaluated_args_cp, foo, foo);
 ///                     return (evaluated_args_cp, env, err);
 ///                 }
 ///             };
@@ -376,7 +405,12 @@ fn run_cproc(cproc_sym: Symbol, arity: usize) -> Func {
     let cproc_name = Var::new("cproc_name");
     let cproc_out = vec![expr.clone(), env.clone(), cont.clone()];
     let func_out = vec![expr, env.clone(), cont.clone()];
-    let err_block = Block::ctrl(ctrl!(return (evaluated_args_cp, env, err)));
+    // unlike in the step function, there's no `make_thunk` after a coprocessor
+    // call in NIVC, so the error continuation must be built right away
+    let err_block = Block {
+        ops: vec![op!(let err: Cont::Error = cons4(arity_mismatch, evaluated_args_cp, foo, foo))],
+        ctrl: ctrl!(return (evaluated_args_cp, env, err)),
+    };
     let def_block = Block::ctrl(ctrl!(return (cproc, env, cont)));
     let mut cproc_inp = (0..arity)
         .map(|i| Var(format!("x{i}").into()))
@@ -435,7 +469,8 @@ fn run_cproc(cproc_sym: Symbol, arity: usize) -> Func {
             vec![]
         } else {
             vec![
-                op!(let err: Cont::Error = HASH_8_ZEROS),
+                op!(let arity_mismatch = Keyword("arity-mismatch")),
+                op!(let foo: Expr::Nil),
                 op!(let nil = Symbol("nil")),
                 op!(let nil = cast(nil, Expr::Nil)),
             ]
@@ -517,11 +552,11 @@ fn is_cproc(cprocs: &[(&Symbol, usize)]) -> Func {
 ///
 /// ```ignore
 /// match_and_run_cproc(cproc_name, evaluated_args, env, cont): 4 {
-///     let err: Cont::Error = HASH_8_ZEROS;
 ///     let nil = Symbol("nil");
 ///     let nil = cast(nil, Expr::Nil);
 ///     let makethunk = Symbol("make-thunk");
 ///     let errctrl = Symbol("error");
+///     let arity_mismatch = Keyword("arity-mismatch");
 ///     let ret = Symbol("return");
 ///     match symbol cproc_name {
 ///         x => {
@@ -541,7 +576,8 @@ fn is_cproc(cprocs: &[(&Symbol, usize)]) -> Func {
 ///                         Op::Cproc([expr, env, cont], x, [x0, x1, ..., x{n-1}, env, cont]);
 ///                         match cont.tag {
 ///                             Cont::Error => {
-///                                 return (expr, env, err, errctrl);
+///                                 // keep the error raised by the coprocessor
+///                                 return (expr, env, cont, ret);
 ///                             }
 ///                             Cont::Terminal => {
 ///                                 return (expr, env, cont, ret);
@@ -549,11 +585,11 @@ fn is_cproc(cprocs: &[(&Symbol, usize)]) -> Func {
 ///                         };
 ///                         return (expr, env, cont, makethunk);
 ///                     }
-///                     return (evaluated_args_cp, env, err, errctrl);
+///                     return (evaluated_args_cp, env, arity_mismatch, errctrl);
 ///                 }
-///                 return (evaluated_args_cp, env, err, errctrl);
+///                 return (evaluated_args_cp, env, arity_mismatch, errctrl);
 ///             }
-///             return (evaluated_args_cp, env, err, errctrl);
+///             return (evaluated_args_cp, env, arity_mismatch, errctrl);
 ///         }
 ///         ...
 ///     }
@@ -574,13 +610,14 @@ fn match_and_run_cproc(cprocs: &[(&Symbol, usize)]) -> Func {
     let is_nil = Var::new("is_nil");
     let cproc_out = vec![expr.clone(), env.clone(), cont.clone()];
     let func_out = vec![expr, env.clone(), cont.clone(), Var::new("makethunk")];
-    let err_block = Block::ctrl(ctrl!(return (evaluated_args_cp, env, err, errctrl)));
-    let err_block_from_cproc = Block::ctrl(ctrl!(return (expr, env, err, errctrl)));
+    let err_block = Block::ctrl(ctrl!(
+        return (evaluated_args_cp, env, arity_mismatch, errctrl)
+    ));
     let ret_block_from_cproc = Block::ctrl(ctrl!(return (expr, env, cont, ret)));
     let check_cproc_error_ctrl = Ctrl::match_tag(
         cont.clone(),
         vec![
-            (Tag::Cont(Error), err_block_from_cproc),
+            (Tag::Cont(Error), ret_block_from_cproc.clone()),
             (Tag::Cont(Terminal), ret_block_from_cproc),
         ],
         Some(Block::ctrl(Ctrl::Return(func_out))),
@@ -636,16 +673,14 @@ fn match_and_run_cproc(cprocs: &[(&Symbol, usize)]) -> Func {
     let func_inp = vec![cproc_name, evaluated_args, env, cont];
     let ops = if max_arity == 0 {
         vec![
-            op!(let err: Cont::Error = HASH_8_ZEROS),
             op!(let makethunk = Symbol("make-thunk")),
-            op!(let errctrl = Symbol("error")),
             op!(let ret = Symbol("return")),
         ]
     } else {
         vec![
-            op!(let err: Cont::Error = HASH_8_ZEROS),
             op!(let makethunk = Symbol("make-thunk")),
             op!(let errctrl = Symbol("error")),
+            op!(let arity_mismatch = Keyword("arity-mismatch")),
             op!(let ret = Symbol("return")),
             op!(let nil = Symbol("nil")),
             op!(let nil = cast(nil, Expr::Nil)),
@@ -696,18 +731,64 @@ fn reduce(cprocs: &[(&Symbol, usize)]) -> Func {
         let expanded: Expr::Cons = cons2(head, expanded_0);
         return (expanded)
     });
-    let choose_let_cont = func!(choose_let_cont(head, var, env, expanded, cont): 1 => {
+    let choose_let_cont = func!(choose_let_cont(head, var, env, body, body1, rest_bindings, cont): 1 => {
         match symbol head {
             "let" => {
+                let (expanded) = expand_bindings(head, body, body1, rest_bindings);
                 let cont: Cont::Let = cons4(var, env, expanded, cont);
                 return (cont)
             }
             "letrec" => {
-                let cont: Cont::LetRec = cons4(var, env, expanded, cont);
+                // The bindings of a `letrec` aren't expanded into nested forms
+                // because they must all end up in the same recursive frame
+                let rest: Expr::Cons = cons2(rest_bindings, body);
+                let cont: Cont::LetRec = cons4(var, env, rest, cont);
                 return (cont)
             }
         }
     });
+    // Returns the recursive frame that `frame` is a suffix of. A lookup consumes
+    // a frame one binding at a time, so we retrieve the full frame from the
+    // lookup continuation if it has already started consuming the same frame
+    let full_frame = func!(full_frame(frame, frame_env, cont): 1 => {
+        match cont.tag {
+            Cont::Lookup => {
+                let (_saved_env, _continuation, full_frame, full_frame_env) = decons4(cont);
+                let same_tag = eq_tag(frame_env, full_frame_env);
+                let same_val = eq_val(frame_env, full_frame_env);
+                let same_env = and(same_tag, same_val);
+                if same_env {
+                    return (full_frame)
+                }
+                return (frame)
+            }
+        };
+        return (frame)
+    });
+    // Tells whether `params` has the form `(&rest <var>)`
+    let is_rest_params = func!(is_rest_params(params): 1 => {
+        let nil = Symbol("nil");
+        let nil = cast(nil, Expr::Nil);
+        let (param, more_params) = car_cdr(params);
+        match symbol param {
+            "&rest" => {
+                let (var, end) = car_cdr(more_params);
+                match var.tag {
+                    Expr::Sym => {
+                        match end.tag {
+                            Expr::Nil => {
+                                let t = Symbol("t");
+                                return (t)
+                            }
+                        };
+                        return (nil)
+                    }
+                };
+                return (nil)
+            }
+        };
+        return (nil)
+    });
     let get_unop = func!(get_unop(head): 1 => {
         let nil = Symbol("nil");
         let nil = cast(nil, Expr::Nil);
@@ -728,10 +809,30 @@ fn reduce(cprocs: &[(&Symbol, usize)]) -> Func {
                 let op: Op1::Num;
                 return (op);
             }
+            "u8" => {
+                let op: Op1::U8;
+                return (op);
+            }
+            "u16" => {
+                let op: Op1::U16;
+                return (op);
+            }
+            "u32" => {
+                let op: Op1::U32;
+                return (op);
+            }
             "u64" => {
                 let op: Op1::U64;
                 return (op);
             }
+            "u128" => {
+                let op: Op1::U128;
+                return (op);
+            }
+            "i64" => {
+                let op: Op1::I64;
+                return (op);
+            }
             "comm" => {
                 let op: Op1::Comm;
                 return (op);
@@ -756,6 +857,30 @@ fn reduce(cprocs: &[(&Symbol, usize)]) -> Func {
                 let op: Op1::Emit;
                 return (op);
             }
+            "string-length" => {
+                let op: Op1::StringLength;
+                return (op);
+            }
+            "symbol->string" => {
+                let op: Op1::SymbolToString;
+                return (op);
+            }
+            "string->symbol" => {
+                let op: Op1::StringToSymbol;
+                return (op);
+            }
+            "num->string" => {
+                let op: Op1::NumToString;
+                return (op);
+            }
+            "vector-length" => {
+                let op: Op1::VectorLength;
+                return (op);
+            }
+            "type-of" => {
+                let op: Op1::TypeOf;
+                return (op);
+            }
         };
         return (nil)
     });
@@ -819,6 +944,58 @@ fn reduce(cprocs: &[(&Symbol, usize)]) -> Func {
                 let op: Op2::GreaterEqual;
                 return (op);
             }
+            "logand" => {
+                let op: Op2::LogAnd;
+                return (op);
+            }
+            "logor" => {
+                let op: Op2::LogOr;
+                return (op);
+            }
+            "logxor" => {
+                let op: Op2::LogXor;
+                return (op);
+            }
+            "shl" => {
+                let op: Op2::Shl;
+                return (op);
+            }
+            "shr" => {
+                let op: Op2::Shr;
+                return (op);
+            }
+            "rotl" => {
+                let op: Op2::Rotl;
+                return (op);
+            }
+            "rotr" => {
+                let op: Op2::Rotr;
+                return (op);
+            }
+            "apply" => {
+                let op: Op2::Apply;
+                return (op);
+            }
+            "string-append" => {
+                let op: Op2::StringAppend;
+                return (op);
+            }
+            "substring" => {
+                let op: Op2::Substring;
+                return (op);
+            }
+            "string<" => {
+                let op: Op2::StringLess;
+                return (op);
+            }
+            "vector-ref" => {
+                let op: Op2::VectorRef;
+                return (op);
+            }
+            "vector-set" => {
+                let op: Op2::VectorSet;
+                return (op);
+            }
         };
         return (nil)
     });
@@ -831,19 +1008,11 @@ fn reduce(cprocs: &[(&Symbol, usize)]) -> Func {
                 return (head, env, cont, ret)
             }
             Expr::Cons => {
+                // the arguments after the first are kept as the pending
+                // arguments of this call, which mark where it ends
                 let (arg, more_args) = decons2(rest);
-                match more_args.tag {
-                    Expr::Nil => {
-                        let cont: Cont::Call = cons4(arg, env, cont, foo);
-                        return (head, env, cont, ret)
-                    }
-                };
-                let nil = Symbol("nil");
-                let nil = cast(nil, Expr::Nil);
-                let expanded_inner0: Expr::Cons = cons2(arg, nil);
-                let expanded_inner: Expr::Cons = cons2(head, expanded_inner0);
-                let expanded: Expr::Cons = cons2(expanded_inner, more_args);
-                return (expanded, env, cont, ret)
+                let cont: Cont::Call = cons4(arg, env, cont, more_args);
+                return (head, env, cont, ret)
             }
         }
     });
@@ -881,6 +1050,12 @@ fn reduce(cprocs: &[(&Symbol, usize)]) -> Func {
             return (expr, env, cont, ret)
         }
         let apply = Symbol("apply-continuation");
+        let unwind: Cont::Unwind;
+        let cont_is_unwind = eq_tag(cont, unwind);
+        if cont_is_unwind {
+            // a failure is being propagated, so `expr` is already evaluated
+            return (expr, env, cont, apply)
+        }
         let thunk: Expr::Thunk;
         let sym: Expr::Sym;
         let cons: Expr::Cons;
@@ -894,6 +1069,10 @@ fn reduce(cprocs: &[(&Symbol, usize)]) -> Func {
             return (expr, env, cont, apply)
         }
         let errctrl = Symbol("error");
+        let unbound_var = Keyword("unbound-var");
+        let invalid_form = Keyword("invalid-form");
+        let arity_mismatch = Keyword("arity-mismatch");
+        let not_a_function = Keyword("not-a-function");
         let t = Symbol("t");
         let nil = Symbol("nil");
         let nil = cast(nil, Expr::Nil);
@@ -914,14 +1093,14 @@ fn reduce(cprocs: &[(&Symbol, usize)]) -> Func {
 
                 match env.tag {
                     Expr::Nil => {
-                        return (expr, env, err, errctrl)
+                        return (expr, env, unbound_var, errctrl)
                     }
                 };
 
                 let (binding, smaller_env) = car_cdr(env);
                 match binding.tag {
                     Expr::Nil => {
-                        return (expr, env, err, errctrl)
+                        return (expr, env, unbound_var, errctrl)
                     }
                 };
 
@@ -938,19 +1117,25 @@ fn reduce(cprocs: &[(&Symbol, usize)]) -> Func {
                                 return (expr, smaller_env, cont, ret)
                             }
                         };
-                        let cont: Cont::Lookup = cons4(env, cont, foo, foo);
+                        // no recursive frame is being consumed yet
+                        let no_frame: Expr::Nil;
+                        let no_frame_env: Expr::Nil;
+                        let cont: Cont::Lookup = cons4(env, cont, no_frame, no_frame_env);
                         return (expr, smaller_env, cont, ret)
                     }
                     Expr::Cons => {
                         let (v2, val2) = decons2(var_or_rec_binding);
+                        let (frame) = full_frame(binding, smaller_env, cont);
 
                         let eq_val = eq_val(v2, expr);
                         if eq_val {
                             match val2.tag {
                                 Expr::Fun => {
                                     // if `val2` is a closure, then extend its environment
+                                    // with the whole frame, so it can see every function
+                                    // defined by the same `letrec`
                                     let (arg, body, closed_env) = decons3(val2);
-                                    let extended: Expr::Cons = cons2(binding, closed_env);
+                                    let extended: Expr::Cons = cons2(frame, closed_env);
                                     // and return the extended closure
                                     let fun: Expr::Fun = cons3(arg, body, extended);
                                     return (fun, env, cont, apply)
@@ -961,16 +1146,19 @@ fn reduce(cprocs: &[(&Symbol, usize)]) -> Func {
                         }
                         let (env_to_use) = env_to_use(smaller_env, val_or_more_rec_env);
 
+                        // remember the full frame while it's being consumed
                         match cont.tag {
                             Cont::Lookup => {
+                                let (saved_env, continuation, _frame, _frame_env) = decons4(cont);
+                                let cont: Cont::Lookup = cons4(saved_env, continuation, frame, smaller_env);
                                 return (expr, env_to_use, cont, ret)
                             }
                         };
-                        let cont: Cont::Lookup = cons4(env, cont, foo, foo);
+                        let cont: Cont::Lookup = cons4(env, cont, frame, smaller_env);
                         return (expr, env_to_use, cont, ret)
                     }
                 };
-                return (expr, env, err, errctrl)
+                return (expr, env, invalid_form, errctrl)
             }
             Expr::Cons => {
                 // No need for `car_cdr` since the expression is already a `Cons`
@@ -980,7 +1168,7 @@ fn reduce(cprocs: &[(&Symbol, usize)]) -> Func {
                 let rest_is_nil_or_cons = or(rest_is_nil, rest_is_cons);
                 if !rest_is_nil_or_cons {
                     // rest's tag can only be Nil or Cons
-                    return (expr, env, err, errctrl);
+                    return (expr, env, invalid_form, errctrl);
                 }
                 match head.tag {
                     Expr::Sym => {
@@ -995,7 +1183,7 @@ fn reduce(cprocs: &[(&Symbol, usize)]) -> Func {
                             // Only a single body form allowed for now.
                             match body.tag {
                                 Expr::Nil => {
-                                    return (expr, env, err, errctrl)
+                                    return (expr, env, invalid_form, errctrl)
                                 }
                             };
                             match rest_body.tag {
@@ -1012,18 +1200,17 @@ fn reduce(cprocs: &[(&Symbol, usize)]) -> Func {
                                             let (val, end) = car_cdr(vals);
                                             match end.tag {
                                                 Expr::Nil => {
-                                                    let (expanded) = expand_bindings(head, body, body1, rest_bindings);
-                                                    let (cont) = choose_let_cont(head, var, env, expanded, cont);
+                                                    let (cont) = choose_let_cont(head, var, env, body, body1, rest_bindings, cont);
                                                     return (val, env, cont, ret)
                                                 }
                                             };
-                                            return (expr, env, err, errctrl)
+                                            return (expr, env, invalid_form, errctrl)
                                         }
                                     };
-                                    return (expr, env, err, errctrl)
+                                    return (expr, env, invalid_form, errctrl)
                                 }
                             };
-                            return (expr, env, err, errctrl)
+                            return (expr, env, invalid_form, errctrl)
                         }
                         match symbol head {
                             "lambda" => {
@@ -1032,12 +1219,32 @@ fn reduce(cprocs: &[(&Symbol, usize)]) -> Func {
 
                                 match arg.tag {
                                     Expr::Sym => {
+                                        match symbol arg {
+                                            "&rest" => {
+                                                // a variadic closure keeps its parameters as a list
+                                                let (is_rest_params) = is_rest_params(args);
+                                                let is_rest_params = eq_val(is_rest_params, t);
+                                                if is_rest_params {
+                                                    let function: Expr::Fun = cons3(args, body, env);
+                                                    return (function, env, cont, apply)
+                                                }
+                                                return (expr, env, invalid_form, errctrl)
+                                            }
+                                        };
                                         match cdr_args.tag {
                                             Expr::Nil => {
                                                 let function: Expr::Fun = cons3(arg, body, env);
                                                 return (function, env, cont, apply)
                                             }
                                         };
+                                        // the last fixed parameter before `&rest` isn't curried
+                                        // so the closure knows when to collect the rest
+                                        let (is_rest_params) = is_rest_params(cdr_args);
+                                        let is_rest_params = eq_val(is_rest_params, t);
+                                        if is_rest_params {
+                                            let function: Expr::Fun = cons3(args, body, env);
+                                            return (function, env, cont, apply)
+                                        }
                                         let inner: Expr::Cons = cons2(cdr_args, body);
                                         let l: Expr::Cons = cons2(head, inner);
                                         let inner_body: Expr::Cons = cons2(l, nil);
@@ -1045,7 +1252,7 @@ fn reduce(cprocs: &[(&Symbol, usize)]) -> Func {
                                         return (function, env, cont, apply)
                                     }
                                 };
-                                return (expr, env, err, errctrl)
+                                return (expr, env, invalid_form, errctrl)
                             }
                             "quote" => {
                                 let (quoted, end) = car_cdr(rest);
@@ -1055,7 +1262,7 @@ fn reduce(cprocs: &[(&Symbol, usize)]) -> Func {
                                         return (quoted, env, cont, apply)
                                     }
                                 };
-                                return (expr, env, err, errctrl)
+                                return (expr, env, invalid_form, errctrl)
                             }
                             "begin" => {
                                 let (arg1, more) = car_cdr(rest);
@@ -1071,7 +1278,7 @@ fn reduce(cprocs: &[(&Symbol, usize)]) -> Func {
                             "eval" => {
                                 match rest.tag {
                                     Expr::Nil => {
-                                        return (expr, env, err, errctrl)
+                                        return (expr, env, invalid_form, errctrl)
                                     }
                                 };
                                 let (arg1, more) = car_cdr(rest);
@@ -1090,7 +1297,7 @@ fn reduce(cprocs: &[(&Symbol, usize)]) -> Func {
                                 let (condition, more) = car_cdr(rest);
                                 match more.tag {
                                     Expr::Nil => {
-                                        return (expr, env, err, errctrl)
+                                        return (expr, env, invalid_form, errctrl)
                                     }
                                 };
                                 let cont: Cont::If = cons4(more, cont, foo, foo);
@@ -1102,7 +1309,57 @@ fn reduce(cprocs: &[(&Symbol, usize)]) -> Func {
                                         return (env, env, cont, apply)
                                     }
                                 };
-                                return (expr, env, err, errctrl)
+                                return (expr, env, invalid_form, errctrl)
+                            }
+                            "fail" => {
+                                match rest.tag {
+                                    Expr::Cons => {
+                                        let (code, end) = decons2(rest);
+                                        match end.tag {
+                                            Expr::Nil => {
+                                                let cont: Cont::Fail = cons4(expr, cont, foo, foo);
+                                                return (code, env, cont, ret)
+                                            }
+                                        };
+                                        return (expr, env, invalid_form, errctrl)
+                                    }
+                                };
+                                return (expr, env, invalid_form, errctrl)
+                            }
+                            "catch" => {
+                                let (handler, more) = car_cdr(rest);
+                                match more.tag {
+                                    Expr::Cons => {
+                                        let (body, end) = decons2(more);
+                                        match end.tag {
+                                            Expr::Nil => {
+                                                let cont: Cont::Catch = cons4(handler, env, cont, foo);
+                                                return (body, env, cont, ret)
+                                            }
+                                        };
+                                        return (expr, env, invalid_form, errctrl)
+                                    }
+                                };
+                                return (expr, env, invalid_form, errctrl)
+                            }
+                            "vector" => {
+                                let one = Num(1);
+                                match rest.tag {
+                                    Expr::Nil => {
+                                        let zero = Num(0);
+                                        let empty: Expr::Vec = cons3(zero, one, nil);
+                                        return (empty, env, cont, apply)
+                                    }
+                                    Expr::Cons => {
+                                        // the first argument is a subtree of size 1
+                                        let (arg, more) = decons2(rest);
+                                        let header: Expr::Cons = cons2(one, one);
+                                        let state: Expr::Cons = cons2(header, nil);
+                                        let cont: Cont::Vector = cons4(state, more, env, cont);
+                                        return (arg, env, cont, ret)
+                                    }
+                                };
+                                return (expr, env, invalid_form, errctrl)
                             }
                         };
                         // unops
@@ -1116,9 +1373,9 @@ fn reduce(cprocs: &[(&Symbol, usize)]) -> Func {
                                     let cont: Cont::Unop = cons4(op, cont, foo, foo);
                                     return (arg1, env, cont, ret)
                                 }
-                                return (expr, env, err, errctrl);
+                                return (expr, env, arity_mismatch, errctrl);
                             }
-                            return (expr, env, err, errctrl);
+                            return (expr, env, arity_mismatch, errctrl);
                         }
                         // binops
                         let (op) = get_binop(head);
@@ -1128,12 +1385,34 @@ fn reduce(cprocs: &[(&Symbol, usize)]) -> Func {
                                 let (arg1, more) = decons2(rest);
                                 let more_is_nil = eq_tag(more, nil);
                                 if !more_is_nil {
+                                    match op.tag {
+                                        Op2::Substring | Op2::VectorSet => {
+                                            // the only binops with three arguments. Their last
+                                            // two arguments are evaluated as a pair, so they
+                                            // take the same continuations as the other binops
+                                            let (_arg2, last) = car_cdr(more);
+                                            let last_is_nil = eq_tag(last, nil);
+                                            if last_is_nil {
+                                                return (expr, env, arity_mismatch, errctrl);
+                                            }
+                                            let (_arg3, end) = car_cdr(last);
+                                            let end_is_nil = eq_tag(end, nil);
+                                            if !end_is_nil {
+                                                return (expr, env, arity_mismatch, errctrl);
+                                            }
+                                            let cons = Symbol("cons");
+                                            let pair: Expr::Cons = cons2(cons, more);
+                                            let more: Expr::Cons = cons2(pair, nil);
+                                            let cont: Cont::Binop = cons4(op, env, more, cont);
+                                            return (arg1, env, cont, ret);
+                                        }
+                                    };
                                     let cont: Cont::Binop = cons4(op, env, more, cont);
                                     return (arg1, env, cont, ret);
                                 }
-                                return (expr, env, err, errctrl);
+                                return (expr, env, arity_mismatch, errctrl);
                             }
-                            return (expr, env, err, errctrl);
+                            return (expr, env, arity_mismatch, errctrl);
                         }
                         //coprocessors
                         let (is_cproc) = is_cproc(head);
@@ -1160,7 +1439,7 @@ fn reduce(cprocs: &[(&Symbol, usize)]) -> Func {
                     let (fun, env, cont, ret) = make_call(head, rest, env, cont);
                     return (fun, env, cont, ret);
                 }
-                return (expr, env, err, errctrl)
+                return (expr, env, not_a_function, errctrl)
             }
         }
     })
@@ -1180,9 +1459,9 @@ fn reduce(cprocs: &[(&Symbol, usize)]) -> Func {
 fn choose_cproc_call(cprocs: &[(&Symbol, usize)], ivc: bool) -> Func {
     if cprocs.is_empty() {
         func!(no_cproc_error(cproc_name, _evaluated_args, env, _cont): 4 => {
-            let err: Cont::Error = HASH_8_ZEROS;
             let errctrl = Symbol("error");
-            return (cproc_name, env, err, errctrl);
+            let unknown_cproc = Keyword("unknown-coprocessor");
+            return (cproc_name, env, unknown_cproc, errctrl);
         })
     } else if ivc {
         match_and_run_cproc(cprocs)
@@ -1209,55 +1488,256 @@ fn apply_cont(cprocs: &[(&Symbol, usize)], ivc: bool) -> Func {
         return (tail_continuation);
     });
 
-    let extend_rec = func!(extend_rec(env, var, result): 1 => {
+    // Evaluates the next binding of a `letrec`, or its body if there are no
+    // more bindings. `rest` holds the remaining bindings and the body
+    let letrec_rest = func!(letrec_rest(env, saved_env, rest, cont): 4 => {
+        let ret = Symbol("return");
+        let errctrl = Symbol("error");
+        let invalid_form = Keyword("invalid-form");
+        let (bindings, body) = decons2(rest);
+        match bindings.tag {
+            Expr::Nil => {
+                let (body1, _rest_body) = car_cdr(body);
+                let (cont) = make_tail_continuation(saved_env, cont);
+                return (body1, env, cont, ret)
+            }
+        };
+        let (binding1, rest_bindings) = car_cdr(bindings);
+        let (var, vals) = car_cdr(binding1);
+        match var.tag {
+            Expr::Sym => {
+                let (val, end) = car_cdr(vals);
+                match end.tag {
+                    Expr::Nil => {
+                        let rest: Expr::Cons = cons2(rest_bindings, body);
+                        let cont: Cont::LetRecNext = cons4(var, saved_env, rest, cont);
+                        return (val, env, cont, ret)
+                    }
+                };
+                return (binding1, env, invalid_form, errctrl)
+            }
+        };
+        return (binding1, env, invalid_form, errctrl)
+    });
+    // Evaluates the body of the variadic closure `function`, whose parameters
+    // are `(&rest <var>)`, binding its rest parameter to `args`
+    let call_rest = func!(call_rest(function, args, env, cont): 4 => {
+        let ret = Symbol("return");
+        let errctrl = Symbol("error");
+        let invalid_form = Keyword("invalid-form");
+        let (params, body, closed_env) = decons3(function);
+        let (_rest, more_params) = decons2(params);
+        let (var, _end) = decons2(more_params);
+        match body.tag {
+            Expr::Cons => {
+                let (body_form, end) = decons2(body);
+                match end.tag {
+                    Expr::Nil => {
+                        let binding: Expr::Cons = cons2(var, args);
+                        let newer_env: Expr::Cons = cons2(binding, closed_env);
+                        return (body_form, newer_env, cont, ret)
+                    }
+                };
+                return (function, env, invalid_form, errctrl)
+            }
+        };
+        return (function, env, invalid_form, errctrl)
+    });
+    // Collects the `pending` arguments of the call for the rest parameter of
+    // `function`. Each one is evaluated under a `Rest` continuation holding the
+    // values `collected` so far, last first, along with the arguments still
+    // pending. Once there are none left, the collected values are put back in
+    // order and `function` is called
+    let collect_rest = func!(collect_rest(function, collected, pending, env, continuation): 4 => {
+        let ret = Symbol("return");
+        let makethunk = Symbol("make-thunk");
         let nil = Symbol("nil");
         let nil = cast(nil, Expr::Nil);
-        let sym: Expr::Sym;
-        let (binding_or_env, rest) = car_cdr(env);
-        let (var_or_binding, _val_or_more_bindings) = car_cdr(binding_or_env);
-        let binding: Expr::Cons = cons2(var, result);
-        let var_or_binding_is_sym = eq_tag(var_or_binding, sym);
-        let var_or_binding_is_nil = eq_tag(var_or_binding, nil);
-        let var_or_binding_is_sym_or_nil = or(var_or_binding_is_sym, var_or_binding_is_nil);
-        if var_or_binding_is_sym_or_nil {
-            // It's a var, so we are extending a simple env with a recursive env.
-            let list: Expr::Cons = cons2(binding, nil);
-            let res: Expr::Cons = cons2(list, env);
-            return (res)
-        }
-        // It's a binding, so we are extending a recursive env.
-        let cons2: Expr::Cons = cons2(binding, binding_or_env);
-        let res: Expr::Cons = cons2(cons2, rest);
-        return (res)
+        let foo: Expr::Nil;
+        match pending.tag {
+            Expr::Cons => {
+                let (next_arg, pending) = decons2(pending);
+                let state: Expr::Cons = cons2(collected, pending);
+                let cont: Cont::Rest = cons4(function, state, foo, continuation);
+                return (next_arg, env, cont, ret)
+            }
+        };
+        let (cont) = make_tail_continuation(env, continuation);
+        match collected.tag {
+            Expr::Nil => {
+                let (expr, env, cont, ctrl) = call_rest(function, nil, env, cont);
+                return (expr, env, cont, ctrl)
+            }
+        };
+        let (last, previous) = decons2(collected);
+        let args: Expr::Cons = cons2(last, nil);
+        match previous.tag {
+            Expr::Nil => {
+                let (expr, env, cont, ctrl) = call_rest(function, args, env, cont);
+                return (expr, env, cont, ctrl)
+            }
+        };
+        let cont: Cont::Rest = cons4(function, previous, args, cont);
+        return (nil, env, cont, makethunk)
+    });
+    // Applies the variadic closure `function` to `result`, then collects the
+    // `pending` arguments of the same call for its rest parameter
+    let call_variadic = func!(call_variadic(function, result, pending, saved_env, continuation): 4 => {
+        let nil = Symbol("nil");
+        let nil = cast(nil, Expr::Nil);
+        let (params, body, closed_env) = decons3(function);
+        let (param, more_params) = decons2(params);
+        match symbol param {
+            "&rest" => {
+                let collected: Expr::Cons = cons2(result, nil);
+                let (expr, env, cont, ctrl) = collect_rest(function, collected, pending, saved_env, continuation);
+                return (expr, env, cont, ctrl)
+            }
+        };
+        // bind the last fixed parameter
+        let binding: Expr::Cons = cons2(param, result);
+        let closed_env: Expr::Cons = cons2(binding, closed_env);
+        let function: Expr::Fun = cons3(more_params, body, closed_env);
+        let (expr, env, cont, ctrl) = collect_rest(function, nil, pending, saved_env, continuation);
+        return (expr, env, cont, ctrl)
+    });
+    // Applies `function` to the list of evaluated arguments `args`. Curried
+    // closures take one argument per step, with the remaining ones kept in an
+    // `Apply` continuation
+    let apply_list = func!(apply_list(function, args, env, continuation): 4 => {
+        let ret = Symbol("return");
+        let errctrl = Symbol("error");
+        let invalid_form = Keyword("invalid-form");
+        let arity_mismatch = Keyword("arity-mismatch");
+        let not_a_function = Keyword("not-a-function");
+        let type_mismatch = Keyword("type-mismatch");
+        let nil = Symbol("nil");
+        let nil = cast(nil, Expr::Nil);
+        let foo: Expr::Nil;
+        match function.tag {
+            Expr::Fun => {
+                let (params, body, closed_env) = decons3(function);
+                match args.tag {
+                    Expr::Nil => {
+                        // the same as calling `function` without arguments
+                        match params.tag {
+                            Expr::Cons => {
+                                let (param, _more_params) = decons2(params);
+                                match symbol param {
+                                    "&rest" => {
+                                        let (cont) = make_tail_continuation(env, continuation);
+                                        let (expr, env, cont, ctrl) = call_rest(function, nil, env, cont);
+                                        return (expr, env, cont, ctrl)
+                                    }
+                                };
+                                return (function, env, continuation, ret)
+                            }
+                        };
+                        match symbol params {
+                            "dummy" => {
+                                match body.tag {
+                                    Expr::Cons => {
+                                        let (body_form, end) = decons2(body);
+                                        match end.tag {
+                                            Expr::Nil => {
+                                                let (cont) = make_tail_continuation(env, continuation);
+                                                return (body_form, closed_env, cont, ret)
+                                            }
+                                        };
+                                        return (function, env, invalid_form, errctrl)
+                                    }
+                                };
+                                return (function, env, invalid_form, errctrl)
+                            }
+                        };
+                        return (function, env, continuation, ret)
+                    }
+                    Expr::Cons => {
+                        let (arg, rest_args) = decons2(args);
+                        match params.tag {
+                            Expr::Cons => {
+                                let (param, more_params) = decons2(params);
+                                let (cont) = make_tail_continuation(env, continuation);
+                                match symbol param {
+                                    "&rest" => {
+                                        let (expr, env, cont, ctrl) = call_rest(function, args, env, cont);
+                                        return (expr, env, cont, ctrl)
+                                    }
+                                };
+                                let binding: Expr::Cons = cons2(param, arg);
+                                let closed_env: Expr::Cons = cons2(binding, closed_env);
+                                let function: Expr::Fun = cons3(more_params, body, closed_env);
+                                let (expr, env, cont, ctrl) = call_rest(function, rest_args, env, cont);
+                                return (expr, env, cont, ctrl)
+                            }
+                        };
+                        match symbol params {
+                            "dummy" => {
+                                return (args, env, arity_mismatch, errctrl)
+                            }
+                        };
+                        match body.tag {
+                            Expr::Cons => {
+                                let (body_form, end) = decons2(body);
+                                match end.tag {
+                                    Expr::Nil => {
+                                        let binding: Expr::Cons = cons2(params, arg);
+                                        let newer_env: Expr::Cons = cons2(binding, closed_env);
+                                        match rest_args.tag {
+                                            Expr::Nil => {
+                                                let (cont) = make_tail_continuation(env, continuation);
+                                                return (body_form, newer_env, cont, ret)
+                                            }
+                                        };
+                                        // the result is applied to the remaining arguments
+                                        let cont: Cont::Apply = cons4(rest_args, env, continuation, foo);
+                                        return (body_form, newer_env, cont, ret)
+                                    }
+                                };
+                                return (function, env, invalid_form, errctrl)
+                            }
+                        };
+                        return (function, env, invalid_form, errctrl)
+                    }
+                };
+                return (args, env, type_mismatch, errctrl)
+            }
+        };
+        return (function, env, not_a_function, errctrl)
     });
-    // Returns 0u64 if both arguments are U64, 0 (num) if the arguments are some kind of number (either U64 or Num),
-    // and nil otherwise
+    // Returns a pointer tagged with the numeric type of an operation over `arg1`
+    // and `arg2`: an unsigned integer type if both are unsigned integers of the
+    // same width, i64 if both are i64s, num if one is a num and the other is an
+    // unsigned integer or a num, and nil otherwise
     let args_num_type = func!(args_num_type(arg1, arg2): 1 => {
         let nil = Symbol("nil");
         let nil = cast(nil, Expr::Nil);
+        let ret: Expr::Num;
         match arg1.tag {
             Expr::Num => {
                 match arg2.tag {
-                    Expr::Num => {
-                        let ret: Expr::Num;
-                        return (ret)
-                    }
-                    Expr::U64 => {
-                        let ret: Expr::Num;
+                    Expr::Num | Expr::U8 | Expr::U16 | Expr::U32 | Expr::U64 | Expr::U128 => {
                         return (ret)
                     }
                 };
                 return (nil)
             }
-            Expr::U64 => {
+            Expr::U8 | Expr::U16 | Expr::U32 | Expr::U64 | Expr::U128 => {
                 match arg2.tag {
                     Expr::Num => {
-                        let ret: Expr::Num;
                         return (ret)
                     }
-                    Expr::U64 => {
-                        let ret: Expr::U64;
-                        return (ret)
+                };
+                let same_type = eq_tag(arg1, arg2);
+                if same_type {
+                    return (arg1)
+                }
+                return (nil)
+            }
+            Expr::I64 => {
+                match arg2.tag {
+                    Expr::I64 => {
+                        return (arg1)
                     }
                 };
                 return (nil)
@@ -1265,51 +1745,414 @@ fn apply_cont(cprocs: &[(&Symbol, usize)], ivc: bool) -> Func {
         };
         return (nil)
     });
-    let choose_cproc_call = choose_cproc_call(cprocs, ivc);
-    func!(apply_cont(result, env, cont, ctrl): 4 => {
-        match symbol ctrl {
-            "apply-continuation" => {
-                let makethunk = Symbol("make-thunk");
-                let lookup: Cont::Lookup;
-                let tail: Cont::Tail;
-                let cont_is_lookup = eq_tag(cont, lookup);
-                let cont_is_tail = eq_tag(cont, tail);
-                let cont_is_tail_or_lookup = or(cont_is_lookup, cont_is_tail);
-                if cont_is_tail_or_lookup {
-                    let (saved_env, continuation, _foo, _foo) = decons4(cont);
-                    return (result, saved_env, continuation, makethunk)
-                }
-
-                let errctrl = Symbol("error");
-                let ret = Symbol("return");
-                let t = Symbol("t");
-                let nil = Symbol("nil");
-                let nil = cast(nil, Expr::Nil);
-                let empty_str = String("");
-                let zero = Num(0);
-                let foo: Expr::Nil;
-                let char: Expr::Char;
-                let u64: Expr::U64;
-                let err: Cont::Error = HASH_8_ZEROS;
-                let term: Cont::Terminal = HASH_8_ZEROS;
-                match cont.tag {
-                    Cont::Outermost => {
-                        return (result, env, term, ret)
-                    }
-                    Cont::Emit => {
-                        let (cont, _rest, _foo, _foo) = decons4(cont);
-                        return (result, env, cont, makethunk)
-                    }
-                    Cont::Call0 => {
-                        let (saved_env, continuation, _foo, _foo) = decons4(cont);
-                        match result.tag {
-                            Expr::Fun => {
-                                let (arg, body, closed_env) = decons3(result);
-                                match symbol arg {
-                                    "dummy" => {
+    // Divides two unsigned integers of the type of `typ`, with a division
+    // gadget as wide as that type
+    let div_rem_uint = func!(div_rem_uint(a, b, typ): 2 => {
+        match typ.tag {
+            Expr::U8 => {
+                let (div, rem) = div_rem(a, b, 8);
+                let div = cast(div, Expr::U8);
+                let rem = cast(rem, Expr::U8);
+                return (div, rem)
+            }
+            Expr::U16 => {
+                let (div, rem) = div_rem(a, b, 16);
+                let div = cast(div, Expr::U16);
+                let rem = cast(rem, Expr::U16);
+                return (div, rem)
+            }
+            Expr::U32 => {
+                let (div, rem) = div_rem(a, b, 32);
+                let div = cast(div, Expr::U32);
+                let rem = cast(rem, Expr::U32);
+                return (div, rem)
+            }
+            Expr::U64 => {
+                let (div, rem) = div_rem(a, b, 64);
+                let div = cast(div, Expr::U64);
+                let rem = cast(rem, Expr::U64);
+                return (div, rem)
+            }
+            Expr::U128 => {
+                let (div, rem) = div_rem(a, b, 128);
+                let div = cast(div, Expr::U128);
+                let rem = cast(rem, Expr::U128);
+                return (div, rem)
+            }
+        }
+    });
+    // Truncates `val` to the width of the integer type of `typ`, which computes
+    // it modulo 2^width, and tags it with that type. Since i64s are stored in
+    // two's complement, they wrap around just like u64s
+    let wrap_uint = func!(wrap_uint(val, typ): 1 => {
+        match typ.tag {
+            Expr::U8 => {
+                let val = truncate(val, 8);
+                let val = cast(val, Expr::U8);
+                return (val)
+            }
+            Expr::U16 => {
+                let val = truncate(val, 16);
+                let val = cast(val, Expr::U16);
+                return (val)
+            }
+            Expr::U32 => {
+                let val = truncate(val, 32);
+                let val = cast(val, Expr::U32);
+                return (val)
+            }
+            Expr::U64 => {
+                let val = truncate(val, 64);
+                let val = cast(val, Expr::U64);
+                return (val)
+            }
+            Expr::U128 => {
+                let val = truncate(val, 128);
+                let val = cast(val, Expr::U128);
+                return (val)
+            }
+            Expr::I64 => {
+                let val = truncate(val, 64);
+                let val = cast(val, Expr::I64);
+                return (val)
+            }
+        }
+    });
+    // Negates the two's complement bits of an i64
+    let neg_i64 = func!(neg_i64(a): 1 => {
+        let size_u64 = Num(18446744073709551616);
+        let val = sub(size_u64, a);
+        let val = truncate(val, 64);
+        let val = cast(val, Expr::I64);
+        return (val)
+    });
+    // The absolute value of an i64 as an unsigned number, so that the absolute
+    // value of the most negative i64 is 2^63
+    let abs_i64 = func!(abs_i64(a): 1 => {
+        let min_i64 = Num(9223372036854775808);
+        let is_pos = lt(a, min_i64);
+        if is_pos {
+            return (a)
+        }
+        let size_u64 = Num(18446744073709551616);
+        let val = sub(size_u64, a);
+        return (val)
+    });
+    // Divides two i64s, rounding the quotient towards zero. The remainder has
+    // the sign of the dividend and the division of the most negative i64 by -1
+    // wraps around to itself
+    let div_rem_i64 = func!(div_rem_i64(a, b): 2 => {
+        let min_i64 = Num(9223372036854775808);
+        let (a_abs) = abs_i64(a);
+        let (b_abs) = abs_i64(b);
+        let (div, rem) = div_rem(a_abs, b_abs, 64);
+        let a_is_pos = lt(a, min_i64);
+        let b_is_pos = lt(b, min_i64);
+        if a_is_pos {
+            let rem = cast(rem, Expr::I64);
+            if b_is_pos {
+                let div = cast(div, Expr::I64);
+                return (div, rem)
+            }
+            let (div) = neg_i64(div);
+            return (div, rem)
+        }
+        let (rem) = neg_i64(rem);
+        if b_is_pos {
+            let (div) = neg_i64(div);
+            return (div, rem)
+        }
+        let div = cast(div, Expr::I64);
+        return (div, rem)
+    });
+    // Maps an i64 to an unsigned number with the same ordering by flipping its
+    // sign bit, so that `lt` compares i64s correctly. Other numbers are left
+    // untouched
+    let lt_operand = func!(lt_operand(a, typ): 1 => {
+        match typ.tag {
+            Expr::I64 => {
+                let min_i64 = Num(9223372036854775808);
+                let val = add(a, min_i64);
+                let val = truncate(val, 64);
+                return (val)
+            }
+        };
+        return (a)
+    });
+    // Multiplies two u128s modulo 2^128. The product of two u128s could overflow
+    // the field, so we split them in 64 bits limbs and drop the upper terms
+    let mul_u128 = func!(mul_u128(a, b): 1 => {
+        let size_u64 = Num(18446744073709551616);
+        let a_lo = truncate(a, 64);
+        let a_hi = sub(a, a_lo);
+        let a_hi = div(a_hi, size_u64);
+        let b_lo = truncate(b, 64);
+        let b_hi = sub(b, b_lo);
+        let b_hi = div(b_hi, size_u64);
+        let lo = mul(a_lo, b_lo);
+        let cross1 = mul(a_hi, b_lo);
+        let cross2 = mul(a_lo, b_hi);
+        let cross = add(cross1, cross2);
+        let cross = truncate(cross, 64);
+        let cross = mul(cross, size_u64);
+        let val = add(lo, cross);
+        let val = truncate(val, 128);
+        let val = cast(val, Expr::U128);
+        return (val)
+    });
+    // Splits `a * 2^e` at its 64th bit, for a u64 `a` and `e` in `[0, 64]`,
+    // which doesn't overflow the field. Every u64 shift and rotation follows
+    // from the lower and upper halves of such a product
+    let split_u64 = func!(split_u64(a, e): 2 => {
+        let size_u64 = Num(18446744073709551616);
+        let pow = pow2(e, 7);
+        let val = mul(a, pow);
+        let lo = truncate(val, 64);
+        let hi = sub(val, lo);
+        let hi = div(hi, size_u64);
+        return (lo, hi)
+    });
+    // The exponent `e` for `split_u64` that implements the shift or rotation
+    // `op` by `k`, for `k` in `[0, 64)`
+    let shift_exponent = func!(shift_exponent(op, k): 1 => {
+        match op.tag {
+            Op2::Shl | Op2::Rotl => {
+                return (k)
+            }
+        };
+        let size = Num(64);
+        let e = sub(size, k);
+        return (e)
+    });
+    // Moves the first character of `from` to the front of `onto`, if `from`
+    // isn't empty. Moving all of them reverses `from` onto `onto`
+    let move_char = func!(move_char(from, onto): 2 => {
+        let empty_str = String("");
+        let from_is_empty = eq_val(from, empty_str);
+        if from_is_empty {
+            return (from, onto)
+        }
+        let (char, rest) = decons2(from);
+        let onto: Expr::Str = cons2(char, onto);
+        return (rest, onto)
+    });
+    // Counts the first character of `from`, if `from` isn't empty
+    let count_char = func!(count_char(from, count): 2 => {
+        let one = Num(1);
+        let empty_str = String("");
+        let from_is_empty = eq_val(from, empty_str);
+        if from_is_empty {
+            return (from, count)
+        }
+        let (_char, rest) = decons2(from);
+        let count = add(count, one);
+        return (rest, count)
+    });
+    // Compares the first characters of `a` and `b` for `string<`, unless it's
+    // already `decided` whether `a` is `less` than `b`. Both are `nil` while
+    // the strings are equal so far
+    let compare_char = func!(compare_char(a, b, decided, less): 4 => {
+        let t = Symbol("t");
+        let nil = Symbol("nil");
+        let nil = cast(nil, Expr::Nil);
+        let empty_str = String("");
+        let is_decided = eq_val(decided, t);
+        if is_decided {
+            return (a, b, decided, less)
+        }
+        let b_is_empty = eq_val(b, empty_str);
+        if b_is_empty {
+            return (a, b, t, nil)
+        }
+        let a_is_empty = eq_val(a, empty_str);
+        if a_is_empty {
+            return (a, b, t, t)
+        }
+        let (a_char, a_rest) = decons2(a);
+        let (b_char, b_rest) = decons2(b);
+        let same_char = eq_val(a_char, b_char);
+        if same_char {
+            return (a_rest, b_rest, nil, nil)
+        }
+        let a_lt_b = lt(a_char, b_char);
+        if a_lt_b {
+            return (a_rest, b_rest, t, t)
+        }
+        return (a_rest, b_rest, t, nil)
+    });
+    // Consumes the first character of `from` for `substring`: it's skipped
+    // while `skip` is positive and then moved onto `taken` while `take` is
+    // positive. Nothing changes once both are zero or `from` is empty
+    let substring_char = func!(substring_char(from, taken, skip, take): 4 => {
+        let zero = Num(0);
+        let one = Num(1);
+        let empty_str = String("");
+        let from_is_empty = eq_val(from, empty_str);
+        if from_is_empty {
+            return (from, taken, skip, take)
+        }
+        let skip_done = eq_val(skip, zero);
+        if skip_done {
+            let take_done = eq_val(take, zero);
+            if take_done {
+                return (from, taken, skip, take)
+            }
+            let (char, rest) = decons2(from);
+            let taken: Expr::Str = cons2(char, taken);
+            let take = sub(take, one);
+            return (rest, taken, skip, take)
+        }
+        let (_char, rest) = decons2(from);
+        let skip = sub(skip, one);
+        return (rest, taken, skip, take)
+    });
+    // Returns the halves of an inner node of a vector's tree. Subtrees that
+    // lie past the end of the vector are `nil`
+    let vector_children = func!(vector_children(node): 2 => {
+        match node.tag {
+            Expr::Cons => {
+                let (left, right) = decons2(node);
+                return (left, right)
+            }
+        };
+        let nil = Symbol("nil");
+        let nil = cast(nil, Expr::Nil);
+        return (nil, nil)
+    });
+    // Pushes a subtree of a vector being built onto its stack and moves on
+    // to the next argument of `vector`, see `Cont::Vector`
+    let push_subtree = func!(push_subtree(tree, size, len, stack, unevaled_args, saved_env, continuation): 2 => {
+        let one = Num(1);
+        let entry: Expr::Cons = cons2(size, tree);
+        let stack: Expr::Cons = cons2(entry, stack);
+        let len = add(len, one);
+        let header: Expr::Cons = cons2(len, one);
+        let state: Expr::Cons = cons2(header, stack);
+        let (arg, more) = car_cdr(unevaled_args);
+        let cont: Cont::Vector = cons4(state, more, saved_env, continuation);
+        return (arg, cont)
+    });
+    // Returns the continuation that follows `cont`, or the outermost one if
+    // there's no such continuation
+    let parent_cont = func!(parent_cont(cont): 1 => {
+        match cont.tag {
+            Cont::Emit => {
+                let (parent, _foo, _foo, _foo) = decons4(cont);
+                return (parent)
+            }
+            Cont::Call0 | Cont::Tail | Cont::Lookup | Cont::Unop | Cont::If | Cont::Fail => {
+                let (_a, parent, _foo, _foo) = decons4(cont);
+                return (parent)
+            }
+            Cont::Call | Cont::Call2 | Cont::Binop2 | Cont::Catch | Cont::Apply | Cont::StringOp | Cont::VectorOp => {
+                let (_a, _b, parent, _foo) = decons4(cont);
+                return (parent)
+            }
+            Cont::Binop | Cont::Let | Cont::LetRec | Cont::LetRecNext | Cont::Cproc | Cont::Rest | Cont::Vector => {
+                let (_a, _b, _c, parent) = decons4(cont);
+                return (parent)
+            }
+        };
+        let outermost: Cont::Outermost = HASH_8_ZEROS;
+        return (outermost)
+    });
+    let choose_cproc_call = choose_cproc_call(cprocs, ivc);
+    func!(apply_cont(result, env, cont, ctrl): 4 => {
+        match symbol ctrl {
+            "apply-continuation" => {
+                let makethunk = Symbol("make-thunk");
+                let lookup: Cont::Lookup;
+                let tail: Cont::Tail;
+                let cont_is_lookup = eq_tag(cont, lookup);
+                let cont_is_tail = eq_tag(cont, tail);
+                let cont_is_tail_or_lookup = or(cont_is_lookup, cont_is_tail);
+                if cont_is_tail_or_lookup {
+                    // both start with the saved env and the continuation
+                    let (saved_env, continuation, _frame, _frame_env) = decons4(cont);
+                    return (result, saved_env, continuation, makethunk)
+                }
+
+                let errctrl = Symbol("error");
+                let invalid_form = Keyword("invalid-form");
+                let arity_mismatch = Keyword("arity-mismatch");
+                let not_a_function = Keyword("not-a-function");
+                let type_mismatch = Keyword("type-mismatch");
+                let div_by_zero = Keyword("div-by-zero");
+                let out_of_range = Keyword("out-of-range");
+                let ret = Symbol("return");
+                let t = Symbol("t");
+                let nil = Symbol("nil");
+                let nil = cast(nil, Expr::Nil);
+                let empty_str = String("");
+                let zero = Num(0);
+                let foo: Expr::Nil;
+                let char: Expr::Char;
+                let term: Cont::Terminal = HASH_8_ZEROS;
+                match cont.tag {
+                    Cont::Outermost => {
+                        return (result, env, term, ret)
+                    }
+                    Cont::Emit => {
+                        let (cont, _rest, _foo, _foo) = decons4(cont);
+                        return (result, env, cont, makethunk)
+                    }
+                    Cont::Fail => {
+                        // `result` is the error code, so start unwinding
+                        let (form, continuation, _foo, _foo) = decons4(cont);
+                        let unwind: Cont::Unwind = cons4(form, continuation, foo, foo);
+                        return (result, env, unwind, ret)
+                    }
+                    Cont::Catch => {
+                        // `body` finished without failing
+                        let (_handler, saved_env, continuation, _foo) = decons4(cont);
+                        return (result, saved_env, continuation, makethunk)
+                    }
+                    Cont::Unwind => {
+                        let (form, continuation, _foo, _foo) = decons4(cont);
+                        match continuation.tag {
+                            Cont::Outermost => {
+                                // uncaught failure
+                                return (form, env, result, errctrl)
+                            }
+                            Cont::Catch => {
+                                // call `handler` on the (quoted) error code
+                                let (handler, saved_env, continuation, _foo) = decons4(continuation);
+                                let quote = Symbol("quote");
+                                let quoted: Expr::Cons = cons2(result, nil);
+                                let quoted: Expr::Cons = cons2(quote, quoted);
+                                let cont: Cont::Call = cons4(quoted, saved_env, continuation, foo);
+                                return (handler, saved_env, cont, ret)
+                            }
+                        };
+                        // pop one continuation per step
+                        let (parent) = parent_cont(continuation);
+                        let unwind: Cont::Unwind = cons4(form, parent, foo, foo);
+                        return (result, env, unwind, ret)
+                    }
+                    Cont::Call0 => {
+                        let (saved_env, continuation, _foo, _foo) = decons4(cont);
+                        match result.tag {
+                            Expr::Fun => {
+                                let (arg, body, closed_env) = decons3(result);
+                                match arg.tag {
+                                    Expr::Cons => {
+                                        let (param, _more_params) = decons2(arg);
+                                        match symbol param {
+                                            "&rest" => {
+                                                // the rest parameter is empty
+                                                let (cont) = make_tail_continuation(saved_env, continuation);
+                                                let (expr, env, cont, ctrl) = call_rest(result, nil, env, cont);
+                                                return (expr, env, cont, ctrl)
+                                            }
+                                        };
+                                        return (result, env, continuation, ret)
+                                    }
+                                };
+                                match symbol arg {
+                                    "dummy" => {
                                         match body.tag {
                                             Expr::Nil => {
-                                                return (result, env, err, errctrl)
+                                                return (result, env, invalid_form, errctrl)
                                             }
                                         };
                                         let (body_form, end) = car_cdr(body);
@@ -1319,37 +2162,43 @@ fn apply_cont(cprocs: &[(&Symbol, usize)], ivc: bool) -> Func {
                                                 return (body_form, closed_env, cont, ret)
                                             }
                                         };
-                                        return (result, env, err, errctrl)
+                                        return (result, env, invalid_form, errctrl)
                                     }
                                 };
                                 return (result, env, continuation, ret)
                             }
                         };
-                        return (result, env, err, errctrl)
+                        return (result, env, not_a_function, errctrl)
                     }
                     Cont::Call => {
                         match result.tag {
                             Expr::Fun => {
-                                let (unevaled_arg, saved_env, continuation, _foo) = decons4(cont);
-                                let newer_cont: Cont::Call2 = cons4(result, saved_env, continuation, foo);
+                                let (unevaled_arg, saved_env, continuation, pending) = decons4(cont);
+                                let newer_cont: Cont::Call2 = cons4(result, saved_env, continuation, pending);
                                 return (unevaled_arg, env, newer_cont, ret)
                             }
                         };
-                        return (result, env, err, errctrl)
+                        return (result, env, not_a_function, errctrl)
                     }
                     Cont::Call2 => {
-                        let (function, saved_env, continuation, _foo) = decons4(cont);
+                        let (function, saved_env, continuation, pending) = decons4(cont);
                         match function.tag {
                             Expr::Fun => {
                                 let (arg, body, closed_env) = decons3(function);
+                                match arg.tag {
+                                    Expr::Cons => {
+                                        let (expr, env, cont, ctrl) = call_variadic(function, result, pending, saved_env, continuation);
+                                        return (expr, env, cont, ctrl)
+                                    }
+                                };
                                 match symbol arg {
                                     "dummy" => {
-                                        return (result, env, err, errctrl)
+                                        return (result, env, arity_mismatch, errctrl)
                                     }
                                 };
                                 match body.tag {
                                     Expr::Nil => {
-                                        return (result, env, err, errctrl)
+                                        return (result, env, invalid_form, errctrl)
                                     }
                                 };
                                 let (body_form, end) = decons2(body);
@@ -1357,14 +2206,23 @@ fn apply_cont(cprocs: &[(&Symbol, usize)], ivc: bool) -> Func {
                                     Expr::Nil => {
                                         let binding: Expr::Cons = cons2(arg, result);
                                         let newer_env: Expr::Cons = cons2(binding, closed_env);
+                                        match pending.tag {
+                                            Expr::Cons => {
+                                                // the result is called with the next argument
+                                                let (next_arg, pending) = decons2(pending);
+                                                let continuation: Cont::Call = cons4(next_arg, saved_env, continuation, pending);
+                                                let (cont) = make_tail_continuation(saved_env, continuation);
+                                                return (body_form, newer_env, cont, ret)
+                                            }
+                                        };
                                         let (cont) = make_tail_continuation(saved_env, continuation);
                                         return (body_form, newer_env, cont, ret)
                                     }
                                 };
-                                return (result, env, err, errctrl)
+                                return (result, env, invalid_form, errctrl)
                             }
                         };
-                        return (result, env, err, errctrl)
+                        return (result, env, not_a_function, errctrl)
                     }
                     Cont::Let => {
                         let (var, saved_env, body, cont) = decons4(cont);
@@ -1374,16 +2232,315 @@ fn apply_cont(cprocs: &[(&Symbol, usize)], ivc: bool) -> Func {
                         return (body, extended_env, cont, ret)
                     }
                     Cont::LetRec => {
-                        let (var, saved_env, body, cont) = decons4(cont);
-                        let (extended_env) = extend_rec(env, var, result);
-                        let (cont) = make_tail_continuation(saved_env, cont);
-                        return (body, extended_env, cont, ret)
+                        // the first binding starts a new recursive frame
+                        let (var, saved_env, rest, cont) = decons4(cont);
+                        let binding: Expr::Cons = cons2(var, result);
+                        let frame: Expr::Cons = cons2(binding, nil);
+                        let extended_env: Expr::Cons = cons2(frame, env);
+                        let (expr, env, cont, ctrl) = letrec_rest(extended_env, saved_env, rest, cont);
+                        return (expr, env, cont, ctrl)
+                    }
+                    Cont::LetRecNext => {
+                        // the following ones join the frame of the first
+                        let (var, saved_env, rest, cont) = decons4(cont);
+                        let (frame, smaller_env) = car_cdr(env);
+                        let binding: Expr::Cons = cons2(var, result);
+                        let frame: Expr::Cons = cons2(binding, frame);
+                        let extended_env: Expr::Cons = cons2(frame, smaller_env);
+                        let (expr, env, cont, ctrl) = letrec_rest(extended_env, saved_env, rest, cont);
+                        return (expr, env, cont, ctrl)
+                    }
+                    Cont::Rest => {
+                        let (function, collected, args, continuation) = decons4(cont);
+                        match args.tag {
+                            Expr::Cons => {
+                                // move one collected argument back into place per step
+                                let (arg, collected) = decons2(collected);
+                                let args: Expr::Cons = cons2(arg, args);
+                                match collected.tag {
+                                    Expr::Nil => {
+                                        let (expr, env, cont, ctrl) = call_rest(function, args, env, continuation);
+                                        return (expr, env, cont, ctrl)
+                                    }
+                                };
+                                let cont: Cont::Rest = cons4(function, collected, args, continuation);
+                                return (nil, env, cont, makethunk)
+                            }
+                        };
+                        let (collected, pending) = decons2(collected);
+                        let collected: Expr::Cons = cons2(result, collected);
+                        let (expr, env, cont, ctrl) = collect_rest(function, collected, pending, env, continuation);
+                        return (expr, env, cont, ctrl)
+                    }
+                    Cont::Apply => {
+                        let (args, saved_env, continuation, _foo) = decons4(cont);
+                        let (expr, env, cont, ctrl) = apply_list(result, args, saved_env, continuation);
+                        return (expr, env, cont, ctrl)
+                    }
+                    Cont::StringOp => {
+                        // string operations go through their arguments over several
+                        // steps, four characters at a time, keeping what they've
+                        // computed so far in `state`
+                        let (operator, state, continuation, negative) = decons4(cont);
+                        let result_is_empty = eq_val(result, empty_str);
+                        match operator.tag {
+                            Op1::StringLength => {
+                                // `state` is the number of characters seen
+                                let (rest, state) = count_char(result, state);
+                                let (rest, state) = count_char(rest, state);
+                                let (rest, state) = count_char(rest, state);
+                                let (rest, state) = count_char(rest, state);
+                                let rest_is_empty = eq_val(rest, empty_str);
+                                if rest_is_empty {
+                                    return (state, env, continuation, makethunk)
+                                }
+                                let cont: Cont::StringOp = cons4(operator, state, continuation, foo);
+                                return (rest, env, cont, makethunk)
+                            }
+                            Op1::NumToString => {
+                                // `result` is what's left of the absolute value of the
+                                // number and `state` holds the digits already written.
+                                // The minus sign of a negative number goes in front of
+                                // them at the end
+                                let ten = Num(10);
+                                let char_zero = Num(48);
+                                let (quotient, digit) = div_rem(result, ten, 128);
+                                let digit = add(digit, char_zero);
+                                let digit = cast(digit, Expr::Char);
+                                let state: Expr::Str = cons2(digit, state);
+                                let done = eq_val(quotient, zero);
+                                if done {
+                                    let is_negative = eq_val(negative, t);
+                                    if is_negative {
+                                        let minus = Num(45);
+                                        let minus = cast(minus, Expr::Char);
+                                        let state: Expr::Str = cons2(minus, state);
+                                        return (state, env, continuation, makethunk)
+                                    }
+                                    return (state, env, continuation, makethunk)
+                                }
+                                let cont: Cont::StringOp = cons4(operator, state, continuation, negative);
+                                return (quotient, env, cont, makethunk)
+                            }
+                        };
+                        // a `match` can't mix unary and binary operator tags
+                        match operator.tag {
+                            Op2::StringAppend => {
+                                // `state` holds the characters taken from the first
+                                // string, in reverse order, and the second string. Four
+                                // characters are moved per step, first off the first
+                                // string and then back onto the second one
+                                let (reversed, second) = decons2(state);
+                                if result_is_empty {
+                                    let (reversed, second) = move_char(reversed, second);
+                                    let (reversed, second) = move_char(reversed, second);
+                                    let (reversed, second) = move_char(reversed, second);
+                                    let (reversed, second) = move_char(reversed, second);
+                                    let reversed_is_empty = eq_val(reversed, empty_str);
+                                    if reversed_is_empty {
+                                        return (second, env, continuation, makethunk)
+                                    }
+                                    let state: Expr::Cons = cons2(reversed, second);
+                                    let cont: Cont::StringOp = cons4(operator, state, continuation, foo);
+                                    return (result, env, cont, makethunk)
+                                }
+                                let (rest, reversed) = move_char(result, reversed);
+                                let (rest, reversed) = move_char(rest, reversed);
+                                let (rest, reversed) = move_char(rest, reversed);
+                                let (rest, reversed) = move_char(rest, reversed);
+                                let state: Expr::Cons = cons2(reversed, second);
+                                let cont: Cont::StringOp = cons4(operator, state, continuation, foo);
+                                return (rest, env, cont, makethunk)
+                            }
+                            Op2::Substring => {
+                                // `state` holds how many characters are left to skip
+                                // and to take, and the taken ones in reverse order. Once
+                                // they're all taken, the counts are dropped and `result`
+                                // is the substring, built by moving the taken characters
+                                // back like `string-append` does
+                                let (counts, taken) = decons2(state);
+                                match counts.tag {
+                                    Expr::Nil => {
+                                        let (taken, result) = move_char(taken, result);
+                                        let (taken, result) = move_char(taken, result);
+                                        let (taken, result) = move_char(taken, result);
+                                        let (taken, result) = move_char(taken, result);
+                                        let taken_is_empty = eq_val(taken, empty_str);
+                                        if taken_is_empty {
+                                            return (result, env, continuation, makethunk)
+                                        }
+                                        let state: Expr::Cons = cons2(counts, taken);
+                                        let cont: Cont::StringOp = cons4(operator, state, continuation, foo);
+                                        return (result, env, cont, makethunk)
+                                    }
+                                };
+                                let (skip, take) = decons2(counts);
+                                let (rest, taken, skip, take) = substring_char(result, taken, skip, take);
+                                let (rest, taken, skip, take) = substring_char(rest, taken, skip, take);
+                                let (rest, taken, skip, take) = substring_char(rest, taken, skip, take);
+                                let (rest, taken, skip, take) = substring_char(rest, taken, skip, take);
+                                let skip_done = eq_val(skip, zero);
+                                let take_done = eq_val(take, zero);
+                                let done = and(skip_done, take_done);
+                                if done {
+                                    let state: Expr::Cons = cons2(nil, taken);
+                                    let cont: Cont::StringOp = cons4(operator, state, continuation, foo);
+                                    return (empty_str, env, cont, makethunk)
+                                }
+                                let rest_is_empty = eq_val(rest, empty_str);
+                                if rest_is_empty {
+                                    return (rest, env, out_of_range, errctrl)
+                                }
+                                let counts: Expr::Cons = cons2(skip, take);
+                                let state: Expr::Cons = cons2(counts, taken);
+                                let cont: Cont::StringOp = cons4(operator, state, continuation, foo);
+                                return (rest, env, cont, makethunk)
+                            }
+                            Op2::StringLess => {
+                                // `state` is the rest of the second string
+                                let (a, b, decided, less) = compare_char(result, state, nil, nil);
+                                let (a, b, decided, less) = compare_char(a, b, decided, less);
+                                let (a, b, decided, less) = compare_char(a, b, decided, less);
+                                let (a, b, decided, less) = compare_char(a, b, decided, less);
+                                let is_decided = eq_val(decided, t);
+                                if is_decided {
+                                    return (less, env, continuation, makethunk)
+                                }
+                                let cont: Cont::StringOp = cons4(operator, b, continuation, foo);
+                                return (a, env, cont, makethunk)
+                            }
+                        };
+                        return (result, env, invalid_form, errctrl)
+                    }
+                    Cont::Vector => {
+                        // `vector` keeps the subtrees built so far in a stack, like the
+                        // digits of a binary counter, each paired with its size. The
+                        // subtree in `result` is merged with the one on top while both
+                        // have the same size, coming back here with the merged one.
+                        // Once the arguments run out, it's padded with `nil` up to the
+                        // size of the one on top, until a single tree is left
+                        let (state, unevaled_args, saved_env, continuation) = decons4(cont);
+                        let (header, stack) = decons2(state);
+                        let (len, size) = decons2(header);
+                        let double = add(size, size);
+                        let no_more_args = eq_tag(unevaled_args, nil);
+                        match stack.tag {
+                            Expr::Cons => {
+                                let (top, below) = decons2(stack);
+                                let (top_size, top_tree) = decons2(top);
+                                let same_size = eq_val(top_size, size);
+                                if same_size {
+                                    let tree: Expr::Cons = cons2(top_tree, result);
+                                    let header: Expr::Cons = cons2(len, double);
+                                    let state: Expr::Cons = cons2(header, below);
+                                    let cont: Cont::Vector = cons4(state, unevaled_args, saved_env, continuation);
+                                    return (tree, env, cont, makethunk)
+                                }
+                                if no_more_args {
+                                    let tree: Expr::Cons = cons2(result, nil);
+                                    let header: Expr::Cons = cons2(len, double);
+                                    let state: Expr::Cons = cons2(header, stack);
+                                    let cont: Cont::Vector = cons4(state, unevaled_args, saved_env, continuation);
+                                    return (tree, env, cont, makethunk)
+                                }
+                                let (arg, cont) = push_subtree(result, size, len, stack, unevaled_args, saved_env, continuation);
+                                return (arg, saved_env, cont, ret)
+                            }
+                        };
+                        if no_more_args {
+                            let vector: Expr::Vec = cons3(len, size, result);
+                            return (vector, env, continuation, makethunk)
+                        }
+                        let (arg, cont) = push_subtree(result, size, len, stack, unevaled_args, saved_env, continuation);
+                        return (arg, saved_env, cont, ret)
+                    }
+                    Cont::VectorOp => {
+                        // vector accesses go down the vector's tree one level per step.
+                        // `state` holds the index within the current subtree and half
+                        // of its span
+                        let (operator, state, continuation, extra) = decons4(cont);
+                        let cons_op: Op2::Cons;
+                        let two = Num(2);
+                        match operator.tag {
+                            Op2::Cons => {
+                                // `state` is the sibling of the subtree in `result` and
+                                // `extra` tells on which side it goes
+                                let on_left = eq_val(extra, nil);
+                                if on_left {
+                                    let node: Expr::Cons = cons2(result, state);
+                                    return (node, env, continuation, makethunk)
+                                }
+                                let node: Expr::Cons = cons2(state, result);
+                                return (node, env, continuation, makethunk)
+                            }
+                            Op2::VectorRef => {
+                                let (index, half) = decons2(state);
+                                let at_leaf = eq_val(half, zero);
+                                if at_leaf {
+                                    return (result, env, continuation, makethunk)
+                                }
+                                let (left, right) = vector_children(result);
+                                let (quarter, _rem) = div_rem(half, two, 128);
+                                let go_left = lt(index, half);
+                                if go_left {
+                                    let state: Expr::Cons = cons2(index, quarter);
+                                    let cont: Cont::VectorOp = cons4(operator, state, continuation, foo);
+                                    return (left, env, cont, makethunk)
+                                }
+                                let index = sub(index, half);
+                                let state: Expr::Cons = cons2(index, quarter);
+                                let cont: Cont::VectorOp = cons4(operator, state, continuation, foo);
+                                return (right, env, cont, makethunk)
+                            }
+                            Op2::VectorSet => {
+                                // `extra` is the new element. The sibling of each subtree
+                                // on the way down is kept in a continuation that conses
+                                // the new subtree back with it
+                                let (index, half) = decons2(state);
+                                let at_leaf = eq_val(half, zero);
+                                if at_leaf {
+                                    return (extra, env, continuation, makethunk)
+                                }
+                                let (left, right) = vector_children(result);
+                                let (quarter, _rem) = div_rem(half, two, 128);
+                                let go_left = lt(index, half);
+                                if go_left {
+                                    let state: Expr::Cons = cons2(index, quarter);
+                                    let continuation: Cont::VectorOp = cons4(cons_op, right, continuation, nil);
+                                    let cont: Cont::VectorOp = cons4(operator, state, continuation, extra);
+                                    return (left, env, cont, makethunk)
+                                }
+                                let index = sub(index, half);
+                                let state: Expr::Cons = cons2(index, quarter);
+                                let continuation: Cont::VectorOp = cons4(cons_op, left, continuation, t);
+                                let cont: Cont::VectorOp = cons4(operator, state, continuation, extra);
+                                return (right, env, cont, makethunk)
+                            }
+                        };
+                        return (result, env, invalid_form, errctrl)
                     }
                     Cont::Unop => {
                         let comm: Expr::Comm;
                         let result_is_char = eq_tag(result, char);
+                        let u8: Expr::U8;
+                        let u16: Expr::U16;
+                        let u32: Expr::U32;
+                        let u64: Expr::U64;
+                        let u128: Expr::U128;
+                        let result_is_u8 = eq_tag(result, u8);
+                        let result_is_u16 = eq_tag(result, u16);
+                        let result_is_u32 = eq_tag(result, u32);
                         let result_is_u64 = eq_tag(result, u64);
+                        let result_is_u128 = eq_tag(result, u128);
+                        let i64: Expr::I64;
+                        let result_is_i64 = eq_tag(result, i64);
+                        let result_is_uint = or(result_is_u8, result_is_u16);
+                        let result_is_uint = or(result_is_uint, result_is_u32);
+                        let result_is_uint = or(result_is_uint, result_is_u64);
+                        let result_is_uint = or(result_is_uint, result_is_u128);
                         let result_is_num = eq_tag(result, zero);
+                        let result_is_num_or_uint = or(result_is_num, result_is_uint);
+                        let result_is_num_or_int = or(result_is_num_or_uint, result_is_i64);
                         let result_is_comm = eq_tag(result, comm);
                         let result_is_num_or_comm = or(result_is_num, result_is_comm);
                         let (operator, continuation, _foo, _foo) = decons4(cont);
@@ -1407,7 +2564,7 @@ fn apply_cont(cprocs: &[(&Symbol, usize)], ivc: bool) -> Func {
                                         return (car, env, continuation, makethunk)
                                     }
                                 };
-                                return(result, env, err, errctrl)
+                                return(result, env, type_mismatch, errctrl)
                             }
                             Op1::Cdr => {
                                 // `car_cdr` semantics
@@ -1428,7 +2585,7 @@ fn apply_cont(cprocs: &[(&Symbol, usize)], ivc: bool) -> Func {
                                         return (cdr, env, continuation, makethunk)
                                     }
                                 };
-                                return(result, env, err, errctrl)
+                                return(result, env, type_mismatch, errctrl)
                             }
                             Op1::Atom => {
                                 match result.tag {
@@ -1449,7 +2606,7 @@ fn apply_cont(cprocs: &[(&Symbol, usize)], ivc: bool) -> Func {
                                     let (_secret, payload) = open(result);
                                     return(payload, env, continuation, makethunk)
                                 }
-                                return(result, env, err, errctrl)
+                                return(result, env, type_mismatch, errctrl)
                             }
                             Op1::Secret => {
                                 if result_is_num_or_comm {
@@ -1457,37 +2614,116 @@ fn apply_cont(cprocs: &[(&Symbol, usize)], ivc: bool) -> Func {
                                     let (secret, _payload) = open(result);
                                     return(secret, env, continuation, makethunk)
                                 }
-                                return(result, env, err, errctrl)
+                                return(result, env, type_mismatch, errctrl)
                             }
                             Op1::Commit => {
                                 let comm = hide(zero, result);
                                 return(comm, env, continuation, makethunk)
                             }
                             Op1::Num => {
+                                if result_is_i64 {
+                                    // Negative i64s become negative nums
+                                    let min_i64 = Num(9223372036854775808);
+                                    let is_pos = lt(result, min_i64);
+                                    if is_pos {
+                                        let cast = cast(result, Expr::Num);
+                                        return(cast, env, continuation, makethunk)
+                                    }
+                                    let size_u64 = Num(18446744073709551616);
+                                    let val = sub(result, size_u64);
+                                    return(val, env, continuation, makethunk)
+                                }
                                 let acc_cast = or(result_is_num_or_comm, result_is_char);
-                                let acc_cast = or(acc_cast, result_is_u64);
+                                let acc_cast = or(acc_cast, result_is_uint);
                                 if acc_cast {
                                     let cast = cast(result, Expr::Num);
                                     return(cast, env, continuation, makethunk)
                                 }
-                                return(result, env, err, errctrl)
+                                return(result, env, type_mismatch, errctrl)
+                            }
+                            Op1::U8 => {
+                                if result_is_num_or_int {
+                                    // The limit is 2**8 - 1
+                                    let trunc = truncate(result, 8);
+                                    let cast = cast(trunc, Expr::U8);
+                                    return(cast, env, continuation, makethunk)
+                                }
+                                return(result, env, type_mismatch, errctrl)
+                            }
+                            Op1::U16 => {
+                                if result_is_num_or_int {
+                                    // The limit is 2**16 - 1
+                                    let trunc = truncate(result, 16);
+                                    let cast = cast(trunc, Expr::U16);
+                                    return(cast, env, continuation, makethunk)
+                                }
+                                return(result, env, type_mismatch, errctrl)
+                            }
+                            Op1::U32 => {
+                                if result_is_num_or_int {
+                                    // The limit is 2**32 - 1
+                                    let trunc = truncate(result, 32);
+                                    let cast = cast(trunc, Expr::U32);
+                                    return(cast, env, continuation, makethunk)
+                                }
+                                return(result, env, type_mismatch, errctrl)
                             }
                             Op1::U64 => {
-                                let result_is_num_or_u64 = or(result_is_num, result_is_u64);
-                                if result_is_num_or_u64 {
+                                if result_is_num_or_int {
                                     // The limit is 2**64 - 1
                                     let trunc = truncate(result, 64);
                                     let cast = cast(trunc, Expr::U64);
                                     return(cast, env, continuation, makethunk)
                                 }
-                                return(result, env, err, errctrl)
+                                return(result, env, type_mismatch, errctrl)
+                            }
+                            Op1::U128 => {
+                                if result_is_i64 {
+                                    // Negative i64s are sign extended
+                                    let min_i64 = Num(9223372036854775808);
+                                    let is_pos = lt(result, min_i64);
+                                    if is_pos {
+                                        let cast = cast(result, Expr::U128);
+                                        return(cast, env, continuation, makethunk)
+                                    }
+                                    let size_u64 = Num(18446744073709551616);
+                                    let size_u128 = mul(size_u64, size_u64);
+                                    let ext = sub(size_u128, size_u64);
+                                    let val = add(result, ext);
+                                    let cast = cast(val, Expr::U128);
+                                    return(cast, env, continuation, makethunk)
+                                }
+                                if result_is_num_or_uint {
+                                    // The limit is 2**128 - 1
+                                    let trunc = truncate(result, 128);
+                                    let cast = cast(trunc, Expr::U128);
+                                    return(cast, env, continuation, makethunk)
+                                }
+                                return(result, env, type_mismatch, errctrl)
+                            }
+                            Op1::I64 => {
+                                if result_is_num_or_int {
+                                    // Only nums can be negative here, and they
+                                    // become negative i64s
+                                    let is_neg = lt(result, zero);
+                                    if is_neg {
+                                        let val = sub(zero, result);
+                                        let val = truncate(val, 64);
+                                        let (val) = neg_i64(val);
+                                        return(val, env, continuation, makethunk)
+                                    }
+                                    let trunc = truncate(result, 64);
+                                    let cast = cast(trunc, Expr::I64);
+                                    return(cast, env, continuation, makethunk)
+                                }
+                                return(result, env, type_mismatch, errctrl)
                             }
                             Op1::Comm => {
                                 if result_is_num_or_comm {
                                     let cast = cast(result, Expr::Comm);
                                     return(cast, env, continuation, makethunk)
                                 }
-                                return(result, env, err, errctrl)
+                                return(result, env, type_mismatch, errctrl)
                             }
                             Op1::Char => {
                                 let result_is_num_or_char = or(result_is_num, result_is_char);
@@ -1497,13 +2733,165 @@ fn apply_cont(cprocs: &[(&Symbol, usize)], ivc: bool) -> Func {
                                     let cast = cast(trunc, Expr::Char);
                                     return(cast, env, continuation, makethunk)
                                 }
-                                return(result, env, err, errctrl)
+                                return(result, env, type_mismatch, errctrl)
                             }
                             Op1::Eval => {
                                 return(result, nil, continuation, ret)
                             }
+                            Op1::StringLength => {
+                                match result.tag {
+                                    Expr::Str => {
+                                        let cont: Cont::StringOp = cons4(operator, zero, continuation, foo);
+                                        return (result, env, cont, makethunk)
+                                    }
+                                };
+                                return(result, env, type_mismatch, errctrl)
+                            }
+                            Op1::SymbolToString => {
+                                match result.tag {
+                                    Expr::Sym | Expr::Nil | Expr::Key => {
+                                        let sym = cast(result, Expr::Sym);
+                                        let is_root = eq_val(sym, zero);
+                                        if is_root {
+                                            return (empty_str, env, continuation, makethunk)
+                                        }
+                                        // a symbol is its name consed onto the symbol of its package
+                                        let (name, _package) = decons2(sym);
+                                        return (name, env, continuation, makethunk)
+                                    }
+                                };
+                                return(result, env, type_mismatch, errctrl)
+                            }
+                            Op1::StringToSymbol => {
+                                match result.tag {
+                                    Expr::Str => {
+                                        // the new symbol belongs to the user package, like the
+                                        // symbols read by the REPL
+                                        let package = Symbol("user");
+                                        let sym: Expr::Sym = cons2(result, package);
+                                        return (sym, env, continuation, makethunk)
+                                    }
+                                };
+                                return(result, env, type_mismatch, errctrl)
+                            }
+                            Op1::NumToString => {
+                                if result_is_num_or_uint {
+                                    // The absolute value is limited to 2**128 - 1, so the
+                                    // digits can be computed with 128-bit divisions
+                                    let num = cast(result, Expr::Num);
+                                    let is_negative = lt(num, zero);
+                                    if is_negative {
+                                        let abs = sub(zero, num);
+                                        let trunc = truncate(abs, 128);
+                                        let fits = eq_val(trunc, abs);
+                                        if fits {
+                                            let cont: Cont::StringOp = cons4(operator, empty_str, continuation, t);
+                                            return (abs, env, cont, makethunk)
+                                        }
+                                        return(result, env, out_of_range, errctrl)
+                                    }
+                                    let trunc = truncate(num, 128);
+                                    let fits = eq_val(trunc, num);
+                                    if fits {
+                                        let cont: Cont::StringOp = cons4(operator, empty_str, continuation, nil);
+                                        return (num, env, cont, makethunk)
+                                    }
+                                    return(result, env, out_of_range, errctrl)
+                                }
+                                return(result, env, type_mismatch, errctrl)
+                            }
+                            Op1::VectorLength => {
+                                match result.tag {
+                                    Expr::Vec => {
+                                        let (len, _span, _root) = decons3(result);
+                                        return (len, env, continuation, makethunk)
+                                    }
+                                };
+                                return(result, env, type_mismatch, errctrl)
+                            }
+                            Op1::TypeOf => {
+                                // every expression tag has its own keyword
+                                match result.tag {
+                                    Expr::Nil => {
+                                        let key = Keyword("nil");
+                                        return (key, env, continuation, makethunk)
+                                    }
+                                    Expr::Cons => {
+                                        let key = Keyword("cons");
+                                        return (key, env, continuation, makethunk)
+                                    }
+                                    Expr::Sym => {
+                                        let key = Keyword("sym");
+                                        return (key, env, continuation, makethunk)
+                                    }
+                                    Expr::Fun => {
+                                        let key = Keyword("fun");
+                                        return (key, env, continuation, makethunk)
+                                    }
+                                    Expr::Num => {
+                                        let key = Keyword("num");
+                                        return (key, env, continuation, makethunk)
+                                    }
+                                    Expr::Thunk => {
+                                        let key = Keyword("thunk");
+                                        return (key, env, continuation, makethunk)
+                                    }
+                                    Expr::Str => {
+                                        let key = Keyword("str");
+                                        return (key, env, continuation, makethunk)
+                                    }
+                                    Expr::Char => {
+                                        let key = Keyword("char");
+                                        return (key, env, continuation, makethunk)
+                                    }
+                                    Expr::Comm => {
+                                        let key = Keyword("comm");
+                                        return (key, env, continuation, makethunk)
+                                    }
+                                    Expr::U64 => {
+                                        let key = Keyword("u64");
+                                        return (key, env, continuation, makethunk)
+                                    }
+                                    Expr::Key => {
+                                        let key = Keyword("key");
+                                        return (key, env, continuation, makethunk)
+                                    }
+                                    Expr::Cproc => {
+                                        let key = Keyword("cproc");
+                                        return (key, env, continuation, makethunk)
+                                    }
+                                    Expr::U8 => {
+                                        let key = Keyword("u8");
+                                        return (key, env, continuation, makethunk)
+                                    }
+                                    Expr::U16 => {
+                                        let key = Keyword("u16");
+                                        return (key, env, continuation, makethunk)
+                                    }
+                                    Expr::U32 => {
+                                        let key = Keyword("u32");
+                                        return (key, env, continuation, makethunk)
+                                    }
+                                    Expr::U128 => {
+                                        let key = Keyword("u128");
+                                        return (key, env, continuation, makethunk)
+                                    }
+                                    Expr::I64 => {
+                                        let key = Keyword("i64");
+                                        return (key, env, continuation, makethunk)
+                                    }
+                                    Expr::Vec => {
+                                        let key = Keyword("vec");
+                                        return (key, env, continuation, makethunk)
+                                    }
+                                    Expr::Map => {
+                                        let key = Keyword("map");
+                                        return (key, env, continuation, makethunk)
+                                    }
+                                }
+                            }
                         };
-                        return (result, env, err, errctrl)
+                        return (result, env, invalid_form, errctrl)
                     }
                     Cont::Binop => {
                         let (operator, saved_env, unevaled_args, continuation) = decons4(cont);
@@ -1526,10 +2914,9 @@ fn apply_cont(cprocs: &[(&Symbol, usize)], ivc: bool) -> Func {
                                 return (arg2, saved_env, cont, ret)
                             }
                         };
-                        return (result, env, err, errctrl)
+                        return (result, env, arity_mismatch, errctrl)
                     }
                     Cont::Binop2 => {
-                        let size_u64 = Num(18446744073709551616);
                         let (operator, evaled_arg, continuation, _foo) = decons4(cont);
                         let (args_num_type) = args_num_type(evaled_arg, result);
                         let args_num_type_eq_nil = eq_tag(args_num_type, nil);
@@ -1537,6 +2924,109 @@ fn apply_cont(cprocs: &[(&Symbol, usize)], ivc: bool) -> Func {
                             Op2::Eval => {
                                 return (evaled_arg, result, continuation, ret)
                             }
+                            Op2::Apply => {
+                                let (expr, env, cont, ctrl) = apply_list(evaled_arg, result, env, continuation);
+                                return (expr, env, cont, ctrl)
+                            }
+                            Op2::StringAppend | Op2::StringLess => {
+                                let evaled_arg_is_str = eq_tag(evaled_arg, empty_str);
+                                let result_is_str = eq_tag(result, empty_str);
+                                let acc_ok = and(evaled_arg_is_str, result_is_str);
+                                if acc_ok {
+                                    match operator.tag {
+                                        Op2::StringAppend => {
+                                            // no character of the first string was taken yet
+                                            let state: Expr::Cons = cons2(empty_str, result);
+                                            let cont: Cont::StringOp = cons4(operator, state, continuation, foo);
+                                            return (evaled_arg, env, cont, makethunk)
+                                        }
+                                    };
+                                    let cont: Cont::StringOp = cons4(operator, result, continuation, foo);
+                                    return (evaled_arg, env, cont, makethunk)
+                                }
+                                return (result, env, type_mismatch, errctrl)
+                            }
+                            Op2::Substring => {
+                                // the start and the end come as a pair, see `reduce`
+                                let (start, end) = decons2(result);
+                                let string_is_str = eq_tag(evaled_arg, empty_str);
+                                let start_is_num = eq_tag(start, zero);
+                                let end_is_num = eq_tag(end, zero);
+                                let acc_ok = and(string_is_str, start_is_num);
+                                let acc_ok = and(acc_ok, end_is_num);
+                                if acc_ok {
+                                    let start_is_neg = lt(start, zero);
+                                    let take = sub(end, start);
+                                    let take_is_neg = lt(take, zero);
+                                    let out = or(start_is_neg, take_is_neg);
+                                    if out {
+                                        return (end, env, out_of_range, errctrl)
+                                    }
+                                    let counts: Expr::Cons = cons2(start, take);
+                                    let state: Expr::Cons = cons2(counts, empty_str);
+                                    let cont: Cont::StringOp = cons4(operator, state, continuation, foo);
+                                    return (evaled_arg, env, cont, makethunk)
+                                }
+                                return (result, env, type_mismatch, errctrl)
+                            }
+                            Op2::VectorRef => {
+                                match evaled_arg.tag {
+                                    Expr::Vec => {
+                                        match result.tag {
+                                            Expr::Num => {
+                                                let (len, span, root) = decons3(evaled_arg);
+                                                let index_is_neg = lt(result, zero);
+                                                if index_is_neg {
+                                                    return (result, env, out_of_range, errctrl)
+                                                }
+                                                let index_in_range = lt(result, len);
+                                                if !index_in_range {
+                                                    return (result, env, out_of_range, errctrl)
+                                                }
+                                                let two = Num(2);
+                                                let (half, _rem) = div_rem(span, two, 128);
+                                                let state: Expr::Cons = cons2(result, half);
+                                                let cont: Cont::VectorOp = cons4(operator, state, continuation, foo);
+                                                return (root, env, cont, makethunk)
+                                            }
+                                        };
+                                        return (result, env, type_mismatch, errctrl)
+                                    }
+                                };
+                                return (result, env, type_mismatch, errctrl)
+                            }
+                            Op2::VectorSet => {
+                                // the index and the value come as a pair, see `reduce`
+                                let (index, value) = decons2(result);
+                                match evaled_arg.tag {
+                                    Expr::Vec => {
+                                        match index.tag {
+                                            Expr::Num => {
+                                                let (len, span, root) = decons3(evaled_arg);
+                                                let index_is_neg = lt(index, zero);
+                                                if index_is_neg {
+                                                    return (index, env, out_of_range, errctrl)
+                                                }
+                                                let index_in_range = lt(index, len);
+                                                if !index_in_range {
+                                                    return (index, env, out_of_range, errctrl)
+                                                }
+                                                // the new tree is put back into a vector of the same shape
+                                                let two = Num(2);
+                                                let (half, _rem) = div_rem(span, two, 128);
+                                                let header: Expr::Cons = cons2(len, span);
+                                                let state: Expr::Cons = cons2(header, nil);
+                                                let next: Cont::Vector = cons4(state, nil, env, continuation);
+                                                let state: Expr::Cons = cons2(index, half);
+                                                let cont: Cont::VectorOp = cons4(operator, state, next, value);
+                                                return (root, env, cont, makethunk)
+                                            }
+                                        };
+                                        return (index, env, type_mismatch, errctrl)
+                                    }
+                                };
+                                return (evaled_arg, env, type_mismatch, errctrl)
+                            }
                             Op2::Cons => {
                                 let val: Expr::Cons = cons2(evaled_arg, result);
                                 return (val, env, continuation, makethunk)
@@ -1549,7 +3039,7 @@ fn apply_cont(cprocs: &[(&Symbol, usize)], ivc: bool) -> Func {
                                     let val: Expr::Str = cons2(evaled_arg, result);
                                     return (val, env, continuation, makethunk)
                                 }
-                                return (result, env, err, errctrl)
+                                return (result, env, type_mismatch, errctrl)
                             }
                             Op2::Hide => {
                                 match evaled_arg.tag {
@@ -1558,7 +3048,7 @@ fn apply_cont(cprocs: &[(&Symbol, usize)], ivc: bool) -> Func {
                                         return(hidden, env, continuation, makethunk)
                                     }
                                 };
-                                return (result, env, err, errctrl)
+                                return (result, env, type_mismatch, errctrl)
                             }
                             Op2::Equal => {
                                 let eq_tag = eq_tag(evaled_arg, result);
@@ -1572,102 +3062,98 @@ fn apply_cont(cprocs: &[(&Symbol, usize)], ivc: bool) -> Func {
                             Op2::Sum => {
                                 match args_num_type.tag {
                                     Expr::Nil => {
-                                        return (result, env, err, errctrl)
+                                        return (result, env, type_mismatch, errctrl)
                                     }
                                     Expr::Num => {
                                         let val = add(evaled_arg, result);
                                         return (val, env, continuation, makethunk)
                                     }
-                                    Expr::U64 => {
-                                        let val = add(evaled_arg, result);
-                                        let not_overflow = lt(val, size_u64);
-                                        if not_overflow {
-                                            let val = cast(val, Expr::U64);
-                                            return (val, env, continuation, makethunk)
-                                        }
-                                        let val = sub(val, size_u64);
-                                        let val = cast(val, Expr::U64);
-                                        return (val, env, continuation, makethunk)
-                                    }
-                                }
+                                };
+                                let val = add(evaled_arg, result);
+                                let (val) = wrap_uint(val, args_num_type);
+                                return (val, env, continuation, makethunk)
                             }
                             Op2::Diff => {
                                 match args_num_type.tag {
                                     Expr::Nil => {
-                                        return (result, env, err, errctrl)
+                                        return (result, env, type_mismatch, errctrl)
                                     }
                                     Expr::Num => {
                                         let val = sub(evaled_arg, result);
                                         return (val, env, continuation, makethunk)
                                     }
-                                    Expr::U64 => {
-                                        // Subtraction in U64 is almost the same as subtraction
-                                        // in the field. If the difference is negative, we need
-                                        // to add 2^64 to get back to U64 domain.
-                                        let val = sub(evaled_arg, result);
-                                        let is_neg = lt(val, zero);
-                                        let not_neg = not(is_neg);
-                                        if not_neg {
-                                            let val = cast(val, Expr::U64);
-                                            return (val, env, continuation, makethunk)
-                                        }
-                                        let val = add(val, size_u64);
-                                        let val = cast(val, Expr::U64);
-                                        return (val, env, continuation, makethunk)
-                                    }
-                                }
+                                };
+                                // Adding 2^128 keeps the difference positive without
+                                // changing it modulo 2^width for any of the widths
+                                let size_u64 = Num(18446744073709551616);
+                                let size_u128 = mul(size_u64, size_u64);
+                                let val = add(evaled_arg, size_u128);
+                                let val = sub(val, result);
+                                let (val) = wrap_uint(val, args_num_type);
+                                return (val, env, continuation, makethunk)
                             }
                             Op2::Product => {
                                 match args_num_type.tag {
                                     Expr::Nil => {
-                                        return (result, env, err, errctrl)
+                                        return (result, env, type_mismatch, errctrl)
                                     }
                                     Expr::Num => {
                                         let val = mul(evaled_arg, result);
                                         return (val, env, continuation, makethunk)
                                     }
-                                    Expr::U64 => {
-                                        let val = mul(evaled_arg, result);
-                                        // The limit is 2**64 - 1
-                                        let trunc = truncate(val, 64);
-                                        let cast = cast(trunc, Expr::U64);
-                                        return (cast, env, continuation, makethunk)
+                                    Expr::U128 => {
+                                        let (val) = mul_u128(evaled_arg, result);
+                                        return (val, env, continuation, makethunk)
                                     }
-                                }
+                                };
+                                // Products of up to 64 bits don't overflow the field
+                                let val = mul(evaled_arg, result);
+                                let (val) = wrap_uint(val, args_num_type);
+                                return (val, env, continuation, makethunk)
                             }
                             Op2::Quotient => {
+                                if args_num_type_eq_nil {
+                                    return (result, env, type_mismatch, errctrl)
+                                }
                                 let is_z = eq_val(result, zero);
-                                let acc_err = or(is_z, args_num_type_eq_nil);
-                                if acc_err {
-                                    return (result, env, err, errctrl)
+                                if is_z {
+                                    return (result, env, div_by_zero, errctrl)
                                 }
                                 match args_num_type.tag {
                                     Expr::Num => {
                                         let val = div(evaled_arg, result);
                                         return (val, env, continuation, makethunk)
                                     }
-                                    Expr::U64 => {
-                                        let (div, _rem) = div_rem64(evaled_arg, result);
-                                        let div = cast(div, Expr::U64);
+                                    Expr::I64 => {
+                                        let (div, _rem) = div_rem_i64(evaled_arg, result);
                                         return (div, env, continuation, makethunk)
                                     }
-                                }
+                                };
+                                let (div, _rem) = div_rem_uint(evaled_arg, result, args_num_type);
+                                return (div, env, continuation, makethunk)
                             }
                             Op2::Modulo => {
+                                match args_num_type.tag {
+                                    Expr::Nil | Expr::Num => {
+                                        return (result, env, type_mismatch, errctrl)
+                                    }
+                                };
                                 let is_z = eq_val(result, zero);
-                                let is_not_z = not(is_z);
-                                let args_num_type_is_num = eq_tag(args_num_type, u64);
-                                let acc_ok = and(is_not_z, args_num_type_is_num);
-                                if acc_ok {
-                                    let (_div, rem) = div_rem64(evaled_arg, result);
-                                    let rem = cast(rem, Expr::U64);
-                                    return (rem, env, continuation, makethunk)
+                                if is_z {
+                                    return (result, env, div_by_zero, errctrl)
                                 }
-                                return (result, env, err, errctrl)
+                                match args_num_type.tag {
+                                    Expr::I64 => {
+                                        let (_div, rem) = div_rem_i64(evaled_arg, result);
+                                        return (rem, env, continuation, makethunk)
+                                    }
+                                };
+                                let (_div, rem) = div_rem_uint(evaled_arg, result, args_num_type);
+                                return (rem, env, continuation, makethunk)
                             }
                             Op2::NumEqual => {
                                 if args_num_type_eq_nil {
-                                    return (result, env, err, errctrl)
+                                    return (result, env, type_mismatch, errctrl)
                                 }
                                 let eq = eq_val(evaled_arg, result);
                                 if eq {
@@ -1675,48 +3161,102 @@ fn apply_cont(cprocs: &[(&Symbol, usize)], ivc: bool) -> Func {
                                 }
                                 return (nil, env, continuation, makethunk)
                             }
-                            Op2::Less => {
+                            Op2::Less | Op2::Greater | Op2::LessEqual | Op2::GreaterEqual => {
                                 if args_num_type_eq_nil {
-                                    return (result, env, err, errctrl)
+                                    return (result, env, type_mismatch, errctrl)
                                 }
-                                let val = lt(evaled_arg, result);
-                                if val {
-                                    return (t, env, continuation, makethunk)
+                                let (lt_arg) = lt_operand(evaled_arg, args_num_type);
+                                let (lt_result) = lt_operand(result, args_num_type);
+                                match operator.tag {
+                                    Op2::Less => {
+                                        let val = lt(lt_arg, lt_result);
+                                        if val {
+                                            return (t, env, continuation, makethunk)
+                                        }
+                                        return (nil, env, continuation, makethunk)
+                                    }
+                                    Op2::Greater => {
+                                        let val = lt(lt_result, lt_arg);
+                                        if val {
+                                            return (t, env, continuation, makethunk)
+                                        }
+                                        return (nil, env, continuation, makethunk)
+                                    }
+                                    Op2::LessEqual => {
+                                        let val = lt(lt_result, lt_arg);
+                                        if val {
+                                            return (nil, env, continuation, makethunk)
+                                        }
+                                        return (t, env, continuation, makethunk)
+                                    }
+                                    Op2::GreaterEqual => {
+                                        let val = lt(lt_arg, lt_result);
+                                        if val {
+                                            return (nil, env, continuation, makethunk)
+                                        }
+                                        return (t, env, continuation, makethunk)
+                                    }
                                 }
-                                return (nil, env, continuation, makethunk)
                             }
-                            Op2::Greater => {
-                                if args_num_type_eq_nil {
-                                    return (result, env, err, errctrl)
-                                }
-                                let val = lt(result, evaled_arg);
-                                if val {
-                                    return (t, env, continuation, makethunk)
-                                }
-                                return (nil, env, continuation, makethunk)
+                            Op2::LogAnd => {
+                                match args_num_type.tag {
+                                    Expr::U64 => {
+                                        let val = bit_and(evaled_arg, result, 64);
+                                        let val = cast(val, Expr::U64);
+                                        return (val, env, continuation, makethunk)
+                                    }
+                                };
+                                return (result, env, type_mismatch, errctrl)
                             }
-                            Op2::LessEqual => {
-                                if args_num_type_eq_nil {
-                                    return (result, env, err, errctrl)
-                                }
-                                let val = lt(result, evaled_arg);
-                                if val {
-                                    return (nil, env, continuation, makethunk)
-                                }
-                                return (t, env, continuation, makethunk)
+                            Op2::LogOr => {
+                                match args_num_type.tag {
+                                    Expr::U64 => {
+                                        let val = bit_or(evaled_arg, result, 64);
+                                        let val = cast(val, Expr::U64);
+                                        return (val, env, continuation, makethunk)
+                                    }
+                                };
+                                return (result, env, type_mismatch, errctrl)
                             }
-                            Op2::GreaterEqual => {
-                                if args_num_type_eq_nil {
-                                    return (result, env, err, errctrl)
-                                }
-                                let val = lt(evaled_arg, result);
-                                if val {
-                                    return (nil, env, continuation, makethunk)
-                                }
-                                return (t, env, continuation, makethunk)
+                            Op2::LogXor => {
+                                match args_num_type.tag {
+                                    Expr::U64 => {
+                                        let val = bit_xor(evaled_arg, result, 64);
+                                        let val = cast(val, Expr::U64);
+                                        return (val, env, continuation, makethunk)
+                                    }
+                                };
+                                return (result, env, type_mismatch, errctrl)
+                            }
+                            Op2::Shl | Op2::Shr | Op2::Rotl | Op2::Rotr => {
+                                match args_num_type.tag {
+                                    Expr::U64 => {
+                                        // Shift amounts are taken modulo 64. Left
+                                        // operations split `a * 2^k` and right ones
+                                        // split `a * 2^(64 - k)`
+                                        let k = truncate(result, 6);
+                                        let (e) = shift_exponent(operator, k);
+                                        let (lo, hi) = split_u64(evaled_arg, e);
+                                        match operator.tag {
+                                            Op2::Shl => {
+                                                let val = cast(lo, Expr::U64);
+                                                return (val, env, continuation, makethunk)
+                                            }
+                                            Op2::Shr => {
+                                                let val = cast(hi, Expr::U64);
+                                                return (val, env, continuation, makethunk)
+                                            }
+                                        };
+                                        // The halves have no bits in common
+                                        let val = add(lo, hi);
+                                        let val = cast(val, Expr::U64);
+                                        return (val, env, continuation, makethunk)
+                                    }
+                                };
+                                return (result, env, type_mismatch, errctrl)
                             }
                         };
-                        return (result, env, err, errctrl)
+                        return (result, env, invalid_form, errctrl)
                     }
                     Cont::If => {
                         let (unevaled_args, continuation, _foo, _foo) = decons4(cont);
@@ -1732,7 +3272,7 @@ fn apply_cont(cprocs: &[(&Symbol, usize)], ivc: bool) -> Func {
                                 return (arg1, env, continuation, ret)
                             }
                         };
-                        return (arg1, env, err, errctrl)
+                        return (arg1, env, invalid_form, errctrl)
                     }
                     Cont::Cproc => {
                         let (cproc_name, unevaled_args, evaluated_args, cont) = decons4(cont);
@@ -1764,9 +3304,17 @@ fn apply_cont(cprocs: &[(&Symbol, usize)], ivc: bool) -> Func {
     })
 }
 
+/// Finishes the step. When `ctrl` is `"error"`, `cont` holds the error code
+/// and `expr` the offending expression, which are packed as the children of the
+/// resulting error continuation.
 fn make_thunk() -> Func {
     func!(make_thunk(expr, env, cont, ctrl): 3 => {
         match symbol ctrl {
+            "error" => {
+                let foo: Expr::Nil;
+                let err: Cont::Error = cons4(cont, expr, foo, foo);
+                return (expr, env, err)
+            }
             "make-thunk" => {
                 match cont.tag {
                     Cont::Tail => {
@@ -1796,7 +3344,7 @@ mod tests {
         eval::lang::{Coproc, Lang},
         lem::{slot::SlotsCounter, store::Store},
     };
-    use bellpepper_core::{test_cs::TestConstraintSystem, Comparable};
+    use bellpepper_core::test_cs::TestConstraintSystem;
     use pasta_curves::pallas::Scalar as Fr;
 
     #[test]
@@ -1810,16 +3358,15 @@ mod tests {
         assert_eq!(
             func.slots_count,
             SlotsCounter {
-                hash4: 14,
-                hash6: 3,
-                hash8: 4,
+                hash4: 21,
+                hash6: 6,
+                hash8: 7,
                 commitment: 1,
-                bit_decomp: 3,
+                bit_decomp: 14,
             }
         );
         assert_eq!(cs.num_inputs(), 1);
-        assert_eq!(cs.aux().len(), 9118);
-        assert_eq!(cs.num_constraints(), 11064);
+        assert_eq!(cs.num_constraints(), 29634);
         assert_eq!(func.num_constraints(&store), cs.num_constraints());
     }
 }
@@ -119,6 +119,8 @@ pub struct Frame<F: LurkField> {
     pub hints: Hints<F>,
     pub blank: bool,
     pub pc: usize,
+    /// The concrete path taken by the interpreter
+    pub path: Path,
 }
 
 impl<F: LurkField> Frame<F> {
@@ -133,6 +135,7 @@ impl<F: LurkField> Frame<F> {
             hints,
             blank: true,
             pc,
+            path: Path::default(),
         }
     }
 }
@@ -307,31 +310,65 @@ impl Block {
                     bindings.insert_bool(tgt.clone(), c);
                 }
                 Op::Trunc(tgt, a, n) => {
-                    assert!(*n <= 64);
+                    assert!(*n <= 128);
                     let a = bindings.get_ptr(a)?;
                     let c = if let Ptr::Atom(_, f) = a {
                         hints.bit_decomp.push(Some(SlotData::F(f)));
-                        let b = if *n < 64 { (1 << *n) - 1 } else { u64::MAX };
-                        Ptr::Atom(Tag::Expr(Num), F::from_u64(f.to_u64_unchecked() & b))
+                        let b = if *n < 128 { (1 << *n) - 1 } else { u128::MAX };
+                        Ptr::Atom(Tag::Expr(Num), F::from_u128(f.to_u128_unchecked() & b))
                     } else {
                         bail!("`Trunc` only works on atoms")
                     };
                     bindings.insert_ptr(tgt.clone(), c);
                 }
-                Op::DivRem64(tgt, a, b) => {
+                Op::BitAnd(tgt, a, b, n) | Op::BitOr(tgt, a, b, n) | Op::BitXor(tgt, a, b, n) => {
+                    assert!(*n <= 128);
+                    let a = bindings.get_ptr(a)?;
+                    let b = bindings.get_ptr(b)?;
+                    let c = if let (Ptr::Atom(_, f), Ptr::Atom(_, g)) = (a, b) {
+                        hints.bit_decomp.push(Some(SlotData::F(f)));
+                        hints.bit_decomp.push(Some(SlotData::F(g)));
+                        let mask = if *n < 128 { (1 << *n) - 1 } else { u128::MAX };
+                        let f = f.to_u128_unchecked() & mask;
+                        let g = g.to_u128_unchecked() & mask;
+                        let c = match op {
+                            Op::BitAnd(..) => f & g,
+                            Op::BitOr(..) => f | g,
+                            _ => f ^ g,
+                        };
+                        Ptr::Atom(Tag::Expr(Num), F::from_u128(c))
+                    } else {
+                        bail!("Bitwise operations only work on atoms")
+                    };
+                    bindings.insert_ptr(tgt.clone(), c);
+                }
+                Op::Pow2(tgt, a, n) => {
+                    assert!(*n <= 7);
+                    let a = bindings.get_ptr(a)?;
+                    let c = if let Ptr::Atom(_, f) = a {
+                        hints.bit_decomp.push(Some(SlotData::F(f)));
+                        let k = f.to_u128_unchecked() & ((1 << *n) - 1);
+                        Ptr::Atom(Tag::Expr(Num), F::from_u128(1 << k))
+                    } else {
+                        bail!("`Pow2` only works on atoms")
+                    };
+                    bindings.insert_ptr(tgt.clone(), c);
+                }
+                Op::DivRem(tgt, a, b, n) => {
+                    assert!(*n <= 128);
                     let a = bindings.get_ptr(a)?;
                     let b = bindings.get_ptr(b)?;
                     let (c1, c2) = if let (Ptr::Atom(_, f), Ptr::Atom(_, g)) = (a, b) {
                         if g == F::ZERO {
                             bail!("Can't divide by zero")
                         }
-                        let f = f.to_u64_unchecked();
-                        let g = g.to_u64_unchecked();
-                        let c1 = Ptr::Atom(Tag::Expr(Num), F::from_u64(f / g));
-                        let c2 = Ptr::Atom(Tag::Expr(Num), F::from_u64(f % g));
+                        let f = f.to_u128_unchecked();
+                        let g = g.to_u128_unchecked();
+                        let c1 = Ptr::Atom(Tag::Expr(Num), F::from_u128(f / g));
+                        let c2 = Ptr::Atom(Tag::Expr(Num), F::from_u128(f % g));
                         (c1, c2)
                     } else {
-                        bail!("`DivRem64` only works on atoms")
+                        bail!("`DivRem` only works on atoms")
                     };
                     bindings.insert_ptr(tgt[0].clone(), c1);
                     bindings.insert_ptr(tgt[1].clone(), c2);
@@ -494,6 +531,7 @@ impl Block {
                         hints,
                         blank: false,
                         pc,
+                        path: path.clone(),
                     },
                     path,
                 ))
@@ -25,6 +25,9 @@ macro_rules! lit {
     ( Symbol($lit:literal) ) => {
         $crate::lem::Lit::Symbol($crate::state::lurk_sym(&$lit))
     };
+    ( Keyword($lit:literal) ) => {
+        $crate::lem::Lit::Symbol($crate::Symbol::key(&[$lit]))
+    };
 }
 
 #[macro_export]
@@ -149,11 +152,43 @@ macro_rules! op {
             $b,
         )
     };
-    ( let ($tgt1:ident, $tgt2:ident) = div_rem64($a:ident, $b:ident) ) => {
-        $crate::lem::Op::DivRem64(
+    ( let $tgt:ident = bit_and($a:ident, $b:ident, $n:literal) ) => {
+        $crate::lem::Op::BitAnd(
+            $crate::var!($tgt),
+            $crate::var!($a),
+            $crate::var!($b),
+            $n,
+        )
+    };
+    ( let $tgt:ident = bit_or($a:ident, $b:ident, $n:literal) ) => {
+        $crate::lem::Op::BitOr(
+            $crate::var!($tgt),
+            $crate::var!($a),
+            $crate::var!($b),
+            $n,
+        )
+    };
+    ( let $tgt:ident = bit_xor($a:ident, $b:ident, $n:literal) ) => {
+        $crate::lem::Op::BitXor(
+            $crate::var!($tgt),
+            $crate::var!($a),
+            $crate::var!($b),
+            $n,
+        )
+    };
+    ( let $tgt:ident = pow2($a:ident, $n:literal) ) => {
+        $crate::lem::Op::Pow2(
+            $crate::var!($tgt),
+            $crate::var!($a),
+            $n,
+        )
+    };
+    ( let ($tgt1:ident, $tgt2:ident) = div_rem($a:ident, $b:ident, $n:literal) ) => {
+        $crate::lem::Op::DivRem(
             $crate::vars!($tgt1, $tgt2),
             $crate::var!($a),
             $crate::var!($b),
+            $n,
         )
     };
     ( emit($v:ident) ) => {
@@ -464,12 +499,52 @@ macro_rules! block {
             $($tail)*
         )
     };
-    (@seq {$($limbs:expr)*},  let ($tgt1:ident, $tgt2:ident) = div_rem64($a:ident, $b:ident) ; $($tail:tt)*) => {
+    (@seq {$($limbs:expr)*}, let $tgt:ident = bit_and($a:ident, $b:ident, $n:literal) ; $($tail:tt)*) => {
+        $crate::block! (
+            @seq
+            {
+                $($limbs)*
+                $crate::op!(let $tgt = bit_and($a, $b, $n))
+            },
+            $($tail)*
+        )
+    };
+    (@seq {$($limbs:expr)*}, let $tgt:ident = bit_or($a:ident, $b:ident, $n:literal) ; $($tail:tt)*) => {
+        $crate::block! (
+            @seq
+            {
+                $($limbs)*
+                $crate::op!(let $tgt = bit_or($a, $b, $n))
+            },
+            $($tail)*
+        )
+    };
+    (@seq {$($limbs:expr)*}, let $tgt:ident = bit_xor($a:ident, $b:ident, $n:literal) ; $($tail:tt)*) => {
+        $crate::block! (
+            @seq
+            {
+                $($limbs)*
+                $crate::op!(let $tgt = bit_xor($a, $b, $n))
+            },
+            $($tail)*
+        )
+    };
+    (@seq {$($limbs:expr)*}, let $tgt:ident = pow2($a:ident, $n:literal) ; $($tail:tt)*) => {
+        $crate::block! (
+            @seq
+            {
+                $($limbs)*
+                $crate::op!(let $tgt = pow2($a, $n))
+            },
+            $($tail)*
+        )
+    };
+    (@seq {$($limbs:expr)*},  let ($tgt1:ident, $tgt2:ident) = div_rem($a:ident, $b:ident, $n:literal) ; $($tail:tt)*) => {
         $crate::block! (
             @seq
             {
                 $($limbs)*
-                $crate::op!(let ($tgt1, $tgt2) = div_rem64($a, $b))
+                $crate::op!(let ($tgt1, $tgt2) = div_rem($a, $b, $n))
             },
             $($tail)*
         )
@@ -514,6 +589,16 @@ macro_rules! block {
             $($tail)*
         )
     };
+    (@seq {$($limbs:expr)*}, let $tgt:ident = Keyword($sym:literal) ; $($tail:tt)*) => {
+        $crate::block! (
+            @seq
+            {
+                $($limbs)*
+                $crate::op!(let $tgt = Keyword($sym))
+            },
+            $($tail)*
+        )
+    };
     (@seq {$($limbs:expr)*}, let $tgt:ident : $kind:ident::$tag:ident = cons2($src1:ident, $src2:ident) ; $($tail:tt)*) => {
         $crate::block! (
             @seq
@@ -64,10 +64,14 @@ pub mod eval;
 pub(crate) mod interpreter;
 mod macros;
 pub mod multiframe;
-mod path;
+pub mod optimize;
+pub mod path;
 pub mod pointers;
+pub mod profile;
 mod slot;
 pub mod store;
+pub mod text;
+pub mod types;
 mod var_map;
 
 use anyhow::{bail, Result};
@@ -80,6 +84,7 @@ mod tests;
 
 use crate::{
     field::LurkField,
+    state::lurk_sym,
     symbol::Symbol,
     tag::{ContTag, ExprTag, Op1, Op2, Tag as TagTrait},
 };
@@ -187,6 +192,26 @@ impl Lit {
             Self::Num(num) => Ptr::num(F::from_u128(*num)),
         }
     }
+
+    /// The tag of the pointer that represents this `Lit`
+    pub fn tag(&self) -> Tag {
+        match self {
+            Self::Symbol(s) => symbol_tag(s),
+            Self::String(..) => Tag::Expr(ExprTag::Str),
+            Self::Num(..) => Tag::Expr(ExprTag::Num),
+        }
+    }
+}
+
+/// The tag of an interned symbol, which can be `nil` or a keyword
+fn symbol_tag(sym: &Symbol) -> Tag {
+    if sym == &lurk_sym("nil") {
+        Tag::Expr(ExprTag::Nil)
+    } else if sym.is_keyword() {
+        Tag::Expr(ExprTag::Key)
+    } else {
+        Tag::Expr(ExprTag::Sym)
+    }
 }
 
 impl std::fmt::Display for Var {
@@ -282,10 +307,23 @@ pub enum Op {
     Div(Var, Var, Var),
     /// `Lt(y, a, b)` binds `y` to `1` if `a < b`, or to `0` otherwise
     Lt(Var, Var, Var),
-    /// `Trunc(y, a, n)` binds `y` to `a` truncated to `n` bits, up to 64 bits
+    /// `Trunc(y, a, n)` binds `y` to `a` truncated to `n` bits, up to 128 bits
     Trunc(Var, Var, u32),
-    /// `DivRem64(ys, a, b)` binds `ys` to `(a / b, a % b)` as if they were u64
-    DivRem64([Var; 2], Var, Var),
+    /// `DivRem(ys, a, b, n)` binds `ys` to `(a / b, a % b)` as if they were
+    /// unsigned integers of `n` bits, up to 128 bits
+    DivRem([Var; 2], Var, Var, u32),
+    /// `BitAnd(y, a, b, n)` binds `y` to the bitwise and of the first `n` bits
+    /// of `a` and `b`, up to 128 bits
+    BitAnd(Var, Var, Var, u32),
+    /// `BitOr(y, a, b, n)` binds `y` to the bitwise or of the first `n` bits
+    /// of `a` and `b`, up to 128 bits
+    BitOr(Var, Var, Var, u32),
+    /// `BitXor(y, a, b, n)` binds `y` to the bitwise xor of the first `n` bits
+    /// of `a` and `b`, up to 128 bits
+    BitXor(Var, Var, Var, u32),
+    /// `Pow2(y, a, n)` binds `y` to `2^k`, where `k` is `a` truncated to `n`
+    /// bits, up to 7 bits
+    Pow2(Var, Var, u32),
     /// `Emit(v)` simply prints out the value of `v` when interpreting the code
     Emit(Var),
     /// `Cons2(x, t, ys)` binds `x` to a `Ptr` with tag `t` and 2 children `ys`
@@ -414,17 +452,37 @@ impl Func {
                         is_unique(tgt, map);
                     }
                     Op::Trunc(tgt, a, n) => {
-                        if *n > 64 {
-                            bail!("Cannot yet truncate over 64 bits")
+                        if *n > 128 {
+                            bail!("Cannot truncate over 128 bits")
                         }
                         is_bound(a, map)?;
                         is_unique(tgt, map);
                     }
-                    Op::DivRem64(tgt, a, b) => {
+                    Op::DivRem(tgt, a, b, n) => {
+                        if *n > 128 {
+                            bail!("Cannot divide integers of over 128 bits")
+                        }
                         is_bound(a, map)?;
                         is_bound(b, map)?;
                         tgt.iter().for_each(|var| is_unique(var, map))
                     }
+                    Op::BitAnd(tgt, a, b, n)
+                    | Op::BitOr(tgt, a, b, n)
+                    | Op::BitXor(tgt, a, b, n) => {
+                        if *n > 128 {
+                            bail!("Cannot operate on bits over 128 bits")
+                        }
+                        is_bound(a, map)?;
+                        is_bound(b, map)?;
+                        is_unique(tgt, map);
+                    }
+                    Op::Pow2(tgt, a, n) => {
+                        if *n > 7 {
+                            bail!("Cannot compute powers of 2 over 127")
+                        }
+                        is_bound(a, map)?;
+                        is_unique(tgt, map);
+                    }
                     Op::Emit(a) => {
                         is_bound(a, map)?;
                     }
@@ -696,11 +754,34 @@ impl Block {
                     let tgt = insert_one(map, uniq, &tgt);
                     ops.push(Op::Trunc(tgt, a, b))
                 }
-                Op::DivRem64(tgt, a, b) => {
+                Op::DivRem(tgt, a, b, n) => {
                     let a = map.get_cloned(&a)?;
                     let b = map.get_cloned(&b)?;
                     let tgt = insert_many(map, uniq, &tgt);
-                    ops.push(Op::DivRem64(tgt.try_into().unwrap(), a, b))
+                    ops.push(Op::DivRem(tgt.try_into().unwrap(), a, b, n))
+                }
+                Op::BitAnd(tgt, a, b, n) => {
+                    let a = map.get_cloned(&a)?;
+                    let b = map.get_cloned(&b)?;
+                    let tgt = insert_one(map, uniq, &tgt);
+                    ops.push(Op::BitAnd(tgt, a, b, n))
+                }
+                Op::BitOr(tgt, a, b, n) => {
+                    let a = map.get_cloned(&a)?;
+                    let b = map.get_cloned(&b)?;
+                    let tgt = insert_one(map, uniq, &tgt);
+                    ops.push(Op::BitOr(tgt, a, b, n))
+                }
+                Op::BitXor(tgt, a, b, n) => {
+                    let a = map.get_cloned(&a)?;
+                    let b = map.get_cloned(&b)?;
+                    let tgt = insert_one(map, uniq, &tgt);
+                    ops.push(Op::BitXor(tgt, a, b, n))
+                }
+                Op::Pow2(tgt, a, n) => {
+                    let a = map.get_cloned(&a)?;
+                    let tgt = insert_one(map, uniq, &tgt);
+                    ops.push(Op::Pow2(tgt, a, n))
                 }
                 Op::Emit(a) => {
                     let a = map.get_cloned(&a)?;
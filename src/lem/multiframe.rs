@@ -14,7 +14,7 @@ use crate::{
     coprocessor::Coprocessor,
     error::{ProofError, ReductionError},
     eval::lang::Lang,
-    field::{LanguageField, LurkField},
+    field::LurkField,
     proof::{
         nova::{CurveCycleEquipped, E1, E2},
         supernova::{FoldingConfig, C2},
@@ -32,7 +32,7 @@ use super::{
     },
     interpreter::Frame,
     pointers::Ptr,
-    slot::SlotType,
+    slot::{bit_decomp_cost, SlotType},
     store::Store,
     Func, Tag,
 };
@@ -143,12 +143,6 @@ fn assert_eq_ptrs_aptrs<F: LurkField>(
     Ok(())
 }
 
-// Hardcoded slot witness sizes, empirically collected
-const BIT_DECOMP_PALLAS_WITNESS_SIZE: usize = 298;
-const BIT_DECOMP_VESTA_WITNESS_SIZE: usize = 301;
-const BIT_DECOMP_BN256_WITNESS_SIZE: usize = 354;
-const BIT_DECOMP_GRUMPKIN_WITNESS_SIZE: usize = 364;
-
 /// Computes the witness size for a `SlotType`. Note that the witness size for
 /// bit decomposition depends on the field we're in.
 #[inline]
@@ -158,12 +152,7 @@ fn compute_witness_size<F: LurkField>(slot_type: &SlotType, store: &Store<F>) ->
         SlotType::Hash6 => store.hash6_cost() + 6, // 6 preimg elts
         SlotType::Hash8 => store.hash8_cost() + 8, // 8 preimg elts
         SlotType::Commitment => store.hash3_cost() + 3, // 3 preimg elts
-        SlotType::BitDecomp => match F::FIELD {
-            LanguageField::Pallas => BIT_DECOMP_PALLAS_WITNESS_SIZE,
-            LanguageField::Vesta => BIT_DECOMP_VESTA_WITNESS_SIZE,
-            LanguageField::BN256 => BIT_DECOMP_BN256_WITNESS_SIZE,
-            LanguageField::Grumpkin => BIT_DECOMP_GRUMPKIN_WITNESS_SIZE,
-        },
+        SlotType::BitDecomp => bit_decomp_cost::<F>(),
     }
 }
 
@@ -0,0 +1,1002 @@
+//! ## LEM optimizations
+//!
+//! Semantics-preserving transformations of a `Func` that reduce the number of
+//! constraints of its circuit. The passes rely on the SSA form established by
+//! `Func::deconflict`, so they must run on `Func`s created with `Func::new`.
+//!
+//! Every pass rebuilds the function body and recounts its slots afterwards.
+//! Bindings left unused by a pass are prefixed by `_`, so the optimized `Func`
+//! still passes `Func::check`.
+//!
+//! Lurk's step function only goes through the passes when it's built from an
+//! `EvalConfig` that asks for them, see `EvalConfig::with_optimizations`.
+
+use anyhow::{Context, Result};
+use indexmap::IndexMap;
+use std::collections::{HashMap, HashSet};
+
+use crate::{
+    field::LurkField,
+    symbol::Symbol,
+    tag::ExprTag::{Comm, Num},
+};
+
+use super::{store::Store, symbol_tag, Block, Ctrl, Func, Lit, Op, Tag, Var};
+
+/// The optimization passes over LEM functions
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Pass {
+    /// Folds arithmetic on numeric literals and computes what's statically
+    /// known about tags, symbols and booleans, eliminating the `If`s and
+    /// matches whose branch can be decided from that knowledge
+    ConstantFolding,
+    /// Reuses the results of `Cons*` and `Decons*` operations that were
+    /// already computed with the same arguments
+    CommonSubexpressions,
+    /// Removes match cases that are identical to the default case and `If`s
+    /// whose branches are identical
+    MergeBranches,
+    /// Removes operations without side effects whose results are never used
+    DeadCode,
+}
+
+/// All the passes, in the order in which `Func::optimize` runs them
+pub const PASSES: [Pass; 4] = [
+    Pass::ConstantFolding,
+    Pass::CommonSubexpressions,
+    Pass::MergeBranches,
+    Pass::DeadCode,
+];
+
+impl std::fmt::Display for Pass {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Pass::ConstantFolding => write!(f, "constant folding"),
+            Pass::CommonSubexpressions => write!(f, "common subexpression elimination"),
+            Pass::MergeBranches => write!(f, "branch merging"),
+            Pass::DeadCode => write!(f, "dead code elimination"),
+        }
+    }
+}
+
+impl Pass {
+    fn run(self, func: &Func) -> Func {
+        let func = func.clone();
+        let func = match self {
+            Pass::ConstantFolding => {
+                map_body(func, |body| ConstantFolding::default().block(vec![], body))
+            }
+            Pass::CommonSubexpressions => map_body(func, |body| {
+                CommonSubexpressions::default().block(body, HashMap::new())
+            }),
+            Pass::MergeBranches => map_body(func, merge_branches),
+            Pass::DeadCode => map_body(func, |body| dead_code(body, &mut HashSet::new())),
+        };
+        underscore_unused(func)
+    }
+
+    /// Runs the pass and checks that the resulting function is valid
+    fn run_checked(self, func: &Func) -> Result<Func> {
+        let func = self.run(func);
+        func.check()
+            .with_context(|| format!("{self} produced an invalid function"))?;
+        Ok(func)
+    }
+}
+
+/// The effect of a `Pass` on the number of constraints of a `Func`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PassReport {
+    pub pass: Pass,
+    pub constraints_before: usize,
+    pub constraints_after: usize,
+}
+
+impl PassReport {
+    /// The number of constraints saved by the pass (negative if it added some)
+    #[inline]
+    pub fn saved(&self) -> isize {
+        self.constraints_before as isize - self.constraints_after as isize
+    }
+}
+
+impl Func {
+    /// Runs all the optimization passes on a copy of `self`
+    #[inline]
+    pub fn optimize<F: LurkField>(&self, store: &Store<F>) -> Result<(Func, Vec<PassReport>)> {
+        self.optimize_with(&PASSES, store)
+    }
+
+    /// Runs all the optimization passes on a copy of `self`, without
+    /// measuring them. Unlike `Func::optimize`, it doesn't need a store
+    pub fn optimized(&self) -> Result<Func> {
+        PASSES
+            .iter()
+            .try_fold(self.clone(), |func, pass| pass.run_checked(&func))
+    }
+
+    /// Runs the provided optimization passes, in order, on a copy of `self`,
+    /// reporting the constraints saved by each of them
+    pub fn optimize_with<F: LurkField>(
+        &self,
+        passes: &[Pass],
+        store: &Store<F>,
+    ) -> Result<(Func, Vec<PassReport>)> {
+        let mut func = self.clone();
+        let mut reports = Vec::with_capacity(passes.len());
+        for pass in passes {
+            let constraints_before = func.num_constraints(store);
+            func = pass.run_checked(&func)?;
+            reports.push(PassReport {
+                pass: *pass,
+                constraints_before,
+                constraints_after: func.num_constraints(store),
+            });
+        }
+        Ok((func, reports))
+    }
+}
+
+/// Replaces the body of `func` by `f(body)`, recounting its slots
+fn map_body(func: Func, f: impl FnOnce(Block) -> Block) -> Func {
+    let body = f(func.body);
+    Func {
+        name: func.name,
+        input_params: func.input_params,
+        output_size: func.output_size,
+        slots_count: body.count_slots(),
+        body,
+    }
+}
+
+/// Extends `ops` with the operations of `block`, ending with its control
+#[inline]
+fn append(mut ops: Vec<Op>, block: Block) -> Block {
+    ops.extend(block.ops);
+    Block {
+        ops,
+        ctrl: block.ctrl,
+    }
+}
+
+/// The variables read and bound by an operation, respectively. The variables
+/// of the body of a called function are not included
+fn op_vars(op: &Op) -> (Vec<&Var>, Vec<&Var>) {
+    match op {
+        Op::Cproc(out, _, inp) | Op::Call(out, _, inp) => {
+            (inp.iter().collect(), out.iter().collect())
+        }
+        Op::Zero(tgt, _)
+        | Op::Hash3Zeros(tgt, _)
+        | Op::Hash4Zeros(tgt, _)
+        | Op::Hash6Zeros(tgt, _)
+        | Op::Hash8Zeros(tgt, _)
+        | Op::Lit(tgt, _) => (vec![], vec![tgt]),
+        Op::Copy(tgt, a)
+        | Op::Cast(tgt, _, a)
+        | Op::Not(tgt, a)
+        | Op::Trunc(tgt, a, _)
+        | Op::Pow2(tgt, a, _) => (vec![a], vec![tgt]),
+        Op::EqTag(tgt, a, b)
+        | Op::EqVal(tgt, a, b)
+        | Op::And(tgt, a, b)
+        | Op::Or(tgt, a, b)
+        | Op::Add(tgt, a, b)
+        | Op::Sub(tgt, a, b)
+        | Op::Mul(tgt, a, b)
+        | Op::Div(tgt, a, b)
+        | Op::Lt(tgt, a, b)
+        | Op::BitAnd(tgt, a, b, _)
+        | Op::BitOr(tgt, a, b, _)
+        | Op::BitXor(tgt, a, b, _)
+        | Op::Hide(tgt, a, b) => (vec![a, b], vec![tgt]),
+        Op::DivRem(tgt, a, b, _) => (vec![a, b], tgt.iter().collect()),
+        Op::Emit(a) => (vec![a], vec![]),
+        Op::Cons2(img, _, preimg) => (preimg.iter().collect(), vec![img]),
+        Op::Cons3(img, _, preimg) => (preimg.iter().collect(), vec![img]),
+        Op::Cons4(img, _, preimg) => (preimg.iter().collect(), vec![img]),
+        Op::Decons2(preimg, img) => (vec![img], preimg.iter().collect()),
+        Op::Decons3(preimg, img) => (vec![img], preimg.iter().collect()),
+        Op::Decons4(preimg, img) => (vec![img], preimg.iter().collect()),
+        Op::Open(sec, src, comm) => (vec![comm], vec![sec, src]),
+        Op::Unit(_) => (vec![], vec![]),
+    }
+}
+
+/// Mutable version of `op_vars`
+fn op_vars_mut(op: &mut Op) -> (Vec<&mut Var>, Vec<&mut Var>) {
+    match op {
+        Op::Cproc(out, _, inp) | Op::Call(out, _, inp) => {
+            (inp.iter_mut().collect(), out.iter_mut().collect())
+        }
+        Op::Zero(tgt, _)
+        | Op::Hash3Zeros(tgt, _)
+        | Op::Hash4Zeros(tgt, _)
+        | Op::Hash6Zeros(tgt, _)
+        | Op::Hash8Zeros(tgt, _)
+        | Op::Lit(tgt, _) => (vec![], vec![tgt]),
+        Op::Copy(tgt, a)
+        | Op::Cast(tgt, _, a)
+        | Op::Not(tgt, a)
+        | Op::Trunc(tgt, a, _)
+        | Op::Pow2(tgt, a, _) => (vec![a], vec![tgt]),
+        Op::EqTag(tgt, a, b)
+        | Op::EqVal(tgt, a, b)
+        | Op::And(tgt, a, b)
+        | Op::Or(tgt, a, b)
+        | Op::Add(tgt, a, b)
+        | Op::Sub(tgt, a, b)
+        | Op::Mul(tgt, a, b)
+        | Op::Div(tgt, a, b)
+        | Op::Lt(tgt, a, b)
+        | Op::BitAnd(tgt, a, b, _)
+        | Op::BitOr(tgt, a, b, _)
+        | Op::BitXor(tgt, a, b, _)
+        | Op::Hide(tgt, a, b) => (vec![a, b], vec![tgt]),
+        Op::DivRem(tgt, a, b, _) => (vec![a, b], tgt.iter_mut().collect()),
+        Op::Emit(a) => (vec![a], vec![]),
+        Op::Cons2(img, _, preimg) => (preimg.iter_mut().collect(), vec![img]),
+        Op::Cons3(img, _, preimg) => (preimg.iter_mut().collect(), vec![img]),
+        Op::Cons4(img, _, preimg) => (preimg.iter_mut().collect(), vec![img]),
+        Op::Decons2(preimg, img) => (vec![img], preimg.iter_mut().collect()),
+        Op::Decons3(preimg, img) => (vec![img], preimg.iter_mut().collect()),
+        Op::Decons4(preimg, img) => (vec![img], preimg.iter_mut().collect()),
+        Op::Open(sec, src, comm) => (vec![comm], vec![sec, src]),
+        Op::Unit(_) => (vec![], vec![]),
+    }
+}
+
+/// The blocks nested in a control statement
+fn ctrl_blocks(ctrl: &Ctrl) -> Vec<&Block> {
+    match ctrl {
+        Ctrl::If(_, true_block, false_block) => vec![true_block.as_ref(), false_block.as_ref()],
+        Ctrl::MatchTag(_, cases, def) => cases.values().chain(def.as_deref()).collect(),
+        Ctrl::MatchSymbol(_, cases, def) => cases.values().chain(def.as_deref()).collect(),
+        Ctrl::Return(..) => vec![],
+    }
+}
+
+/// Mutable version of `ctrl_blocks`
+fn ctrl_blocks_mut(ctrl: &mut Ctrl) -> Vec<&mut Block> {
+    match ctrl {
+        Ctrl::If(_, true_block, false_block) => vec![true_block.as_mut(), false_block.as_mut()],
+        Ctrl::MatchTag(_, cases, def) => cases.values_mut().chain(def.as_deref_mut()).collect(),
+        Ctrl::MatchSymbol(_, cases, def) => cases.values_mut().chain(def.as_deref_mut()).collect(),
+        Ctrl::Return(..) => vec![],
+    }
+}
+
+/// The variables read by a control statement, excluding its nested blocks
+fn ctrl_vars_mut(ctrl: &mut Ctrl) -> Vec<&mut Var> {
+    match ctrl {
+        Ctrl::If(x, ..) | Ctrl::MatchTag(x, ..) | Ctrl::MatchSymbol(x, ..) => vec![x],
+        Ctrl::Return(vars) => vars.iter_mut().collect(),
+    }
+}
+
+/// Collects every variable read in `block`, including the ones read by the
+/// bodies of called functions
+fn collect_used(block: &Block, used: &mut HashSet<Var>) {
+    for op in &block.ops {
+        used.extend(op_vars(op).0.into_iter().cloned());
+        if let Op::Call(_, func, _) = op {
+            collect_used(&func.body, used);
+        }
+    }
+    match &block.ctrl {
+        Ctrl::If(x, ..) | Ctrl::MatchTag(x, ..) | Ctrl::MatchSymbol(x, ..) => {
+            used.insert(x.clone());
+        }
+        Ctrl::Return(vars) => used.extend(vars.iter().cloned()),
+    }
+    for block in ctrl_blocks(&block.ctrl) {
+        collect_used(block, used);
+    }
+}
+
+/// Prefixes the variables bound but not read with `_`
+fn underscore_unused(mut func: Func) -> Func {
+    fn underscore(var: &mut Var, used: &HashSet<Var>) {
+        if !used.contains(var) && !var.name().starts_with('_') {
+            *var = Var::new(&format!("_{}", var.name()));
+        }
+    }
+
+    fn recurse(block: &mut Block, used: &HashSet<Var>) {
+        for op in &mut block.ops {
+            if let Op::Call(_, func, _) = op {
+                func.input_params
+                    .iter_mut()
+                    .for_each(|var| underscore(var, used));
+                recurse(&mut func.body, used);
+            }
+            op_vars_mut(op)
+                .1
+                .into_iter()
+                .for_each(|var| underscore(var, used));
+        }
+        for block in ctrl_blocks_mut(&mut block.ctrl) {
+            recurse(block, used);
+        }
+    }
+
+    let mut used = HashSet::new();
+    collect_used(&func.body, &mut used);
+    func.input_params
+        .iter_mut()
+        .for_each(|var| underscore(var, &used));
+    recurse(&mut func.body, &used);
+    func
+}
+
+/// What's statically known about the value of a variable
+#[derive(Clone)]
+enum Known {
+    /// A number, with tag `Expr::Num`
+    Num(u128),
+    /// A symbol, whose tag depends on whether it's `nil` or a keyword
+    Symbol(Symbol),
+    /// A pointer with a known tag
+    Tag(Tag),
+    Bool(bool),
+}
+
+impl Known {
+    fn tag(&self) -> Option<Tag> {
+        match self {
+            Known::Num(_) => Some(Tag::Expr(Num)),
+            Known::Symbol(sym) => Some(symbol_tag(sym)),
+            Known::Tag(tag) => Some(*tag),
+            Known::Bool(_) => None,
+        }
+    }
+}
+
+/// Removes the case selected by `key` from a match, or its default if no case
+/// matches
+fn select_case<K: std::hash::Hash + Eq>(
+    cases: &mut IndexMap<K, Block>,
+    def: &mut Option<Box<Block>>,
+    key: &K,
+) -> Option<Block> {
+    cases
+        .swap_remove(key)
+        .or_else(|| def.take().map(|def| *def))
+}
+
+#[derive(Default)]
+struct ConstantFolding {
+    known: HashMap<Var, Known>,
+}
+
+impl ConstantFolding {
+    #[inline]
+    fn num(&self, var: &Var) -> Option<u128> {
+        match self.known.get(var) {
+            Some(Known::Num(n)) => Some(*n),
+            _ => None,
+        }
+    }
+
+    #[inline]
+    fn bool(&self, var: &Var) -> Option<bool> {
+        match self.known.get(var) {
+            Some(Known::Bool(b)) => Some(*b),
+            _ => None,
+        }
+    }
+
+    #[inline]
+    fn tag(&self, var: &Var) -> Option<Tag> {
+        self.known.get(var).and_then(Known::tag)
+    }
+
+    /// Folds the operations of `block`, appending them to `ops`
+    fn block(&mut self, mut ops: Vec<Op>, block: Block) -> Block {
+        for op in block.ops {
+            ops.push(self.op(op));
+        }
+        match block.ctrl {
+            Ctrl::If(x, true_block, false_block) => match self.bool(&x) {
+                Some(true) => self.block(ops, *true_block),
+                Some(false) => self.block(ops, *false_block),
+                None => {
+                    let true_block = self.block(vec![], *true_block);
+                    let false_block = self.block(vec![], *false_block);
+                    Block {
+                        ops,
+                        ctrl: Ctrl::if_(x, true_block, false_block),
+                    }
+                }
+            },
+            Ctrl::MatchTag(x, mut cases, mut def) => {
+                if let Some(tag) = self.tag(&x) {
+                    if let Some(block) = select_case(&mut cases, &mut def, &tag) {
+                        return self.block(ops, block);
+                    }
+                }
+                let cases = cases
+                    .into_iter()
+                    .map(|(tag, block)| (tag, self.block(vec![], block)))
+                    .collect();
+                let def = def.map(|def| Box::new(self.block(vec![], *def)));
+                Block {
+                    ops,
+                    ctrl: Ctrl::MatchTag(x, cases, def),
+                }
+            }
+            Ctrl::MatchSymbol(x, mut cases, mut def) => {
+                if let Some(Known::Symbol(sym)) = self.known.get(&x).cloned() {
+                    if let Some(block) = select_case(&mut cases, &mut def, &sym) {
+                        return self.block(ops, block);
+                    }
+                }
+                let cases = cases
+                    .into_iter()
+                    .map(|(sym, block)| (sym, self.block(vec![], block)))
+                    .collect();
+                let def = def.map(|def| Box::new(self.block(vec![], *def)));
+                Block {
+                    ops,
+                    ctrl: Ctrl::MatchSymbol(x, cases, def),
+                }
+            }
+            ctrl @ Ctrl::Return(..) => Block { ops, ctrl },
+        }
+    }
+
+    /// Folds arithmetic operations on known numbers, as long as the result
+    /// doesn't overflow and thus is the same as in the field
+    fn fold_arithmetic(&self, op: &Op) -> Option<(Var, u128)> {
+        let (tgt, a, b, f): (_, _, _, fn(u128, u128) -> Option<u128>) = match op {
+            Op::Add(tgt, a, b) => (tgt, a, b, u128::checked_add),
+            Op::Sub(tgt, a, b) => (tgt, a, b, u128::checked_sub),
+            Op::Mul(tgt, a, b) => (tgt, a, b, u128::checked_mul),
+            _ => return None,
+        };
+        Some((tgt.clone(), f(self.num(a)?, self.num(b)?)?))
+    }
+
+    fn op(&mut self, op: Op) -> Op {
+        if let Op::Call(out, func, inp) = op {
+            let func = map_body(*func, |body| self.block(vec![], body));
+            return Op::Call(out, Box::new(func), inp);
+        }
+        if let Some((tgt, n)) = self.fold_arithmetic(&op) {
+            self.known.insert(tgt.clone(), Known::Num(n));
+            return Op::Lit(tgt, Lit::Num(n));
+        }
+        let (tgt, known) = match &op {
+            Op::Lit(tgt, Lit::Num(n)) => (tgt, Known::Num(*n)),
+            Op::Lit(tgt, lit @ Lit::String(_)) => (tgt, Known::Tag(lit.tag())),
+            Op::Lit(tgt, Lit::Symbol(sym)) => (tgt, Known::Symbol(sym.clone())),
+            Op::Zero(tgt, Tag::Expr(Num)) => (tgt, Known::Num(0)),
+            Op::Zero(tgt, tag)
+            | Op::Hash3Zeros(tgt, tag)
+            | Op::Hash4Zeros(tgt, tag)
+            | Op::Hash6Zeros(tgt, tag)
+            | Op::Hash8Zeros(tgt, tag)
+            | Op::Cons2(tgt, tag, _)
+            | Op::Cons3(tgt, tag, _)
+            | Op::Cons4(tgt, tag, _) => (tgt, Known::Tag(*tag)),
+            Op::Cast(tgt, tag, src) => match (tag, self.num(src)) {
+                (Tag::Expr(Num), Some(n)) => (tgt, Known::Num(n)),
+                _ => (tgt, Known::Tag(*tag)),
+            },
+            Op::Copy(tgt, src) => match self.known.get(src) {
+                Some(known) => (tgt, known.clone()),
+                None => return op,
+            },
+            Op::Add(tgt, ..) | Op::Sub(tgt, ..) | Op::Mul(tgt, ..) | Op::Div(tgt, ..) => {
+                (tgt, Known::Tag(Tag::Expr(Num)))
+            }
+            Op::Hide(tgt, ..) => (tgt, Known::Tag(Tag::Expr(Comm))),
+            Op::EqTag(tgt, a, b) => match (self.tag(a), self.tag(b)) {
+                (Some(a), Some(b)) => (tgt, Known::Bool(a == b)),
+                _ => return op,
+            },
+            Op::EqVal(tgt, a, b) => match (self.known.get(a), self.known.get(b)) {
+                (Some(Known::Num(a)), Some(Known::Num(b))) => (tgt, Known::Bool(a == b)),
+                // the value of a symbol is the hash of its path
+                (Some(Known::Symbol(a)), Some(Known::Symbol(b))) => {
+                    (tgt, Known::Bool(a.path() == b.path()))
+                }
+                _ => return op,
+            },
+            Op::Lt(tgt, a, b) => match (self.num(a), self.num(b)) {
+                (Some(a), Some(b)) => (tgt, Known::Bool(a < b)),
+                _ => return op,
+            },
+            Op::Not(tgt, a) => match self.bool(a) {
+                Some(a) => (tgt, Known::Bool(!a)),
+                None => return op,
+            },
+            Op::And(tgt, a, b) => match (self.bool(a), self.bool(b)) {
+                (Some(false), _) | (_, Some(false)) => (tgt, Known::Bool(false)),
+                (Some(true), Some(true)) => (tgt, Known::Bool(true)),
+                _ => return op,
+            },
+            Op::Or(tgt, a, b) => match (self.bool(a), self.bool(b)) {
+                (Some(true), _) | (_, Some(true)) => (tgt, Known::Bool(true)),
+                (Some(false), Some(false)) => (tgt, Known::Bool(false)),
+                _ => return op,
+            },
+            _ => return op,
+        };
+        self.known.insert(tgt.clone(), known);
+        op
+    }
+}
+
+/// A `Cons*` or `Decons*` computation, identified by its arguments
+#[derive(Clone, PartialEq, Eq, Hash)]
+enum Computed {
+    Cons(Tag, Vec<Var>),
+    Decons(usize, Var),
+}
+
+#[derive(Default)]
+struct CommonSubexpressions {
+    /// Variables replaced by the ones bound by an equivalent computation
+    subst: HashMap<Var, Var>,
+}
+
+impl CommonSubexpressions {
+    #[inline]
+    fn rename(&self, var: &mut Var) {
+        if let Some(new_var) = self.subst.get(var) {
+            *var = new_var.clone();
+        }
+    }
+
+    /// Rewrites `block`, where `available` holds the computations performed
+    /// by the operations that dominate it
+    fn block(&mut self, block: Block, mut available: HashMap<Computed, Vec<Var>>) -> Block {
+        let mut ops = Vec::with_capacity(block.ops.len());
+        for mut op in block.ops {
+            op_vars_mut(&mut op)
+                .0
+                .into_iter()
+                .for_each(|var| self.rename(var));
+            let computed = match &op {
+                Op::Call(..) => None,
+                Op::Cons2(img, tag, preimg) => {
+                    Some((Computed::Cons(*tag, preimg.to_vec()), vec![img.clone()]))
+                }
+                Op::Cons3(img, tag, preimg) => {
+                    Some((Computed::Cons(*tag, preimg.to_vec()), vec![img.clone()]))
+                }
+                Op::Cons4(img, tag, preimg) => {
+                    Some((Computed::Cons(*tag, preimg.to_vec()), vec![img.clone()]))
+                }
+                Op::Decons2(preimg, img) => {
+                    Some((Computed::Decons(2, img.clone()), preimg.to_vec()))
+                }
+                Op::Decons3(preimg, img) => {
+                    Some((Computed::Decons(3, img.clone()), preimg.to_vec()))
+                }
+                Op::Decons4(preimg, img) => {
+                    Some((Computed::Decons(4, img.clone()), preimg.to_vec()))
+                }
+                _ => None,
+            };
+            if let Some((computed, out)) = computed {
+                if let Some(prev_out) = available.get(&computed) {
+                    for (var, prev_var) in out.into_iter().zip(prev_out) {
+                        self.subst.insert(var, prev_var.clone());
+                    }
+                    continue;
+                }
+                available.insert(computed, out);
+            }
+            if let Op::Call(out, func, inp) = op {
+                let func = map_body(*func, |body| self.block(body, HashMap::new()));
+                ops.push(Op::Call(out, Box::new(func), inp));
+            } else {
+                ops.push(op);
+            }
+        }
+        let mut ctrl = block.ctrl;
+        ctrl_vars_mut(&mut ctrl)
+            .into_iter()
+            .for_each(|var| self.rename(var));
+        let ctrl = match ctrl {
+            Ctrl::If(x, true_block, false_block) => {
+                let true_block = self.block(*true_block, available.clone());
+                let false_block = self.block(*false_block, available);
+                Ctrl::if_(x, true_block, false_block)
+            }
+            Ctrl::MatchTag(x, cases, def) => {
+                let cases = cases
+                    .into_iter()
+                    .map(|(tag, block)| (tag, self.block(block, available.clone())))
+                    .collect();
+                let def = def.map(|def| Box::new(self.block(*def, available)));
+                Ctrl::MatchTag(x, cases, def)
+            }
+            Ctrl::MatchSymbol(x, cases, def) => {
+                let cases = cases
+                    .into_iter()
+                    .map(|(sym, block)| (sym, self.block(block, available.clone())))
+                    .collect();
+                let def = def.map(|def| Box::new(self.block(*def, available)));
+                Ctrl::MatchSymbol(x, cases, def)
+            }
+            ctrl @ Ctrl::Return(..) => ctrl,
+        };
+        Block { ops, ctrl }
+    }
+}
+
+/// Checks whether two variables are the same modulo the renaming `map` of the
+/// variables bound so far
+#[inline]
+fn alpha_eq_var(a: &Var, b: &Var, map: &HashMap<Var, Var>) -> bool {
+    map.get(a).unwrap_or(a) == b
+}
+
+fn alpha_eq_vars<'a>(
+    a: impl ExactSizeIterator<Item = &'a Var>,
+    b: impl ExactSizeIterator<Item = &'a Var>,
+    map: &HashMap<Var, Var>,
+) -> bool {
+    a.len() == b.len() && a.zip(b).all(|(a, b)| alpha_eq_var(a, b, map))
+}
+
+fn alpha_eq_op(a: &Op, b: &Op, map: &mut HashMap<Var, Var>) -> bool {
+    let ((a_inp, a_out), (b_inp, b_out)) = (op_vars(a), op_vars(b));
+    if !alpha_eq_vars(a_inp.into_iter(), b_inp.into_iter(), map) || a_out.len() != b_out.len() {
+        return false;
+    }
+    map.extend(a_out.into_iter().cloned().zip(b_out.into_iter().cloned()));
+    if let (Op::Call(_, a_func, _), Op::Call(_, b_func, _)) = (a, b) {
+        if a_func.name != b_func.name
+            || a_func.output_size != b_func.output_size
+            || a_func.input_params.len() != b_func.input_params.len()
+        {
+            return false;
+        }
+        map.extend(
+            a_func
+                .input_params
+                .iter()
+                .cloned()
+                .zip(b_func.input_params.iter().cloned()),
+        );
+        return alpha_eq_block(&a_func.body, &b_func.body, map);
+    }
+    // with every variable renamed, the remaining data must be equal
+    let mut a = a.clone();
+    let (a_inp, a_out) = op_vars_mut(&mut a);
+    for var in a_inp.into_iter().chain(a_out) {
+        if let Some(new_var) = map.get(var) {
+            *var = new_var.clone();
+        }
+    }
+    a == *b
+}
+
+/// Checks whether two blocks are the same modulo the renaming of the
+/// variables bound in them
+fn alpha_eq_block(a: &Block, b: &Block, map: &mut HashMap<Var, Var>) -> bool {
+    if a.ops.len() != b.ops.len()
+        || !a
+            .ops
+            .iter()
+            .zip(&b.ops)
+            .all(|(a, b)| alpha_eq_op(a, b, map))
+    {
+        return false;
+    }
+    match (&a.ctrl, &b.ctrl) {
+        (Ctrl::Return(a), Ctrl::Return(b)) => alpha_eq_vars(a.iter(), b.iter(), map),
+        (Ctrl::If(a_x, a_t, a_f), Ctrl::If(b_x, b_t, b_f)) => {
+            alpha_eq_var(a_x, b_x, map)
+                && alpha_eq_block(a_t, b_t, map)
+                && alpha_eq_block(a_f, b_f, map)
+        }
+        (Ctrl::MatchTag(a_x, a_cases, a_def), Ctrl::MatchTag(b_x, b_cases, b_def)) => {
+            alpha_eq_var(a_x, b_x, map)
+                && a_cases.keys().eq(b_cases.keys())
+                && alpha_eq_cases(a_cases.values(), b_cases.values(), a_def, b_def, map)
+        }
+        (Ctrl::MatchSymbol(a_x, a_cases, a_def), Ctrl::MatchSymbol(b_x, b_cases, b_def)) => {
+            alpha_eq_var(a_x, b_x, map)
+                && a_cases.keys().eq(b_cases.keys())
+                && alpha_eq_cases(a_cases.values(), b_cases.values(), a_def, b_def, map)
+        }
+        _ => false,
+    }
+}
+
+fn alpha_eq_cases<'a>(
+    a_cases: impl Iterator<Item = &'a Block>,
+    b_cases: impl Iterator<Item = &'a Block>,
+    a_def: &Option<Box<Block>>,
+    b_def: &Option<Box<Block>>,
+    map: &mut HashMap<Var, Var>,
+) -> bool {
+    a_cases.zip(b_cases).all(|(a, b)| alpha_eq_block(a, b, map))
+        && match (a_def, b_def) {
+            (Some(a), Some(b)) => alpha_eq_block(a, b, map),
+            (None, None) => true,
+            _ => false,
+        }
+}
+
+#[inline]
+fn same_block(a: &Block, b: &Block) -> bool {
+    alpha_eq_block(a, b, &mut HashMap::new())
+}
+
+fn merge_branches(block: Block) -> Block {
+    let ops = block
+        .ops
+        .into_iter()
+        .map(|op| match op {
+            Op::Call(out, func, inp) => {
+                Op::Call(out, Box::new(map_body(*func, merge_branches)), inp)
+            }
+            op => op,
+        })
+        .collect();
+    match block.ctrl {
+        Ctrl::If(x, true_block, false_block) => {
+            let true_block = merge_branches(*true_block);
+            let false_block = merge_branches(*false_block);
+            if same_block(&true_block, &false_block) {
+                append(ops, true_block)
+            } else {
+                Block {
+                    ops,
+                    ctrl: Ctrl::if_(x, true_block, false_block),
+                }
+            }
+        }
+        // cases identical to the default can be dropped: the default will
+        // select them
+        Ctrl::MatchTag(x, cases, Some(def)) => {
+            let def = merge_branches(*def);
+            let cases: IndexMap<_, _> = cases
+                .into_iter()
+                .map(|(tag, block)| (tag, merge_branches(block)))
+                .filter(|(_, block)| !same_block(block, &def))
+                .collect();
+            if cases.is_empty() {
+                append(ops, def)
+            } else {
+                Block {
+                    ops,
+                    ctrl: Ctrl::MatchTag(x, cases, Some(Box::new(def))),
+                }
+            }
+        }
+        // a case must remain so that the symbol match still requires a symbol
+        Ctrl::MatchSymbol(x, cases, Some(def)) => {
+            let def = merge_branches(*def);
+            let (mut cases, merged): (IndexMap<_, _>, IndexMap<_, _>) = cases
+                .into_iter()
+                .map(|(sym, block)| (sym, merge_branches(block)))
+                .partition(|(_, block)| !same_block(block, &def));
+            if cases.is_empty() {
+                cases.extend(merged.into_iter().take(1));
+            }
+            Block {
+                ops,
+                ctrl: Ctrl::MatchSymbol(x, cases, Some(Box::new(def))),
+            }
+        }
+        Ctrl::MatchTag(x, cases, None) => {
+            let cases = cases
+                .into_iter()
+                .map(|(tag, block)| (tag, merge_branches(block)))
+                .collect();
+            Block {
+                ops,
+                ctrl: Ctrl::MatchTag(x, cases, None),
+            }
+        }
+        Ctrl::MatchSymbol(x, cases, None) => {
+            let cases = cases
+                .into_iter()
+                .map(|(sym, block)| (sym, merge_branches(block)))
+                .collect();
+            Block {
+                ops,
+                ctrl: Ctrl::MatchSymbol(x, cases, None),
+            }
+        }
+        ctrl @ Ctrl::Return(..) => Block { ops, ctrl },
+    }
+}
+
+/// Whether an operation can be removed when its results are not used
+fn is_pure(op: &Op) -> bool {
+    fn block_is_pure(block: &Block) -> bool {
+        block.ops.iter().all(is_pure) && ctrl_blocks(&block.ctrl).into_iter().all(block_is_pure)
+    }
+    match op {
+        Op::Cproc(..) | Op::Emit(_) | Op::Unit(_) => false,
+        Op::Call(_, func, _) => block_is_pure(&func.body),
+        _ => true,
+    }
+}
+
+/// Removes the pure operations of `block` whose results aren't in `used`,
+/// adding the variables read by the remaining code to it
+fn dead_code(block: Block, used: &mut HashSet<Var>) -> Block {
+    let ctrl = match block.ctrl {
+        Ctrl::Return(vars) => {
+            used.extend(vars.iter().cloned());
+            Ctrl::Return(vars)
+        }
+        Ctrl::If(x, true_block, false_block) => {
+            used.insert(x.clone());
+            let true_block = dead_code(*true_block, used);
+            let false_block = dead_code(*false_block, used);
+            Ctrl::if_(x, true_block, false_block)
+        }
+        Ctrl::MatchTag(x, cases, def) => {
+            used.insert(x.clone());
+            let cases = cases
+                .into_iter()
+                .map(|(tag, block)| (tag, dead_code(block, used)))
+                .collect();
+            let def = def.map(|def| Box::new(dead_code(*def, used)));
+            Ctrl::MatchTag(x, cases, def)
+        }
+        Ctrl::MatchSymbol(x, cases, def) => {
+            used.insert(x.clone());
+            let cases = cases
+                .into_iter()
+                .map(|(sym, block)| (sym, dead_code(block, used)))
+                .collect();
+            let def = def.map(|def| Box::new(dead_code(*def, used)));
+            Ctrl::MatchSymbol(x, cases, def)
+        }
+    };
+    let mut ops = Vec::with_capacity(block.ops.len());
+    for op in block.ops.into_iter().rev() {
+        let (inp, out) = op_vars(&op);
+        if is_pure(&op) && out.iter().all(|var| !used.contains(*var)) {
+            continue;
+        }
+        used.extend(inp.into_iter().cloned());
+        ops.push(match op {
+            Op::Call(out, func, inp) => {
+                let func = map_body(*func, |body| dead_code(body, &mut HashSet::new()));
+                Op::Call(out, Box::new(func), inp)
+            }
+            op => op,
+        });
+    }
+    ops.reverse();
+    Block { ops, ctrl }
+}
+
+#[cfg(test)]
+mod tests {
+    use bellpepper_core::{test_cs::TestConstraintSystem, Comparable};
+    use pasta_curves::pallas::Scalar as Fr;
+
+    use super::*;
+    use crate::{
+        eval::lang::{DummyCoprocessor, Lang},
+        func,
+        lem::{
+            eval::{eval_step, make_eval_step_from_config, EvalConfig},
+            pointers::Ptr,
+        },
+    };
+
+    /// Checks that `opt` computes the same outputs as `func` on `inputs` and
+    /// that its circuit is satisfied with the expected number of constraints
+    fn assert_equivalent(func: &Func, opt: &Func, inputs: &[Vec<Ptr<Fr>>], store: &Store<Fr>) {
+        let lang: Lang<Fr, DummyCoprocessor<Fr>> = Lang::new();
+        let num_constraints = opt.num_constraints(store);
+        for input in inputs {
+            let frame = func.call_simple(input, store, &lang, 0).unwrap();
+            let opt_frame = opt.call_simple(input, store, &lang, 0).unwrap();
+            assert_eq!(frame.output, opt_frame.output);
+            let mut cs = TestConstraintSystem::<Fr>::new();
+            opt.synthesize_frame_aux(&mut cs, store, &opt_frame, &lang)
+                .unwrap();
+            assert!(cs.is_satisfied());
+            assert_eq!(cs.num_constraints(), num_constraints);
+        }
+    }
+
+    #[test]
+    fn constant_folding_and_dead_code() {
+        let func = func!(foo(x): 1 => {
+            let one = Num(1);
+            let two = Num(2);
+            let three = add(one, two);
+            let also_three = Num(3);
+            let eq = eq_val(three, also_three);
+            if eq {
+                return (x)
+            }
+            let y: Expr::Cons = cons2(x, x);
+            return (y)
+        });
+        let store = Store::<Fr>::default();
+        let (opt, reports) = func.optimize(&store).unwrap();
+        assert!(opt.body.ops.is_empty());
+        assert!(matches!(&opt.body.ctrl, Ctrl::Return(vars) if vars == &opt.input_params));
+        assert_eq!(opt.slots_count.hash4, 0);
+        assert_eq!(
+            reports.iter().map(PassReport::saved).sum::<isize>(),
+            func.num_constraints(&store) as isize - opt.num_constraints(&store) as isize
+        );
+        assert!(reports[0].saved() > 0);
+
+        let inputs = [vec![Ptr::num_u64(42)], vec![store.intern_nil()]];
+        assert_equivalent(&func, &opt, &inputs, &store);
+    }
+
+    #[test]
+    fn common_subexpressions_and_merged_branches() {
+        let func = func!(foo(x, y): 1 => {
+            let a: Expr::Cons = cons2(x, y);
+            let b: Expr::Cons = cons2(x, y);
+            match x.tag {
+                Expr::Num => {
+                    let c: Expr::Cons = cons2(a, b);
+                    return (c)
+                }
+                Expr::Char => {
+                    let c: Expr::Cons = cons2(a, b);
+                    return (c)
+                }
+            };
+            let d: Expr::Cons = cons2(a, b);
+            return (d)
+        });
+        let store = Store::<Fr>::default();
+        let passes = [Pass::CommonSubexpressions, Pass::MergeBranches];
+        let (opt, reports) = func.optimize_with(&passes, &store).unwrap();
+        assert_eq!(func.slots_count.hash4, 3);
+        assert_eq!(opt.slots_count.hash4, 2);
+        assert_eq!(opt.body.ops.len(), 2);
+        assert!(matches!(opt.body.ctrl, Ctrl::Return(..)));
+        assert!(reports.iter().all(|report| report.saved() > 0));
+
+        let nil = store.intern_nil();
+        let inputs = [
+            vec![Ptr::num_u64(1), nil],
+            vec![Ptr::char('a'), nil],
+            vec![nil, nil],
+        ];
+        assert_equivalent(&func, &opt, &inputs, &store);
+    }
+
+    #[test]
+    fn optimized_eval_step() {
+        let store = Store::<Fr>::default();
+        let step = eval_step();
+        let (opt, reports) = step.optimize(&store).unwrap();
+        assert!(opt.num_constraints(&store) < step.num_constraints(&store));
+        assert_eq!(reports.len(), PASSES.len());
+
+        // the optimized step function is opt-in
+        let lang: Lang<Fr, DummyCoprocessor<Fr>> = Lang::new();
+        let config = EvalConfig::new_ivc(&lang).with_optimizations();
+        assert_eq!(opt, make_eval_step_from_config(&config));
+
+        let env = store.intern_nil();
+        for src in [
+            "(let ((x 1) (y 2)) (+ x y))",
+            "(car (cons 1 2))",
+            "((lambda (x) (if (= x 0) :zero :other)) 3)",
+        ] {
+            let expr = store.read_with_default_state(src).unwrap();
+            let mut input = vec![expr, env, store.cont_outermost()];
+            for _ in 0..32 {
+                let output = step.call_simple(&input, &store, &lang, 0).unwrap().output;
+                assert_equivalent(step, &opt, &[input], &store);
+                input = output;
+            }
+        }
+    }
+}
@@ -1,3 +1,5 @@
+use anyhow::{bail, Result};
+use indexmap::IndexMap;
 use std::collections::HashSet;
 
 use crate::Symbol;
@@ -133,3 +135,257 @@ impl Block {
         num_paths
     }
 }
+
+/// A branch of a `Func`, identified by the name of the function whose body
+/// contains it and by the path that leads to it within that body
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Branch {
+    pub func: String,
+    pub path: Path,
+}
+
+impl std::fmt::Display for Branch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.func, self.path)
+    }
+}
+
+/// Counts how many times each branch of a `Func` was taken by the concrete
+/// paths of the frames computed with it. The branches of called functions are
+/// shared by all of their call sites
+#[derive(Clone, Debug, Default)]
+pub struct Coverage(IndexMap<Branch, usize>);
+
+impl Coverage {
+    /// Starts the coverage of `func` with none of its branches taken
+    pub fn new(func: &Func) -> Self {
+        fn collect(block: &Block, func: &str, path: &Path, branches: &mut IndexMap<Branch, usize>) {
+            let mut add = |path: Path, block: &Block| {
+                branches.insert(
+                    Branch {
+                        func: func.to_string(),
+                        path: path.clone(),
+                    },
+                    0,
+                );
+                collect(block, func, &path, branches);
+            };
+            match &block.ctrl {
+                Ctrl::Return(..) => (),
+                Ctrl::If(_, true_block, false_block) => {
+                    add(path.push_bool(true), true_block);
+                    add(path.push_bool(false), false_block);
+                }
+                Ctrl::MatchTag(_, cases, def) => {
+                    for (tag, block) in cases {
+                        add(path.push_tag(tag), block);
+                    }
+                    if let Some(def) = def {
+                        add(path.push_default(), def);
+                    }
+                }
+                Ctrl::MatchSymbol(_, cases, def) => {
+                    for (sym, block) in cases {
+                        add(path.push_symbol(sym.clone()), block);
+                    }
+                    if let Some(def) = def {
+                        add(path.push_default(), def);
+                    }
+                }
+            }
+            for op in &block.ops {
+                if let Op::Call(_, func, _) = op {
+                    collect(&func.body, &func.name, &Path::default(), branches);
+                }
+            }
+        }
+        let mut branches = IndexMap::default();
+        collect(&func.body, &func.name, &Path::default(), &mut branches);
+        Self(branches)
+    }
+
+    /// Marks the branches followed by `path`, which must be the path of a
+    /// frame computed with `func`, as taken
+    pub fn record(&mut self, func: &Func, path: &Path) -> Result<()> {
+        fn replay<'a>(
+            block: &Block,
+            func: &str,
+            mut path: Path,
+            nodes: &mut impl Iterator<Item = &'a PathNode>,
+            branches: &mut IndexMap<Branch, usize>,
+        ) -> Result<()> {
+            for op in &block.ops {
+                if let Op::Call(_, func, _) = op {
+                    replay(&func.body, &func.name, Path::default(), nodes, branches)?;
+                }
+            }
+            if let Ctrl::Return(..) = &block.ctrl {
+                return Ok(());
+            }
+            let node = nodes.next();
+            let block = match (&block.ctrl, node) {
+                (Ctrl::If(_, true_block, false_block), Some(PathNode::Bool(b))) => {
+                    Some(if *b { &**true_block } else { &**false_block })
+                }
+                (Ctrl::MatchTag(_, cases, _), Some(PathNode::Tag(tag))) => cases.get(tag),
+                (Ctrl::MatchSymbol(_, cases, _), Some(PathNode::Symbol(sym))) => cases.get(sym),
+                (Ctrl::MatchTag(_, _, def), Some(PathNode::Default))
+                | (Ctrl::MatchSymbol(_, _, def), Some(PathNode::Default)) => def.as_deref(),
+                _ => None,
+            };
+            let (Some(block), Some(node)) = (block, node) else {
+                bail!("Path doesn't match the control statement at {path} in `{func}`")
+            };
+            path.0.push(node.clone());
+            let branch = Branch {
+                func: func.to_string(),
+                path,
+            };
+            *branches.entry(branch.clone()).or_insert(0) += 1;
+            replay(block, func, branch.path, nodes, branches)
+        }
+        let mut nodes = path.0.iter();
+        replay(
+            &func.body,
+            &func.name,
+            Path::default(),
+            &mut nodes,
+            &mut self.0,
+        )?;
+        if nodes.next().is_some() {
+            bail!("Path {path} is longer than the paths of `{}`", func.name)
+        }
+        Ok(())
+    }
+
+    /// The branches and how many times each was taken
+    pub fn branches(&self) -> impl Iterator<Item = (&Branch, usize)> {
+        self.0.iter().map(|(branch, count)| (branch, *count))
+    }
+
+    pub fn num_branches(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn num_taken(&self) -> usize {
+        self.0.values().filter(|count| **count > 0).count()
+    }
+
+    /// The branches that were never taken but whose enclosing branch was,
+    /// which leaves out the ones that could only be reached through another
+    /// untaken branch of the same function
+    pub fn untaken(&self) -> impl Iterator<Item = &Branch> {
+        self.0.iter().filter_map(|(branch, count)| {
+            if *count > 0 {
+                return None;
+            }
+            let mut parent = branch.path.0.clone();
+            parent.pop();
+            if parent.is_empty() {
+                return Some(branch);
+            }
+            let parent = Branch {
+                func: branch.func.clone(),
+                path: Path(parent),
+            };
+            (self.0.get(&parent) != Some(&0)).then_some(branch)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        eval::lang::{Coproc, Lang},
+        func,
+        lem::{eval::evaluate, pointers::Ptr, store::Store},
+        tag::ExprTag,
+    };
+    use pasta_curves::pallas::Scalar as Fr;
+
+    #[test]
+    fn coverage_of_frames() {
+        let inner = func!(inner(x): 1 => {
+            match x.tag {
+                Expr::Num => {
+                    return (x)
+                }
+            };
+            return (x)
+        });
+        let func = func!(outer(x): 1 => {
+            let (y) = inner(x);
+            match y.tag {
+                Expr::Num => {
+                    let zero = Num(0);
+                    let b = lt(y, zero);
+                    if b {
+                        return (zero)
+                    }
+                    return (y)
+                }
+                Expr::Char => {
+                    return (y)
+                }
+            }
+        });
+        let store = Store::<Fr>::default();
+        let lang = Lang::<Fr, Coproc<Fr>>::new();
+        let mut coverage = Coverage::new(&func);
+        assert_eq!(coverage.num_branches(), 6);
+        for input in [Ptr::num_u64(1), Ptr::num_u64(2)] {
+            let frame = func.call_simple(&[input], &store, &lang, 0).unwrap();
+            coverage.record(&func, &frame.path).unwrap();
+        }
+        assert_eq!(coverage.num_taken(), 3);
+
+        let num = Tag::Expr(ExprTag::Num);
+        let branch = |func: &str, path: Path| Branch {
+            func: func.to_string(),
+            path,
+        };
+        let taken = coverage
+            .branches()
+            .filter(|(_, count)| *count > 0)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            taken,
+            [
+                (&branch("outer", Path::default().push_tag(&num)), 2),
+                (
+                    &branch("outer", Path::default().push_tag(&num).push_bool(false)),
+                    2
+                ),
+                (&branch("inner", Path::default().push_tag(&num)), 2),
+            ]
+        );
+        let untaken = coverage.untaken().cloned().collect::<Vec<_>>();
+        assert_eq!(
+            untaken,
+            [
+                branch("outer", Path::default().push_tag(&num).push_bool(true)),
+                branch("outer", Path::default().push_tag(&Tag::Expr(ExprTag::Char))),
+                branch("inner", Path::default().push_default()),
+            ]
+        );
+
+        // paths of other functions are rejected
+        let path = Path::default().push_bool(true);
+        assert!(coverage.record(&func, &path).is_err());
+    }
+
+    #[test]
+    fn coverage_of_eval_step() {
+        let store = Store::<Fr>::default();
+        let expr = store.read_with_default_state("(+ 1 2)").unwrap();
+        let (frames, _) = evaluate::<Fr, Coproc<Fr>>(None, expr, &store, 10).unwrap();
+        let step = crate::lem::eval::eval_step();
+        let mut coverage = Coverage::new(step);
+        for frame in &frames {
+            coverage.record(step, &frame.path).unwrap();
+        }
+        assert!(coverage.num_taken() > 0);
+        assert!(coverage.num_taken() < coverage.num_branches());
+    }
+}
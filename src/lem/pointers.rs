@@ -2,7 +2,8 @@ use serde::{Deserialize, Serialize};
 
 use crate::{
     field::*,
-    tag::ExprTag::{Char, Comm, Nil, Num, U64},
+    tag::ExprTag::{Char, Comm, Nil, Num, I64, U128, U16, U32, U64, U8},
+    uint::UInt,
 };
 
 use super::Tag;
@@ -56,11 +57,42 @@ impl<F: LurkField> Ptr<F> {
         Ptr::Atom(Tag::Expr(Num), F::from_u64(u))
     }
 
+    #[inline]
+    pub fn u8(u: u8) -> Self {
+        Ptr::Atom(Tag::Expr(U8), F::from_u64(u.into()))
+    }
+
+    #[inline]
+    pub fn u16(u: u16) -> Self {
+        Ptr::Atom(Tag::Expr(U16), F::from_u16(u))
+    }
+
+    #[inline]
+    pub fn u32(u: u32) -> Self {
+        Ptr::Atom(Tag::Expr(U32), F::from_u32(u))
+    }
+
     #[inline]
     pub fn u64(u: u64) -> Self {
         Ptr::Atom(Tag::Expr(U64), F::from_u64(u))
     }
 
+    #[inline]
+    pub fn u128(u: u128) -> Self {
+        Ptr::Atom(Tag::Expr(U128), F::from_u128(u))
+    }
+
+    /// Builds an i64 pointer from the two's complement bits of `i`
+    #[inline]
+    pub fn i64(i: i64) -> Self {
+        Ptr::Atom(Tag::Expr(I64), F::from_u64(i as u64))
+    }
+
+    #[inline]
+    pub fn uint(u: UInt) -> Self {
+        Ptr::Atom(Tag::Expr(u.tag()), F::from_u128(u.into()))
+    }
+
     #[inline]
     pub fn char(c: char) -> Self {
         Ptr::Atom(Tag::Expr(Char), F::from_char(c))
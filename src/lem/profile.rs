@@ -0,0 +1,310 @@
+//! ## Constraint cost profiles
+//!
+//! `Func::num_constraints` only gives the total number of constraints of a
+//! function. A `CostProfile` breaks that total down by block, identifying each
+//! block by the `Path` the interpreter records when executing it, and by the
+//! kind of operation or control statement that creates the constraints.
+//!
+//! Some constraints are shared by the whole function and can't be attributed
+//! to a single block: the ones for the slots, which are reused by every path,
+//! and the ones for the global constants, which are allocated only once.
+
+use serde::{Serialize, Serializer};
+use std::collections::{HashMap, HashSet};
+
+use crate::field::{FWrap, LurkField};
+
+use super::{path::Path, store::Store, Block, Ctrl, Func, Op};
+
+/// The constraints created by a block of a function
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct BlockCost {
+    /// The path taken to reach the block
+    #[serde(serialize_with = "serialize_path")]
+    pub path: Path,
+    /// The number of branches taken to reach the block
+    pub depth: usize,
+    /// The name of the function whose body contains the block
+    pub func: String,
+    /// The constraints created by the operations and the control statement of
+    /// the block itself
+    pub own: usize,
+    /// The constraints created by the block and every block nested in it,
+    /// including the bodies of the functions it calls
+    pub total: usize,
+}
+
+fn serialize_path<S: Serializer>(path: &Path, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&path.to_string())
+}
+
+/// The constraints created by every operation or control statement of a kind
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct KindCost {
+    /// The name of the `Op` or `Ctrl` variant
+    pub kind: &'static str,
+    /// How many times it occurs in the function
+    pub count: usize,
+    /// The constraints created by all of its occurrences
+    pub constraints: usize,
+}
+
+/// The breakdown of the constraints of a function, as computed by
+/// `Func::cost_profile`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct CostProfile {
+    /// The total number of constraints, equal to `Func::num_constraints`
+    pub total: usize,
+    /// The constraints spent on slots
+    pub slots: usize,
+    /// The constraints spent on global constants
+    pub globals: usize,
+    /// The cost of each block, in depth-first order. The first one is the body
+    /// of the function
+    pub blocks: Vec<BlockCost>,
+    /// The cost of each kind of operation and control statement, from the most
+    /// to the least expensive
+    pub kinds: Vec<KindCost>,
+}
+
+fn op_kind(op: &Op) -> &'static str {
+    match op {
+        Op::Cproc(..) => "Cproc",
+        Op::Call(..) => "Call",
+        Op::Copy(..) => "Copy",
+        Op::Zero(..) => "Zero",
+        Op::Hash3Zeros(..) => "Hash3Zeros",
+        Op::Hash4Zeros(..) => "Hash4Zeros",
+        Op::Hash6Zeros(..) => "Hash6Zeros",
+        Op::Hash8Zeros(..) => "Hash8Zeros",
+        Op::Lit(..) => "Lit",
+        Op::Cast(..) => "Cast",
+        Op::EqTag(..) => "EqTag",
+        Op::EqVal(..) => "EqVal",
+        Op::Not(..) => "Not",
+        Op::And(..) => "And",
+        Op::Or(..) => "Or",
+        Op::Add(..) => "Add",
+        Op::Sub(..) => "Sub",
+        Op::Mul(..) => "Mul",
+        Op::Div(..) => "Div",
+        Op::Lt(..) => "Lt",
+        Op::Trunc(..) => "Trunc",
+        Op::DivRem(..) => "DivRem",
+        Op::BitAnd(..) => "BitAnd",
+        Op::BitOr(..) => "BitOr",
+        Op::BitXor(..) => "BitXor",
+        Op::Pow2(..) => "Pow2",
+        Op::Emit(..) => "Emit",
+        Op::Cons2(..) => "Cons2",
+        Op::Cons3(..) => "Cons3",
+        Op::Cons4(..) => "Cons4",
+        Op::Decons2(..) => "Decons2",
+        Op::Decons3(..) => "Decons3",
+        Op::Decons4(..) => "Decons4",
+        Op::Hide(..) => "Hide",
+        Op::Open(..) => "Open",
+        Op::Unit(..) => "Unit",
+    }
+}
+
+fn ctrl_kind(ctrl: &Ctrl) -> &'static str {
+    match ctrl {
+        Ctrl::MatchTag(..) => "MatchTag",
+        Ctrl::MatchSymbol(..) => "MatchSymbol",
+        Ctrl::If(..) => "If",
+        Ctrl::Return(..) => "Return",
+    }
+}
+
+struct Profiler<'a, F: LurkField> {
+    store: &'a Store<F>,
+    globals: HashSet<FWrap<F>>,
+    blocks: Vec<BlockCost>,
+    kinds: HashMap<&'static str, (usize, usize)>,
+}
+
+impl<F: LurkField> Profiler<'_, F> {
+    fn add_kind(&mut self, kind: &'static str, constraints: usize) {
+        let (count, total) = self.kinds.entry(kind).or_default();
+        *count += 1;
+        *total += constraints;
+    }
+
+    /// Profiles `block` and the blocks nested in it, returning the total
+    /// number of constraints they create
+    fn block(
+        &mut self,
+        block: &Block,
+        func: &str,
+        path: Path,
+        depth: usize,
+        is_nested: bool,
+    ) -> usize {
+        // reserve the position of the block, so it precedes its nested blocks
+        let idx = self.blocks.len();
+        self.blocks.push(BlockCost {
+            path: path.clone(),
+            depth,
+            func: func.to_string(),
+            own: 0,
+            total: 0,
+        });
+        let mut own = 0;
+        let mut nested = 0;
+        for op in &block.ops {
+            let constraints = op.num_constraints(&mut self.globals, self.store);
+            self.add_kind(op_kind(op), constraints);
+            own += constraints;
+            if let Op::Call(_, callee, _) = op {
+                nested += self.block(&callee.body, &callee.name, path.clone(), depth, is_nested);
+            }
+        }
+        let constraints = block.ctrl.num_constraints(&mut self.globals, is_nested);
+        self.add_kind(ctrl_kind(&block.ctrl), constraints);
+        own += constraints;
+        match &block.ctrl {
+            Ctrl::Return(..) => (),
+            Ctrl::If(_, true_block, false_block) => {
+                nested += self.block(true_block, func, path.push_bool(true), depth + 1, true);
+                nested += self.block(false_block, func, path.push_bool(false), depth + 1, true);
+            }
+            Ctrl::MatchTag(_, cases, def) => {
+                for (tag, block) in cases {
+                    nested += self.block(block, func, path.push_tag(tag), depth + 1, true);
+                }
+                if let Some(def) = def {
+                    nested += self.block(def, func, path.push_default(), depth + 1, true);
+                }
+            }
+            Ctrl::MatchSymbol(_, cases, def) => {
+                for (sym, block) in cases {
+                    let path = path.push_symbol(sym.clone());
+                    nested += self.block(block, func, path, depth + 1, true);
+                }
+                if let Some(def) = def {
+                    nested += self.block(def, func, path.push_default(), depth + 1, true);
+                }
+            }
+        }
+        let cost = &mut self.blocks[idx];
+        cost.own = own;
+        cost.total = own + nested;
+        cost.total
+    }
+}
+
+impl Func {
+    /// Computes the breakdown of the constraints that `synthesize` creates.
+    /// The blocks of called functions are listed right after the block with
+    /// the call, with paths extending its path, as done by the interpreter
+    pub fn cost_profile<F: LurkField>(&self, store: &Store<F>) -> CostProfile {
+        let mut profiler = Profiler {
+            store,
+            globals: HashSet::default(),
+            blocks: vec![],
+            kinds: HashMap::default(),
+        };
+        let body = profiler.block(&self.body, &self.name, Path::default(), 0, false);
+        let slots = self.slot_constraints(store);
+        let globals = profiler.globals.len();
+        let mut kinds = profiler
+            .kinds
+            .into_iter()
+            .map(|(kind, (count, constraints))| KindCost {
+                kind,
+                count,
+                constraints,
+            })
+            .collect::<Vec<_>>();
+        kinds.sort_by(|a, b| b.constraints.cmp(&a.constraints).then(a.kind.cmp(b.kind)));
+        CostProfile {
+            total: slots + globals + body,
+            slots,
+            globals,
+            blocks: profiler.blocks,
+            kinds,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        func,
+        lem::eval::eval_step,
+        tag::{ExprTag, Tag},
+    };
+    use pasta_curves::pallas::Scalar as Fr;
+
+    #[test]
+    fn profile_blocks_and_kinds() {
+        let func = func!(foo(x): 1 => {
+            let one = Num(1);
+            match x.tag {
+                Expr::Num => {
+                    let y = add(x, one);
+                    return (y)
+                }
+                Expr::Char => {
+                    return (x)
+                }
+            }
+        });
+        let store = Store::<Fr>::default();
+        let profile = func.cost_profile(&store);
+        assert_eq!(profile.total, 12);
+        assert_eq!(profile.total, func.num_constraints(&store));
+        assert_eq!((profile.slots, profile.globals), (0, 2));
+
+        let num = Tag::Expr(ExprTag::Num);
+        let char = Tag::Expr(ExprTag::Char);
+        let blocks = profile
+            .blocks
+            .iter()
+            .map(|cost| (cost.path.clone(), cost.depth, cost.own, cost.total))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            blocks,
+            [
+                (Path::default(), 0, 5, 10),
+                (Path::default().push_tag(&num), 1, 3, 3),
+                (Path::default().push_tag(&char), 1, 2, 2),
+            ]
+        );
+
+        let kinds = profile
+            .kinds
+            .iter()
+            .map(|cost| (cost.kind, cost.count, cost.constraints))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            kinds,
+            [
+                ("MatchTag", 1, 5),
+                ("Return", 2, 4),
+                ("Add", 1, 1),
+                ("Lit", 1, 0)
+            ]
+        );
+    }
+
+    #[test]
+    fn eval_step_profile() {
+        let store = Store::<Fr>::default();
+        let func = eval_step();
+        let profile = func.cost_profile(&store);
+        assert_eq!(profile.total, func.num_constraints(&store));
+
+        let own: usize = profile.blocks.iter().map(|cost| cost.own).sum();
+        assert_eq!(profile.blocks[0].total, own);
+        assert_eq!(profile.total, profile.slots + profile.globals + own);
+        let kinds: usize = profile.kinds.iter().map(|cost| cost.constraints).sum();
+        assert_eq!(kinds, own);
+
+        // `reduce` and `apply_cont` are profiled as part of the step function
+        assert!(profile.blocks.iter().any(|cost| cost.func == "reduce"));
+        assert!(profile.blocks.iter().any(|cost| cost.func == "apply_cont"));
+    }
+}
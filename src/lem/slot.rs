@@ -104,7 +104,7 @@
 //! expression and so will STEP 3.
 
 use super::{pointers::Ptr, Block, Ctrl, Op};
-use crate::field::LurkField;
+use crate::field::{LanguageField, LurkField};
 
 #[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
 pub struct SlotsCounter {
@@ -202,6 +202,10 @@ impl Block {
                 Op::Hide(..) | Op::Open(..) => SlotsCounter::new((0, 0, 0, 1, 0)),
                 Op::Lt(..) => SlotsCounter::new((0, 0, 0, 0, 3)),
                 Op::Trunc(..) => SlotsCounter::new((0, 0, 0, 0, 1)),
+                Op::BitAnd(..) | Op::BitOr(..) | Op::BitXor(..) => {
+                    SlotsCounter::new((0, 0, 0, 0, 2))
+                }
+                Op::Pow2(..) => SlotsCounter::new((0, 0, 0, 0, 1)),
                 Op::Call(_, func, _) => func.slots_count,
                 _ => SlotsCounter::default(),
             };
@@ -273,6 +277,17 @@ impl SlotType {
     }
 }
 
+/// The size of the witness of a bit decomposition slot, which is also its
+/// number of constraints. It depends on the field and was collected empirically
+pub(crate) fn bit_decomp_cost<F: LurkField>() -> usize {
+    match F::FIELD {
+        LanguageField::Pallas => 298,
+        LanguageField::Vesta => 301,
+        LanguageField::BN256 => 354,
+        LanguageField::Grumpkin => 364,
+    }
+}
+
 impl std::fmt::Display for SlotType {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -12,10 +12,14 @@ use rayon::prelude::{IntoParallelRefIterator, ParallelIterator};
 use std::{cell::RefCell, rc::Rc, sync::Arc};
 
 use crate::{
+    coprocessor::trie::{StandardTrie, Trie},
     field::{FWrap, LurkField},
     hash::{InversePoseidonCache, PoseidonCache},
     lem::Tag,
-    parser::{syntax, Error, Span},
+    parser::{
+        string::{escape_char, escape_string},
+        syntax, Error, Span,
+    },
     state::{lurk_sym, user_sym, State},
     symbol::Symbol,
     syntax::Syntax,
@@ -23,7 +27,11 @@ use crate::{
         self, Binop, Binop2, Call, Call0, Call2, Dummy, Emit, If, Let, LetRec, Lookup, Outermost,
         Tail, Terminal, Unop,
     },
-    tag::ExprTag::{Char, Comm, Cons, Cproc, Fun, Key, Nil, Num, Str, Sym, Thunk, U64},
+    tag::ExprTag::{
+        self, Char, Comm, Cons, Cproc, Fun, Key, Nil, Num, Str, Sym, Thunk, I64, U128, U16, U32,
+        U64, U8,
+    },
+    uint::UInt,
 };
 
 use super::pointers::{Ptr, ZPtr};
@@ -439,6 +447,30 @@ impl<F: LurkField> Store<F> {
         Ptr::Atom(Tag::Cont(ContTag::Error), self.hash8zeros)
     }
 
+    /// Interns an error continuation, as built by Lurk's step function, with
+    /// a keyword `code` and the offending expression `expr`
+    #[inline]
+    pub fn intern_cont_error(&self, code: &str, expr: Ptr<F>) -> Ptr<F> {
+        self.intern_4_ptrs(
+            Tag::Cont(ContTag::Error),
+            self.key(code),
+            expr,
+            Ptr::dummy(),
+            Ptr::dummy(),
+        )
+    }
+
+    /// Returns the error code and the offending expression of an error
+    /// continuation. Returns `None` for blank or opaque error continuations
+    pub fn fetch_cont_error(&self, ptr: &Ptr<F>) -> Option<(&Ptr<F>, &Ptr<F>)> {
+        match ptr {
+            Ptr::Tuple4(Tag::Cont(ContTag::Error), idx) => self
+                .fetch_4_ptrs(*idx)
+                .map(|(code, expr, _, _)| (code, expr)),
+            _ => None,
+        }
+    }
+
     #[inline]
     pub fn cont_terminal(&self) -> Ptr<F> {
         Ptr::Atom(Tag::Cont(Terminal), self.hash8zeros)
@@ -526,10 +558,153 @@ impl<F: LurkField> Store<F> {
         }
     }
 
+    /// Interns a vector as a balanced binary tree of conses whose leaves are
+    /// the elements. The tree has `span` leaves, where `span` is the smallest
+    /// power of two that fits the elements, and subtrees that lie entirely
+    /// past the last element are `nil`
+    pub fn intern_vector(&self, elts: Vec<Ptr<F>>) -> Ptr<F> {
+        fn build<F: LurkField>(store: &Store<F>, elts: &[Ptr<F>], span: usize) -> Ptr<F> {
+            if elts.is_empty() {
+                store.intern_nil()
+            } else if span == 1 {
+                elts[0]
+            } else {
+                let half = span / 2;
+                let (left, right) = elts.split_at(half.min(elts.len()));
+                store.cons(build(store, left, half), build(store, right, half))
+            }
+        }
+        let len = elts.len();
+        let span = len.next_power_of_two();
+        let root = build(self, &elts, span);
+        self.intern_3_ptrs(
+            Tag::Expr(ExprTag::Vec),
+            Ptr::num((len as u64).into()),
+            Ptr::num((span as u64).into()),
+            root,
+        )
+    }
+
+    /// Fetches the elements of a vector that was interned
+    pub fn fetch_vector(&self, ptr: &Ptr<F>) -> Option<Vec<Ptr<F>>> {
+        fn collect<F: LurkField>(
+            store: &Store<F>,
+            node: &Ptr<F>,
+            span: usize,
+            len: usize,
+            elts: &mut Vec<Ptr<F>>,
+        ) -> Option<()> {
+            if elts.len() >= len {
+                return Some(());
+            }
+            if span == 1 {
+                elts.push(*node);
+                return Some(());
+            }
+            let (left, right) = store.fetch_2_ptrs(node.get_index2()?)?;
+            collect(store, left, span / 2, len, elts)?;
+            collect(store, right, span / 2, len, elts)
+        }
+        let Ptr::Tuple3(Tag::Expr(ExprTag::Vec), idx) = ptr else {
+            return None;
+        };
+        let (len, span, root) = self.fetch_3_ptrs(*idx)?;
+        let len = usize::try_from(len.get_atom()?.to_u64()?).ok()?;
+        let span = usize::try_from(span.get_atom()?.to_u64()?).ok()?;
+        let mut elts = Vec::with_capacity(len);
+        collect(self, root, span, len, &mut elts)?;
+        Some(elts)
+    }
+
+    /// The key under which `key` is bound in the trie of a map: the hash of
+    /// its tag and value, as `hide` computes it with a zero secret
+    pub fn map_key(&self, key: &Ptr<F>) -> F {
+        let z_ptr = self.hash_ptr(key);
+        self.poseidon_cache
+            .hash3(&[F::ZERO, z_ptr.tag_field(), *z_ptr.value()])
+    }
+
+    /// The value stored in the trie of a map for the binding of `key` to `val`:
+    /// a commitment to `(key . val)` with a zero secret, so the binding can be
+    /// opened back from the trie
+    pub fn map_entry(&self, key: Ptr<F>, val: Ptr<F>) -> F {
+        self.hide_and_return_z_payload(F::ZERO, self.cons(key, val))
+            .0
+    }
+
+    /// The trie whose root a map wraps
+    fn map_trie(&self, map: &Ptr<F>) -> Option<StandardTrie<'_, F>> {
+        match map {
+            Ptr::Atom(Tag::Expr(ExprTag::Map), root) => Some(Trie::new_with_root(
+                &self.poseidon_cache,
+                &self.inverse_poseidon_cache,
+                *root,
+            )),
+            _ => None,
+        }
+    }
+
+    /// Interns an empty map, which wraps the root of an empty `StandardTrie`
+    pub fn intern_empty_map(&self) -> Ptr<F> {
+        let trie: StandardTrie<'_, F> =
+            Trie::new(&self.poseidon_cache, &self.inverse_poseidon_cache);
+        Ptr::Atom(Tag::Expr(ExprTag::Map), trie.root())
+    }
+
+    /// Returns `map` with `key` bound to `val`. Returns `None` if `map` isn't
+    /// a map whose trie is known by the store
+    pub fn map_assoc(&self, map: &Ptr<F>, key: Ptr<F>, val: Ptr<F>) -> Option<Ptr<F>> {
+        let mut trie = self.map_trie(map)?;
+        trie.insert(self.map_key(&key), self.map_entry(key, val))
+            .ok()?;
+        Some(Ptr::Atom(Tag::Expr(ExprTag::Map), trie.root()))
+    }
+
+    /// Returns the value bound to `key` in `map`, or `nil` if there's none.
+    /// Returns `None` if `map` isn't a map whose trie is known by the store
+    pub fn map_get(&self, map: &Ptr<F>, key: &Ptr<F>) -> Option<Ptr<F>> {
+        let trie = self.map_trie(map)?;
+        match trie.lookup(self.map_key(key)).ok()? {
+            Some(entry) => {
+                let (_, entry) = self.open(entry)?;
+                let (_, val) = self.car_cdr(entry).ok()?;
+                Some(val)
+            }
+            None => Some(self.intern_nil()),
+        }
+    }
+
+    /// Interns a map with the bindings in `entries`. Later bindings of a key
+    /// override earlier ones
+    pub fn intern_map(&self, entries: Vec<(Ptr<F>, Ptr<F>)>) -> Ptr<F> {
+        entries
+            .into_iter()
+            .fold(self.intern_empty_map(), |map, (key, val)| {
+                self.map_assoc(&map, key, val)
+                    .expect("the trie was built by the store")
+            })
+    }
+
+    /// Fetches the bindings of a map whose trie is known by the store, in the
+    /// order of their keys in the trie
+    pub fn fetch_map(&self, ptr: &Ptr<F>) -> Option<Vec<(Ptr<F>, Ptr<F>)>> {
+        self.map_trie(ptr)?
+            .values()
+            .ok()?
+            .into_iter()
+            .map(|entry| {
+                let (_, entry) = self.open(entry)?;
+                let (key, val) = self.fetch_2_ptrs(entry.get_index2()?)?;
+                Some((*key, *val))
+            })
+            .collect()
+    }
+
     pub fn intern_syntax(&self, syn: Syntax<F>) -> Ptr<F> {
         match syn {
             Syntax::Num(_, x) => Ptr::num(x.into_scalar()),
-            Syntax::UInt(_, x) => Ptr::u64(x.into()),
+            Syntax::UInt(_, x) => Ptr::uint(x),
+            Syntax::I64(_, x) => Ptr::i64(x),
             Syntax::Char(_, x) => Ptr::char(x),
             Syntax::Symbol(_, x) => self.intern_symbol(&x),
             Syntax::String(_, x) => self.intern_string(&x),
@@ -537,6 +712,15 @@ impl<F: LurkField> Store<F> {
                 self.intern_symbol(&lurk_sym("quote")),
                 self.intern_syntax(*x),
             ]),
+            Syntax::Quasiquote(_, x) => self.intern_syntax(x.expand_quasiquote()),
+            Syntax::Unquote(_, x) => self.list(vec![
+                self.intern_symbol(&lurk_sym("unquote")),
+                self.intern_syntax(*x),
+            ]),
+            Syntax::UnquoteSplicing(_, x) => self.list(vec![
+                self.intern_symbol(&lurk_sym("unquote-splicing")),
+                self.intern_syntax(*x),
+            ]),
             Syntax::List(_, xs) => {
                 self.list(xs.into_iter().map(|x| self.intern_syntax(x)).collect())
             }
@@ -544,12 +728,17 @@ impl<F: LurkField> Store<F> {
                 xs.into_iter().map(|x| self.intern_syntax(x)).collect(),
                 self.intern_syntax(*y),
             ),
+            Syntax::Map(_, kvs) => self.intern_map(
+                kvs.into_iter()
+                    .map(|(k, v)| (self.intern_syntax(k), self.intern_syntax(v)))
+                    .collect(),
+            ),
         }
     }
 
     pub fn read(&self, state: Rc<RefCell<State>>, input: &str) -> Result<Ptr<F>> {
         match preceded(
-            syntax::parse_space,
+            syntax::parse_space(state.clone(), false),
             syntax::parse_syntax(state, false, false),
         )
         .parse(Span::new(input))
@@ -564,8 +753,11 @@ impl<F: LurkField> Store<F> {
         state: Rc<RefCell<State>>,
         input: &'a str,
     ) -> Result<(usize, Span<'a>, Ptr<F>, bool), Error> {
-        match preceded(syntax::parse_space, syntax::parse_maybe_meta(state, false))
-            .parse(input.into())
+        match preceded(
+            syntax::parse_space(state.clone(), false),
+            syntax::parse_maybe_meta(state, false),
+        )
+        .parse(input.into())
         {
             Ok((i, Some((is_meta, x)))) => {
                 let from_offset = x
@@ -827,13 +1019,13 @@ impl<F: LurkField> Ptr<F> {
                 }
                 Str => {
                     if let Some(str) = store.fetch_string(self) {
-                        format!("\"{str}\"")
+                        format!("\"{}\"", escape_string(&str))
                     } else {
                         "<Opaque Str>".into()
                     }
                 }
                 Char => match self.get_atom().map(F::to_char) {
-                    Some(Some(c)) => format!("\'{c}\'"),
+                    Some(Some(c)) => format!("'{}'", escape_char(c)),
                     _ => "<Malformed Char>".into(),
                 },
                 Cons => {
@@ -855,6 +1047,34 @@ impl<F: LurkField> Ptr<F> {
                         "<Opaque Cons>".into()
                     }
                 }
+                ExprTag::Map => {
+                    if let Some(entries) = store.fetch_map(self) {
+                        let entries = entries
+                            .iter()
+                            .map(|(k, v)| {
+                                format!(
+                                    "{} {}",
+                                    k.fmt_to_string(store, state),
+                                    v.fmt_to_string(store, state)
+                                )
+                            })
+                            .collect::<Vec<_>>();
+                        format!("{{{}}}", entries.join(" "))
+                    } else {
+                        "<Opaque Map>".into()
+                    }
+                }
+                ExprTag::Vec => {
+                    if let Some(elts) = store.fetch_vector(self) {
+                        let elts = elts
+                            .iter()
+                            .map(|p| p.fmt_to_string(store, state))
+                            .collect::<Vec<_>>();
+                        format!("[{}]", elts.join(" "))
+                    } else {
+                        "<Opaque Vec>".into()
+                    }
+                }
                 Num => match self.get_atom() {
                     None => "<Malformed Num>".into(),
                     Some(f) => {
@@ -865,28 +1085,36 @@ impl<F: LurkField> Ptr<F> {
                         }
                     }
                 },
-                U64 => match self.get_atom().map(F::to_u64) {
-                    Some(Some(u)) => format!("{u}u64"),
-                    _ => "<Malformed U64>".into(),
+                U8 | U16 | U32 | U64 | U128 => match self
+                    .get_atom()
+                    .and_then(F::to_u128)
+                    .and_then(|u| UInt::from_tag(*t, u))
+                {
+                    Some(u) => format!("{u}{}", u.suffix()),
+                    _ => format!("<Malformed {t}>"),
+                },
+                I64 => match self.get_atom().and_then(F::to_u64) {
+                    Some(u) => format!("{}i64", u as i64),
+                    _ => "<Malformed I64>".into(),
                 },
                 Fun => match self.get_index3() {
                     None => "<Malformed Fun>".into(),
                     Some(idx) => {
                         if let Some((arg, bod, _)) = store.fetch_3_ptrs(idx) {
+                            // variadic closures keep their parameters as a list
+                            let args = match arg.tag() {
+                                Tag::Expr(Cons) => arg.fmt_to_string(store, state),
+                                _ => format!("({})", arg.fmt_to_string(store, state)),
+                            };
                             match bod.tag() {
                                 Tag::Expr(Nil) => {
-                                    format!(
-                                        "<FUNCTION ({}) {}>",
-                                        arg.fmt_to_string(store, state),
-                                        bod.fmt_to_string(store, state)
-                                    )
+                                    format!("<FUNCTION {args} {}>", bod.fmt_to_string(store, state))
                                 }
                                 Tag::Expr(Cons) => {
                                     if let Some(idx) = bod.get_index2() {
                                         if let Some((bod, _)) = store.fetch_2_ptrs(idx) {
                                             format!(
-                                                "<FUNCTION ({}) {}>",
-                                                arg.fmt_to_string(store, state),
+                                                "<FUNCTION {args} {}>",
                                                 bod.fmt_to_string(store, state)
                                             )
                                         } else {
@@ -945,7 +1173,17 @@ impl<F: LurkField> Ptr<F> {
             Tag::Cont(t) => match t {
                 Outermost => "Outermost".into(),
                 Dummy => "Dummy".into(),
-                ContTag::Error => "Error".into(),
+                ContTag::Error => match self {
+                    Ptr::Atom(..) => "Error".into(),
+                    _ => match store.fetch_cont_error(self) {
+                        Some((code, expr)) => format!(
+                            "Error{{ code: {}, expr: {} }}",
+                            code.fmt_to_string(store, state),
+                            expr.fmt_to_string(store, state)
+                        ),
+                        None => "<Opaque Error>".into(),
+                    },
+                },
                 Terminal => "Terminal".into(),
                 Call0 => self.fmt_cont2_to_string("Call0", "saved_env", store, state),
                 Call => {
@@ -953,7 +1191,19 @@ impl<F: LurkField> Ptr<F> {
                 }
                 Call2 => self.fmt_cont3_to_string("Call2", ("function", "saved_env"), store, state),
                 Tail => self.fmt_cont2_to_string("Tail", "saved_env", store, state),
-                Lookup => self.fmt_cont2_to_string("Lookup", "saved_env", store, state),
+                Lookup => match self.get_index4() {
+                    None => "<Malformed Lookup>".into(),
+                    Some(idx) => match store.fetch_4_ptrs(idx) {
+                        Some((saved_env, cont, frame, frame_env)) => format!(
+                            "Lookup{{ saved_env: {}, frame: {}, frame_env: {}, continuation: {} }}",
+                            saved_env.fmt_to_string(store, state),
+                            frame.fmt_to_string(store, state),
+                            frame_env.fmt_to_string(store, state),
+                            cont.fmt_to_string(store, state)
+                        ),
+                        None => "<Opaque Lookup>".into(),
+                    },
+                },
                 Unop => self.fmt_cont2_to_string("Unop", "saved_env", store, state),
                 Binop => self.fmt_cont4_to_string(
                     "Binop",
@@ -967,8 +1217,14 @@ impl<F: LurkField> Ptr<F> {
                 If => self.fmt_cont2_to_string("If", "unevaled_args", store, state),
                 Let => self.fmt_cont4_to_string("Let", ("var", "saved_env", "body"), store, state),
                 LetRec => {
-                    self.fmt_cont4_to_string("LetRec", ("var", "saved_env", "body"), store, state)
+                    self.fmt_cont4_to_string("LetRec", ("var", "saved_env", "rest"), store, state)
                 }
+                ContTag::LetRecNext => self.fmt_cont4_to_string(
+                    "LetRecNext",
+                    ("var", "saved_env", "rest"),
+                    store,
+                    state,
+                ),
                 Emit => "Emit <CONTINUATION>".into(),
                 ContTag::Cproc => self.fmt_cont4_to_string(
                     "Cproc",
@@ -976,6 +1232,32 @@ impl<F: LurkField> Ptr<F> {
                     store,
                     state,
                 ),
+                ContTag::Fail => self.fmt_cont2_to_string("Fail", "form", store, state),
+                ContTag::Catch => {
+                    self.fmt_cont3_to_string("Catch", ("handler", "saved_env"), store, state)
+                }
+                ContTag::Unwind => self.fmt_cont2_to_string("Unwind", "form", store, state),
+                ContTag::Rest => self.fmt_cont4_to_string(
+                    "Rest",
+                    ("function", "collected", "args"),
+                    store,
+                    state,
+                ),
+                ContTag::Apply => {
+                    self.fmt_cont3_to_string("Apply", ("args", "saved_env"), store, state)
+                }
+                ContTag::StringOp => {
+                    self.fmt_cont3_to_string("StringOp", ("operator", "state"), store, state)
+                }
+                ContTag::Vector => self.fmt_cont4_to_string(
+                    "Vector",
+                    ("vector", "unevaled_args", "saved_env"),
+                    store,
+                    state,
+                ),
+                ContTag::VectorOp => {
+                    self.fmt_cont3_to_string("VectorOp", ("operator", "state"), store, state)
+                }
             },
             Tag::Op1(op) => op.to_string(),
             Tag::Op2(op) => op.to_string(),
@@ -1073,7 +1355,7 @@ mod tests {
         Num, Symbol,
     };
 
-    use super::{Ptr, Store};
+    use super::{Ptr, StandardTrie, Store};
 
     #[test]
     fn test_car_cdr() {
@@ -1103,6 +1385,22 @@ mod tests {
         assert_eq!((&nil, &empty_str), (&car, &cdr));
     }
 
+    #[test]
+    fn test_print_escapes_round_trip() {
+        let store = Store::<Fr>::default();
+        let state = initial_lurk_state();
+        for (ptr, printed) in [
+            (store.intern_string("a\"b\\c\nd"), r#""a\"b\\c\nd""#),
+            (store.intern_string("\u{1}é"), r#""\u{1}é""#),
+            (Ptr::char('\n'), r"'\n'"),
+            (Ptr::char('('), r"'\('"),
+            (Ptr::char('\''), r"'\''"),
+        ] {
+            assert_eq!(ptr.fmt_to_string(&store, state), printed);
+            assert_eq!(store.read_with_default_state(printed).unwrap(), ptr);
+        }
+    }
+
     #[test]
     fn test_list() {
         let store = Store::<Fr>::default();
@@ -1265,13 +1563,63 @@ mod tests {
         assert_eq!(foo_bar_hash, foo_bar_hash_manual);
     }
 
+    #[test]
+    fn test_map_interning() {
+        let store = Store::<Fr>::default();
+        let entries = (0..8)
+            .map(|i| (Ptr::num_u64(i), Ptr::num_u64(i * i)))
+            .collect::<Vec<_>>();
+        let map = store.intern_map(entries.clone());
+
+        // the trie only depends on the keys, not on the order of insertion
+        let mut reversed = entries.clone();
+        reversed.reverse();
+        assert_eq!(map, store.intern_map(reversed));
+
+        let mut fetched = store.fetch_map(&map).unwrap();
+        fetched.sort_by_key(|(k, _)| k.get_atom().unwrap().to_u64_unchecked());
+        assert_eq!(fetched, entries);
+
+        assert_eq!(store.map_get(&map, &Ptr::num_u64(3)), Some(Ptr::num_u64(9)));
+        assert_eq!(
+            store.map_get(&map, &Ptr::num_u64(8)),
+            Some(store.intern_nil())
+        );
+
+        let updated = store
+            .map_assoc(&map, Ptr::num_u64(3), Ptr::num_u64(0))
+            .unwrap();
+        assert_ne!(map, updated);
+        let restored = store
+            .map_assoc(&updated, Ptr::num_u64(3), Ptr::num_u64(9))
+            .unwrap();
+        assert_eq!(map, restored);
+
+        // maps wrap the root of a `StandardTrie`
+        let mut trie = StandardTrie::new(&store.poseidon_cache, &store.inverse_poseidon_cache);
+        for (key, val) in entries {
+            trie.insert(store.map_key(&key), store.map_entry(key, val))
+                .unwrap();
+        }
+        assert_eq!(map, Ptr::Atom(Tag::Expr(ExprTag::Map), trie.root()));
+    }
+
     // helper function to test syntax interning roundtrip
     fn fetch_syntax(ptr: Ptr<Fr>, store: &Store<Fr>) -> Syntax<Fr> {
         match ptr {
             Ptr::Atom(Tag::Expr(ExprTag::Num), f) => Syntax::Num(Pos::No, Num::Scalar(f)),
             Ptr::Atom(Tag::Expr(ExprTag::Char), f) => Syntax::Char(Pos::No, f.to_char().unwrap()),
-            Ptr::Atom(Tag::Expr(ExprTag::U64), f) => {
-                Syntax::UInt(Pos::No, crate::UInt::U64(f.to_u64_unchecked()))
+            Ptr::Atom(
+                Tag::Expr(
+                    t @ (ExprTag::U8 | ExprTag::U16 | ExprTag::U32 | ExprTag::U64 | ExprTag::U128),
+                ),
+                f,
+            ) => Syntax::UInt(
+                Pos::No,
+                crate::UInt::from_tag(t, f.to_u128_unchecked()).unwrap(),
+            ),
+            Ptr::Atom(Tag::Expr(ExprTag::I64), f) => {
+                Syntax::I64(Pos::No, f.to_u64_unchecked() as i64)
             }
             Ptr::Atom(Tag::Expr(ExprTag::Sym), _)
             | Ptr::Atom(Tag::Expr(ExprTag::Key), _)
@@ -1297,6 +1645,15 @@ mod tests {
             Ptr::Tuple2(Tag::Expr(ExprTag::Nil), _) => {
                 Syntax::Symbol(Pos::No, lurk_sym("nil").into())
             }
+            Ptr::Atom(Tag::Expr(ExprTag::Map), _) => Syntax::Map(
+                Pos::No,
+                store
+                    .fetch_map(&ptr)
+                    .unwrap()
+                    .into_iter()
+                    .map(|(k, v)| (fetch_syntax(k, store), fetch_syntax(v, store)))
+                    .collect(),
+            ),
             _ => unreachable!(),
         }
     }
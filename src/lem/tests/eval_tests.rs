@@ -11,7 +11,7 @@ use crate::{
         Tag,
     },
     state::State,
-    tag::Op,
+    tag::{ExprTag, Op},
 };
 
 fn test_aux<C: Coprocessor<Fr>>(
@@ -98,6 +98,35 @@ fn do_test_aux<C: Coprocessor<Fr>>(
     }
 }
 
+/// The error continuation for the error `code`, caused by the expression read
+/// from `expr`
+fn error_cont(s: &Store<Fr>, code: &str, expr: &str) -> Ptr<Fr> {
+    s.intern_cont_error(code, s.read_with_default_state(expr).unwrap())
+}
+
+/// Evaluates the expression read from `expr` without pinning its iterations,
+/// returning the final expression, environment and continuation
+fn evaluate_expr(s: &Store<Fr>, expr: &str) -> Vec<Ptr<Fr>> {
+    let ptr = s.read_with_default_state(expr).unwrap();
+    evaluate_simple::<Fr, Coproc<Fr>>(None, ptr, s, 1000)
+        .unwrap()
+        .0
+}
+
+/// Asserts that evaluating the expression read from `expr` terminates with the
+/// value read from `expected`
+fn assert_evaluates_to(s: &Store<Fr>, expr: &str, expected: &str) {
+    let output = evaluate_expr(s, expr);
+    assert_eq!(output[2], s.cont_terminal());
+    assert!(s.ptr_eq(&output[0], &s.read_with_default_state(expected).unwrap()));
+}
+
+/// Asserts that evaluating the expression read from `expr` ends in the error
+/// `code`, caused by the expression read from `offending`
+fn assert_error(s: &Store<Fr>, expr: &str, code: &str, offending: &str) {
+    assert_eq!(evaluate_expr(s, expr)[2], error_cont(s, code, offending));
+}
+
 fn do_test<C: Coprocessor<Fr>>(
     s: &Store<Fr>,
     expr: &Ptr<Fr>,
@@ -422,10 +451,215 @@ fn evaluate_quotient_divide_by_zero() {
     let s = &Store::<Fr>::default();
     let expr = "(/ 21 0)";
 
-    let error = s.cont_error();
+    let error = error_cont(s, "div-by-zero", "0");
     test_aux::<Coproc<Fr>>(s, expr, None, None, Some(error), None, 3, &None);
 }
 
+#[test]
+fn evaluate_error_causes() {
+    let s = &Store::<Fr>::default();
+    assert_error(s, "x", "unbound-var", "x");
+    assert_error(s, "(quote 1 2)", "invalid-form", "(quote 1 2)");
+    assert_error(s, "(cons 1)", "arity-mismatch", "(cons 1)");
+    assert_error(s, "(1 2)", "not-a-function", "(1 2)");
+    assert_error(s, "(car 1)", "type-mismatch", "1");
+    assert_error(s, "(+ 1 'a)", "type-mismatch", "a");
+    assert_error(s, "(/ 21 0)", "div-by-zero", "0");
+    assert_error(s, "(% 21u64 0u64)", "div-by-zero", "0u64");
+}
+
+#[test]
+fn evaluate_fail_and_catch() {
+    let s = &Store::<Fr>::default();
+
+    // uncaught failures become errors
+    assert_error(s, "(+ 1 (fail :oops))", "oops", "(fail :oops)");
+
+    assert_evaluates_to(s, "(catch (lambda (e) e) (+ 1 2))", "3");
+    assert_evaluates_to(s, "(+ 1 (catch (lambda (e) 10) (* 2 (fail 'x))))", "11");
+
+    // the error code is evaluated, and the handler receives its value as is
+    assert_evaluates_to(
+        s,
+        "(catch (lambda (e) e) (let ((f (lambda (x) (fail (cons x 'y))))) (f 1)))",
+        "(1 . y)",
+    );
+
+    // failures inside handlers propagate to the enclosing `catch`
+    assert_evaluates_to(
+        s,
+        "(catch (lambda (e) (cons :outer e)) (catch (lambda (e) (fail e)) (fail :inner)))",
+        "(:outer . :inner)",
+    );
+}
+
+#[test]
+fn evaluate_quasiquote() {
+    let s = &Store::<Fr>::default();
+
+    for (expr, expected) in [
+        ("`(a b)", "(a b)"),
+        ("(let ((x 1)) `(a ,x))", "(a 1)"),
+        ("(let ((x 1)) `(a ,(+ x 1) . ,x))", "(a 2 . 1)"),
+        ("(let ((xs '(1 2))) `(a ,@xs))", "(a 1 2)"),
+        ("(let ((xs '(1 2))) `(a ,@xs b ,@xs))", "(a 1 2 b 1 2)"),
+        ("(let ((x 1)) `(a '(b ,x)))", "(a (quote (b 1)))"),
+        // only the unquotes nested as deeply as the quasiquotes are evaluated
+        ("`(a `(b ,c))", "(a (quasiquote (b (unquote c))))"),
+        (
+            "(let ((x 1)) `(a `(b ,(c ,x))))",
+            "(a (quasiquote (b (unquote (c 1)))))",
+        ),
+        // the splicing helper doesn't shadow the user's own `append`
+        (
+            "(letrec ((append (lambda (a b) 0))) (let ((xs '(1))) `(,@xs ,(append 1 2))))",
+            "(1 0)",
+        ),
+    ] {
+        assert_evaluates_to(s, expr, expected);
+    }
+
+    // the expansion is made of ordinary forms, so it survives the writer
+    let state = State::init_lurk_state();
+    let expanded = s.read_with_default_state("`(a ,@xs b ,(car ys))").unwrap();
+    let printed = expanded.fmt_to_string(s, &state);
+    assert!(s.ptr_eq(&expanded, &s.read_with_default_state(&printed).unwrap()));
+
+    // every splice shares the same helper
+    let expanded = s.read_with_default_state("`(,@xs ,@ys ,@zs)").unwrap();
+    let expanded = expanded.fmt_to_string(s, &state);
+    assert_eq!(expanded.matches("lambda").count(), 1);
+}
+
+#[test]
+fn evaluate_uint_widths() {
+    let s = &Store::<Fr>::default();
+
+    for (expr, expected) in [
+        ("(+ 255u8 1u8)", "0u8"),
+        ("(- 0u16 1u16)", "65535u16"),
+        ("(* 65536u32 65536u32)", "0u32"),
+        ("(* 4294967296u64 4294967295u64)", "18446744069414584320u64"),
+        ("(/ 7u8 2u8)", "3u8"),
+        ("(% 7u32 2u32)", "1u32"),
+        ("(/ 65535u16 256u16)", "255u16"),
+        ("(% 4294967295u32 65536u32)", "65535u32"),
+        ("(/ 18446744073709551615u64 3u64)", "6148914691236517205u64"),
+        ("(% 18446744073709551615u64 10u64)", "5u64"),
+        (
+            "(- 0u128 1u128)",
+            "340282366920938463463374607431768211455u128",
+        ),
+        (
+            "(* 340282366920938463463374607431768211455u128 3u128)",
+            "340282366920938463463374607431768211453u128",
+        ),
+        (
+            "(/ 340282366920938463463374607431768211455u128 3u128)",
+            "113427455640312821154458202477256070485u128",
+        ),
+        ("(u8 300)", "44u8"),
+        ("(u16 70000u32)", "4464u16"),
+        ("(u128 1u8)", "1u128"),
+        ("(num 5u8)", "5"),
+    ] {
+        assert_evaluates_to(s, expr, expected);
+    }
+
+    // integers of different widths don't mix
+    assert_error(s, "(+ 1u8 1u16)", "type-mismatch", "1u16");
+}
+
+#[test]
+fn evaluate_i64() {
+    let s = &Store::<Fr>::default();
+
+    for (expr, expected) in [
+        ("(+ -1i64 1i64)", "0i64"),
+        ("(- 0i64 5i64)", "-5i64"),
+        ("(+ 9223372036854775807i64 1i64)", "-9223372036854775808i64"),
+        ("(* -3i64 4i64)", "-12i64"),
+        ("(* 4294967296i64 4294967296i64)", "0i64"),
+        ("(/ -7i64 2i64)", "-3i64"),
+        ("(/ 7i64 -2i64)", "-3i64"),
+        ("(/ -7i64 -2i64)", "3i64"),
+        ("(% -7i64 2i64)", "-1i64"),
+        ("(% 7i64 -2i64)", "1i64"),
+        (
+            "(/ -9223372036854775808i64 -1i64)",
+            "-9223372036854775808i64",
+        ),
+        ("(< -1i64 1i64)", "t"),
+        ("(> -1i64 1i64)", "nil"),
+        ("(<= -9223372036854775808i64 -1i64)", "t"),
+        ("(= -2i64 -2i64)", "t"),
+        ("(i64 -5)", "-5i64"),
+        ("(i64 18446744073709551615u64)", "-1i64"),
+        ("(num -5i64)", "-5"),
+        ("(u8 -1i64)", "255u8"),
+        (
+            "(u128 -1i64)",
+            "340282366920938463463374607431768211455u128",
+        ),
+    ] {
+        assert_evaluates_to(s, expr, expected);
+    }
+
+    // i64s don't mix with other numbers
+    assert_error(s, "(+ 1i64 1)", "type-mismatch", "1");
+    assert_error(s, "(/ 1i64 0i64)", "div-by-zero", "0i64");
+
+    test_aux::<Coproc<Fr>>(
+        s,
+        "(/ -7i64 2i64)",
+        Some(Ptr::i64(-3)),
+        None,
+        Some(s.cont_terminal()),
+        None,
+        3,
+        &None,
+    );
+}
+
+#[test]
+fn evaluate_bitwise_u64() {
+    let s = &Store::<Fr>::default();
+
+    for (expr, expected) in [
+        ("(logand 12u64 10u64)", "8u64"),
+        ("(logor 12u64 10u64)", "14u64"),
+        ("(logxor 12u64 10u64)", "6u64"),
+        (
+            "(logxor 18446744073709551615u64 1u64)",
+            "18446744073709551614u64",
+        ),
+        ("(shl 1u64 63u64)", "9223372036854775808u64"),
+        ("(shl 3u64 63u64)", "9223372036854775808u64"),
+        ("(shl 1u64 64u64)", "1u64"),
+        ("(shr 18446744073709551615u64 60u64)", "15u64"),
+        ("(shr 5u64 0u64)", "5u64"),
+        ("(rotl 9223372036854775809u64 1u64)", "3u64"),
+        ("(rotr 3u64 1u64)", "9223372036854775809u64"),
+        ("(rotr 3u64 0u64)", "3u64"),
+    ] {
+        assert_evaluates_to(s, expr, expected);
+    }
+
+    // bitwise operations are only defined on u64s
+    assert_error(s, "(logand 1u32 1u32)", "type-mismatch", "1u32");
+
+    test_aux::<Coproc<Fr>>(
+        s,
+        "(rotl 1u64 65u64)",
+        Some(Ptr::u64(2)),
+        None,
+        Some(s.cont_terminal()),
+        None,
+        3,
+        &None,
+    );
+}
+
 #[test]
 fn evaluate_num_equal() {
     let s = &Store::<Fr>::default();
@@ -568,7 +802,7 @@ fn evaluate_let_empty_error() {
     let s = &Store::<Fr>::default();
     let expr = "(let)";
 
-    let error = s.cont_error();
+    let error = error_cont(s, "invalid-form", expr);
     test_aux::<Coproc<Fr>>(s, expr, None, None, Some(error), None, 1, &None);
 }
 
@@ -577,7 +811,7 @@ fn evaluate_let_empty_body_error() {
     let s = &Store::<Fr>::default();
     let expr = "(let ((a 1)))";
 
-    let error = s.cont_error();
+    let error = error_cont(s, "invalid-form", expr);
     test_aux::<Coproc<Fr>>(s, expr, None, None, Some(error), None, 1, &None);
 }
 
@@ -586,7 +820,7 @@ fn evaluate_letrec_empty_error() {
     let s = &Store::<Fr>::default();
     let expr = "(letrec)";
 
-    let error = s.cont_error();
+    let error = error_cont(s, "invalid-form", expr);
     test_aux::<Coproc<Fr>>(s, expr, None, None, Some(error), None, 1, &None);
 }
 
@@ -595,7 +829,7 @@ fn evaluate_letrec_empty_body_error() {
     let s = &Store::<Fr>::default();
     let expr = "(letrec ((a 1)))";
 
-    let error = s.cont_error();
+    let error = error_cont(s, "invalid-form", expr);
     test_aux::<Coproc<Fr>>(s, expr, None, None, Some(error), None, 1, &None);
 }
 
@@ -845,7 +1079,7 @@ fn evaluate_recursion_multiarg() {
         None,
         Some(terminal),
         None,
-        95,
+        87,
         &None,
     );
 }
@@ -945,7 +1179,7 @@ fn evaluate_multiple_letrec_bindings() {
         None,
         Some(terminal),
         None,
-        22,
+        21,
         &None,
     );
 }
@@ -966,7 +1200,7 @@ fn evaluate_multiple_letrec_bindings_referencing() {
         None,
         Some(terminal),
         None,
-        31,
+        30,
         &None,
     );
 }
@@ -998,7 +1232,34 @@ fn evaluate_multiple_letrec_bindings_recursive() {
         None,
         Some(terminal),
         None,
-        242,
+        227,
+        &None,
+    );
+}
+
+#[test]
+fn evaluate_mutually_recursive_letrec_bindings() {
+    let s = &Store::<Fr>::default();
+    let expr = "(letrec ((even? (lambda (n)
+                                  (if (= n 0)
+                                      t
+                                      (odd? (- n 1)))))
+                           (odd? (lambda (n)
+                                  (if (= n 0)
+                                      nil
+                                      (even? (- n 1))))))
+                   (odd? 3))";
+
+    let expected = s.intern_lurk_symbol("t");
+    let terminal = s.cont_terminal();
+    test_aux::<Coproc<Fr>>(
+        s,
+        expr,
+        Some(expected),
+        None,
+        Some(terminal),
+        None,
+        53,
         &None,
     );
 }
@@ -1170,7 +1431,7 @@ fn evaluate_zero_arg_lambda() {
             None,
             Some(terminal),
             None,
-            12,
+            11,
             &None,
         );
     }
@@ -1182,18 +1443,134 @@ fn evaluate_zero_arg_lambda_variants() {
         let s = &Store::<Fr>::default();
         let expr = "((lambda () 123) 1)";
 
-        let error = s.cont_error();
+        let error = error_cont(s, "arity-mismatch", "1");
         test_aux::<Coproc<Fr>>(s, expr, None, None, Some(error), None, 3, &None);
     }
     {
         let s = &Store::<Fr>::default();
         let expr = "(123)";
 
-        let error = s.cont_error();
+        let error = error_cont(s, "not-a-function", expr);
+        test_aux::<Coproc<Fr>>(s, expr, None, None, Some(error), None, 1, &None);
+    }
+}
+
+#[test]
+fn evaluate_variadic_lambda() {
+    let s = &Store::<Fr>::default();
+    let terminal = s.cont_terminal();
+    {
+        let expr = "((lambda (&rest xs) xs) 1 2 3)";
+        let expected = s.read_with_default_state("(1 2 3)").unwrap();
+        test_aux::<Coproc<Fr>>(
+            s,
+            expr,
+            Some(expected),
+            None,
+            Some(terminal),
+            None,
+            8,
+            &None,
+        );
+    }
+    {
+        let expr = "((lambda (&rest xs) xs))";
+        let expected = s.intern_nil();
+        test_aux::<Coproc<Fr>>(
+            s,
+            expr,
+            Some(expected),
+            None,
+            Some(terminal),
+            None,
+            3,
+            &None,
+        );
+    }
+    {
+        let expr = "((lambda (a &rest xs) (cons a xs)) 1)";
+        let expected = s.read_with_default_state("(1)").unwrap();
+        test_aux::<Coproc<Fr>>(
+            s,
+            expr,
+            Some(expected),
+            None,
+            Some(terminal),
+            None,
+            9,
+            &None,
+        );
+    }
+    {
+        let expr = "((lambda (a &rest xs) (cons a xs)) 1 2 3)";
+        let expected = s.read_with_default_state("(1 2 3)").unwrap();
+        test_aux::<Coproc<Fr>>(
+            s,
+            expr,
+            Some(expected),
+            None,
+            Some(terminal),
+            None,
+            12,
+            &None,
+        );
+    }
+    {
+        // only the arguments of the same call are collected
+        let expr = "(let ((vf (lambda (&rest xs) (lambda (y) (cons y xs))))) ((vf 1) 2))";
+        let expected = s.read_with_default_state("(2 1)").unwrap();
+        test_aux::<Coproc<Fr>>(
+            s,
+            expr,
+            Some(expected),
+            None,
+            Some(terminal),
+            None,
+            15,
+            &None,
+        );
+    }
+    {
+        let expr = "(lambda (&rest) 1)";
+        let error = error_cont(s, "invalid-form", expr);
         test_aux::<Coproc<Fr>>(s, expr, None, None, Some(error), None, 1, &None);
     }
 }
 
+#[test]
+fn evaluate_apply() {
+    let s = &Store::<Fr>::default();
+    let terminal = s.cont_terminal();
+    {
+        let expr = "(apply (lambda (a b) (+ a b)) '(1 2))";
+        let expected = Ptr::num_u64(3);
+        test_aux::<Coproc<Fr>>(
+            s,
+            expr,
+            Some(expected),
+            None,
+            Some(terminal),
+            None,
+            10,
+            &None,
+        );
+    }
+    {
+        let expr = "(apply (lambda (&rest xs) xs) '(1 2))";
+        let expected = s.read_with_default_state("(1 2)").unwrap();
+        test_aux::<Coproc<Fr>>(
+            s,
+            expr,
+            Some(expected),
+            None,
+            Some(terminal),
+            None,
+            4,
+            &None,
+        );
+    }
+}
+
 #[test]
 fn evaluate_make_tree() {
     {
@@ -1233,7 +1610,7 @@ fn evaluate_make_tree() {
             None,
             Some(terminal),
             None,
-            493,
+            521,
             &None,
         );
     }
@@ -1279,7 +1656,7 @@ fn evaluate_map_tree_bug() {
             None,
             Some(terminal),
             None,
-            170,
+            156,
             &None,
         );
     }
@@ -1298,8 +1675,8 @@ fn evaluate_map_tree_numequal_bug() {
                                                   (map-tree f (cdr tree)))))))
                        (map-tree (lambda (x) (+ 1 x)) '((1 . 2) . (3 . 4))))";
         let expected = s.intern_nil();
-        let error = s.cont_error();
-        test_aux::<Coproc<Fr>>(s, expr, Some(expected), None, Some(error), None, 169, &None);
+        let error = error_cont(s, "type-mismatch", "nil");
+        test_aux::<Coproc<Fr>>(s, expr, Some(expected), None, Some(error), None, 155, &None);
     }
 }
 
@@ -1332,7 +1709,7 @@ fn env_lost_bug() {
             None,
             Some(terminal),
             None,
-            25,
+            24,
             &None,
         );
     }
@@ -1357,7 +1734,7 @@ fn dont_discard_rest_env() {
             None,
             Some(terminal),
             None,
-            22,
+            20,
             &None,
         );
     }
@@ -1374,7 +1751,6 @@ fn test_str_car_cdr_cons() {
     let empty = s.intern_string("");
     let nil = s.intern_nil();
     let terminal = s.cont_terminal();
-    let error = s.cont_error();
 
     test_aux::<Coproc<Fr>>(
         s,
@@ -1441,7 +1817,7 @@ fn test_str_car_cdr_cons() {
         r"(strcons #\a #\b)",
         None,
         None,
-        Some(error),
+        Some(error_cont(s, "type-mismatch", r"#\b")),
         None,
         3,
         &None,
@@ -1451,7 +1827,7 @@ fn test_str_car_cdr_cons() {
         r#"(strcons "a" "b")"#,
         None,
         None,
-        Some(error),
+        Some(error_cont(s, "type-mismatch", r#""b""#)),
         None,
         3,
         &None,
@@ -1461,18 +1837,208 @@ fn test_str_car_cdr_cons() {
         r#"(strcons 1 2)"#,
         None,
         None,
-        Some(error),
+        Some(error_cont(s, "type-mismatch", "2")),
         None,
         3,
         &None,
     );
-    test_aux::<Coproc<Fr>>(s, r#"(strcons)"#, None, None, Some(error), None, 1, &None);
+    let error = error_cont(s, "arity-mismatch", "(strcons)");
+    test_aux::<Coproc<Fr>>(s, "(strcons)", None, None, Some(error), None, 1, &None);
+}
+
+#[test]
+fn evaluate_string_builtins() {
+    let s = &Store::<Fr>::default();
+    let terminal = s.cont_terminal();
+    let t = s.intern_lurk_symbol("t");
+    let nil = s.intern_nil();
+
+    let cases = [
+        (r#"(string-length "abc")"#, Ptr::num_u64(3), 3),
+        (r#"(string-length "")"#, Ptr::num_u64(0), 3),
+        (r#"(string-length "hello")"#, Ptr::num_u64(5), 4),
+        (r#"(string-append "ab" "c")"#, s.intern_string("abc"), 5),
+        (r#"(substring "hello" 1 3)"#, s.intern_string("el"), 8),
+        (r#"(string< "ab" "b")"#, t, 4),
+        (r#"(string< "ab" "ab")"#, nil, 4),
+        (r#"(string< "a" "ab")"#, t, 4),
+        (r#"(string< "abcdef" "abcdeg")"#, t, 5),
+        ("(symbol->string 'foo)", s.intern_string("foo"), 2),
+        (r#"(string->symbol "foo")"#, s.intern_user_symbol("foo"), 2),
+        (r#"(eq 'foo (string->symbol "foo"))"#, t, 5),
+        ("(num->string 123)", s.intern_string("123"), 5),
+        ("(num->string 0)", s.intern_string("0"), 3),
+        ("(num->string -45)", s.intern_string("-45"), 4),
+    ];
+    for (expr, expected, iterations) in cases {
+        test_aux::<Coproc<Fr>>(
+            s,
+            expr,
+            Some(expected),
+            None,
+            Some(terminal),
+            None,
+            iterations,
+            &None,
+        );
+    }
+
+    let errors = [
+        (r#"(string-length 'abc)"#, "type-mismatch", "abc", 2),
+        (r#"(string-append "a" 1)"#, "type-mismatch", "1", 3),
+        (r#"(substring "abc" 2 1)"#, "out-of-range", "1", 6),
+        (r#"(substring "abc" 2 5)"#, "out-of-range", r#""""#, 7),
+        (
+            r#"(substring "abc" 2)"#,
+            "arity-mismatch",
+            r#"(substring "abc" 2)"#,
+            1,
+        ),
+        // the absolute value must fit in 128 bits
+        (
+            "(num->string 340282366920938463463374607431768211456)",
+            "out-of-range",
+            "340282366920938463463374607431768211456",
+            2,
+        ),
+        (
+            "(num->string -340282366920938463463374607431768211456)",
+            "out-of-range",
+            "-340282366920938463463374607431768211456",
+            2,
+        ),
+        ("(num->string 'a)", "type-mismatch", "a", 2),
+    ];
+    for (expr, code, offending, iterations) in errors {
+        let error = error_cont(s, code, offending);
+        test_aux::<Coproc<Fr>>(s, expr, None, None, Some(error), None, iterations, &None);
+    }
+}
+
+#[test]
+fn evaluate_vector_builtins() {
+    let s = &Store::<Fr>::default();
+    let terminal = s.cont_terminal();
+
+    let cases = [
+        ("(vector)", s.intern_vector(vec![]), 1),
+        (
+            "(vector 1 2)",
+            s.intern_vector(vec![Ptr::num_u64(1), Ptr::num_u64(2)]),
+            4,
+        ),
+        (
+            "(vector 1 2 3)",
+            s.intern_vector(vec![Ptr::num_u64(1), Ptr::num_u64(2), Ptr::num_u64(3)]),
+            7,
+        ),
+        (
+            "(vector 1 2 3 4 5)",
+            s.intern_vector((1..=5).map(Ptr::num_u64).collect()),
+            12,
+        ),
+        ("(vector-length (vector 1 2))", Ptr::num_u64(2), 6),
+        ("(vector-ref (vector 1 2 3) 2)", Ptr::num_u64(3), 13),
+        (
+            "(vector-set (vector 1 2) 0 3)",
+            s.intern_vector(vec![Ptr::num_u64(3), Ptr::num_u64(2)]),
+            14,
+        ),
+    ];
+    for (expr, expected, iterations) in cases {
+        test_aux::<Coproc<Fr>>(
+            s,
+            expr,
+            Some(expected),
+            None,
+            Some(terminal),
+            None,
+            iterations,
+            &None,
+        );
+    }
+
+    let errors = [
+        ("(vector-length 1)", "type-mismatch", "1", 2),
+        ("(vector-ref (vector) 0)", "out-of-range", "0", 3),
+        ("(vector-set (vector 1) 1 2)", "out-of-range", "1", 8),
+    ];
+    for (expr, code, offending, iterations) in errors {
+        let error = error_cont(s, code, offending);
+        test_aux::<Coproc<Fr>>(s, expr, None, None, Some(error), None, iterations, &None);
+    }
+}
+
+#[test]
+fn evaluate_maps() {
+    let s = &Store::<Fr>::default();
+    let terminal = s.cont_terminal();
+    let t = s.intern_lurk_symbol("t");
+    let map = |entries: &[(u64, u64)]| {
+        s.intern_map(
+            entries
+                .iter()
+                .map(|(k, v)| (Ptr::num_u64(*k), Ptr::num_u64(*v)))
+                .collect(),
+        )
+    };
+
+    let cases = [
+        ("{}", map(&[]), 1),
+        ("{1 2 3 4}", map(&[(1, 2), (3, 4)]), 1),
+        ("(eq {1 2 3 4} {3 4 1 2})", t, 3),
+    ];
+    for (expr, expected, iterations) in cases {
+        test_aux::<Coproc<Fr>>(
+            s,
+            expr,
+            Some(expected),
+            None,
+            Some(terminal),
+            None,
+            iterations,
+            &None,
+        );
+    }
+}
+
+#[test]
+fn evaluate_type_of() {
+    let s = &Store::<Fr>::default();
+    let terminal = s.cont_terminal();
+
+    let cases = [
+        ("(type-of 1)", "num", 2),
+        ("(type-of 1u64)", "u64", 2),
+        ("(type-of '(1 2))", "cons", 2),
+        ("(type-of nil)", "nil", 2),
+        ("(type-of 'a)", "sym", 2),
+        ("(type-of :a)", "key", 2),
+        ("(type-of \"abc\")", "str", 2),
+        ("(type-of #\\a)", "char", 2),
+        ("(type-of (lambda (x) x))", "fun", 2),
+        ("(type-of (comm 0))", "comm", 4),
+        ("(type-of (vector 1 2))", "vec", 6),
+        ("(type-of {1 2})", "map", 2),
+    ];
+    for (expr, key, iterations) in cases {
+        test_aux::<Coproc<Fr>>(
+            s,
+            expr,
+            Some(s.key(key)),
+            None,
+            Some(terminal),
+            None,
+            iterations,
+            &None,
+        );
+    }
 }
 
 #[test]
 fn test_one_arg_cons_error() {
     let s = &Store::<Fr>::default();
-    let error = s.cont_error();
+    let error = error_cont(s, "arity-mismatch", r#"(cons "")"#);
     test_aux::<Coproc<Fr>>(s, r#"(cons "")"#, None, None, Some(error), None, 1, &None);
 }
 
@@ -1513,7 +2079,7 @@ fn test_cdr_nil() {
 #[test]
 fn test_car_cdr_invalid_tag_error_sym() {
     let s = &Store::<Fr>::default();
-    let error = s.cont_error();
+    let error = error_cont(s, "type-mismatch", "car");
     test_aux::<Coproc<Fr>>(s, r#"(car 'car)"#, None, None, Some(error), None, 2, &None);
     test_aux::<Coproc<Fr>>(s, r#"(cdr 'car)"#, None, None, Some(error), None, 2, &None);
 }
@@ -1521,7 +2087,7 @@ fn test_car_cdr_invalid_tag_error_sym() {
 #[test]
 fn test_car_cdr_invalid_tag_error_char() {
     let s = &Store::<Fr>::default();
-    let error = s.cont_error();
+    let error = error_cont(s, "type-mismatch", r"#\a");
     test_aux::<Coproc<Fr>>(s, r"(car #\a)", None, None, Some(error), None, 2, &None);
     test_aux::<Coproc<Fr>>(s, r"(cdr #\a)", None, None, Some(error), None, 2, &None);
 }
@@ -1529,7 +2095,7 @@ fn test_car_cdr_invalid_tag_error_char() {
 #[test]
 fn test_car_cdr_invalid_tag_error_num() {
     let s = &Store::<Fr>::default();
-    let error = s.cont_error();
+    let error = error_cont(s, "type-mismatch", "42");
     test_aux::<Coproc<Fr>>(s, r#"(car 42)"#, None, None, Some(error), None, 2, &None);
     test_aux::<Coproc<Fr>>(s, r#"(cdr 42)"#, None, None, Some(error), None, 2, &None);
 }
@@ -1537,7 +2103,10 @@ fn test_car_cdr_invalid_tag_error_num() {
 #[test]
 fn test_car_cdr_invalid_tag_error_lambda() {
     let s = &Store::<Fr>::default();
-    let error = s.cont_error();
+    let x = s.intern_user_symbol("x");
+    let body = s.read_with_default_state("(x)").unwrap();
+    let closure = s.intern_fun(x, body, s.intern_nil());
+    let error = s.intern_cont_error("type-mismatch", closure);
     test_aux::<Coproc<Fr>>(
         s,
         r#"(car (lambda (x) x))"#,
@@ -1591,7 +2160,7 @@ fn go_translate() {
         None,
         None,
         None,
-        1114,
+        1044,
         &None,
     );
 }
@@ -1731,7 +2300,7 @@ fn commit_nil() {
 fn commit_error() {
     let s = &Store::<Fr>::default();
     let expr = "(commit 123 456)";
-    let error = s.cont_error();
+    let error = error_cont(s, "arity-mismatch", expr);
     test_aux::<Coproc<Fr>>(s, expr, None, None, Some(error), None, 1, &None);
 }
 
@@ -1739,7 +2308,7 @@ fn commit_error() {
 fn open_error() {
     let s = &Store::<Fr>::default();
     let expr = "(open 123 456)";
-    let error = s.cont_error();
+    let error = error_cont(s, "arity-mismatch", expr);
     test_aux::<Coproc<Fr>>(s, expr, None, None, Some(error), None, 1, &None);
 }
 
@@ -1747,7 +2316,7 @@ fn open_error() {
 fn secret_error() {
     let s = &Store::<Fr>::default();
     let expr = "(secret 123 456)";
-    let error = s.cont_error();
+    let error = error_cont(s, "arity-mismatch", expr);
     test_aux::<Coproc<Fr>>(s, expr, None, None, Some(error), None, 1, &None);
 }
 
@@ -1755,7 +2324,7 @@ fn secret_error() {
 fn num_error() {
     let s = &Store::<Fr>::default();
     let expr = "(num 123 456)";
-    let error = s.cont_error();
+    let error = error_cont(s, "arity-mismatch", expr);
     test_aux::<Coproc<Fr>>(s, expr, None, None, Some(error), None, 1, &None);
 }
 
@@ -1763,7 +2332,7 @@ fn num_error() {
 fn comm_error() {
     let s = &Store::<Fr>::default();
     let expr = "(comm 123 456)";
-    let error = s.cont_error();
+    let error = error_cont(s, "arity-mismatch", expr);
     test_aux::<Coproc<Fr>>(s, expr, None, None, Some(error), None, 1, &None);
 }
 
@@ -1771,7 +2340,7 @@ fn comm_error() {
 fn char_error() {
     let s = &Store::<Fr>::default();
     let expr = "(char 123 456)";
-    let error = s.cont_error();
+    let error = error_cont(s, "arity-mismatch", expr);
     test_aux::<Coproc<Fr>>(s, expr, None, None, Some(error), None, 1, &None);
 }
 
@@ -1969,9 +2538,11 @@ fn num_invalid_tag() {
     let expr = "(num (quote x))";
     let expr1 = "(num \"asdf\")";
     let expr2 = "(num '(1))";
-    let error = s.cont_error();
+    let error = error_cont(s, "type-mismatch", "x");
     test_aux::<Coproc<Fr>>(s, expr, None, None, Some(error), None, 2, &None);
+    let error = error_cont(s, "type-mismatch", "\"asdf\"");
     test_aux::<Coproc<Fr>>(s, expr1, None, None, Some(error), None, 2, &None);
+    let error = error_cont(s, "type-mismatch", "(1)");
     test_aux::<Coproc<Fr>>(s, expr2, None, None, Some(error), None, 2, &None);
 }
 
@@ -1981,9 +2552,11 @@ fn comm_invalid_tag() {
     let expr = "(comm (quote x))";
     let expr1 = "(comm \"asdf\")";
     let expr2 = "(comm '(1))";
-    let error = s.cont_error();
+    let error = error_cont(s, "type-mismatch", "x");
     test_aux::<Coproc<Fr>>(s, expr, None, None, Some(error), None, 2, &None);
+    let error = error_cont(s, "type-mismatch", "\"asdf\"");
     test_aux::<Coproc<Fr>>(s, expr1, None, None, Some(error), None, 2, &None);
+    let error = error_cont(s, "type-mismatch", "(1)");
     test_aux::<Coproc<Fr>>(s, expr2, None, None, Some(error), None, 2, &None);
 }
 
@@ -1993,9 +2566,11 @@ fn char_invalid_tag() {
     let expr = "(char (quote x))";
     let expr1 = "(char \"asdf\")";
     let expr2 = "(char '(1))";
-    let error = s.cont_error();
+    let error = error_cont(s, "type-mismatch", "x");
     test_aux::<Coproc<Fr>>(s, expr, None, None, Some(error), None, 2, &None);
+    let error = error_cont(s, "type-mismatch", "\"asdf\"");
     test_aux::<Coproc<Fr>>(s, expr1, None, None, Some(error), None, 2, &None);
+    let error = error_cont(s, "type-mismatch", "(1)");
     test_aux::<Coproc<Fr>>(s, expr2, None, None, Some(error), None, 2, &None);
 }
 
@@ -2020,7 +2595,7 @@ fn open_opaque_commit() {
 fn open_wrong_type() {
     let s = &Store::<Fr>::default();
     let expr = "(open 'asdf)";
-    let error = s.cont_error();
+    let error = error_cont(s, "type-mismatch", "asdf");
     test_aux::<Coproc<Fr>>(s, expr, None, None, Some(error), None, 2, &None);
 }
 
@@ -2028,7 +2603,7 @@ fn open_wrong_type() {
 fn secret_wrong_type() {
     let s = &Store::<Fr>::default();
     let expr = "(secret 'asdf)";
-    let error = s.cont_error();
+    let error = error_cont(s, "type-mismatch", "asdf");
     test_aux::<Coproc<Fr>>(s, expr, None, None, Some(error), None, 2, &None);
 }
 
@@ -2307,7 +2882,7 @@ fn test_eval_env_regression() {
     let expr = "(let ((a 1)) (eval 'a))";
     let expr2 = "(let ((a 1)) (eval 'a (current-env)))";
     let res = Ptr::num_u64(1);
-    let error = s.cont_error();
+    let error = error_cont(s, "unbound-var", "a");
     let terminal = s.cont_terminal();
 
     test_aux::<Coproc<Fr>>(s, expr, None, None, Some(error), None, 5, &None);
@@ -2372,7 +2947,7 @@ fn test_u64_div() {
     let expr3 = "(/ 100u64 0u64)";
 
     let terminal = s.cont_terminal();
-    let error = s.cont_error();
+    let error = error_cont(s, "div-by-zero", "0u64");
 
     test_aux::<Coproc<Fr>>(s, expr, Some(res), None, Some(terminal), None, 3, &None);
     test_aux::<Coproc<Fr>>(s, expr2, Some(res2), None, Some(terminal), None, 3, &None);
@@ -2392,7 +2967,7 @@ fn test_u64_mod() {
     let expr3 = "(% 100u64 0u64)";
 
     let terminal = s.cont_terminal();
-    let error = s.cont_error();
+    let error = error_cont(s, "div-by-zero", "0u64");
 
     test_aux::<Coproc<Fr>>(s, expr, Some(res), None, Some(terminal), None, 3, &None);
     test_aux::<Coproc<Fr>>(s, expr2, Some(res2), None, Some(terminal), None, 3, &None);
@@ -2407,10 +2982,11 @@ fn test_num_mod() {
     let expr2 = "(% 100 3u64)";
     let expr3 = "(% 100u64 3)";
 
-    let error = s.cont_error();
+    let error = error_cont(s, "type-mismatch", "3");
+    let error2 = error_cont(s, "type-mismatch", "3u64");
 
     test_aux::<Coproc<Fr>>(s, expr, None, None, Some(error), None, 3, &None);
-    test_aux::<Coproc<Fr>>(s, expr2, None, None, Some(error), None, 3, &None);
+    test_aux::<Coproc<Fr>>(s, expr2, None, None, Some(error2), None, 3, &None);
     test_aux::<Coproc<Fr>>(s, expr3, None, None, Some(error), None, 3, &None);
 }
 
@@ -2472,7 +3048,6 @@ fn test_u64_conversion() {
     let res3 = Ptr::u64(2);
     let res5 = Ptr::u64(123);
     let terminal = s.cont_terminal();
-    let error = s.cont_error();
 
     test_aux::<Coproc<Fr>>(s, expr, Some(res), None, Some(terminal), None, 3, &None);
     test_aux::<Coproc<Fr>>(s, expr2, Some(res), None, Some(terminal), None, 2, &None);
@@ -2480,14 +3055,16 @@ fn test_u64_conversion() {
 
     test_aux::<Coproc<Fr>>(s, expr4, Some(res3), None, Some(terminal), None, 5, &None);
     test_aux::<Coproc<Fr>>(s, expr5, Some(res5), None, Some(terminal), None, 2, &None);
-    test_aux::<Coproc<Fr>>(s, expr6, None, None, Some(error), None, 1, &None);
-    test_aux::<Coproc<Fr>>(s, expr7, None, None, Some(error), None, 1, &None);
+    let error6 = error_cont(s, "arity-mismatch", expr6);
+    let error7 = error_cont(s, "arity-mismatch", expr7);
+    test_aux::<Coproc<Fr>>(s, expr6, None, None, Some(error6), None, 1, &None);
+    test_aux::<Coproc<Fr>>(s, expr7, None, None, Some(error7), None, 1, &None);
 }
 
 #[test]
 fn test_numeric_type_error() {
     let s = &Store::<Fr>::default();
-    let error = s.cont_error();
+    let error = error_cont(s, "type-mismatch", "a");
 
     let test = |op| {
         let expr = &format!("({op} 0 'a)");
@@ -2542,7 +3119,7 @@ fn test_hide_u64_secret() {
     let s = &Store::<Fr>::default();
 
     let expr = "(hide 0u64 123)";
-    let error = s.cont_error();
+    let error = error_cont(s, "type-mismatch", "123");
 
     test_aux::<Coproc<Fr>>(s, expr, None, None, Some(error), None, 3, &None);
 }
@@ -2635,7 +3212,7 @@ fn test_fold_cons_regression() {
     let res = Ptr::num_u64(6);
     let terminal = s.cont_terminal();
 
-    test_aux::<Coproc<Fr>>(s, expr, Some(res), None, Some(terminal), None, 152, &None);
+    test_aux::<Coproc<Fr>>(s, expr, Some(res), None, Some(terminal), None, 130, &None);
 }
 
 #[test]
@@ -2652,7 +3229,7 @@ fn test_lambda_args_regression() {
 fn test_eval_bad_form() {
     let s = &Store::<Fr>::default();
     let expr = "(* 5 (eval '(+ 1 a) '((0 . 3))))"; // two-arg eval, optional second arg is env.
-    let error = s.cont_error();
+    let error = error_cont(s, "invalid-form", "a");
 
     test_aux::<Coproc<Fr>>(s, expr, None, None, Some(error), None, 8, &None);
 }
@@ -2660,11 +3237,13 @@ fn test_eval_bad_form() {
 #[test]
 fn test_eval_quote_error() {
     let s = &Store::<Fr>::default();
-    let error = s.cont_error();
-
+    let error = error_cont(s, "not-a-function", "(1)");
     test_aux::<Coproc<Fr>>(s, "(1)", None, None, Some(error), None, 1, &None);
+    let error = error_cont(s, "invalid-form", "(quote . 1)");
     test_aux::<Coproc<Fr>>(s, "(quote . 1)", None, None, Some(error), None, 1, &None);
+    let error = error_cont(s, "invalid-form", "(quote 1 . 1)");
     test_aux::<Coproc<Fr>>(s, "(quote 1 . 1)", None, None, Some(error), None, 1, &None);
+    let error = error_cont(s, "invalid-form", "(quote 1 1)");
     test_aux::<Coproc<Fr>>(s, "(quote 1 1)", None, None, Some(error), None, 1, &None);
 }
 
@@ -2672,7 +3251,7 @@ fn test_eval_quote_error() {
 fn test_eval_dotted_syntax_error() {
     let s = &Store::<Fr>::default();
     let expr = "(let ((a (lambda (x) (+ x 1)))) (a . 1))";
-    let error = s.cont_error();
+    let error = error_cont(s, "invalid-form", "(a . 1)");
 
     test_aux::<Coproc<Fr>>(s, expr, None, None, Some(error), None, 3, &None);
 }
@@ -2680,36 +3259,52 @@ fn test_eval_dotted_syntax_error() {
 #[allow(dead_code)]
 fn op_syntax_error<T: Op + Copy>() {
     let s = &Store::<Fr>::default();
-    let error = s.cont_error();
     let test = |op: T| {
         let name = op.symbol_name();
 
         {
             let expr = format!("({name} . 1)");
             tracing::debug!("{}", &expr);
+            let error = error_cont(s, "invalid-form", &expr);
             test_aux::<Coproc<Fr>>(s, &expr, None, None, Some(error), None, 1, &None);
         }
 
         if !op.supports_arity(0) {
             let expr = format!("({name})");
             tracing::debug!("{}", &expr);
+            // `eval` is a special form
+            let code = if name == "eval" {
+                "invalid-form"
+            } else {
+                "arity-mismatch"
+            };
+            let error = error_cont(s, code, &expr);
             test_aux::<Coproc<Fr>>(s, &expr, None, None, Some(error), None, 1, &None);
         }
         if !op.supports_arity(1) {
             let expr = format!("({name} 123)");
             tracing::debug!("{}", &expr);
+            let error = error_cont(s, "arity-mismatch", &expr);
             test_aux::<Coproc<Fr>>(s, &expr, None, None, Some(error), None, 1, &None);
         }
         if !op.supports_arity(2) {
             let expr = format!("({name} 123 456)");
             tracing::debug!("{}", &expr);
+            let error = error_cont(s, "arity-mismatch", &expr);
             test_aux::<Coproc<Fr>>(s, &expr, None, None, Some(error), None, 1, &None);
         }
 
         if !op.supports_arity(3) {
             let expr = format!("({name} 123 456 789)");
             tracing::debug!("{}", &expr);
-            let iterations = if op.supports_arity(2) { 2 } else { 1 };
+            // binary operators only find out about the third argument after
+            // evaluating the first one
+            let (offending, iterations) = if op.supports_arity(2) {
+                ("123", 2)
+            } else {
+                (expr.as_str(), 1)
+            };
+            let error = error_cont(s, "arity-mismatch", offending);
             test_aux::<Coproc<Fr>>(s, &expr, None, None, Some(error), None, iterations, &None);
         }
     };
@@ -2732,7 +3327,14 @@ fn test_eval_binop_syntax_error() {
 #[test]
 fn test_eval_lambda_body_syntax() {
     let s = &Store::<Fr>::default();
-    let error = s.cont_error();
+    let dummy = s.intern_lurk_symbol("dummy");
+    let closure = |body| {
+        let body = s.read_with_default_state(body).unwrap();
+        s.intern_fun(dummy, body, s.intern_nil())
+    };
+    let error = s.intern_cont_error("invalid-form", closure("nil"));
+    let error2 = s.intern_cont_error("invalid-form", closure("(1 2)"));
+    let error3 = error_cont(s, "invalid-form", "1");
 
     test_aux::<Coproc<Fr>>(s, "((lambda ()))", None, None, Some(error), None, 2, &None);
     test_aux::<Coproc<Fr>>(
@@ -2740,7 +3342,7 @@ fn test_eval_lambda_body_syntax() {
         "((lambda () 1 2))",
         None,
         None,
-        Some(error),
+        Some(error2),
         None,
         2,
         &None,
@@ -2750,7 +3352,7 @@ fn test_eval_lambda_body_syntax() {
         "((lambda (x)) 1)",
         None,
         None,
-        Some(error),
+        Some(error3),
         None,
         3,
         &None,
@@ -2760,7 +3362,7 @@ fn test_eval_lambda_body_syntax() {
         "((lambda (x) 1 2) 1)",
         None,
         None,
-        Some(error),
+        Some(error3),
         None,
         3,
         &None,
@@ -2770,16 +3372,17 @@ fn test_eval_lambda_body_syntax() {
 #[test]
 fn test_eval_non_symbol_binding_error() {
     let s = &Store::<Fr>::default();
-    let error = s.cont_error();
-
     let test = |x| {
         let expr = format!("(let (({x} 123)) {x})");
         let expr2 = format!("(letrec (({x} 123)) {x})");
         let expr3 = format!("(lambda ({x}) {x})");
+        let error = error_cont(s, "invalid-form", &expr);
+        let error2 = error_cont(s, "invalid-form", &expr2);
+        let error3 = error_cont(s, "invalid-form", &expr3);
 
         test_aux::<Coproc<Fr>>(s, &expr, None, None, Some(error), None, 1, &None);
-        test_aux::<Coproc<Fr>>(s, &expr2, None, None, Some(error), None, 1, &None);
-        test_aux::<Coproc<Fr>>(s, &expr3, None, None, Some(error), None, 1, &None);
+        test_aux::<Coproc<Fr>>(s, &expr2, None, None, Some(error2), None, 1, &None);
+        test_aux::<Coproc<Fr>>(s, &expr3, None, None, Some(error3), None, 1, &None);
     };
 
     test(":a");
@@ -2825,7 +3428,12 @@ fn test_dumb_lang() {
     let error6 = Ptr::char('x');
     let error7 = Ptr::char('y');
 
-    let error = s.cont_error();
+    // arity errors are caught by the step function, but type errors are up to
+    // the coprocessor
+    let arity_error4 = s.intern_cont_error("arity-mismatch", error4);
+    let arity_error5 = s.intern_cont_error("arity-mismatch", error5);
+    let type_error6 = s.intern_cont_error("type-mismatch", error6);
+    let type_error7 = s.intern_cont_error("type-mismatch", error7);
     let terminal = s.cont_terminal();
 
     test_aux(
@@ -2863,7 +3471,7 @@ fn test_dumb_lang() {
         expr4,
         Some(error4),
         None,
-        Some(error),
+        Some(arity_error4),
         None,
         4,
         &Some(&lang),
@@ -2873,7 +3481,7 @@ fn test_dumb_lang() {
         expr5,
         Some(error5),
         None,
-        Some(error),
+        Some(arity_error5),
         None,
         2,
         &Some(&lang),
@@ -2883,7 +3491,7 @@ fn test_dumb_lang() {
         expr6,
         Some(error6),
         None,
-        Some(error),
+        Some(type_error6),
         None,
         3,
         &Some(&lang),
@@ -2893,7 +3501,7 @@ fn test_dumb_lang() {
         expr6_,
         Some(error6),
         None,
-        Some(error),
+        Some(type_error6),
         None,
         3,
         &Some(&lang),
@@ -2903,7 +3511,7 @@ fn test_dumb_lang() {
         expr7,
         Some(error7),
         None,
-        Some(error),
+        Some(type_error7),
         None,
         3,
         &Some(&lang),
@@ -3026,6 +3634,153 @@ fn test_trie_lang() {
         13,
         &Some(&lang),
     );
+
+    let expr7 = "(.lurk.trie.remove 0x1b22dc5a394231c34e4529af674dc56a736fbd07508acfd1d12c0e67c8b4de27 123)";
+    let res7 = s
+        .read_with_default_state(
+            "0x1cc5b90039db85fd519af975afa1de9d2b92960a585a546637b653b115bc3b53",
+        )
+        .unwrap();
+
+    test_aux_with_state(
+        s,
+        state.clone(),
+        expr7,
+        Some(res7),
+        None,
+        None,
+        None,
+        3,
+        &Some(&lang),
+    );
+
+    let expr8 = "(.lurk.trie.lookup (.lurk.trie.update 0x1b22dc5a394231c34e4529af674dc56a736fbd07508acfd1d12c0e67c8b4de27 123 789) 123)";
+    let res8 = Ptr::comm(Fr::from(789));
+
+    test_aux_with_state(
+        s,
+        state.clone(),
+        expr8,
+        Some(res8),
+        None,
+        None,
+        None,
+        6,
+        &Some(&lang),
+    );
+
+    let expr9 = "(.lurk.trie.update 0x1cc5b90039db85fd519af975afa1de9d2b92960a585a546637b653b115bc3b53 123 789)";
+    let error = error_cont(s, "missing-key", "123");
+
+    test_aux_with_state(
+        s,
+        state.clone(),
+        expr9,
+        None,
+        None,
+        Some(error),
+        None,
+        4,
+        &Some(&lang),
+    );
+}
+
+#[test]
+fn test_map_lang() {
+    use crate::coprocessor::trie::{install, TrieCoproc};
+
+    let s = &Store::<Fr>::default();
+    let state = State::init_lurk_state().rccell();
+    let mut lang = Lang::<Fr, TrieCoproc<Fr>>::new();
+
+    install(&state, &mut lang);
+
+    let terminal = s.cont_terminal();
+    let nil = s.intern_nil();
+    let map = |entries: &[(u64, u64)]| {
+        s.intern_map(
+            entries
+                .iter()
+                .map(|(k, v)| (Ptr::num_u64(*k), Ptr::num_u64(*v)))
+                .collect(),
+        )
+    };
+
+    let cases = [
+        ("(.lurk.map.get {1 2} 1)", Ptr::num_u64(2), 3),
+        ("(.lurk.map.get {} 1)", nil, 3),
+        ("(.lurk.map.assoc {} 1 2)", map(&[(1, 2)]), 4),
+        ("(.lurk.map.assoc {1 2} 1 3)", map(&[(1, 3)]), 4),
+        (
+            "(.lurk.map.get (.lurk.map.assoc {1 2} 3 4) 3)",
+            Ptr::num_u64(4),
+            6,
+        ),
+    ];
+    for (expr, expected, iterations) in cases {
+        test_aux_with_state(
+            s,
+            state.clone(),
+            expr,
+            Some(expected),
+            None,
+            Some(terminal),
+            None,
+            iterations,
+            &Some(&lang),
+        );
+    }
+
+    // unquotes inside quasiquoted maps are evaluated
+    let func = make_eval_step_from_config(&EvalConfig::new_ivc(&lang));
+    let expr = s
+        .read(state.clone(), "`{1 ,(+ 1 1) ,(car '(3)) 4}")
+        .unwrap();
+    let output = evaluate_simple(Some((&func, &lang)), expr, s, 1000)
+        .unwrap()
+        .0;
+    assert_eq!(output[2], terminal);
+    assert_eq!(output[0], map(&[(1, 2), (3, 4)]));
+
+    let errors = [
+        ("(.lurk.map.get 1 2)", "type-mismatch", "1", 3),
+        ("(.lurk.map.assoc 1 2 3)", "type-mismatch", "1", 4),
+    ];
+    for (expr, code, offending, iterations) in errors {
+        let error = error_cont(s, code, offending);
+        test_aux_with_state(
+            s,
+            state.clone(),
+            expr,
+            None,
+            None,
+            Some(error),
+            None,
+            iterations,
+            &Some(&lang),
+        );
+    }
+
+    // the store doesn't have the preimages of this map's trie
+    let unknown = Ptr::Atom(Tag::Expr(ExprTag::Map), Fr::from(1u64));
+    let one = Ptr::num_u64(1);
+    let error = s.intern_cont_error("missing-preimage", unknown);
+    for (name, mut args, iterations) in [
+        (".lurk.map.get", vec![unknown, one], 3),
+        (".lurk.map.assoc", vec![unknown, one, one], 4),
+    ] {
+        args.insert(0, s.read(state.clone(), name).unwrap());
+        do_test_aux(
+            s,
+            &s.list(args),
+            None,
+            None,
+            Some(error),
+            None,
+            iterations,
+            &Some(&lang),
+        );
+    }
 }
 
 #[test]
@@ -6,7 +6,13 @@ use crate::{
     eval::lang::{DummyCoprocessor, Lang},
     field::LurkField,
     func,
-    lem::{pointers::Ptr, slot::SlotsCounter, store::Store, Func},
+    lem::{
+        interpreter::Frame,
+        pointers::Ptr,
+        slot::{SlotData, SlotsCounter},
+        store::Store,
+        Func,
+    },
 };
 
 /// Helper function for testing circuit synthesis.
@@ -266,3 +272,103 @@ fn test_unhash_nested_slots() {
     let inputs = vec![Ptr::num(Fr::from_u64(42)), Ptr::char('c')];
     synthesize_test_helper(&lem, inputs, SlotsCounter::new((4, 4, 4, 0, 0)));
 }
+
+/// Synthesizes `func` on the frame computed from `inputs`, after `tamper`
+/// changes its witness, and returns the frame's first output and whether the
+/// constraints are satisfied.
+fn synthesize_tampered(
+    func: &Func,
+    inputs: &[Ptr<Fr>],
+    tamper: impl Fn(&mut Frame<Fr>),
+) -> (Ptr<Fr>, bool) {
+    let store = &Store::default();
+    let lang: Lang<Fr, DummyCoprocessor<Fr>> = Lang::new();
+    let (mut frame, _) = func
+        .call(inputs, store, Default::default(), &mut vec![], &lang, 0)
+        .unwrap();
+    tamper(&mut frame);
+
+    let mut cs = TestConstraintSystem::<Fr>::new();
+    func.synthesize_frame_aux(&mut cs, store, &frame, &lang)
+        .unwrap();
+    assert_eq!(func.num_constraints(store), cs.num_constraints());
+    (frame.output[0], cs.is_satisfied())
+}
+
+/// Checks that `func`, which computes `expected(a, b)`, is satisfied by honest
+/// witnesses only: a wrong output or a wrong bit decomposition of an operand
+/// must not satisfy the constraints.
+fn test_binary_gadget(func: &Func, expected: impl Fn(u64, u64) -> u64) {
+    for (a, b) in [(0, 0), (202, 166), (255, 1), (0x1ff, 0x100)] {
+        let inputs = [Ptr::num_u64(a), Ptr::num_u64(b)];
+        let c = expected(a, b);
+        assert_eq!(
+            synthesize_tampered(func, &inputs, |_| ()),
+            (Ptr::num_u64(c), true)
+        );
+
+        let (_, satisfied) = synthesize_tampered(func, &inputs, |frame| {
+            frame.output[0] = Ptr::num_u64(c ^ 1);
+        });
+        assert!(!satisfied);
+
+        let (_, satisfied) = synthesize_tampered(func, &inputs, |frame| {
+            frame.hints.bit_decomp[1] = Some(SlotData::F(Fr::from_u64(b ^ 1)));
+        });
+        assert!(!satisfied);
+    }
+}
+
+#[test]
+fn test_bit_and_gadget() {
+    let lem = func!(foo(a, b): 1 => {
+        let c = bit_and(a, b, 8);
+        return (c);
+    });
+    assert_eq!(lem.slots_count, SlotsCounter::new((0, 0, 0, 0, 2)));
+    test_binary_gadget(&lem, |a, b| a & b & 0xff);
+}
+
+#[test]
+fn test_bit_or_gadget() {
+    let lem = func!(foo(a, b): 1 => {
+        let c = bit_or(a, b, 8);
+        return (c);
+    });
+    assert_eq!(lem.slots_count, SlotsCounter::new((0, 0, 0, 0, 2)));
+    test_binary_gadget(&lem, |a, b| (a | b) & 0xff);
+}
+
+#[test]
+fn test_bit_xor_gadget() {
+    let lem = func!(foo(a, b): 1 => {
+        let c = bit_xor(a, b, 8);
+        return (c);
+    });
+    assert_eq!(lem.slots_count, SlotsCounter::new((0, 0, 0, 0, 2)));
+    test_binary_gadget(&lem, |a, b| (a ^ b) & 0xff);
+}
+
+#[test]
+fn test_pow2_gadget() {
+    let lem = func!(foo(a): 1 => {
+        let c = pow2(a, 7);
+        return (c);
+    });
+    assert_eq!(lem.slots_count, SlotsCounter::new((0, 0, 0, 0, 1)));
+    for k in [0, 1, 5, 64, 127, 128 + 3] {
+        let inputs = [Ptr::num_u64(k)];
+        let expected = Ptr::num(Fr::from_u128(1 << (k % 128)));
+        assert_eq!(synthesize_tampered(&lem, &inputs, |_| ()), (expected, true));
+
+        let (_, satisfied) = synthesize_tampered(&lem, &inputs, |frame| {
+            frame.output[0] = Ptr::num(Fr::from_u128(1 << ((k + 1) % 128)));
+        });
+        assert!(!satisfied);
+
+        let (_, satisfied) = synthesize_tampered(&lem, &inputs, |frame| {
+            frame.hints.bit_decomp[0] = Some(SlotData::F(Fr::from_u64(k ^ 1)));
+        });
+        assert!(!satisfied);
+    }
+}
@@ -0,0 +1,1338 @@
+//! ## Textual LEM
+//!
+//! A standalone text syntax for LEM, mirroring the `func!` macro. A `.lem`
+//! source is a sequence of function definitions, the last of which is the
+//! function being defined. Earlier definitions are helpers that can be called
+//! by the ones that follow them:
+//!
+//! ```text
+//! fn car_cdr(xs): 2 {
+//!     let nil = Symbol("nil");
+//!     let nil = cast(nil, Expr::Nil);
+//!     match xs.tag {
+//!         Expr::Cons => {
+//!             let (car, cdr) = decons2(xs);
+//!             return (car, cdr)
+//!         }
+//!     };
+//!     return (nil, nil)
+//! }
+//!
+//! fn cadr(xs): 1 {
+//!     let (_car, cdr) = car_cdr(xs);
+//!     let (cadr, _cddr) = car_cdr(cdr);
+//!     return (cadr)
+//! }
+//! ```
+//!
+//! Besides the operations accepted by `func!`, the text syntax has
+//! `let x = copy(y);` for `Op::Copy` and `let (ys) = cproc <sym>(xs);` for
+//! `Op::Cproc`. Symbols are written as `"name"` for a symbol in the `lurk`
+//! package, `["a", "b"]` for an absolute path and prefixed by `:` for keywords.
+//! Line comments start with `//`.
+//!
+//! The pretty-printer is the `Display` implementation of `Func`. It erases the
+//! suffixes added by `Func::deconflict`, so parsing what it prints reproduces
+//! the original `Func`. The only exception is `Op::Unit`, whose callback can't
+//! be expressed as text and is read back as a no-op. Optimized `Func`s may
+//! refer to variables that were shadowed in the source, so they should be
+//! printed before `Func::optimized` runs on them.
+
+use anyhow::{anyhow, bail, Context, Result};
+use indexmap::IndexMap;
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::{self, Write},
+    str::FromStr,
+};
+
+use crate::{
+    parser::string::escape_string,
+    state::lurk_sym,
+    symbol::Symbol,
+    tag::{ContTag, ExprTag, Op1, Op2},
+};
+
+use super::{Block, Ctrl, Func, Lit, Op, Tag, Var};
+
+/// Names that can't be used for user-defined functions
+const BUILTINS: &[&str] = &[
+    "Num", "String", "Symbol", "Keyword", "cast", "copy", "eq_tag", "eq_val", "not", "and", "or",
+    "add", "sub", "mul", "div", "lt", "truncate", "div_rem", "bit_and", "bit_or", "bit_xor",
+    "pow2", "emit", "cons2", "cons3", "cons4", "decons2", "decons3", "decons4", "hide", "open",
+    "cproc", "unit",
+];
+
+/// Parses a `.lem` source into the `Func` defined last in it
+pub fn parse_func(src: &str) -> Result<Func> {
+    Parser::new(src)?.parse_file()
+}
+
+impl FromStr for Func {
+    type Err = anyhow::Error;
+
+    fn from_str(src: &str) -> Result<Self> {
+        parse_func(src)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Tok {
+    Ident(String),
+    Int(u128),
+    Str(String),
+    Punct(&'static str),
+    Eof,
+}
+
+impl fmt::Display for Tok {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Tok::Ident(x) => write!(f, "`{x}`"),
+            Tok::Int(n) => write!(f, "`{n}`"),
+            Tok::Str(s) => write!(f, "\"{}\"", escape_string(s)),
+            Tok::Punct(p) => write!(f, "`{p}`"),
+            Tok::Eof => write!(f, "end of input"),
+        }
+    }
+}
+
+/// Punctuation, longest first so that `::` and `=>` win over `:` and `=`
+const PUNCTS: [&str; 15] = [
+    "::", "=>", "(", ")", "{", "}", "[", "]", ",", ";", ":", ".", "|", "!", "=",
+];
+
+struct Lexer<'a> {
+    src: &'a str,
+    pos: usize,
+    line: usize,
+    col: usize,
+}
+
+impl Lexer<'_> {
+    fn peek_char(&self) -> Option<char> {
+        self.src[self.pos..].chars().next()
+    }
+
+    fn next_char(&mut self) -> Option<char> {
+        let c = self.peek_char()?;
+        self.pos += c.len_utf8();
+        if c == '\n' {
+            self.line += 1;
+            self.col = 1;
+        } else {
+            self.col += 1;
+        }
+        Some(c)
+    }
+
+    fn skip_trivia(&mut self) {
+        loop {
+            match self.peek_char() {
+                Some(c) if c.is_whitespace() => {
+                    self.next_char();
+                }
+                Some('/') if self.src[self.pos..].starts_with("//") => {
+                    while !matches!(self.next_char(), Some('\n') | None) {}
+                }
+                _ => return,
+            }
+        }
+    }
+
+    fn string(&mut self, line: usize, col: usize) -> Result<String> {
+        let mut s = String::new();
+        loop {
+            match self.next_char() {
+                None => bail!("{line}:{col}: unterminated string"),
+                Some('"') => return Ok(s),
+                Some('\\') => match self.next_char() {
+                    Some('\\') => s.push('\\'),
+                    Some('"') => s.push('"'),
+                    Some('n') => s.push('\n'),
+                    Some('r') => s.push('\r'),
+                    Some('t') => s.push('\t'),
+                    Some('u') if self.next_char() == Some('{') => {
+                        let mut hex = String::new();
+                        loop {
+                            match self.next_char() {
+                                Some('}') => break,
+                                Some(c) if c.is_ascii_hexdigit() => hex.push(c),
+                                _ => bail!("{line}:{col}: invalid unicode escape"),
+                            }
+                        }
+                        let c = u32::from_str_radix(&hex, 16)
+                            .ok()
+                            .and_then(char::from_u32)
+                            .ok_or_else(|| anyhow!("{line}:{col}: invalid unicode escape"))?;
+                        s.push(c)
+                    }
+                    _ => bail!("{line}:{col}: invalid escape in string"),
+                },
+                Some(c) => s.push(c),
+            }
+        }
+    }
+
+    fn tokenize(mut self) -> Result<Vec<(Tok, usize, usize)>> {
+        let mut toks = vec![];
+        loop {
+            self.skip_trivia();
+            let (line, col) = (self.line, self.col);
+            let Some(c) = self.peek_char() else {
+                toks.push((Tok::Eof, line, col));
+                return Ok(toks);
+            };
+            let tok = if c.is_ascii_alphabetic() || c == '_' {
+                let mut x = String::new();
+                while let Some(c) = self.peek_char() {
+                    if !(c.is_ascii_alphanumeric() || c == '_') {
+                        break;
+                    }
+                    x.push(c);
+                    self.next_char();
+                }
+                Tok::Ident(x)
+            } else if c.is_ascii_digit() {
+                let mut n = String::new();
+                while let Some(c) = self.peek_char() {
+                    if !c.is_ascii_digit() {
+                        break;
+                    }
+                    n.push(c);
+                    self.next_char();
+                }
+                let n = n
+                    .parse()
+                    .map_err(|_| anyhow!("{line}:{col}: number `{n}` is too large"))?;
+                Tok::Int(n)
+            } else if c == '"' {
+                self.next_char();
+                Tok::Str(self.string(line, col)?)
+            } else if let Some(p) = PUNCTS
+                .iter()
+                .find(|p| self.src[self.pos..].starts_with(**p))
+            {
+                for _ in 0..p.len() {
+                    self.next_char();
+                }
+                Tok::Punct(*p)
+            } else {
+                bail!("{line}:{col}: unexpected character `{c}`")
+            };
+            toks.push((tok, line, col));
+        }
+    }
+}
+
+struct Parser {
+    toks: Vec<(Tok, usize, usize)>,
+    pos: usize,
+    /// Functions defined so far, callable by the ones that follow
+    funcs: IndexMap<String, Func>,
+}
+
+impl Parser {
+    fn new(src: &str) -> Result<Self> {
+        let lexer = Lexer {
+            src,
+            pos: 0,
+            line: 1,
+            col: 1,
+        };
+        Ok(Self {
+            toks: lexer.tokenize()?,
+            pos: 0,
+            funcs: IndexMap::new(),
+        })
+    }
+
+    #[inline]
+    fn peek(&self) -> &Tok {
+        &self.toks[self.pos].0
+    }
+
+    fn next(&mut self) -> Tok {
+        let tok = self.toks[self.pos].0.clone();
+        if tok != Tok::Eof {
+            self.pos += 1;
+        }
+        tok
+    }
+
+    /// Fails with `msg`, pointing at the token at position `at`
+    fn error_at<T>(&self, at: usize, msg: impl fmt::Display) -> Result<T> {
+        let (_, line, col) = &self.toks[at];
+        bail!("{line}:{col}: {msg}")
+    }
+
+    #[inline]
+    fn error<T>(&self, msg: impl fmt::Display) -> Result<T> {
+        self.error_at(self.pos, msg)
+    }
+
+    fn unexpected<T>(&self, expected: &str) -> Result<T> {
+        self.error(format!("expected {expected}, found {}", self.peek()))
+    }
+
+    fn is_punct(&self, p: &str) -> bool {
+        matches!(self.peek(), Tok::Punct(q) if *q == p)
+    }
+
+    fn is_keyword(&self, kw: &str) -> bool {
+        matches!(self.peek(), Tok::Ident(x) if x == kw)
+    }
+
+    fn eat_punct(&mut self, p: &str) -> bool {
+        let is_punct = self.is_punct(p);
+        if is_punct {
+            self.next();
+        }
+        is_punct
+    }
+
+    fn expect_punct(&mut self, p: &str) -> Result<()> {
+        if !self.eat_punct(p) {
+            return self.unexpected(&format!("`{p}`"));
+        }
+        Ok(())
+    }
+
+    fn expect_keyword(&mut self, kw: &str) -> Result<()> {
+        if !self.is_keyword(kw) {
+            return self.unexpected(&format!("`{kw}`"));
+        }
+        self.next();
+        Ok(())
+    }
+
+    fn ident(&mut self) -> Result<String> {
+        match self.peek() {
+            Tok::Ident(x) => {
+                let x = x.clone();
+                self.next();
+                Ok(x)
+            }
+            _ => self.unexpected("an identifier"),
+        }
+    }
+
+    #[inline]
+    fn var(&mut self) -> Result<Var> {
+        Ok(Var::new(&self.ident()?))
+    }
+
+    fn int(&mut self) -> Result<u128> {
+        match self.peek() {
+            Tok::Int(n) => {
+                let n = *n;
+                self.next();
+                Ok(n)
+            }
+            _ => self.unexpected("a number"),
+        }
+    }
+
+    fn int_u32(&mut self) -> Result<u32> {
+        let at = self.pos;
+        let n = self.int()?;
+        match u32::try_from(n) {
+            Ok(n) => Ok(n),
+            Err(_) => self.error_at(at, format!("number `{n}` is too large")),
+        }
+    }
+
+    fn string(&mut self) -> Result<String> {
+        match self.peek() {
+            Tok::Str(s) => {
+                let s = s.clone();
+                self.next();
+                Ok(s)
+            }
+            _ => self.unexpected("a string"),
+        }
+    }
+
+    /// Parses `p1, p2, ..., pn` up to (and including) the closing `close`
+    fn list<T>(
+        &mut self,
+        close: &str,
+        mut p: impl FnMut(&mut Self) -> Result<T>,
+    ) -> Result<Vec<T>> {
+        let mut xs = vec![];
+        if self.eat_punct(close) {
+            return Ok(xs);
+        }
+        loop {
+            xs.push(p(self)?);
+            if self.eat_punct(close) {
+                return Ok(xs);
+            }
+            self.expect_punct(",")?;
+        }
+    }
+
+    /// Parses `(v1, ..., vn)`
+    fn vars(&mut self) -> Result<Vec<Var>> {
+        self.expect_punct("(")?;
+        self.list(")", Self::var)
+    }
+
+    /// Parses `(v1, ..., vn)` for a fixed `n`
+    fn vars_n<const N: usize>(&mut self) -> Result<[Var; N]> {
+        let vars = self.vars()?;
+        let len = vars.len();
+        match vars.try_into() {
+            Ok(vars) => Ok(vars),
+            Err(_) => self.error(format!("expected {N} variables, found {len}")),
+        }
+    }
+
+    fn tag(&mut self) -> Result<Tag> {
+        let at = self.pos;
+        let kind = self.ident()?;
+        self.expect_punct("::")?;
+        let name = self.ident()?;
+        match tag_from_names(&kind, &name) {
+            Some(tag) => Ok(tag),
+            None => self.error_at(at, format!("unknown tag `{kind}::{name}`")),
+        }
+    }
+
+    /// Parses a symbol path, `"x"` or `["a", "b"]`, as a keyword or not. A
+    /// single name stands for a symbol in the `lurk` package or for a keyword
+    /// with that name
+    fn path(&mut self, keyword: bool) -> Result<Symbol> {
+        if self.eat_punct("[") {
+            let path = self.list("]", Self::string)?;
+            Ok(Symbol::new_from_vec(path, keyword))
+        } else if keyword {
+            Ok(Symbol::key(&[self.string()?]))
+        } else {
+            Ok(lurk_sym(&self.string()?))
+        }
+    }
+
+    /// Parses a symbol: a path, optionally prefixed by `:` for keywords
+    fn symbol(&mut self) -> Result<Symbol> {
+        let keyword = self.eat_punct(":");
+        self.path(keyword)
+    }
+
+    fn parse_file(mut self) -> Result<Func> {
+        loop {
+            let func = self.func()?;
+            if *self.peek() == Tok::Eof {
+                return Ok(func);
+            }
+            self.funcs.insert(func.name.clone(), func);
+        }
+    }
+
+    fn func(&mut self) -> Result<Func> {
+        self.expect_keyword("fn")?;
+        let at = self.pos;
+        let name = self.ident()?;
+        if BUILTINS.contains(&name.as_str()) {
+            return self.error_at(at, format!("`{name}` is a reserved name"));
+        }
+        if self.funcs.contains_key(&name) {
+            return self.error_at(at, format!("function `{name}` is already defined"));
+        }
+        let params = self.vars()?;
+        self.expect_punct(":")?;
+        let size = self.int()?;
+        self.expect_punct("{")?;
+        let body = self.block()?;
+        self.expect_punct("}")?;
+        Func::new(name.clone(), params, size as usize, body)
+            .with_context(|| format!("invalid function `{name}`"))
+    }
+
+    /// Parses a sequence of operations followed by a control statement
+    fn block(&mut self) -> Result<Block> {
+        let mut ops = vec![];
+        loop {
+            let Tok::Ident(kw) = self.peek().clone() else {
+                return self.unexpected("an operation or a control statement");
+            };
+            match kw.as_str() {
+                "let" => ops.push(self.op_let()?),
+                "emit" => {
+                    self.next();
+                    let [v] = self.vars_n()?;
+                    self.expect_punct(";")?;
+                    ops.push(Op::Emit(v));
+                }
+                "unit" => {
+                    self.next();
+                    self.expect_punct("(")?;
+                    self.expect_punct(")")?;
+                    self.expect_punct(";")?;
+                    ops.push(Op::Unit(|| ()));
+                }
+                "return" => {
+                    self.next();
+                    let vars = self.vars()?;
+                    self.eat_punct(";");
+                    return Ok(Block {
+                        ops,
+                        ctrl: Ctrl::Return(vars),
+                    });
+                }
+                "if" => {
+                    self.next();
+                    let negated = self.eat_punct("!");
+                    let x = self.var()?;
+                    self.expect_punct("{")?;
+                    let block = self.block()?;
+                    self.expect_punct("}")?;
+                    let rest = self.block()?;
+                    let ctrl = if negated {
+                        Ctrl::if_(x, rest, block)
+                    } else {
+                        Ctrl::if_(x, block, rest)
+                    };
+                    return Ok(Block { ops, ctrl });
+                }
+                "match" => {
+                    self.next();
+                    let ctrl = if self.is_keyword("symbol") {
+                        self.next();
+                        let x = self.var()?;
+                        let (cases, def) = self.cases(Self::symbol)?;
+                        Ctrl::MatchSymbol(x, cases, def)
+                    } else {
+                        let x = self.var()?;
+                        self.expect_punct(".")?;
+                        self.expect_keyword("tag")?;
+                        let (cases, def) = self.cases(Self::tag)?;
+                        Ctrl::MatchTag(x, cases, def)
+                    };
+                    return Ok(Block { ops, ctrl });
+                }
+                _ => return self.unexpected("an operation or a control statement"),
+            }
+        }
+    }
+
+    /// Parses the cases of a `match`, each being a list of patterns separated
+    /// by `|` or `,` and followed by a block, and the optional default
+    fn cases<K: std::hash::Hash + Eq + fmt::Debug>(
+        &mut self,
+        mut pattern: impl FnMut(&mut Self) -> Result<K>,
+    ) -> Result<(IndexMap<K, Block>, Option<Box<Block>>)> {
+        self.expect_punct("{")?;
+        let mut cases = IndexMap::new();
+        while !self.eat_punct("}") {
+            let mut keys = vec![pattern(self)?];
+            while self.eat_punct("|") || self.eat_punct(",") {
+                keys.push(pattern(self)?);
+            }
+            self.expect_punct("=>")?;
+            self.expect_punct("{")?;
+            let block = self.block()?;
+            self.expect_punct("}")?;
+            for key in keys {
+                if cases.contains_key(&key) {
+                    return self.error(format!("repeated case `{key:?}` on `match`"));
+                }
+                cases.insert(key, block.clone());
+            }
+        }
+        let def = if self.eat_punct(";") {
+            Some(Box::new(self.block()?))
+        } else {
+            None
+        };
+        Ok((cases, def))
+    }
+
+    fn op_let(&mut self) -> Result<Op> {
+        self.expect_keyword("let")?;
+        let op = if self.is_punct("(") {
+            let tgts = self.vars()?;
+            self.expect_punct("=")?;
+            self.op_tuple(tgts)?
+        } else {
+            let tgt = self.var()?;
+            if self.eat_punct(":") {
+                let tag = self.tag()?;
+                if self.is_punct(";") {
+                    Op::Zero(tgt, tag)
+                } else {
+                    self.expect_punct("=")?;
+                    self.op_typed(tgt, tag)?
+                }
+            } else {
+                self.expect_punct("=")?;
+                self.op_single(tgt)?
+            }
+        };
+        self.expect_punct(";")?;
+        Ok(op)
+    }
+
+    /// Operations of the form `let x: K::T = ...`
+    fn op_typed(&mut self, tgt: Var, tag: Tag) -> Result<Op> {
+        let name = self.ident()?;
+        let op = match name.as_str() {
+            "HASH_3_ZEROS" => Op::Hash3Zeros(tgt, tag),
+            "HASH_4_ZEROS" => Op::Hash4Zeros(tgt, tag),
+            "HASH_6_ZEROS" => Op::Hash6Zeros(tgt, tag),
+            "HASH_8_ZEROS" => Op::Hash8Zeros(tgt, tag),
+            "cons2" => Op::Cons2(tgt, tag, self.vars_n()?),
+            "cons3" => Op::Cons3(tgt, tag, self.vars_n()?),
+            "cons4" => Op::Cons4(tgt, tag, self.vars_n()?),
+            _ => return self.error(format!("unknown typed operation `{name}`")),
+        };
+        Ok(op)
+    }
+
+    /// Operations of the form `let x = ...`
+    fn op_single(&mut self, tgt: Var) -> Result<Op> {
+        let name = self.ident()?;
+        let op = match name.as_str() {
+            "Num" | "String" | "Symbol" | "Keyword" => {
+                self.expect_punct("(")?;
+                let lit = match name.as_str() {
+                    "Num" => Lit::Num(self.int()?),
+                    "String" => Lit::String(self.string()?),
+                    "Symbol" => Lit::Symbol(self.path(false)?),
+                    _ => Lit::Symbol(self.path(true)?),
+                };
+                self.expect_punct(")")?;
+                Op::Lit(tgt, lit)
+            }
+            "cast" => {
+                self.expect_punct("(")?;
+                let src = self.var()?;
+                self.expect_punct(",")?;
+                let tag = self.tag()?;
+                self.expect_punct(")")?;
+                Op::Cast(tgt, tag, src)
+            }
+            "truncate" | "pow2" => {
+                self.expect_punct("(")?;
+                let a = self.var()?;
+                self.expect_punct(",")?;
+                let n = self.int_u32()?;
+                self.expect_punct(")")?;
+                if name == "truncate" {
+                    Op::Trunc(tgt, a, n)
+                } else {
+                    Op::Pow2(tgt, a, n)
+                }
+            }
+            "bit_and" | "bit_or" | "bit_xor" => {
+                self.expect_punct("(")?;
+                let a = self.var()?;
+                self.expect_punct(",")?;
+                let b = self.var()?;
+                self.expect_punct(",")?;
+                let n = self.int_u32()?;
+                self.expect_punct(")")?;
+                match name.as_str() {
+                    "bit_and" => Op::BitAnd(tgt, a, b, n),
+                    "bit_or" => Op::BitOr(tgt, a, b, n),
+                    _ => Op::BitXor(tgt, a, b, n),
+                }
+            }
+            "copy" => {
+                let [a] = self.vars_n()?;
+                Op::Copy(tgt, a)
+            }
+            "not" => {
+                let [a] = self.vars_n()?;
+                Op::Not(tgt, a)
+            }
+            "eq_tag" | "eq_val" | "and" | "or" | "add" | "sub" | "mul" | "div" | "lt" | "hide" => {
+                let [a, b] = self.vars_n()?;
+                match name.as_str() {
+                    "eq_tag" => Op::EqTag(tgt, a, b),
+                    "eq_val" => Op::EqVal(tgt, a, b),
+                    "and" => Op::And(tgt, a, b),
+                    "or" => Op::Or(tgt, a, b),
+                    "add" => Op::Add(tgt, a, b),
+                    "sub" => Op::Sub(tgt, a, b),
+                    "mul" => Op::Mul(tgt, a, b),
+                    "div" => Op::Div(tgt, a, b),
+                    "lt" => Op::Lt(tgt, a, b),
+                    _ => Op::Hide(tgt, a, b),
+                }
+            }
+            _ => {
+                self.pos -= 1;
+                self.op_tuple(vec![tgt])?
+            }
+        };
+        Ok(op)
+    }
+
+    /// Operations of the form `let (x1, ..., xn) = ...`
+    fn op_tuple(&mut self, tgts: Vec<Var>) -> Result<Op> {
+        let at = self.pos;
+        let n_tgts = tgts.len();
+        let arity_error = move |this: &Self, name: &str| {
+            this.error_at(at, format!("`{name}` can't bind {n_tgts} variables"))
+        };
+        let name = self.ident()?;
+        let op = match name.as_str() {
+            "cproc" => {
+                let sym = self.symbol()?;
+                Op::Cproc(tgts, sym, self.vars()?)
+            }
+            "div_rem" => {
+                self.expect_punct("(")?;
+                let a = self.var()?;
+                self.expect_punct(",")?;
+                let b = self.var()?;
+                self.expect_punct(",")?;
+                let n = self.int_u32()?;
+                self.expect_punct(")")?;
+                let Ok(tgts) = tgts.try_into() else {
+                    return arity_error(self, &name);
+                };
+                Op::DivRem(tgts, a, b, n)
+            }
+            "open" => {
+                let [h] = self.vars_n()?;
+                let Ok([s, p]) = <[Var; 2]>::try_from(tgts) else {
+                    return arity_error(self, &name);
+                };
+                Op::Open(s, p, h)
+            }
+            "decons2" | "decons3" | "decons4" => {
+                let [src] = self.vars_n()?;
+                let op = match name.as_str() {
+                    "decons2" => tgts.try_into().map(|tgts| Op::Decons2(tgts, src)),
+                    "decons3" => tgts.try_into().map(|tgts| Op::Decons3(tgts, src)),
+                    _ => tgts.try_into().map(|tgts| Op::Decons4(tgts, src)),
+                };
+                match op {
+                    Ok(op) => op,
+                    Err(_) => return arity_error(self, &name),
+                }
+            }
+            _ => {
+                let Some(func) = self.funcs.get(&name) else {
+                    if BUILTINS.contains(&name.as_str()) {
+                        return arity_error(self, &name);
+                    }
+                    return self.error_at(at, format!("unknown function `{name}`"));
+                };
+                let func = Box::new(func.clone());
+                Op::Call(tgts, func, self.vars()?)
+            }
+        };
+        Ok(op)
+    }
+}
+
+// The tags that can be named in a `.lem` source, by kind
+const EXPR_TAGS: [ExprTag; 19] = [
+    ExprTag::Nil,
+    ExprTag::Cons,
+    ExprTag::Sym,
+    ExprTag::Fun,
+    ExprTag::Num,
+    ExprTag::Thunk,
+    ExprTag::Str,
+    ExprTag::Char,
+    ExprTag::Comm,
+    ExprTag::U64,
+    ExprTag::Key,
+    ExprTag::Cproc,
+    ExprTag::U8,
+    ExprTag::U16,
+    ExprTag::U32,
+    ExprTag::U128,
+    ExprTag::I64,
+    ExprTag::Vec,
+    ExprTag::Map,
+];
+
+const CONT_TAGS: [ContTag; 26] = [
+    ContTag::Outermost,
+    ContTag::Call0,
+    ContTag::Call,
+    ContTag::Call2,
+    ContTag::Tail,
+    ContTag::Error,
+    ContTag::Lookup,
+    ContTag::Unop,
+    ContTag::Binop,
+    ContTag::Binop2,
+    ContTag::If,
+    ContTag::Let,
+    ContTag::LetRec,
+    ContTag::Dummy,
+    ContTag::Terminal,
+    ContTag::Emit,
+    ContTag::Cproc,
+    ContTag::Fail,
+    ContTag::Catch,
+    ContTag::Unwind,
+    ContTag::LetRecNext,
+    ContTag::Rest,
+    ContTag::Apply,
+    ContTag::StringOp,
+    ContTag::Vector,
+    ContTag::VectorOp,
+];
+
+const OP1_TAGS: [Op1; 23] = [
+    Op1::Car,
+    Op1::Cdr,
+    Op1::Atom,
+    Op1::Emit,
+    Op1::Open,
+    Op1::Secret,
+    Op1::Commit,
+    Op1::Num,
+    Op1::Comm,
+    Op1::Char,
+    Op1::Eval,
+    Op1::U64,
+    Op1::U8,
+    Op1::U16,
+    Op1::U32,
+    Op1::U128,
+    Op1::I64,
+    Op1::StringLength,
+    Op1::SymbolToString,
+    Op1::StringToSymbol,
+    Op1::NumToString,
+    Op1::VectorLength,
+    Op1::TypeOf,
+];
+
+const OP2_TAGS: [Op2; 29] = [
+    Op2::Sum,
+    Op2::Diff,
+    Op2::Product,
+    Op2::Quotient,
+    Op2::Equal,
+    Op2::NumEqual,
+    Op2::Less,
+    Op2::Greater,
+    Op2::LessEqual,
+    Op2::GreaterEqual,
+    Op2::Cons,
+    Op2::StrCons,
+    Op2::Begin,
+    Op2::Hide,
+    Op2::Modulo,
+    Op2::Eval,
+    Op2::LogAnd,
+    Op2::LogOr,
+    Op2::LogXor,
+    Op2::Shl,
+    Op2::Shr,
+    Op2::Rotl,
+    Op2::Rotr,
+    Op2::Apply,
+    Op2::StringAppend,
+    Op2::Substring,
+    Op2::StringLess,
+    Op2::VectorRef,
+    Op2::VectorSet,
+];
+
+fn expr_tag_name(tag: ExprTag) -> &'static str {
+    match tag {
+        ExprTag::Nil => "Nil",
+        ExprTag::Cons => "Cons",
+        ExprTag::Sym => "Sym",
+        ExprTag::Fun => "Fun",
+        ExprTag::Num => "Num",
+        ExprTag::Thunk => "Thunk",
+        ExprTag::Str => "Str",
+        ExprTag::Char => "Char",
+        ExprTag::Comm => "Comm",
+        ExprTag::U64 => "U64",
+        ExprTag::Key => "Key",
+        ExprTag::Cproc => "Cproc",
+        ExprTag::U8 => "U8",
+        ExprTag::U16 => "U16",
+        ExprTag::U32 => "U32",
+        ExprTag::U128 => "U128",
+        ExprTag::I64 => "I64",
+        ExprTag::Vec => "Vec",
+        ExprTag::Map => "Map",
+    }
+}
+
+fn cont_tag_name(tag: ContTag) -> &'static str {
+    match tag {
+        ContTag::Outermost => "Outermost",
+        ContTag::Call0 => "Call0",
+        ContTag::Call => "Call",
+        ContTag::Call2 => "Call2",
+        ContTag::Tail => "Tail",
+        ContTag::Error => "Error",
+        ContTag::Lookup => "Lookup",
+        ContTag::Unop => "Unop",
+        ContTag::Binop => "Binop",
+        ContTag::Binop2 => "Binop2",
+        ContTag::If => "If",
+        ContTag::Let => "Let",
+        ContTag::LetRec => "LetRec",
+        ContTag::Dummy => "Dummy",
+        ContTag::Terminal => "Terminal",
+        ContTag::Emit => "Emit",
+        ContTag::Cproc => "Cproc",
+        ContTag::Fail => "Fail",
+        ContTag::Catch => "Catch",
+        ContTag::Unwind => "Unwind",
+        ContTag::LetRecNext => "LetRecNext",
+        ContTag::Rest => "Rest",
+        ContTag::Apply => "Apply",
+        ContTag::StringOp => "StringOp",
+        ContTag::Vector => "Vector",
+        ContTag::VectorOp => "VectorOp",
+    }
+}
+
+fn op1_name(tag: Op1) -> &'static str {
+    match tag {
+        Op1::Car => "Car",
+        Op1::Cdr => "Cdr",
+        Op1::Atom => "Atom",
+        Op1::Emit => "Emit",
+        Op1::Open => "Open",
+        Op1::Secret => "Secret",
+        Op1::Commit => "Commit",
+        Op1::Num => "Num",
+        Op1::Comm => "Comm",
+        Op1::Char => "Char",
+        Op1::Eval => "Eval",
+        Op1::U64 => "U64",
+        Op1::U8 => "U8",
+        Op1::U16 => "U16",
+        Op1::U32 => "U32",
+        Op1::U128 => "U128",
+        Op1::I64 => "I64",
+        Op1::StringLength => "StringLength",
+        Op1::SymbolToString => "SymbolToString",
+        Op1::StringToSymbol => "StringToSymbol",
+        Op1::NumToString => "NumToString",
+        Op1::VectorLength => "VectorLength",
+        Op1::TypeOf => "TypeOf",
+    }
+}
+
+fn op2_name(tag: Op2) -> &'static str {
+    match tag {
+        Op2::Sum => "Sum",
+        Op2::Diff => "Diff",
+        Op2::Product => "Product",
+        Op2::Quotient => "Quotient",
+        Op2::Equal => "Equal",
+        Op2::NumEqual => "NumEqual",
+        Op2::Less => "Less",
+        Op2::Greater => "Greater",
+        Op2::LessEqual => "LessEqual",
+        Op2::GreaterEqual => "GreaterEqual",
+        Op2::Cons => "Cons",
+        Op2::StrCons => "StrCons",
+        Op2::Begin => "Begin",
+        Op2::Hide => "Hide",
+        Op2::Modulo => "Modulo",
+        Op2::Eval => "Eval",
+        Op2::LogAnd => "LogAnd",
+        Op2::LogOr => "LogOr",
+        Op2::LogXor => "LogXor",
+        Op2::Shl => "Shl",
+        Op2::Shr => "Shr",
+        Op2::Rotl => "Rotl",
+        Op2::Rotr => "Rotr",
+        Op2::Apply => "Apply",
+        Op2::StringAppend => "StringAppend",
+        Op2::Substring => "Substring",
+        Op2::StringLess => "StringLess",
+        Op2::VectorRef => "VectorRef",
+        Op2::VectorSet => "VectorSet",
+    }
+}
+
+fn tag_from_names(kind: &str, name: &str) -> Option<Tag> {
+    match kind {
+        "Expr" => EXPR_TAGS
+            .into_iter()
+            .find(|t| expr_tag_name(*t) == name)
+            .map(Tag::Expr),
+        "Cont" => CONT_TAGS
+            .into_iter()
+            .find(|t| cont_tag_name(*t) == name)
+            .map(Tag::Cont),
+        "Op1" => OP1_TAGS
+            .into_iter()
+            .find(|t| op1_name(*t) == name)
+            .map(Tag::Op1),
+        "Op2" => OP2_TAGS
+            .into_iter()
+            .find(|t| op2_name(*t) == name)
+            .map(Tag::Op2),
+        _ => None,
+    }
+}
+
+fn fmt_tag(tag: &Tag) -> String {
+    match tag {
+        Tag::Expr(t) => format!("Expr::{}", expr_tag_name(*t)),
+        Tag::Cont(t) => format!("Cont::{}", cont_tag_name(*t)),
+        Tag::Op1(t) => format!("Op1::{}", op1_name(*t)),
+        Tag::Op2(t) => format!("Op2::{}", op2_name(*t)),
+    }
+}
+
+/// Formats the path of a symbol, without the keyword marker
+fn fmt_path(sym: &Symbol) -> String {
+    let path = sym.path();
+    if sym.is_keyword() && path.len() == 1 {
+        return format!("\"{}\"", escape_string(&path[0]));
+    }
+    if !sym.is_keyword() && path.len() == 2 && lurk_sym(&path[1]) == *sym {
+        return format!("\"{}\"", escape_string(&path[1]));
+    }
+    let path = path
+        .iter()
+        .map(|x| format!("\"{}\"", escape_string(x)))
+        .collect::<Vec<_>>();
+    format!("[{}]", path.join(", "))
+}
+
+fn fmt_symbol(sym: &Symbol) -> String {
+    if sym.is_keyword() {
+        format!(":{}", fmt_path(sym))
+    } else {
+        fmt_path(sym)
+    }
+}
+
+/// Drops the `#n` suffixes added by `Func::deconflict`
+fn fmt_var(var: &Var) -> &str {
+    let mut name = var.name().as_ref();
+    while let Some((prefix, suffix)) = name.rsplit_once('#') {
+        if suffix.is_empty() || !suffix.bytes().all(|b| b.is_ascii_digit()) {
+            break;
+        }
+        name = prefix;
+    }
+    name
+}
+
+fn fmt_vars(vars: &[Var]) -> String {
+    vars.iter().map(fmt_var).collect::<Vec<_>>().join(", ")
+}
+
+/// Prints a `Func` along with the functions it calls, each one printed once
+#[derive(Default)]
+struct Printer {
+    defs: Vec<String>,
+    /// Maps a function's name and printed text to the name it was printed with
+    printed: HashMap<(String, String), String>,
+    used_names: HashSet<String>,
+}
+
+impl Printer {
+    /// Prints `func` after its dependencies and returns the name used for it
+    fn func(&mut self, func: &Func, name: Option<String>) -> String {
+        let mut text = String::new();
+        let _ = write!(
+            text,
+            "({}): {} {{",
+            fmt_vars(&func.input_params),
+            func.output_size
+        );
+        self.block(&mut text, &func.body, 1);
+        text.push_str("\n}");
+        let key = (func.name.clone(), text);
+        if let Some(name) = self.printed.get(&key) {
+            return name.clone();
+        }
+        let name = name.unwrap_or_else(|| {
+            let mut name = func.name.clone();
+            let mut i = 1;
+            while self.used_names.contains(&name) {
+                i += 1;
+                name = format!("{}_{i}", func.name);
+            }
+            name
+        });
+        self.used_names.insert(name.clone());
+        self.defs.push(format!("fn {name}{}", key.1));
+        self.printed.insert(key, name.clone());
+        name
+    }
+
+    fn line(out: &mut String, indent: usize, text: &str) {
+        out.push('\n');
+        for _ in 0..indent {
+            out.push_str("    ");
+        }
+        out.push_str(text);
+    }
+
+    fn block(&mut self, out: &mut String, block: &Block, indent: usize) {
+        for op in &block.ops {
+            let text = self.op(op);
+            Self::line(out, indent, &text);
+        }
+        match &block.ctrl {
+            Ctrl::Return(vars) => Self::line(out, indent, &format!("return ({})", fmt_vars(vars))),
+            Ctrl::If(x, true_block, false_block) => {
+                Self::line(out, indent, &format!("if {} {{", fmt_var(x)));
+                self.block(out, true_block, indent + 1);
+                Self::line(out, indent, "}");
+                self.block(out, false_block, indent);
+            }
+            Ctrl::MatchTag(x, cases, def) => {
+                Self::line(out, indent, &format!("match {}.tag {{", fmt_var(x)));
+                for (tag, block) in cases {
+                    self.case(out, &fmt_tag(tag), block, indent + 1);
+                }
+                self.default(out, def, indent);
+            }
+            Ctrl::MatchSymbol(x, cases, def) => {
+                Self::line(out, indent, &format!("match symbol {} {{", fmt_var(x)));
+                for (sym, block) in cases {
+                    self.case(out, &fmt_symbol(sym), block, indent + 1);
+                }
+                self.default(out, def, indent);
+            }
+        }
+    }
+
+    fn case(&mut self, out: &mut String, pattern: &str, block: &Block, indent: usize) {
+        Self::line(out, indent, &format!("{pattern} => {{"));
+        self.block(out, block, indent + 1);
+        Self::line(out, indent, "}");
+    }
+
+    fn default(&mut self, out: &mut String, def: &Option<Box<Block>>, indent: usize) {
+        match def {
+            None => Self::line(out, indent, "}"),
+            Some(def) => {
+                Self::line(out, indent, "};");
+                self.block(out, def, indent);
+            }
+        }
+    }
+
+    fn op(&mut self, op: &Op) -> String {
+        let v = fmt_var;
+        match op {
+            Op::Cproc(out, sym, inp) => format!(
+                "let ({}) = cproc {}({});",
+                fmt_vars(out),
+                fmt_symbol(sym),
+                fmt_vars(inp)
+            ),
+            Op::Call(out, func, inp) => {
+                let name = self.func(func, None);
+                format!("let ({}) = {name}({});", fmt_vars(out), fmt_vars(inp))
+            }
+            Op::Copy(x, y) => format!("let {} = copy({});", v(x), v(y)),
+            Op::Zero(x, t) => format!("let {}: {};", v(x), fmt_tag(t)),
+            Op::Hash3Zeros(x, t) => format!("let {}: {} = HASH_3_ZEROS;", v(x), fmt_tag(t)),
+            Op::Hash4Zeros(x, t) => format!("let {}: {} = HASH_4_ZEROS;", v(x), fmt_tag(t)),
+            Op::Hash6Zeros(x, t) => format!("let {}: {} = HASH_6_ZEROS;", v(x), fmt_tag(t)),
+            Op::Hash8Zeros(x, t) => format!("let {}: {} = HASH_8_ZEROS;", v(x), fmt_tag(t)),
+            Op::Lit(x, lit) => {
+                let lit = match lit {
+                    Lit::Num(n) => format!("Num({n})"),
+                    Lit::String(s) => format!("String(\"{}\")", escape_string(s)),
+                    Lit::Symbol(s) if s.is_keyword() => format!("Keyword({})", fmt_path(s)),
+                    Lit::Symbol(s) => format!("Symbol({})", fmt_path(s)),
+                };
+                format!("let {} = {lit};", v(x))
+            }
+            Op::Cast(y, t, x) => format!("let {} = cast({}, {});", v(y), v(x), fmt_tag(t)),
+            Op::EqTag(y, a, b) => format!("let {} = eq_tag({}, {});", v(y), v(a), v(b)),
+            Op::EqVal(y, a, b) => format!("let {} = eq_val({}, {});", v(y), v(a), v(b)),
+            Op::Not(y, a) => format!("let {} = not({});", v(y), v(a)),
+            Op::And(y, a, b) => format!("let {} = and({}, {});", v(y), v(a), v(b)),
+            Op::Or(y, a, b) => format!("let {} = or({}, {});", v(y), v(a), v(b)),
+            Op::Add(y, a, b) => format!("let {} = add({}, {});", v(y), v(a), v(b)),
+            Op::Sub(y, a, b) => format!("let {} = sub({}, {});", v(y), v(a), v(b)),
+            Op::Mul(y, a, b) => format!("let {} = mul({}, {});", v(y), v(a), v(b)),
+            Op::Div(y, a, b) => format!("let {} = div({}, {});", v(y), v(a), v(b)),
+            Op::Lt(y, a, b) => format!("let {} = lt({}, {});", v(y), v(a), v(b)),
+            Op::Trunc(y, a, n) => format!("let {} = truncate({}, {n});", v(y), v(a)),
+            Op::DivRem([q, r], a, b, n) => {
+                format!(
+                    "let ({}, {}) = div_rem({}, {}, {n});",
+                    v(q),
+                    v(r),
+                    v(a),
+                    v(b)
+                )
+            }
+            Op::BitAnd(y, a, b, n) => format!("let {} = bit_and({}, {}, {n});", v(y), v(a), v(b)),
+            Op::BitOr(y, a, b, n) => format!("let {} = bit_or({}, {}, {n});", v(y), v(a), v(b)),
+            Op::BitXor(y, a, b, n) => format!("let {} = bit_xor({}, {}, {n});", v(y), v(a), v(b)),
+            Op::Pow2(y, a, n) => format!("let {} = pow2({}, {n});", v(y), v(a)),
+            Op::Emit(x) => format!("emit({});", v(x)),
+            Op::Cons2(x, t, ys) => {
+                format!("let {}: {} = cons2({});", v(x), fmt_tag(t), fmt_vars(ys))
+            }
+            Op::Cons3(x, t, ys) => {
+                format!("let {}: {} = cons3({});", v(x), fmt_tag(t), fmt_vars(ys))
+            }
+            Op::Cons4(x, t, ys) => {
+                format!("let {}: {} = cons4({});", v(x), fmt_tag(t), fmt_vars(ys))
+            }
+            Op::Decons2(ys, x) => format!("let ({}) = decons2({});", fmt_vars(ys), v(x)),
+            Op::Decons3(ys, x) => format!("let ({}) = decons3({});", fmt_vars(ys), v(x)),
+            Op::Decons4(ys, x) => format!("let ({}) = decons4({});", fmt_vars(ys), v(x)),
+            Op::Hide(x, s, p) => format!("let {} = hide({}, {});", v(x), v(s), v(p)),
+            Op::Open(s, p, h) => format!("let ({}, {}) = open({});", v(s), v(p), v(h)),
+            Op::Unit(_) => "unit();".to_string(),
+        }
+    }
+}
+
+impl fmt::Display for Func {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut printer = Printer::default();
+        // reserve the name of the printed function so that helpers sharing
+        // it get renamed instead
+        printer.used_names.insert(self.name.clone());
+        printer.func(self, Some(self.name.clone()));
+        write!(f, "{}", printer.defs.join("\n\n"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{func, lem::eval::eval_step};
+
+    #[test]
+    fn eval_step_round_trip() {
+        let step = eval_step();
+        let text = step.to_string();
+        let parsed = parse_func(&text).unwrap();
+        assert_eq!(step, &parsed);
+        assert_eq!(text, parsed.to_string());
+    }
+
+    #[test]
+    fn tag_names_round_trip() {
+        fn all<T: TryFrom<u16>>(first: u16) -> Vec<T> {
+            (first..=u16::MAX)
+                .map_while(|i| T::try_from(i).ok())
+                .collect()
+        }
+        let tags = all(ExprTag::Nil as u16)
+            .into_iter()
+            .map(Tag::Expr)
+            .chain(all(ContTag::Outermost as u16).into_iter().map(Tag::Cont))
+            .chain(all(Op1::Car as u16).into_iter().map(Tag::Op1))
+            .chain(all(Op2::Sum as u16).into_iter().map(Tag::Op2));
+        for tag in tags {
+            let text = fmt_tag(&tag);
+            let (kind, name) = text.split_once("::").unwrap();
+            assert_eq!(tag_from_names(kind, name), Some(tag), "{text}");
+        }
+        assert_eq!(tag_from_names("Expr", "Foo"), None);
+    }
+
+    #[test]
+    fn parse_matches_macro() {
+        let src = r#"
+            // splits a list into its head and tail
+            fn car_cdr(xs): 2 {
+                let nil = Symbol("nil");
+                let nil = cast(nil, Expr::Nil);
+                match xs.tag {
+                    Expr::Cons => {
+                        let (car, cdr) = decons2(xs);
+                        return (car, cdr)
+                    }
+                };
+                return (nil, nil)
+            }
+
+            fn cadr(xs): 1 {
+                let (_car, cdr) = car_cdr(xs);
+                let (cadr, _cddr) = car_cdr(cdr);
+                let is_num = Keyword(["x", "y"]);
+                match symbol is_num {
+                    "car", :"cdr" => {
+                        return (cadr)
+                    }
+                    ["a", "b"] => {
+                        let s = String("a \"string\"\n");
+                        return (s)
+                    }
+                };
+                let one = Num(1);
+                let two = add(one, one);
+                let (q, _r) = div_rem(two, one, 64);
+                let t = lt(q, one);
+                if !t {
+                    return (q)
+                }
+                return (xs)
+            }
+        "#;
+        let car_cdr = func!(car_cdr(xs): 2 => {
+            let nil = Symbol("nil");
+            let nil = cast(nil, Expr::Nil);
+            match xs.tag {
+                Expr::Cons => {
+                    let (car, cdr) = decons2(xs);
+                    return (car, cdr)
+                }
+            };
+            return (nil, nil)
+        });
+        let parsed = parse_func(src).unwrap();
+        assert_eq!(parsed.name, "cadr");
+        assert_eq!(parsed.output_size, 1);
+        assert!(matches!(
+            &parsed.body.ops[0],
+            Op::Call(_, f, _) if f.name == "car_cdr"
+        ));
+        let Ctrl::MatchSymbol(_, cases, Some(_)) = &parsed.body.ctrl else {
+            panic!("expected a symbol match")
+        };
+        let syms = cases.keys().cloned().collect::<Vec<_>>();
+        assert_eq!(
+            syms,
+            [
+                lurk_sym("car"),
+                Symbol::key(&["cdr"]),
+                Symbol::sym(&["a", "b"])
+            ]
+        );
+        assert_eq!(parse_func(&parsed.to_string()).unwrap(), parsed);
+        assert_eq!(parse_func(&car_cdr.to_string()).unwrap(), car_cdr);
+    }
+
+    #[test]
+    fn parse_errors() {
+        let err = |src: &str| parse_func(src).unwrap_err().to_string();
+        assert_eq!(
+            err("fn f(x): 1 {\n    return (x) }}"),
+            "2:17: expected `fn`, found `}`"
+        );
+        assert_eq!(
+            err("fn f(x): 1 {\n    let y = frob(x);\n    return (y)\n}"),
+            "2:13: unknown function `frob`"
+        );
+        assert_eq!(
+            err("fn f(x): 1 {\n    let y: Expr::Nope;\n    return (y)\n}"),
+            "2:12: unknown tag `Expr::Nope`"
+        );
+        assert_eq!(
+            err("fn not(x): 1 { return (x) }"),
+            "1:4: `not` is a reserved name"
+        );
+        // unbound variables are caught by `Func::new`
+        assert!(err("fn f(x): 1 { return (y) }").starts_with("invalid function `f`"));
+    }
+}
@@ -0,0 +1,480 @@
+//! ## Type and tag-flow analysis
+//!
+//! LEM variables hold either pointers or booleans, which the interpreter and
+//! the circuit only tell apart at runtime. `Func::infer_types` computes the
+//! type of every variable statically, failing on mismatches, and tracks the
+//! set of tags that each pointer may have.
+//!
+//! The tag sets are refined by `MatchTag`: within a case, the matched variable
+//! has the tag of that case; within the default, it has any of the tags not
+//! covered by the cases. This is what allows detecting match cases that can
+//! never be selected and `Decons*` operations on pointers that can't be
+//! tuples, which are reported as `Warning`s.
+
+use anyhow::{bail, Result};
+use indexmap::IndexSet;
+use std::collections::HashMap;
+
+use crate::tag::ExprTag::{Char, Comm, Num, I64, U128, U16, U32, U64, U8};
+
+use super::{Block, Ctrl, Func, Op, Tag, Var};
+
+/// The set of tags a pointer may have
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Tags {
+    Any,
+    Only(IndexSet<Tag>),
+}
+
+impl Tags {
+    #[inline]
+    fn one(tag: Tag) -> Self {
+        Self::Only(IndexSet::from([tag]))
+    }
+
+    /// Whether a pointer with these tags may have the tag `tag`
+    pub fn may_be(&self, tag: &Tag) -> bool {
+        match self {
+            Self::Any => true,
+            Self::Only(tags) => tags.contains(tag),
+        }
+    }
+
+    fn union(&self, other: &Self) -> Self {
+        match (self, other) {
+            (Self::Only(a), Self::Only(b)) => Self::Only(a.union(b).copied().collect()),
+            _ => Self::Any,
+        }
+    }
+}
+
+/// The type of a LEM variable
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Type {
+    Ptr(Tags),
+    Bool,
+}
+
+impl Type {
+    const ANY: Type = Type::Ptr(Tags::Any);
+
+    fn union(&self, other: &Self) -> Option<Self> {
+        match (self, other) {
+            (Type::Ptr(a), Type::Ptr(b)) => Some(Type::Ptr(a.union(b))),
+            (Type::Bool, Type::Bool) => Some(Type::Bool),
+            _ => None,
+        }
+    }
+}
+
+/// Whether pointers with tag `tag` may be tuples. Numeric data, characters
+/// and commitments are always atoms, as are the operator tags
+fn may_be_tuple(tag: &Tag) -> bool {
+    match tag {
+        Tag::Expr(tag) => !matches!(tag, Num | Char | Comm | U8 | U16 | U32 | U64 | U128 | I64),
+        Tag::Cont(_) => true,
+        Tag::Op1(_) | Tag::Op2(_) => false,
+    }
+}
+
+/// A suspicious construction found by `Func::infer_types`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Warning {
+    /// `UnreachableCase(x, t)`: the case `t` of a `MatchTag` on `x` can never
+    /// be selected because `x` can't have tag `t`
+    UnreachableCase(Var, Tag),
+    /// `UnreachableDefault(x)`: the default of a `MatchTag` on `x` can never be
+    /// selected because the cases cover every tag `x` can have
+    UnreachableDefault(Var),
+    /// `DeconsAtom(x, tags)`: `x` is deconstructed but none of the tags it can
+    /// have are the tags of tuples
+    DeconsAtom(Var, IndexSet<Tag>),
+}
+
+impl std::fmt::Display for Warning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Warning::UnreachableCase(var, tag) => {
+                write!(f, "Case {tag} of the match on {var} is unreachable")
+            }
+            Warning::UnreachableDefault(var) => {
+                write!(f, "Default case of the match on {var} is unreachable")
+            }
+            Warning::DeconsAtom(var, tags) => {
+                let tags = tags.iter().map(Tag::to_string).collect::<Vec<_>>();
+                write!(
+                    f,
+                    "{var} is deconstructed but can only have atom tags: {}",
+                    tags.join(", ")
+                )
+            }
+        }
+    }
+}
+
+/// The result of `Func::infer_types`
+#[derive(Debug, Default)]
+pub struct TypeInfo {
+    /// The type of each variable bound in the function, including the ones in
+    /// the bodies of called functions, as inferred at its binding
+    pub types: HashMap<Var, Type>,
+    /// The types of the function outputs, joined over all reachable returns
+    pub output: Vec<Type>,
+    /// The suspicious constructions found in reachable code
+    pub warnings: Vec<Warning>,
+}
+
+impl TypeInfo {
+    /// The `MatchTag` cases found to be unreachable, as pairs of the matched
+    /// variable and the case tag
+    pub fn unreachable_cases(&self) -> impl Iterator<Item = (&Var, &Tag)> {
+        self.warnings.iter().filter_map(|warning| match warning {
+            Warning::UnreachableCase(var, tag) => Some((var, tag)),
+            _ => None,
+        })
+    }
+}
+
+impl Func {
+    /// Infers the types of the variables of the function, whose input
+    /// parameters may be pointers with any tag. Fails if a variable is used
+    /// with a type different from the one it was bound with
+    pub fn infer_types(&self) -> Result<TypeInfo> {
+        let mut info = TypeInfo::default();
+        let params = vec![Type::ANY; self.input_params.len()];
+        info.output = infer_func(self, &params, true, &mut info)?;
+        Ok(info)
+    }
+}
+
+/// Analyzes the body of `func` with parameters of types `params`, returning
+/// the types of its outputs
+fn infer_func(
+    func: &Func,
+    params: &[Type],
+    reachable: bool,
+    info: &mut TypeInfo,
+) -> Result<Vec<Type>> {
+    let mut env = HashMap::new();
+    for (param, typ) in func.input_params.iter().zip(params) {
+        bind(param, typ.clone(), &mut env, info);
+    }
+    let mut output = None;
+    infer_block(&func.body, env, reachable, &mut output, info)?;
+    Ok(output.unwrap_or_else(|| vec![Type::ANY; func.output_size]))
+}
+
+#[inline]
+fn bind(var: &Var, typ: Type, env: &mut HashMap<Var, Type>, info: &mut TypeInfo) {
+    info.types.insert(var.clone(), typ.clone());
+    env.insert(var.clone(), typ);
+}
+
+fn get<'a>(var: &Var, env: &'a HashMap<Var, Type>) -> Result<&'a Type> {
+    match env.get(var) {
+        Some(typ) => Ok(typ),
+        None => bail!("Variable {var} is unbound."),
+    }
+}
+
+/// Returns the tags of `var`, failing if it's not a pointer
+fn get_ptr<'a>(var: &Var, env: &'a HashMap<Var, Type>) -> Result<&'a Tags> {
+    match get(var, env)? {
+        Type::Ptr(tags) => Ok(tags),
+        Type::Bool => bail!("Variable {var} is a boolean, but a pointer was expected."),
+    }
+}
+
+fn get_bool(var: &Var, env: &HashMap<Var, Type>) -> Result<()> {
+    match get(var, env)? {
+        Type::Bool => Ok(()),
+        Type::Ptr(..) => bail!("Variable {var} is a pointer, but a boolean was expected."),
+    }
+}
+
+fn infer_block(
+    block: &Block,
+    mut env: HashMap<Var, Type>,
+    reachable: bool,
+    output: &mut Option<Vec<Type>>,
+    info: &mut TypeInfo,
+) -> Result<()> {
+    let num = || Type::Ptr(Tags::one(Tag::Expr(Num)));
+    for op in &block.ops {
+        match op {
+            Op::Cproc(out, _, inp) => {
+                inp.iter()
+                    .try_for_each(|var| get_ptr(var, &env).map(|_| ()))?;
+                out.iter()
+                    .for_each(|var| bind(var, Type::ANY, &mut env, info));
+            }
+            Op::Call(out, func, inp) => {
+                let params = inp
+                    .iter()
+                    .map(|var| get_ptr(var, &env).map(|tags| Type::Ptr(tags.clone())))
+                    .collect::<Result<Vec<_>>>()?;
+                let types = infer_func(func, &params, reachable, info)?;
+                for (var, typ) in out.iter().zip(types) {
+                    bind(var, typ, &mut env, info);
+                }
+            }
+            Op::Copy(tgt, src) => {
+                let typ = get(src, &env)?.clone();
+                bind(tgt, typ, &mut env, info);
+            }
+            Op::Zero(tgt, tag)
+            | Op::Hash3Zeros(tgt, tag)
+            | Op::Hash4Zeros(tgt, tag)
+            | Op::Hash6Zeros(tgt, tag)
+            | Op::Hash8Zeros(tgt, tag) => bind(tgt, Type::Ptr(Tags::one(*tag)), &mut env, info),
+            Op::Lit(tgt, lit) => bind(tgt, Type::Ptr(Tags::one(lit.tag())), &mut env, info),
+            Op::Cast(tgt, tag, src) => {
+                get_ptr(src, &env)?;
+                bind(tgt, Type::Ptr(Tags::one(*tag)), &mut env, info);
+            }
+            Op::EqTag(tgt, a, b) | Op::EqVal(tgt, a, b) | Op::Lt(tgt, a, b) => {
+                get_ptr(a, &env)?;
+                get_ptr(b, &env)?;
+                bind(tgt, Type::Bool, &mut env, info);
+            }
+            Op::Not(tgt, a) => {
+                get_bool(a, &env)?;
+                bind(tgt, Type::Bool, &mut env, info);
+            }
+            Op::And(tgt, a, b) | Op::Or(tgt, a, b) => {
+                get_bool(a, &env)?;
+                get_bool(b, &env)?;
+                bind(tgt, Type::Bool, &mut env, info);
+            }
+            Op::Add(tgt, a, b)
+            | Op::Sub(tgt, a, b)
+            | Op::Mul(tgt, a, b)
+            | Op::Div(tgt, a, b)
+            | Op::BitAnd(tgt, a, b, _)
+            | Op::BitOr(tgt, a, b, _)
+            | Op::BitXor(tgt, a, b, _) => {
+                get_ptr(a, &env)?;
+                get_ptr(b, &env)?;
+                bind(tgt, num(), &mut env, info);
+            }
+            Op::Trunc(tgt, a, _) | Op::Pow2(tgt, a, _) => {
+                get_ptr(a, &env)?;
+                bind(tgt, num(), &mut env, info);
+            }
+            Op::DivRem(tgt, a, b, _) => {
+                get_ptr(a, &env)?;
+                get_ptr(b, &env)?;
+                tgt.iter().for_each(|var| bind(var, num(), &mut env, info));
+            }
+            Op::Emit(a) => {
+                get_ptr(a, &env)?;
+            }
+            Op::Cons2(img, tag, preimg) => {
+                preimg
+                    .iter()
+                    .try_for_each(|var| get_ptr(var, &env).map(|_| ()))?;
+                bind(img, Type::Ptr(Tags::one(*tag)), &mut env, info);
+            }
+            Op::Cons3(img, tag, preimg) => {
+                preimg
+                    .iter()
+                    .try_for_each(|var| get_ptr(var, &env).map(|_| ()))?;
+                bind(img, Type::Ptr(Tags::one(*tag)), &mut env, info);
+            }
+            Op::Cons4(img, tag, preimg) => {
+                preimg
+                    .iter()
+                    .try_for_each(|var| get_ptr(var, &env).map(|_| ()))?;
+                bind(img, Type::Ptr(Tags::one(*tag)), &mut env, info);
+            }
+            Op::Decons2([..], img) | Op::Decons3([..], img) | Op::Decons4([..], img) => {
+                if let Tags::Only(tags) = get_ptr(img, &env)? {
+                    if reachable && !tags.iter().any(may_be_tuple) {
+                        info.warnings
+                            .push(Warning::DeconsAtom(img.clone(), tags.clone()));
+                    }
+                }
+                let preimg: &[Var] = match op {
+                    Op::Decons2(preimg, _) => preimg,
+                    Op::Decons3(preimg, _) => preimg,
+                    Op::Decons4(preimg, _) => preimg,
+                    _ => unreachable!(),
+                };
+                preimg
+                    .iter()
+                    .for_each(|var| bind(var, Type::ANY, &mut env, info));
+            }
+            Op::Hide(tgt, sec, src) => {
+                get_ptr(sec, &env)?;
+                get_ptr(src, &env)?;
+                bind(tgt, Type::Ptr(Tags::one(Tag::Expr(Comm))), &mut env, info);
+            }
+            Op::Open(sec, src, comm) => {
+                get_ptr(comm, &env)?;
+                bind(sec, num(), &mut env, info);
+                bind(src, Type::ANY, &mut env, info);
+            }
+            Op::Unit(_) => (),
+        }
+    }
+    match &block.ctrl {
+        Ctrl::Return(vars) => {
+            let types = vars
+                .iter()
+                .map(|var| get_ptr(var, &env).map(|tags| Type::Ptr(tags.clone())))
+                .collect::<Result<Vec<_>>>()?;
+            if reachable {
+                *output = Some(match output.take() {
+                    None => types,
+                    Some(prev) => prev
+                        .iter()
+                        .zip(&types)
+                        .map(|(a, b)| a.union(b).expect("returns are pointers"))
+                        .collect(),
+                });
+            }
+        }
+        Ctrl::If(x, true_block, false_block) => {
+            get_bool(x, &env)?;
+            infer_block(true_block, env.clone(), reachable, output, info)?;
+            infer_block(false_block, env, reachable, output, info)?;
+        }
+        Ctrl::MatchTag(x, cases, def) => {
+            let tags = get_ptr(x, &env)?.clone();
+            for (tag, block) in cases {
+                let case_reachable = tags.may_be(tag);
+                if reachable && !case_reachable {
+                    info.warnings
+                        .push(Warning::UnreachableCase(x.clone(), *tag));
+                }
+                let mut env = env.clone();
+                env.insert(x.clone(), Type::Ptr(Tags::one(*tag)));
+                infer_block(block, env, reachable && case_reachable, output, info)?;
+            }
+            if let Some(def) = def {
+                let def_tags = match tags {
+                    Tags::Any => Tags::Any,
+                    Tags::Only(tags) => Tags::Only(
+                        tags.into_iter()
+                            .filter(|tag| !cases.contains_key(tag))
+                            .collect(),
+                    ),
+                };
+                let def_reachable = def_tags != Tags::Only(IndexSet::new());
+                if reachable && !def_reachable {
+                    info.warnings.push(Warning::UnreachableDefault(x.clone()));
+                }
+                env.insert(x.clone(), Type::Ptr(def_tags));
+                infer_block(def, env, reachable && def_reachable, output, info)?;
+            }
+        }
+        Ctrl::MatchSymbol(x, cases, def) => {
+            get_ptr(x, &env)?;
+            for block in cases.values() {
+                infer_block(block, env.clone(), reachable, output, info)?;
+            }
+            if let Some(def) = def {
+                infer_block(def, env, reachable, output, info)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        func,
+        lem::eval::eval_step,
+        tag::{ContTag, ExprTag},
+    };
+
+    #[test]
+    fn eval_step_is_well_typed() {
+        let info = eval_step().infer_types().unwrap();
+        assert_eq!(info.output.len(), 3);
+        // the step function never deconstructs atoms
+        assert!(info
+            .warnings
+            .iter()
+            .all(|warning| !matches!(warning, Warning::DeconsAtom(..))));
+    }
+
+    #[test]
+    fn tag_flow() {
+        let func = func!(foo(x): 2 => {
+            let one = Num(1);
+            let nil = Symbol("nil");
+            let t: Cont::Terminal;
+            match x.tag {
+                Expr::Num => {
+                    let y = add(x, one);
+                    match y.tag {
+                        Expr::Num => {
+                            return (y, t)
+                        }
+                        Expr::Char => {
+                            // unreachable, so no warning for deconstructing `y`
+                            let (_a, _b) = decons2(y);
+                            return (x, t)
+                        }
+                    }
+                }
+                Expr::Cons => {
+                    match x.tag {
+                        Expr::Num => {
+                            return (x, t)
+                        }
+                    };
+                    let (car, _cdr) = decons2(x);
+                    return (car, nil)
+                }
+            };
+            let (a, _b) = decons2(one);
+            return (a, t)
+        });
+        let info = func.infer_types().unwrap();
+        let var = |name: &str| {
+            info.types
+                .keys()
+                .find(|var| var.name().starts_with(&format!("{name}#")))
+                .unwrap()
+                .clone()
+        };
+        assert_eq!(
+            info.warnings,
+            [
+                Warning::UnreachableCase(var("y"), Tag::Expr(ExprTag::Char)),
+                Warning::UnreachableCase(var("x"), Tag::Expr(ExprTag::Num)),
+                Warning::DeconsAtom(var("one"), IndexSet::from([Tag::Expr(ExprTag::Num)])),
+            ]
+        );
+        assert_eq!(
+            info.types[&var("t")],
+            Type::Ptr(Tags::one(Tag::Cont(ContTag::Terminal)))
+        );
+        assert_eq!(info.types[&var("a")], Type::ANY);
+        assert_eq!(info.output[0], Type::ANY);
+        assert_eq!(
+            info.output[1],
+            Type::Ptr(Tags::Only(IndexSet::from([
+                Tag::Cont(ContTag::Terminal),
+                Tag::Expr(ExprTag::Nil)
+            ])))
+        );
+        assert_eq!(info.unreachable_cases().count(), 2);
+    }
+
+    #[test]
+    fn type_mismatches() {
+        let func = func!(foo(x): 1 => {
+            let b = eq_tag(x, x);
+            let _y: Expr::Cons = cons2(x, b);
+            return (x)
+        });
+        let err = func.infer_types().unwrap_err().to_string();
+        assert!(err.starts_with("Variable b#"));
+        assert!(err.ends_with("is a boolean, but a pointer was expected."));
+    }
+}
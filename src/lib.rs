@@ -30,6 +30,6 @@ mod writer;
 pub mod z_data;
 pub use num::Num;
 pub use symbol::Symbol;
-pub use uint::UInt;
+pub use uint::{UInt, UIntError};
 
 pub use z_data::{z_cont, z_expr, z_ptr, z_store};
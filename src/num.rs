@@ -260,7 +260,8 @@ impl<F: LurkField> From<u64> for Num<F> {
 impl<F: LurkField> From<UInt> for Num<F> {
     fn from(n: UInt) -> Self {
         match n {
-            UInt::U64(n) => Num::<F>::U64(n),
+            UInt::U128(n) => Num::<F>::Scalar(F::from_u128(n)),
+            n => Num::<F>::U64(u128::from(n) as u64),
         }
     }
 }
@@ -38,24 +38,24 @@ impl<F: LurkField> Store<F> {
     pub fn read(&self, input: &str) -> Result<Ptr<F>, Error> {
         let state = State::init_lurk_state().rccell();
         match preceded(
-            syntax::parse_space,
+            syntax::parse_space(state.clone(), false),
             syntax::parse_syntax(state, false, false),
         )
         .parse(Span::new(input))
         {
-            Ok((_i, x)) => Ok(self.intern_syntax(x)),
+            Ok((_i, x)) => self.intern_syntax(x).map_err(|e| Error::Syntax(e.0)),
             Err(e) => Err(Error::Syntax(format!("{e}"))),
         }
     }
 
     pub fn read_with_state(&self, state: Rc<RefCell<State>>, input: &str) -> Result<Ptr<F>, Error> {
         match preceded(
-            syntax::parse_space,
+            syntax::parse_space(state.clone(), false),
             syntax::parse_syntax(state, false, false),
         )
         .parse(Span::new(input))
         {
-            Ok((_i, x)) => Ok(self.intern_syntax(x)),
+            Ok((_i, x)) => self.intern_syntax(x).map_err(|e| Error::Syntax(e.0)),
             Err(e) => Err(Error::Syntax(format!("{e}"))),
         }
     }
@@ -66,8 +66,16 @@ impl<F: LurkField> Store<F> {
         input: Span<'a>,
     ) -> Result<(Span<'a>, Ptr<F>, bool), Error> {
         use syntax::*;
-        match preceded(parse_space, parse_maybe_meta(state, false)).parse(input) {
-            Ok((i, Some((is_meta, x)))) => Ok((i, self.intern_syntax(x), is_meta)),
+        match preceded(
+            parse_space(state.clone(), false),
+            parse_maybe_meta(state, false),
+        )
+        .parse(input)
+        {
+            Ok((i, Some((is_meta, x)))) => {
+                let ptr = self.intern_syntax(x).map_err(|e| Error::Syntax(e.0))?;
+                Ok((i, ptr, is_meta))
+            }
             Ok((_, None)) => Err(Error::NoInput),
             Err(e) => Err(Error::Syntax(format!("{e}"))),
         }
@@ -16,6 +16,7 @@ pub enum ParseErrorKind<F: LurkField> {
     InvalidChar(String),
     Nom(ErrorKind),
     InterningError(String),
+    UnpairedMapKey,
 }
 
 impl<F: LurkField> fmt::Display for ParseErrorKind<F> {
@@ -27,6 +28,9 @@ impl<F: LurkField> fmt::Display for ParseErrorKind<F> {
             Self::ParseIntErr(e) => {
                 write!(f, "Error parsing number: {e}")
             }
+            Self::UnpairedMapKey => {
+                write!(f, "Map literal has a key without a value.")
+            }
             e => write!(f, "internal parser error {e:?}"),
         }
     }
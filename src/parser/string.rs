@@ -6,7 +6,7 @@
 use crate::field::LurkField;
 use nom::{
     branch::alt,
-    bytes::complete::{is_not, take_while_m_n},
+    bytes::complete::{is_not, tag, take_until, take_while, take_while_m_n},
     character::complete::{char, multispace1, one_of},
     combinator::{map, value, verify},
     multi::fold_many0,
@@ -198,6 +198,54 @@ pub fn parse_string<'a, F: LurkField>(
         )(from)
     }
 }
+/// Parse a raw string: `#r"..."`, with no escapes. Any number of `#`s can go
+/// between the `r` and the opening quote, and the string is closed by a quote
+/// followed by as many `#`s, so `#r#"say "hi""#` reads as `say "hi"`.
+pub fn parse_raw_string<'a, F: LurkField>() -> impl Fn(Span<'a>) -> ParseResult<'a, F, String> {
+    move |from: Span<'a>| {
+        let (i, _) = tag("#r")(from)?;
+        let (i, hashes) = take_while(|c| c == '#')(i)?;
+        let (i, _) = char('"')(i)?;
+        let closing = format!("\"{}", hashes.fragment());
+        let (i, s) = take_until(closing.as_str())(i)?;
+        let (i, _) = tag(closing.as_str())(i)?;
+        Ok((i, s.fragment().to_string()))
+    }
+}
+
+/// Escape `c` so that `parse_escaped_char` reads it back inside a literal
+/// whose delimiter or other special characters are in `must_escape`
+fn push_escaped_char(out: &mut String, c: char, must_escape: &str) {
+    match c {
+        '\\' => out.push_str("\\\\"),
+        '\n' => out.push_str("\\n"),
+        '\r' => out.push_str("\\r"),
+        '\t' => out.push_str("\\t"),
+        c if must_escape.contains(c) => {
+            out.push('\\');
+            out.push(c);
+        }
+        c if c.is_control() => out.push_str(&format!("\\u{{{:x}}}", c as u32)),
+        c => out.push(c),
+    }
+}
+
+/// Escape the contents of a `"`-delimited string literal
+pub fn escape_string(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for c in s.chars() {
+        push_escaped_char(&mut out, c, "\"");
+    }
+    out
+}
+
+/// Escape the contents of a `'`-delimited character literal
+pub fn escape_char(c: char) -> String {
+    let mut out = String::new();
+    push_escaped_char(&mut out, c, "()'");
+    out
+}
+
 #[cfg(test)]
 pub mod tests {
     use nom::Parser;
@@ -270,5 +318,55 @@ pub mod tests {
             "b)",
             Some(String::from("b")),
         );
+        test_parse(
+            parse_string('"'),
+            "\"\\u{1F600} \\u{e9}\"",
+            Some(String::from("\u{1F600} \u{e9}")),
+        );
+        test_parse(parse_string('"'), "\"\\u{110000}\"", None);
+    }
+
+    #[test]
+    fn unit_parse_raw_string() {
+        test_parse(parse_raw_string(), "#r\"\"", Some(String::from("")));
+        test_parse(
+            parse_raw_string(),
+            "#r\"a\\nb\"",
+            Some(String::from("a\\nb")),
+        );
+        test_parse(
+            parse_raw_string(),
+            "#r#\"say \"hi\"\"#",
+            Some(String::from("say \"hi\"")),
+        );
+        test_parse(
+            parse_raw_string(),
+            "#r##\"a\"#b\"##",
+            Some(String::from("a\"#b")),
+        );
+        test_parse(parse_raw_string(), "#r\"a", None);
+        test_parse(parse_raw_string(), "#r#\"a\"", None);
+    }
+
+    #[test]
+    fn unit_escape_round_trip() {
+        for s in [
+            "",
+            "a\"b",
+            "a\\b",
+            "tab\tnew\nline\r",
+            "\u{0}\u{7f}",
+            "é\u{1F600}",
+        ] {
+            test_parse(
+                parse_string('"'),
+                &format!("\"{}\"", escape_string(s)),
+                Some(String::from(s)),
+            );
+        }
+        assert_eq!(escape_string("a\"\n\u{1}"), "a\\\"\\n\\u{1}");
+        assert_eq!(escape_char('('), "\\(");
+        assert_eq!(escape_char('\''), "\\'");
+        assert_eq!(escape_char('é'), "é");
     }
 }
@@ -3,8 +3,8 @@ use std::{cell::RefCell, rc::Rc};
 use nom::{
     branch::alt,
     bytes::complete::{tag, take_till},
-    character::complete::{anychar, char, multispace0, multispace1, none_of},
-    combinator::{opt, peek, success, value},
+    character::complete::{anychar, char, multispace0, multispace1, none_of, satisfy},
+    combinator::{not, opt, peek, recognize, success, value},
     error::context,
     multi::{many0, many_till, separated_list1},
     sequence::{delimited, preceded, terminated},
@@ -32,16 +32,66 @@ pub fn parse_line_comment<F: LurkField>(i: Span<'_>) -> ParseResult<'_, F, Span<
     let (i, com) = take_till(|c| c == '\n')(i)?;
     Ok((i, com))
 }
-pub fn parse_space<F: LurkField>(i: Span<'_>) -> ParseResult<'_, F, Vec<Span<'_>>> {
-    let (i, _) = multispace0(i)?;
-    let (i, com) = many0(terminated(parse_line_comment, multispace1))(i)?;
+/// Parses a `#| ... |#` block comment, which can be nested
+pub fn parse_block_comment<F: LurkField>(i: Span<'_>) -> ParseResult<'_, F, Span<'_>> {
+    let (i, _) = tag("#|")(i)?;
+    let (i, com) = recognize(many0(alt((
+        value((), parse_block_comment::<F>),
+        value((), preceded(not(tag("|#")), anychar)),
+    ))))(i)?;
+    let (i, _) = tag("|#")(i)?;
     Ok((i, com))
 }
 
-pub fn parse_space1<F: LurkField>(i: Span<'_>) -> ParseResult<'_, F, Vec<Span<'_>>> {
-    let (i, _) = multispace1(i)?;
-    let (i, com) = many0(terminated(parse_line_comment, multispace1))(i)?;
-    Ok((i, com))
+/// Parses a `#;` datum comment, which skips the next form. The skipped form is
+/// read in the reader's state, like any other form
+pub fn parse_datum_comment<F: LurkField>(
+    state: Rc<RefCell<State>>,
+    create_unknown_packages: bool,
+) -> impl Fn(Span<'_>) -> ParseResult<'_, F, Span<'_>> {
+    move |i: Span<'_>| {
+        let (i, _) = tag("#;")(i)?;
+        recognize(preceded(
+            parse_space(state.clone(), create_unknown_packages),
+            parse_syntax(state.clone(), false, create_unknown_packages),
+        ))(i)
+    }
+}
+
+fn parse_comment<F: LurkField>(
+    state: Rc<RefCell<State>>,
+    create_unknown_packages: bool,
+) -> impl Fn(Span<'_>) -> ParseResult<'_, F, Span<'_>> {
+    move |i: Span<'_>| {
+        alt((
+            terminated(parse_line_comment, multispace1),
+            terminated(parse_block_comment, multispace0),
+            terminated(
+                parse_datum_comment(state.clone(), create_unknown_packages),
+                multispace0,
+            ),
+        ))(i)
+    }
+}
+
+pub fn parse_space<F: LurkField>(
+    state: Rc<RefCell<State>>,
+    create_unknown_packages: bool,
+) -> impl Fn(Span<'_>) -> ParseResult<'_, F, Vec<Span<'_>>> {
+    move |i: Span<'_>| {
+        let (i, _) = multispace0(i)?;
+        many0(parse_comment(state.clone(), create_unknown_packages))(i)
+    }
+}
+
+pub fn parse_space1<F: LurkField>(
+    state: Rc<RefCell<State>>,
+    create_unknown_packages: bool,
+) -> impl Fn(Span<'_>) -> ParseResult<'_, F, Vec<Span<'_>>> {
+    move |i: Span<'_>| {
+        let (i, _) = multispace1(i)?;
+        many0(parse_comment(state.clone(), create_unknown_packages))(i)
+    }
 }
 
 pub fn parse_symbol_limb<F: LurkField>(
@@ -145,8 +195,14 @@ pub fn parse_raw_symbol<F: LurkField>(
 ) -> impl Fn(Span<'_>) -> ParseResult<'_, F, SymbolRef> {
     move |from: Span<'_>| {
         let (i, _) = tag("~(")(from)?;
-        let (i, mut path) = many0(preceded(parse_space, parse_symbol_limb_raw("|()")))(i)?;
-        let (upto, _) = many_till(parse_space, tag(")"))(i)?;
+        let (i, mut path) = many0(preceded(
+            parse_space(state.clone(), create_unknown_packages),
+            parse_symbol_limb_raw("|()"),
+        ))(i)?;
+        let (upto, _) = many_till(
+            parse_space(state.clone(), create_unknown_packages),
+            tag(")"),
+        )(i)?;
         path.reverse();
         intern_path(&state, upto, &path, Some(false), create_unknown_packages)
     }
@@ -158,8 +214,14 @@ pub fn parse_raw_keyword<F: LurkField>(
 ) -> impl Fn(Span<'_>) -> ParseResult<'_, F, SymbolRef> {
     move |from: Span<'_>| {
         let (i, _) = tag("~:(")(from)?;
-        let (i, mut path) = many0(preceded(parse_space, parse_symbol_limb_raw("|()")))(i)?;
-        let (upto, _) = many_till(parse_space, tag(")"))(i)?;
+        let (i, mut path) = many0(preceded(
+            parse_space(state.clone(), create_unknown_packages),
+            parse_symbol_limb_raw("|()"),
+        ))(i)?;
+        let (upto, _) = many_till(
+            parse_space(state.clone(), create_unknown_packages),
+            tag(")"),
+        )(i)?;
         path.reverse();
         intern_path(&state, upto, &path, Some(true), create_unknown_packages)
     }
@@ -191,20 +253,39 @@ pub fn parse_uint<F: LurkField>() -> impl Fn(Span<'_>) -> ParseResult<'_, F, Syn
             success(base::LitBase::Dec),
         ))(from)?;
         let (i, digits) = base::parse_litbase_digits(base)(i)?;
-        // when more uint types are supported we can do:
-        // alt((tag("u8"), tag("u16"), tag("u32"), tag("u64"), tag("u128")))
-        let (upto, suffix) = tag("u64")(i)?;
-        match *suffix.fragment() {
-            "u64" => {
-                let (_, x) =
-                    ParseError::res(u64::from_str_radix(&digits, base.radix()), from, |e| {
-                        ParseErrorKind::ParseIntErr(e)
-                    })?;
-                let pos = Pos::from_upto(from, upto);
-                Ok((upto, Syntax::UInt(pos, UInt::U64(x))))
-            }
+        let (upto, suffix) = alt((tag("u8"), tag("u16"), tag("u32"), tag("u64"), tag("u128")))(i)?;
+        let uint = match *suffix.fragment() {
+            "u8" => u8::from_str_radix(&digits, base.radix()).map(UInt::U8),
+            "u16" => u16::from_str_radix(&digits, base.radix()).map(UInt::U16),
+            "u32" => u32::from_str_radix(&digits, base.radix()).map(UInt::U32),
+            "u64" => u64::from_str_radix(&digits, base.radix()).map(UInt::U64),
+            "u128" => u128::from_str_radix(&digits, base.radix()).map(UInt::U128),
             _ => unreachable!("implementation error in parse_nat"),
-        }
+        };
+        let (_, x) = ParseError::res(uint, from, ParseErrorKind::ParseIntErr)?;
+        let pos = Pos::from_upto(from, upto);
+        Ok((upto, Syntax::UInt(pos, x)))
+    }
+}
+
+pub fn parse_i64<F: LurkField>() -> impl Fn(Span<'_>) -> ParseResult<'_, F, Syntax<F>> {
+    move |from: Span<'_>| {
+        let (i, neg) = opt(tag("-"))(from)?;
+        let (i, base) = alt((
+            preceded(tag("0"), base::parse_litbase_code()),
+            success(base::LitBase::Dec),
+        ))(i)?;
+        let (i, digits) = base::parse_litbase_digits(base)(i)?;
+        let (upto, _) = tag("i64")(i)?;
+        let digits = if neg.is_some() {
+            format!("-{digits}")
+        } else {
+            digits
+        };
+        let x = i64::from_str_radix(&digits, base.radix());
+        let (_, x) = ParseError::res(x, from, ParseErrorKind::ParseIntErr)?;
+        let pos = Pos::from_upto(from, upto);
+        Ok((upto, Syntax::I64(pos, x)))
     }
 }
 
@@ -271,17 +352,37 @@ pub fn parse_num<F: LurkField>() -> impl Fn(Span<'_>) -> ParseResult<'_, F, Synt
 
 pub fn parse_string<F: LurkField>() -> impl Fn(Span<'_>) -> ParseResult<'_, F, Syntax<F>> {
     move |from: Span<'_>| {
-        let (upto, s) = string::parse_string('"')(from)?;
+        let (upto, s) = alt((string::parse_raw_string(), string::parse_string('"')))(from)?;
         let pos = Pos::from_upto(from, upto);
         Ok((upto, Syntax::String(pos, s)))
     }
 }
 
+/// Parses the name of a character, as in `#\newline`. The name must not be
+/// followed by more alphanumeric characters, which would make it a longer name
+fn parse_char_name<'a, F: LurkField>(
+    name: &'static str,
+    c: char,
+) -> impl Fn(Span<'a>) -> ParseResult<'a, F, char> {
+    move |from: Span<'a>| {
+        value(
+            c,
+            terminated(tag(name), not(satisfy(|c| c.is_alphanumeric()))),
+        )(from)
+    }
+}
+
 // hash syntax for chars
 pub fn parse_hash_char<F: LurkField>() -> impl Fn(Span<'_>) -> ParseResult<'_, F, Syntax<F>> {
     |from: Span<'_>| {
         let (i, _) = tag("#\\")(from)?;
-        let (upto, c) = alt((string::parse_unicode(), anychar))(i)?;
+        let (upto, c) = alt((
+            string::parse_unicode(),
+            parse_char_name("newline", '\n'),
+            parse_char_name("space", ' '),
+            parse_char_name("tab", '\t'),
+            anychar,
+        ))(i)?;
         let pos = Pos::from_upto(from, upto);
         Ok((upto, Syntax::Char(pos, c)))
     }
@@ -318,7 +419,7 @@ pub fn parse_list<F: LurkField>(
                 .set_current_package(meta_package_symbol().into())
                 .expect("meta package is available");
             let (i, h) = preceded(
-                parse_space,
+                parse_space(state.clone(), create_unknown_packages),
                 parse_symbol(state.clone(), create_unknown_packages),
             )(i)?;
             // then recover the previous package
@@ -327,7 +428,7 @@ pub fn parse_list<F: LurkField>(
                 .set_current_package(saved_package)
                 .expect("previous package is available");
             let (i, t) = many0(preceded(
-                parse_space,
+                parse_space(state.clone(), create_unknown_packages),
                 parse_syntax(state.clone(), false, create_unknown_packages),
             ))(i)?;
             let mut xs = vec![h];
@@ -335,18 +436,21 @@ pub fn parse_list<F: LurkField>(
             (i, xs)
         } else {
             many0(preceded(
-                parse_space,
+                parse_space(state.clone(), create_unknown_packages),
                 parse_syntax(state.clone(), false, create_unknown_packages),
             ))(i)?
         };
         let (i, end) = opt(preceded(
-            preceded(parse_space, tag(".")),
             preceded(
-                parse_space,
+                parse_space(state.clone(), create_unknown_packages),
+                tag("."),
+            ),
+            preceded(
+                parse_space(state.clone(), create_unknown_packages),
                 parse_syntax(state.clone(), false, create_unknown_packages),
             ),
         ))(i)?;
-        let (i, _) = parse_space(i)?;
+        let (i, _) = parse_space(state.clone(), create_unknown_packages)(i)?;
         let (upto, _) = tag(")")(i)?;
         let pos = Pos::from_upto(from, upto);
         if let Some(end) = end {
@@ -357,6 +461,31 @@ pub fn parse_list<F: LurkField>(
     }
 }
 
+pub fn parse_map<F: LurkField>(
+    state: Rc<RefCell<State>>,
+    create_unknown_packages: bool,
+) -> impl Fn(Span<'_>) -> ParseResult<'_, F, Syntax<F>> {
+    move |from: Span<'_>| {
+        let (i, _) = tag("{")(from)?;
+        let (i, xs) = many0(preceded(
+            parse_space(state.clone(), create_unknown_packages),
+            parse_syntax(state.clone(), false, create_unknown_packages),
+        ))(i)?;
+        let (i, _) = parse_space(state.clone(), create_unknown_packages)(i)?;
+        let (upto, _) = tag("}")(i)?;
+        if xs.len() % 2 != 0 {
+            return ParseError::throw(from, ParseErrorKind::UnpairedMapKey);
+        }
+        let mut xs = xs.into_iter();
+        let mut kvs = Vec::new();
+        while let (Some(k), Some(v)) = (xs.next(), xs.next()) {
+            kvs.push((k, v));
+        }
+        let pos = Pos::from_upto(from, upto);
+        Ok((upto, Syntax::Map(pos, kvs)))
+    }
+}
+
 pub fn parse_quote<F: LurkField>(
     state: Rc<RefCell<State>>,
     create_unknown_packages: bool,
@@ -374,6 +503,38 @@ pub fn parse_quote<F: LurkField>(
     }
 }
 
+pub fn parse_quasiquote<F: LurkField>(
+    state: Rc<RefCell<State>>,
+    create_unknown_packages: bool,
+) -> impl Fn(Span<'_>) -> ParseResult<'_, F, Syntax<F>> {
+    move |from: Span<'_>| {
+        let (i, _) = tag("`")(from)?;
+        let (upto, s) = parse_syntax(state.clone(), false, create_unknown_packages)(i)?;
+        let pos = Pos::from_upto(from, upto);
+        Ok((upto, Syntax::Quasiquote(pos, Box::new(s))))
+    }
+}
+
+pub fn parse_unquote<F: LurkField>(
+    state: Rc<RefCell<State>>,
+    create_unknown_packages: bool,
+) -> impl Fn(Span<'_>) -> ParseResult<'_, F, Syntax<F>> {
+    move |from: Span<'_>| {
+        let (i, _) = tag(",")(from)?;
+        let (i, splicing) = opt(tag("@"))(i)?;
+        let (upto, s) = preceded(
+            parse_space(state.clone(), create_unknown_packages),
+            parse_syntax(state.clone(), false, create_unknown_packages),
+        )(i)?;
+        let pos = Pos::from_upto(from, upto);
+        if splicing.is_some() {
+            Ok((upto, Syntax::UnquoteSplicing(pos, Box::new(s))))
+        } else {
+            Ok((upto, Syntax::Unquote(pos, Box::new(s))))
+        }
+    }
+}
+
 // top-level syntax parser
 pub fn parse_syntax<F: LurkField>(
     state: Rc<RefCell<State>>,
@@ -387,7 +548,9 @@ pub fn parse_syntax<F: LurkField>(
                 "list",
                 parse_list(state.clone(), meta, create_unknown_packages),
             ),
+            context("map", parse_map(state.clone(), create_unknown_packages)),
             parse_uint(),
+            parse_i64(),
             parse_num(),
             context(
                 "symbol",
@@ -395,6 +558,14 @@ pub fn parse_syntax<F: LurkField>(
             ),
             parse_string(),
             context("quote", parse_quote(state.clone(), create_unknown_packages)),
+            context(
+                "quasiquote",
+                parse_quasiquote(state.clone(), create_unknown_packages),
+            ),
+            context(
+                "unquote",
+                parse_unquote(state.clone(), create_unknown_packages),
+            ),
             parse_hash_char(),
         ))(from)
     }
@@ -448,6 +619,12 @@ pub mod tests {
             Some(str!("fo\u{00}o"))
         ));
         assert!(test(parse_string(), "\"foo\\   \"", Some(str!("foo"))));
+        assert!(test(parse_string(), r#"#r"a\b""#, Some(str!("a\\b"))));
+        assert!(test(
+            parse_string(),
+            r##"#r#"say "hi""#"##,
+            Some(str!("say \"hi\""))
+        ));
     }
 
     #[test]
@@ -773,6 +950,15 @@ pub mod tests {
             r"#\u{8f}",
             Some(char!('\u{8f}'))
         ));
+        assert!(test(parse_hash_char(), r"#\newline", Some(char!('\n'))));
+        assert!(test(parse_hash_char(), r"#\space", Some(char!(' '))));
+        assert!(test(parse_hash_char(), r"#\tab", Some(char!('\t'))));
+        assert!(test(parse_hash_char(), r"#\n", Some(char!('n'))));
+        assert!(test(
+            parse_syntax(state(), false, false),
+            r"(#\space #\s)",
+            Some(list!([char!(' '), char!('s')]))
+        ));
     }
 
     #[test]
@@ -816,6 +1002,94 @@ pub mod tests {
         ));
     }
 
+    #[test]
+    fn unit_parse_quasiquote() {
+        let state_ = State::default().rccell();
+        let state = || state_.clone();
+        assert!(test(
+            parse_syntax(state(), false, true),
+            "`a",
+            Some(Syntax::Quasiquote(Pos::No, Box::new(symbol!(["a"]))))
+        ));
+        assert!(test(
+            parse_syntax(state(), false, true),
+            ",a",
+            Some(Syntax::Unquote(Pos::No, Box::new(symbol!(["a"]))))
+        ));
+        assert!(test(
+            parse_syntax(state(), false, true),
+            ",@a",
+            Some(Syntax::UnquoteSplicing(Pos::No, Box::new(symbol!(["a"]))))
+        ));
+        assert!(test(
+            parse_syntax(state(), false, true),
+            "`(a ,b ,@c)",
+            Some(Syntax::Quasiquote(
+                Pos::No,
+                Box::new(list!([
+                    symbol!(["a"]),
+                    Syntax::Unquote(Pos::No, Box::new(symbol!(["b"]))),
+                    Syntax::UnquoteSplicing(Pos::No, Box::new(symbol!(["c"])))
+                ]))
+            ))
+        ));
+        assert!(test(
+            parse_syntax(state(), false, true),
+            "`(a . ,b)",
+            Some(Syntax::Quasiquote(
+                Pos::No,
+                Box::new(list!(
+                    [symbol!(["a"])],
+                    Syntax::Unquote(Pos::No, Box::new(symbol!(["b"])))
+                ))
+            ))
+        ));
+    }
+
+    #[test]
+    fn unit_parse_uint() {
+        let uint = |x: UInt| Syntax::<Scalar>::UInt(Pos::No, x);
+        assert!(test(parse_uint(), "0u8", Some(uint(UInt::U8(0)))));
+        assert!(test(parse_uint(), "255u8", Some(uint(UInt::U8(255)))));
+        assert!(test(parse_uint(), "256u8", None));
+        assert!(test(
+            parse_uint(),
+            "0xffffu16",
+            Some(uint(UInt::U16(65535)))
+        ));
+        assert!(test(parse_uint(), "65536u16", None));
+        assert!(test(parse_uint(), "0b101u32", Some(uint(UInt::U32(5)))));
+        assert!(test(parse_uint(), "4294967296u32", None));
+        assert!(test(parse_uint(), "42u64", Some(uint(UInt::U64(42)))));
+        assert!(test(
+            parse_uint(),
+            "340282366920938463463374607431768211455u128",
+            Some(uint(UInt::U128(u128::MAX)))
+        ));
+        assert!(test(
+            parse_uint(),
+            "340282366920938463463374607431768211456u128",
+            None
+        ));
+        assert!(test(parse_uint(), "1u7", None));
+    }
+
+    #[test]
+    fn unit_parse_i64() {
+        let int = |x: i64| Syntax::<Scalar>::I64(Pos::No, x);
+        assert!(test(parse_i64(), "0i64", Some(int(0))));
+        assert!(test(parse_i64(), "-1i64", Some(int(-1))));
+        assert!(test(parse_i64(), "0xffi64", Some(int(255))));
+        assert!(test(parse_i64(), "-0x10i64", Some(int(-16))));
+        assert!(test(
+            parse_i64(),
+            "-9223372036854775808i64",
+            Some(int(i64::MIN))
+        ));
+        assert!(test(parse_i64(), "9223372036854775808i64", None));
+        assert!(test(parse_i64(), "1u64", None));
+    }
+
     #[test]
     fn unit_parse_num() {
         assert!(test(parse_num(), "0", Some(num!(0))));
@@ -987,6 +1261,86 @@ pub mod tests {
         assert!(test(parse_syntax(state(), false, true), "#", None));
     }
 
+    #[test]
+    fn unit_parse_comments() {
+        fn space_then(
+            state: Rc<RefCell<State>>,
+        ) -> impl Fn(Span<'_>) -> ParseResult<'_, Scalar, Syntax<Scalar>> {
+            move |i| {
+                preceded(
+                    parse_space(state.clone(), true),
+                    parse_syntax(state.clone(), false, true),
+                )(i)
+            }
+        }
+        let state_ = State::default().rccell();
+        let state = || state_.clone();
+        assert!(test(space_then(state()), "; a\nb", Some(symbol!(["b"]))));
+        assert!(test(space_then(state()), "#| a |# b", Some(symbol!(["b"]))));
+        assert!(test(space_then(state()), "#|a|#b", Some(symbol!(["b"]))));
+        assert!(test(
+            space_then(state()),
+            "#| a #| b |# c |# d",
+            Some(symbol!(["d"]))
+        ));
+        assert!(test(space_then(state()), "#| a #| b |# c", None));
+        assert!(test(space_then(state()), "#| a", None));
+        assert!(test(space_then(state()), "#;a b", Some(symbol!(["b"]))));
+        assert!(test(
+            space_then(state()),
+            "#; (a (b)) c",
+            Some(symbol!(["c"]))
+        ));
+        assert!(test(space_then(state()), "#;#;a b c", Some(symbol!(["c"]))));
+        assert!(test(
+            space_then(state()),
+            "#; #| a |# b c",
+            Some(symbol!(["c"]))
+        ));
+        assert!(test(space_then(state()), "#;", None));
+        assert!(test(
+            parse_syntax(state(), false, true),
+            "(a #| b |# c #;d)",
+            Some(list!([symbol!(["a"]), symbol!(["c"])])),
+        ));
+        assert!(test(
+            parse_syntax(state(), false, true),
+            "(a #;(b c) . d)",
+            Some(list!([symbol!(["a"])], symbol!(["d"]))),
+        ));
+        assert!(test(
+            parse_syntax(state(), false, true),
+            "{1 #;2 3}",
+            Some(Syntax::Map(Pos::No, vec![(num!(1), num!(3))])),
+        ));
+
+        // positions keep counting lines and columns inside comments
+        let input = Span::new("#| a\n #| b\n |# |#\n#;(c\n d) ef");
+        let (_, x) = space_then(state())(input).unwrap();
+        let Pos::Pos {
+            from_offset,
+            from_line,
+            from_column,
+            ..
+        } = x.get_pos()
+        else {
+            panic!("missing position")
+        };
+        assert_eq!((*from_offset, *from_line, *from_column), (27, 5, 5));
+
+        // the skipped form is read in the reader's state, so it can't create
+        // packages the reader wouldn't create
+        let lurk_state = State::init_lurk_state().rccell();
+        let read = |input| {
+            preceded(
+                parse_space::<Scalar>(lurk_state.clone(), false),
+                parse_syntax(lurk_state.clone(), false, false),
+            )(Span::new(input))
+        };
+        assert!(read("#;a c").is_ok());
+        assert!(read("#;unknown.a c").is_err());
+    }
+
     #[test]
     fn test_minus_zero_symbol() {
         let x: Syntax<Scalar> = symbol!(["-0"]);
@@ -4,11 +4,14 @@ use abomonation::Abomonation;
 use ff::{Field, PrimeField};
 use nova::{
     supernova::{
-        self, error::SuperNovaError, AuxParams, CircuitDigests, NonUniformCircuit, RecursiveSNARK,
+        self,
+        error::SuperNovaError,
+        snark::{CompressedSNARK, ProverKey, VerifierKey},
+        AuxParams, CircuitDigests, NonUniformCircuit, RecursiveSNARK,
     },
     traits::{
         circuit_supernova::{StepCircuit as SuperStepCircuit, TrivialSecondaryCircuit},
-        snark::default_ck_hint,
+        snark::{BatchedRelaxedR1CSSNARKTrait, RelaxedR1CSSNARKTrait},
         Engine,
     },
 };
@@ -23,7 +26,7 @@ use crate::{
     field::LurkField,
     lem::eval::EvalConfig,
     proof::{
-        nova::{CurveCycleEquipped, NovaCircuitShape, E1, E2},
+        nova::{CurveCycleEquipped, NovaCircuitShape, E1, E2, EE1, EE2},
         {EvaluationStore, FrameLike, MultiFrameTrait, Prover},
     },
 };
@@ -31,6 +34,12 @@ use crate::{
 /// Type alias for a Trivial Test Circuit with G2 scalar field elements.
 pub type C2<F> = TrivialSecondaryCircuit<<E2<F> as Engine>::Scalar>;
 
+/// Type alias for the batched Relaxed R1CS Spartan SNARK using G1 group elements, EE1.
+/// SuperNova compresses all of its running instances at once, so the primary SNARK has to be batched.
+pub type SS1<F> = nova::spartan::batched::BatchedRelaxedR1CSSNARK<E1<F>, EE1<F>>;
+/// Type alias for the Relaxed R1CS Spartan SNARK using G2 group elements, EE2.
+pub type SS2<F> = nova::spartan::snark::RelaxedR1CSSNARK<E2<F>, EE2<F>>;
+
 /// Type alias for SuperNova Aux Parameters with the curve cycle types defined above.
 pub type SuperNovaAuxParams<F> = AuxParams<E1<F>, E2<F>>;
 
@@ -46,9 +55,42 @@ where
 {
     /// Public params for SuperNova.
     pub pp: SuperNovaPublicParams<F, SC>,
-    // SuperNova does not yet have a `CompressedSNARK`.
-    // pk: ProverKey<E1<F>, E2<F>, SC, C2<F>, SS1<F>, SS2<F>>,
-    // vk: VerifierKey<E1<F>, E2<F>, SC, C2<F>, SS1<F>, SS2<F>>,
+    /// Prover key for the SuperNova `CompressedSNARK`.
+    pub pk: SuperNovaProverKey<F, SC>,
+    /// Verifier key for the SuperNova `CompressedSNARK`.
+    pub vk: SuperNovaVerifierKey<F, SC>,
+}
+
+/// Type alias for the prover key of a SuperNova `CompressedSNARK`.
+pub type SuperNovaProverKey<F, C1> = ProverKey<E1<F>, E2<F>, C1, C2<F>, SS1<F>, SS2<F>>;
+
+/// Type alias for the verifier key of a SuperNova `CompressedSNARK`.
+pub type SuperNovaVerifierKey<F, C1> = VerifierKey<E1<F>, E2<F>, C1, C2<F>, SS1<F>, SS2<F>>;
+
+/// Pairs the prover and verifier keys of a SuperNova `CompressedSNARK`, as they are cached together.
+#[derive(Serialize, Deserialize)]
+#[serde(bound = "")]
+pub struct CompressionKeys<F: CurveCycleEquipped, SC: SuperStepCircuit<F>>
+where
+    <<E1<F> as Engine>::Scalar as PrimeField>::Repr: Abomonation,
+    <<E2<F> as Engine>::Scalar as PrimeField>::Repr: Abomonation,
+{
+    /// Prover key for the SuperNova `CompressedSNARK`.
+    pub pk: SuperNovaProverKey<F, SC>,
+    /// Verifier key for the SuperNova `CompressedSNARK`.
+    pub vk: SuperNovaVerifierKey<F, SC>,
+}
+
+impl<F: CurveCycleEquipped, SC: SuperStepCircuit<F>> CompressionKeys<F, SC>
+where
+    <<E1<F> as Engine>::Scalar as PrimeField>::Repr: Abomonation,
+    <<E2<F> as Engine>::Scalar as PrimeField>::Repr: Abomonation,
+{
+    /// Generates the prover and verifier keys for compressing proofs over `pp`.
+    pub fn setup(pp: &SuperNovaPublicParams<F, SC>) -> Result<Self, SuperNovaError> {
+        let (pk, vk) = CompressedSNARK::<_, _, _, _, SS1<F>, SS2<F>>::setup(pp)?;
+        Ok(Self { pk, vk })
+    }
 }
 
 impl<F: CurveCycleEquipped, SC: SuperStepCircuit<F>> Index<usize> for PublicParams<F, SC>
@@ -76,7 +118,8 @@ where
     }
 }
 
-/// Generates the running claim params for the SuperNova proving system.
+/// Generates the running claim params for the SuperNova proving system, along
+/// with the keys for compressing its proofs.
 pub fn public_params<
     'a,
     F: CurveCycleEquipped,
@@ -85,34 +128,44 @@ pub fn public_params<
 >(
     rc: usize,
     lang: Arc<Lang<F, C>>,
-) -> PublicParams<F, M>
+) -> Result<PublicParams<F, M>, SuperNovaError>
 where
     <<E1<F> as Engine>::Scalar as ff::PrimeField>::Repr: Abomonation,
     <<E2<F> as Engine>::Scalar as ff::PrimeField>::Repr: Abomonation,
 {
     let folding_config = Arc::new(FoldingConfig::new_nivc(lang, rc));
     let non_uniform_circuit = M::blank(folding_config, 0);
-    // TODO: use `&*SS::commitment_key_floor()`, where `SS<G>: RelaxedR1CSSNARKTrait<G>`` when https://github.com/lurk-lab/arecibo/issues/27 closes
+    let commitment_size_hint1 = <SS1<F> as BatchedRelaxedR1CSSNARKTrait<E1<F>>>::ck_floor();
+    let commitment_size_hint2 = <SS2<F> as RelaxedR1CSSNARKTrait<E2<F>>>::ck_floor();
+
     let pp = SuperNovaPublicParams::<F, M>::setup(
         &non_uniform_circuit,
-        &*default_ck_hint(),
-        &*default_ck_hint(),
+        &*commitment_size_hint1,
+        &*commitment_size_hint2,
     );
-    PublicParams { pp }
+    let CompressionKeys { pk, vk } = CompressionKeys::setup(&pp)?;
+    Ok(PublicParams { pp, pk, vk })
 }
 
 /// An enum representing the two types of proofs that can be generated and verified.
 #[derive(Serialize, Deserialize)]
-pub enum Proof<'a, F: CurveCycleEquipped, C: Coprocessor<F>, M: MultiFrameTrait<'a, F, C>>
-where
+#[serde(bound = "")]
+pub enum Proof<
+    'a,
+    F: CurveCycleEquipped,
+    C: Coprocessor<F>,
+    M: MultiFrameTrait<'a, F, C> + SuperStepCircuit<F>,
+> where
     <<E1<F> as Engine>::Scalar as ff::PrimeField>::Repr: Abomonation,
     <<E2<F> as Engine>::Scalar as ff::PrimeField>::Repr: Abomonation,
 {
     /// A proof for the intermediate steps of a recursive computation
-    Recursive(Box<RecursiveSNARK<E1<F>, E2<F>>>),
+    Recursive(Box<RecursiveSNARK<E1<F>, E2<F>>>, PhantomData<&'a C>),
     /// A proof for the final step of a recursive computation
-    // Compressed(Box<CompressedSNARK<E1<F>, E2<F>, C1<'a, F, C>, C2<F>, SS1<F>, SS2<F>>>),
-    Compressed(PhantomData<&'a (C, M)>),
+    Compressed(
+        Box<CompressedSNARK<E1<F>, E2<F>, M, C2<F>, SS1<F>, SS2<F>>>,
+        PhantomData<&'a C>,
+    ),
 }
 
 impl<
@@ -172,13 +225,29 @@ where
 
         // This probably should be made unnecessary.
         Ok((
-            Self::Recursive(Box::new(
-                recursive_snark_option.expect("RecursiveSNARK missing"),
-            )),
+            Self::Recursive(
+                Box::new(recursive_snark_option.expect("RecursiveSNARK missing")),
+                PhantomData,
+            ),
             last_circuit_index,
         ))
     }
 
+    /// Compresses the proof using a (batched Spartan) Snark (finishing step)
+    pub fn compress(self, pp: &PublicParams<F, M>) -> Result<Self, ProofError> {
+        match &self {
+            Self::Recursive(recursive_snark, _) => Ok(Self::Compressed(
+                Box::new(CompressedSNARK::<_, _, _, _, SS1<F>, SS2<F>>::prove(
+                    &pp.pp,
+                    &pp.pk,
+                    recursive_snark,
+                )?),
+                PhantomData,
+            )),
+            Self::Compressed(..) => Ok(self),
+        }
+    }
+
     /// Verifies the proof given the claim, which (for now), contains the public parameters.
     pub fn verify(
         &self,
@@ -193,8 +262,8 @@ where
         let zi_secondary = &z0_secondary;
 
         let (zi_primary_verified, zi_secondary_verified) = match self {
-            Self::Recursive(p) => p.verify(&pp.pp, circuit_index, z0_primary, &z0_secondary),
-            Self::Compressed(_) => unimplemented!(),
+            Self::Recursive(p, _) => p.verify(&pp.pp, circuit_index, z0_primary, &z0_secondary),
+            Self::Compressed(p, _) => p.verify(&pp.pp, &pp.vk, z0_primary, &z0_secondary),
         }?;
 
         Ok(zi_primary == zi_primary_verified && *zi_secondary == zi_secondary_verified)
@@ -1,4 +1,5 @@
 mod nova_tests_lem;
+mod supernova_tests;
 
 use abomonation::Abomonation;
 use bellpepper::util_cs::{metric_cs::MetricCS, witness_cs::WitnessCS, Comparable};
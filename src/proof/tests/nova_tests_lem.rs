@@ -15,6 +15,12 @@ use super::{nova_test_full_aux, nova_test_full_aux2, test_aux, DEFAULT_REDUCTION
 
 type M1<'a, Fr> = C1LEM<'a, Fr, Coproc<Fr>>;
 
+/// The error continuation for the error `code`, caused by the expression read
+/// from `expr`
+fn error_cont(s: &Store<Fr>, code: &str, expr: &str) -> Ptr<Fr> {
+    s.intern_cont_error(code, s.read_with_default_state(expr).unwrap())
+}
+
 #[test]
 fn test_prove_self_evaluating() {
     let s = &Store::<Fr>::default();
@@ -183,7 +189,7 @@ fn test_prove_num_equal() {
 fn test_prove_invalid_num_equal() {
     let s = &Store::<Fr>::default();
     let expected = s.intern_nil();
-    let error = s.cont_error();
+    let error = error_cont(s, "type-mismatch", "nil");
     test_aux::<_, _, M1<'_, _>>(
         s,
         "(= 5 nil)",
@@ -251,7 +257,7 @@ fn test_prove_equal() {
 #[test]
 fn test_prove_quote_end_is_nil_error() {
     let s = &Store::<Fr>::default();
-    let error = s.cont_error();
+    let error = error_cont(s, "invalid-form", "(quote (1) (2))");
     test_aux::<_, _, M1<'_, _>>(
         s,
         "(quote (1) (2))",
@@ -298,7 +304,7 @@ fn test_prove_if() {
 fn test_prove_if_end_is_nil_error() {
     let s = &Store::<Fr>::default();
     let expected = Ptr::num_u64(5);
-    let error = s.cont_error();
+    let error = error_cont(s, "invalid-form", "5");
     test_aux::<_, _, M1<'_, _>>(
         s,
         "(if nil 5 6 7)",
@@ -596,7 +602,7 @@ fn test_prove_evaluate_sum() {
 fn test_prove_binop_rest_is_nil() {
     let s = &Store::<Fr>::default();
     let expected = Ptr::num_u64(9);
-    let error = s.cont_error();
+    let error = error_cont(s, "arity-mismatch", "9");
     test_aux::<_, _, M1<'_, _>>(
         s,
         "(- 9 8 7)",
@@ -621,30 +627,45 @@ fn test_prove_binop_rest_is_nil() {
 
 fn op_syntax_error<T: Op + Copy>() {
     let s = &Store::<Fr>::default();
-    let error = s.cont_error();
     let test = |op: T| {
         let name = op.symbol_name();
 
         if !op.supports_arity(0) {
             let expr = format!("({name})");
             tracing::debug!("{:?}", &expr);
+            // `eval` is a special form
+            let code = if name == "eval" {
+                "invalid-form"
+            } else {
+                "arity-mismatch"
+            };
+            let error = error_cont(s, code, &expr);
             test_aux::<_, _, M1<'_, _>>(s, &expr, None, None, Some(error), None, 1, &None);
         }
         if !op.supports_arity(1) {
             let expr = format!("({name} 123)");
             tracing::debug!("{:?}", &expr);
+            let error = error_cont(s, "arity-mismatch", &expr);
             test_aux::<_, _, M1<'_, _>>(s, &expr, None, None, Some(error), None, 1, &None);
         }
         if !op.supports_arity(2) {
             let expr = format!("({name} 123 456)");
             tracing::debug!("{:?}", &expr);
+            let error = error_cont(s, "arity-mismatch", &expr);
             test_aux::<_, _, M1<'_, _>>(s, &expr, None, None, Some(error), None, 1, &None);
         }
 
         if !op.supports_arity(3) {
             let expr = format!("({name} 123 456 789)");
             tracing::debug!("{:?}", &expr);
-            let iterations = if op.supports_arity(2) { 2 } else { 1 };
+            // binary operators only find out about the third argument after
+            // evaluating the first one
+            let (offending, iterations) = if op.supports_arity(2) {
+                ("123", 2)
+            } else {
+                (expr.as_str(), 1)
+            };
+            let error = error_cont(s, "arity-mismatch", offending);
             test_aux::<_, _, M1<'_, _>>(s, &expr, None, None, Some(error), None, iterations, &None);
         }
     };
@@ -723,7 +744,7 @@ fn test_prove_quotient() {
 fn test_prove_error_div_by_zero() {
     let s = &Store::<Fr>::default();
     let expected = Ptr::num_u64(0);
-    let error = s.cont_error();
+    let error = error_cont(s, "div-by-zero", "0");
     test_aux::<_, _, M1<'_, _>>(
         s,
         "(/ 21 0)",
@@ -740,7 +761,7 @@ fn test_prove_error_div_by_zero() {
 fn test_prove_error_invalid_type_and_not_cons() {
     let s = &Store::<Fr>::default();
     let expected = s.intern_nil();
-    let error = s.cont_error();
+    let error = error_cont(s, "type-mismatch", "nil");
     test_aux::<_, _, M1<'_, _>>(
         s,
         "(/ 21 nil)",
@@ -753,6 +774,24 @@ fn test_prove_error_invalid_type_and_not_cons() {
     );
 }
 
+#[test]
+fn test_prove_error_cause() {
+    let s = &Store::<Fr>::default();
+    // the offending expression is the value computed by `cons`
+    let expected = s.read_with_default_state("(2 . 3)").unwrap();
+    let error = s.intern_cont_error("type-mismatch", expected);
+    test_aux::<_, _, M1<'_, _>>(
+        s,
+        "(+ 1 (cons 2 3))",
+        Some(expected),
+        None,
+        Some(error),
+        None,
+        6,
+        &None,
+    );
+}
+
 #[test]
 #[ignore]
 fn test_prove_adder() {
@@ -796,7 +835,7 @@ fn test_prove_current_env_simple() {
 fn test_prove_current_env_rest_is_nil_error() {
     let s = &Store::<Fr>::default();
     let expected = s.read_with_default_state("(current-env a)").unwrap();
-    let error = s.cont_error();
+    let error = error_cont(s, "invalid-form", "(current-env a)");
     test_aux::<_, _, M1<'_, _>>(
         s,
         "(current-env a)",
@@ -831,7 +870,7 @@ fn test_prove_let_simple() {
 #[test]
 fn test_prove_let_end_is_nil_error() {
     let s = &Store::<Fr>::default();
-    let error = s.cont_error();
+    let error = error_cont(s, "invalid-form", "(let ((a 1 2)) a)");
     test_aux::<_, _, M1<'_, _>>(
         s,
         "(let ((a 1 2)) a)",
@@ -847,7 +886,7 @@ fn test_prove_let_end_is_nil_error() {
 #[test]
 fn test_prove_letrec_end_is_nil_error() {
     let s = &Store::<Fr>::default();
-    let error = s.cont_error();
+    let error = error_cont(s, "invalid-form", "(letrec ((a 1 2)) a)");
     test_aux::<_, _, M1<'_, _>>(
         s,
         "(letrec ((a 1 2)) a)",
@@ -863,7 +902,7 @@ fn test_prove_letrec_end_is_nil_error() {
 #[test]
 fn test_prove_lambda_empty_error() {
     let s = &Store::<Fr>::default();
-    let error = s.cont_error();
+    let error = error_cont(s, "invalid-form", "0");
     test_aux::<_, _, M1<'_, _>>(
         s,
         "((lambda (x)) 0)",
@@ -879,28 +918,28 @@ fn test_prove_lambda_empty_error() {
 #[test]
 fn test_prove_let_empty_error() {
     let s = &Store::<Fr>::default();
-    let error = s.cont_error();
+    let error = error_cont(s, "invalid-form", "(let)");
     test_aux::<_, _, M1<'_, _>>(s, "(let)", None, None, Some(error), None, 1, &None);
 }
 
 #[test]
 fn test_prove_let_empty_body_error() {
     let s = &Store::<Fr>::default();
-    let error = s.cont_error();
+    let error = error_cont(s, "invalid-form", "(let ((a 1)))");
     test_aux::<_, _, M1<'_, _>>(s, "(let ((a 1)))", None, None, Some(error), None, 1, &None);
 }
 
 #[test]
 fn test_prove_letrec_empty_error() {
     let s = &Store::<Fr>::default();
-    let error = s.cont_error();
+    let error = error_cont(s, "invalid-form", "(letrec)");
     test_aux::<_, _, M1<'_, _>>(s, "(letrec)", None, None, Some(error), None, 1, &None);
 }
 
 #[test]
 fn test_prove_letrec_empty_body_error() {
     let s = &Store::<Fr>::default();
-    let error = s.cont_error();
+    let error = error_cont(s, "invalid-form", "(letrec ((a 1)))");
     test_aux::<_, _, M1<'_, _>>(
         s,
         "(letrec ((a 1)))",
@@ -933,7 +972,7 @@ fn test_prove_let_body_nil() {
 #[test]
 fn test_prove_let_rest_body_is_nil_error() {
     let s = &Store::<Fr>::default();
-    let error = s.cont_error();
+    let error = error_cont(s, "invalid-form", "(let ((a 1)) a 1)");
     test_aux::<_, _, M1<'_, _>>(
         s,
         "(let ((a 1)) a 1)",
@@ -949,7 +988,7 @@ fn test_prove_let_rest_body_is_nil_error() {
 #[test]
 fn test_prove_letrec_rest_body_is_nil_error() {
     let s = &Store::<Fr>::default();
-    let error = s.cont_error();
+    let error = error_cont(s, "invalid-form", "(letrec ((a 1)) a 1)");
     test_aux::<_, _, M1<'_, _>>(
         s,
         "(letrec ((a 1)) a 1)",
@@ -1207,7 +1246,7 @@ fn test_prove_recursion_multiarg() {
         None,
         Some(terminal),
         None,
-        69,
+        63,
         &None,
     );
 }
@@ -1288,51 +1327,26 @@ fn test_prove_tail_recursion_somewhat_optimized() {
 
 #[test]
 #[ignore]
-fn test_prove_no_mutual_recursion() {
+fn test_prove_mutual_recursion() {
     let s = &Store::<Fr>::default();
     let expected = s.intern_lurk_symbol("t");
     let terminal = s.cont_terminal();
     test_aux::<_, _, M1<'_, _>>(
         s,
-        "(letrec ((even (lambda (n)
-                              (if (= 0 n)
-                                  t
-                                  (odd (- n 1)))))
-                      (odd (lambda (n)
-                             (even (- n 1)))))
-                    ;; NOTE: This is not true mutual-recursion.
-                    ;; However, it exercises the behavior of LETREC.
-                    (odd 1))",
+        "(letrec ((even? (lambda (n)
+                               (if (= n 0)
+                                   t
+                                   (odd? (- n 1)))))
+                      (odd? (lambda (n)
+                              (if (= n 0)
+                                  nil
+                                  (even? (- n 1))))))
+                    (odd? 3))",
         Some(expected),
         None,
         Some(terminal),
         None,
-        22,
-        &None,
-    );
-}
-
-#[test]
-#[ignore]
-fn test_prove_no_mutual_recursion_error() {
-    let s = &Store::<Fr>::default();
-    let error = s.cont_error();
-    test_aux::<_, _, M1<'_, _>>(
-        s,
-        "(letrec ((even (lambda (n)
-                              (if (= 0 n)
-                                  t
-                                  (odd (- n 1)))))
-                      (odd (lambda (n)
-                             (even (- n 1)))))
-                    ;; NOTE: This is not true mutual-recursion.
-                    ;; However, it exercises the behavior of LETREC.
-                    (odd 2))",
-        None,
-        None,
-        Some(error),
-        None,
-        25,
+        53,
         &None,
     );
 }
@@ -1358,28 +1372,28 @@ fn test_prove_cons1() {
 #[test]
 fn test_prove_car_end_is_nil_error() {
     let s = &Store::<Fr>::default();
-    let error = s.cont_error();
+    let error = error_cont(s, "arity-mismatch", "(car (1 2) 3)");
     test_aux::<_, _, M1<'_, _>>(s, "(car (1 2) 3)", None, None, Some(error), None, 1, &None);
 }
 
 #[test]
 fn test_prove_cdr_end_is_nil_error() {
     let s = &Store::<Fr>::default();
-    let error = s.cont_error();
+    let error = error_cont(s, "arity-mismatch", "(cdr (1 2) 3)");
     test_aux::<_, _, M1<'_, _>>(s, "(cdr (1 2) 3)", None, None, Some(error), None, 1, &None);
 }
 
 #[test]
 fn test_prove_atom_end_is_nil_error() {
     let s = &Store::<Fr>::default();
-    let error = s.cont_error();
+    let error = error_cont(s, "arity-mismatch", "(atom 123 4)");
     test_aux::<_, _, M1<'_, _>>(s, "(atom 123 4)", None, None, Some(error), None, 1, &None);
 }
 
 #[test]
 fn test_prove_emit_end_is_nil_error() {
     let s = &Store::<Fr>::default();
-    let error = s.cont_error();
+    let error = error_cont(s, "arity-mismatch", "(emit 123 4)");
     test_aux::<_, _, M1<'_, _>>(s, "(emit 123 4)", None, None, Some(error), None, 1, &None);
 }
 
@@ -1463,7 +1477,7 @@ fn test_prove_zero_arg_lambda3() {
 #[test]
 fn test_prove_zero_arg_lambda4() {
     let s = &Store::<Fr>::default();
-    let error = s.cont_error();
+    let error = error_cont(s, "arity-mismatch", "1");
     test_aux::<_, _, M1<'_, _>>(
         s,
         "((lambda () 123) 1)",
@@ -1480,7 +1494,7 @@ fn test_prove_zero_arg_lambda4() {
 fn test_prove_zero_arg_lambda5() {
     let s = &Store::<Fr>::default();
     let expected = s.read_with_default_state("(123)").unwrap();
-    let error = s.cont_error();
+    let error = error_cont(s, "not-a-function", "(123)");
     test_aux::<_, _, M1<'_, _>>(
         s,
         "(123)",
@@ -1497,7 +1511,7 @@ fn test_prove_zero_arg_lambda5() {
 fn test_prove_zero_arg_lambda6() {
     let s = &Store::<Fr>::default();
     let expected = Ptr::num_u64(123);
-    let error = s.cont_error();
+    let error = error_cont(s, "not-a-function", "123");
     test_aux::<_, _, M1<'_, _>>(
         s,
         "((emit 123))",
@@ -1635,7 +1649,7 @@ fn test_prove_multiple_letrec_bindings() {
         None,
         Some(terminal),
         None,
-        78,
+        77,
         &None,
     );
 }
@@ -1659,7 +1673,7 @@ fn test_prove_tail_call2() {
         None,
         Some(terminal),
         None,
-        84,
+        88,
         &None,
     );
 }
@@ -1679,7 +1693,7 @@ fn test_prove_multiple_letrecstar_bindings() {
         None,
         Some(terminal),
         None,
-        22,
+        21,
         &None,
     );
 }
@@ -1699,7 +1713,7 @@ fn test_prove_multiple_letrecstar_bindings_referencing() {
         None,
         Some(terminal),
         None,
-        31,
+        30,
         &None,
     );
 }
@@ -1730,7 +1744,7 @@ fn test_prove_multiple_letrecstar_bindings_recursive() {
         None,
         Some(terminal),
         None,
-        242,
+        227,
         &None,
     );
 }
@@ -1752,7 +1766,7 @@ fn test_prove_dont_discard_rest_env() {
         None,
         Some(terminal),
         None,
-        22,
+        20,
         &None,
     );
 }
@@ -1778,7 +1792,7 @@ fn test_prove_fibonacci() {
         None,
         Some(terminal),
         None,
-        89,
+        78,
         5,
         false,
         None,
@@ -1846,7 +1860,7 @@ fn test_prove_chained_functional_commitment() {
         None,
         Some(terminal),
         None,
-        39,
+        36,
         &None,
     );
 }
@@ -1971,10 +1985,139 @@ fn test_prove_strcons() {
     );
 }
 
+#[test]
+fn test_prove_string_append() {
+    let s = &Store::<Fr>::default();
+    let expected = s.intern_string("applepie");
+    let terminal = s.cont_terminal();
+    // more than four characters, so the first string is moved over two steps
+    test_aux::<_, _, M1<'_, _>>(
+        s,
+        r#"(string-append "apple" "pie")"#,
+        Some(expected),
+        None,
+        Some(terminal),
+        None,
+        7,
+        &None,
+    );
+}
+
+#[test]
+fn test_prove_substring() {
+    let s = &Store::<Fr>::default();
+    let expected = s.intern_string("ple p");
+    let terminal = s.cont_terminal();
+    test_aux::<_, _, M1<'_, _>>(
+        s,
+        r#"(substring "apple pie" 2 7)"#,
+        Some(expected),
+        None,
+        Some(terminal),
+        None,
+        10,
+        &None,
+    );
+
+    let error = error_cont(s, "out-of-range", r#""""#);
+    test_aux::<_, _, M1<'_, _>>(
+        s,
+        r#"(substring "apple" 3 9)"#,
+        None,
+        None,
+        Some(error),
+        None,
+        8,
+        &None,
+    );
+}
+
+#[test]
+fn test_prove_string_builtins() {
+    let s = &Store::<Fr>::default();
+    let terminal = s.cont_terminal();
+    let t = s.intern_lurk_symbol("t");
+    let cases = [
+        (r#"(string-length "apple")"#, Ptr::num_u64(5), 8),
+        (r#"(string< "apple" "apricot")"#, t, 4),
+        ("(num->string 2024)", s.intern_string("2024"), 6),
+        ("(symbol->string 'apple)", s.intern_string("apple"), 2),
+    ];
+    for (expr, expected, iterations) in cases {
+        test_aux::<_, _, M1<'_, _>>(
+            s,
+            expr,
+            Some(expected),
+            None,
+            Some(terminal),
+            None,
+            iterations,
+            &None,
+        );
+    }
+}
+
+#[test]
+fn test_prove_vector_builtins() {
+    let s = &Store::<Fr>::default();
+    let terminal = s.cont_terminal();
+    let num = Ptr::num_u64;
+    let cases = [
+        (
+            "(vector 1 2 3)",
+            s.intern_vector(vec![num(1), num(2), num(3)]),
+            7,
+        ),
+        ("(vector-ref (vector 1 2 3) 2)", num(3), 13),
+        (
+            "(vector-set (vector 1 2 3) 2 9)",
+            s.intern_vector(vec![num(1), num(2), num(9)]),
+            19,
+        ),
+    ];
+    for (expr, expected, iterations) in cases {
+        test_aux::<_, _, M1<'_, _>>(
+            s,
+            expr,
+            Some(expected),
+            None,
+            Some(terminal),
+            None,
+            iterations,
+            &None,
+        );
+    }
+}
+
+#[test]
+fn test_prove_type_of() {
+    let s = &Store::<Fr>::default();
+    let terminal = s.cont_terminal();
+    let cases = [
+        ("(type-of 1)", "num", 2),
+        ("(type-of '(1 2))", "cons", 2),
+        ("(type-of (lambda (x) x))", "fun", 2),
+        ("(type-of (vector 1 2))", "vec", 6),
+        ("(type-of {1 2})", "map", 2),
+    ];
+    for (expr, key, iterations) in cases {
+        test_aux::<_, _, M1<'_, _>>(
+            s,
+            expr,
+            Some(s.key(key)),
+            None,
+            Some(terminal),
+            None,
+            iterations,
+            &None,
+        );
+    }
+}
+
 #[test]
 fn test_prove_str_cons_error() {
     let s = &Store::<Fr>::default();
-    let error = s.cont_error();
+    let error = error_cont(s, "type-mismatch", "123");
     test_aux::<_, _, M1<'_, _>>(
         s,
         r"(strcons #\a 123)",
@@ -1990,7 +2133,7 @@ fn test_prove_str_cons_error() {
 #[test]
 fn test_prove_one_arg_cons_error() {
     let s = &Store::<Fr>::default();
-    let error = s.cont_error();
+    let error = error_cont(s, "arity-mismatch", r#"(cons "")"#);
     test_aux::<_, _, M1<'_, _>>(s, r#"(cons "")"#, None, None, Some(error), None, 1, &None);
 }
 
@@ -2031,7 +2174,7 @@ fn test_prove_cdr_nil() {
 #[test]
 fn test_prove_car_cdr_invalid_tag_error_sym() {
     let s = &Store::<Fr>::default();
-    let error = s.cont_error();
+    let error = error_cont(s, "unbound-var", "car");
     test_aux::<_, _, M1<'_, _>>(s, r#"(car car)"#, None, None, Some(error), None, 2, &None);
     test_aux::<_, _, M1<'_, _>>(s, r#"(cdr car)"#, None, None, Some(error), None, 2, &None);
 }
@@ -2039,7 +2182,7 @@ fn test_prove_car_cdr_invalid_tag_error_sym() {
 #[test]
 fn test_prove_car_cdr_invalid_tag_error_char() {
     let s = &Store::<Fr>::default();
-    let error = s.cont_error();
+    let error = error_cont(s, "type-mismatch", r"#\a");
     test_aux::<_, _, M1<'_, _>>(s, r"(car #\a)", None, None, Some(error), None, 2, &None);
     test_aux::<_, _, M1<'_, _>>(s, r"(cdr #\a)", None, None, Some(error), None, 2, &None);
 }
@@ -2047,7 +2190,7 @@ fn test_prove_car_cdr_invalid_tag_error_char() {
 #[test]
 fn test_prove_car_cdr_invalid_tag_error_num() {
     let s = &Store::<Fr>::default();
-    let error = s.cont_error();
+    let error = error_cont(s, "type-mismatch", "42");
     test_aux::<_, _, M1<'_, _>>(s, r#"(car 42)"#, None, None, Some(error), None, 2, &None);
     test_aux::<_, _, M1<'_, _>>(s, r#"(cdr 42)"#, None, None, Some(error), None, 2, &None);
 }
@@ -2083,7 +2226,10 @@ fn test_prove_car_cdr_of_cons() {
 #[test]
 fn test_prove_car_cdr_invalid_tag_error_lambda() {
     let s = &Store::<Fr>::default();
-    let error = s.cont_error();
+    let x = s.intern_user_symbol("x");
+    let body = s.read_with_default_state("(x)").unwrap();
+    let closure = s.intern_fun(x, body, s.intern_nil());
+    let error = s.intern_cont_error("type-mismatch", closure);
     test_aux::<_, _, M1<'_, _>>(
         s,
         r#"(car (lambda (x) x))"#,
@@ -2128,7 +2274,7 @@ fn test_prove_hide_open() {
 fn test_prove_hide_wrong_secret_type() {
     let s = &Store::<Fr>::default();
     let expr = "(hide 'x 456)";
-    let error = s.cont_error();
+    let error = error_cont(s, "type-mismatch", "456");
     test_aux::<_, _, M1<'_, _>>(s, expr, None, None, Some(error), None, 3, &None);
 }
 
@@ -2190,7 +2336,7 @@ fn test_prove_commit_open() {
 fn test_prove_commit_error() {
     let s = &Store::<Fr>::default();
     let expr = "(commit 123 456)";
-    let error = s.cont_error();
+    let error = error_cont(s, "arity-mismatch", expr);
     test_aux::<_, _, M1<'_, _>>(s, expr, None, None, Some(error), None, 1, &None);
 }
 
@@ -2198,7 +2344,7 @@ fn test_prove_commit_error() {
 fn test_prove_open_error() {
     let s = &Store::<Fr>::default();
     let expr = "(open 123 456)";
-    let error = s.cont_error();
+    let error = error_cont(s, "arity-mismatch", expr);
     test_aux::<_, _, M1<'_, _>>(s, expr, None, None, Some(error), None, 1, &None);
 }
 
@@ -2206,7 +2352,7 @@ fn test_prove_open_error() {
 fn test_prove_open_wrong_type() {
     let s = &Store::<Fr>::default();
     let expr = "(open 'asdf)";
-    let error = s.cont_error();
+    let error = error_cont(s, "type-mismatch", "asdf");
     test_aux::<_, _, M1<'_, _>>(s, expr, None, None, Some(error), None, 2, &None);
 }
 
@@ -2214,7 +2360,7 @@ fn test_prove_open_wrong_type() {
 fn test_prove_secret_wrong_type() {
     let s = &Store::<Fr>::default();
     let expr = "(secret 'asdf)";
-    let error = s.cont_error();
+    let error = error_cont(s, "type-mismatch", "asdf");
     test_aux::<_, _, M1<'_, _>>(s, expr, None, None, Some(error), None, 2, &None);
 }
 
@@ -2424,9 +2570,11 @@ fn test_prove_num_invalid_tag() {
     let expr = "(num (quote x))";
     let expr1 = "(num \"asdf\")";
     let expr2 = "(num '(1))";
-    let error = s.cont_error();
+    let error = error_cont(s, "type-mismatch", "x");
     test_aux::<_, _, M1<'_, _>>(s, expr, None, None, Some(error), None, 2, &None);
+    let error = error_cont(s, "type-mismatch", "\"asdf\"");
     test_aux::<_, _, M1<'_, _>>(s, expr1, None, None, Some(error), None, 2, &None);
+    let error = error_cont(s, "type-mismatch", "(1)");
     test_aux::<_, _, M1<'_, _>>(s, expr2, None, None, Some(error), None, 2, &None);
 }
 
@@ -2436,9 +2584,11 @@ fn test_prove_comm_invalid_tag() {
     let expr = "(comm (quote x))";
     let expr1 = "(comm \"asdf\")";
     let expr2 = "(comm '(1))";
-    let error = s.cont_error();
+    let error = error_cont(s, "type-mismatch", "x");
     test_aux::<_, _, M1<'_, _>>(s, expr, None, None, Some(error), None, 2, &None);
+    let error = error_cont(s, "type-mismatch", "\"asdf\"");
     test_aux::<_, _, M1<'_, _>>(s, expr1, None, None, Some(error), None, 2, &None);
+    let error = error_cont(s, "type-mismatch", "(1)");
     test_aux::<_, _, M1<'_, _>>(s, expr2, None, None, Some(error), None, 2, &None);
 }
 
@@ -2448,9 +2598,11 @@ fn test_prove_char_invalid_tag() {
     let expr = "(char (quote x))";
     let expr1 = "(char \"asdf\")";
     let expr2 = "(char '(1))";
-    let error = s.cont_error();
+    let error = error_cont(s, "type-mismatch", "x");
     test_aux::<_, _, M1<'_, _>>(s, expr, None, None, Some(error), None, 2, &None);
+    let error = error_cont(s, "type-mismatch", "\"asdf\"");
     test_aux::<_, _, M1<'_, _>>(s, expr1, None, None, Some(error), None, 2, &None);
+    let error = error_cont(s, "type-mismatch", "(1)");
     test_aux::<_, _, M1<'_, _>>(s, expr2, None, None, Some(error), None, 2, &None);
 }
 
@@ -2497,7 +2649,6 @@ fn test_str_car_cdr_cons() {
     let empty = s.intern_string("");
     let nil = s.intern_nil();
     let terminal = s.cont_terminal();
-    let error = s.cont_error();
 
     test_aux::<_, _, M1<'_, _>>(
         s,
@@ -2566,7 +2717,7 @@ fn test_str_car_cdr_cons() {
         r"(strcons #\a #\b)",
         None,
         None,
-        Some(error),
+        Some(error_cont(s, "type-mismatch", r"#\b")),
         None,
         3,
         &None,
@@ -2577,7 +2728,7 @@ fn test_str_car_cdr_cons() {
         r#"(strcons "a" "b")"#,
         None,
         None,
-        Some(error),
+        Some(error_cont(s, "type-mismatch", r#""b""#)),
         None,
         3,
         &None,
@@ -2588,7 +2739,7 @@ fn test_str_car_cdr_cons() {
         r#"(strcons 1 2)"#,
         None,
         None,
-        Some(error),
+        Some(error_cont(s, "type-mismatch", "2")),
         None,
         3,
         &None,
@@ -2809,7 +2960,7 @@ fn test_prove_complicated_functional_commitment() {
     let res = Ptr::num_u64(6);
     let terminal = s.cont_terminal();
 
-    test_aux::<_, _, M1<'_, _>>(s, expr, Some(res), None, Some(terminal), None, 108, &None);
+    test_aux::<_, _, M1<'_, _>>(s, expr, Some(res), None, Some(terminal), None, 103, &None);
 }
 
 #[test]
@@ -2823,7 +2974,7 @@ fn test_prove_test_fold_cons_regression() {
     let res = Ptr::num_u64(6);
     let terminal = s.cont_terminal();
 
-    test_aux::<_, _, M1<'_, _>>(s, expr, Some(res), None, Some(terminal), None, 152, &None);
+    test_aux::<_, _, M1<'_, _>>(s, expr, Some(res), None, Some(terminal), None, 130, &None);
 }
 
 #[test]
@@ -2841,7 +2992,7 @@ fn test_prove_reduce_sym_contradiction_regression() {
     let s = &Store::<Fr>::default();
 
     let expr = "(eval 'a '(nil))";
-    let error = s.cont_error();
+    let error = error_cont(s, "unbound-var", "a");
 
     test_aux::<_, _, M1<'_, _>>(s, expr, None, None, Some(error), None, 4, &None);
 }
@@ -2878,7 +3029,7 @@ fn test_prove_test_env_not_nil_and_binding_nil() {
 
     let expr = "(let ((a 1) (b 2)) c)";
 
-    let error = s.cont_error();
+    let error = error_cont(s, "unbound-var", "c");
     test_aux::<_, _, M1<'_, _>>(s, expr, None, None, Some(error), None, 7, &None);
 }
 
@@ -2886,7 +3037,7 @@ fn test_prove_test_env_not_nil_and_binding_nil() {
 fn test_prove_test_eval_bad_form() {
     let s = &Store::<Fr>::default();
     let expr = "(* 5 (eval '(+ 1 a) '((0 . 3))))"; // two-arg eval, optional second arg is env. This tests for error on malformed env.
-    let error = s.cont_error();
+    let error = error_cont(s, "invalid-form", "a");
 
     test_aux::<_, _, M1<'_, _>>(s, expr, None, None, Some(error), None, 8, &None);
 }
@@ -2952,7 +3103,7 @@ fn test_prove_test_u64_div() {
     let expr3 = "(/ 100u64 0u64)";
 
     let terminal = s.cont_terminal();
-    let error = s.cont_error();
+    let error = error_cont(s, "div-by-zero", "0u64");
 
     test_aux::<_, _, M1<'_, _>>(s, expr, Some(res), None, Some(terminal), None, 3, &None);
     test_aux::<_, _, M1<'_, _>>(s, expr2, Some(res2), None, Some(terminal), None, 3, &None);
@@ -2972,7 +3123,7 @@ fn test_prove_test_u64_mod() {
     let expr3 = "(% 100u64 0u64)";
 
     let terminal = s.cont_terminal();
-    let error = s.cont_error();
+    let error = error_cont(s, "div-by-zero", "0u64");
 
     test_aux::<_, _, M1<'_, _>>(s, expr, Some(res), None, Some(terminal), None, 3, &None);
     test_aux::<_, _, M1<'_, _>>(s, expr2, Some(res2), None, Some(terminal), None, 3, &None);
@@ -2987,10 +3138,11 @@ fn test_prove_test_num_mod() {
     let expr2 = "(% 100 3u64)";
     let expr3 = "(% 100u64 3)";
 
-    let error = s.cont_error();
+    let error = error_cont(s, "type-mismatch", "3");
+    let error2 = error_cont(s, "type-mismatch", "3u64");
 
     test_aux::<_, _, M1<'_, _>>(s, expr, None, None, Some(error), None, 3, &None);
-    test_aux::<_, _, M1<'_, _>>(s, expr2, None, None, Some(error), None, 3, &None);
+    test_aux::<_, _, M1<'_, _>>(s, expr2, None, None, Some(error2), None, 3, &None);
     test_aux::<_, _, M1<'_, _>>(s, expr3, None, None, Some(error), None, 3, &None);
 }
 
@@ -3029,6 +3181,30 @@ fn test_prove_test_u64_comp() {
     test_aux::<_, _, M1<'_, _>>(s, expr10, Some(t), None, Some(terminal), None, 3, &None);
 }
 
+#[test]
+fn test_prove_test_i64_comp() {
+    let s = &Store::<Fr>::default();
+
+    let t = s.intern_lurk_symbol("t");
+    let nil = s.intern_nil();
+    let terminal = s.cont_terminal();
+
+    // negative i64s compare below positive ones, even though their two's
+    // complement representation is larger
+    for (expr, res) in [
+        ("(< -1i64 1i64)", t),
+        ("(< 1i64 -1i64)", nil),
+        ("(<= -9223372036854775808i64 9223372036854775807i64)", t),
+        ("(<= -1i64 -1i64)", t),
+        ("(> -2i64 -1i64)", nil),
+        ("(> 0i64 -9223372036854775808i64)", t),
+        ("(>= -1i64 -2i64)", t),
+        ("(>= -2i64 1i64)", nil),
+    ] {
+        test_aux::<_, _, M1<'_, _>>(s, expr, Some(res), None, Some(terminal), None, 3, &None);
+    }
+}
+
 #[test]
 fn test_prove_test_u64_conversion() {
     let s = &Store::<Fr>::default();
@@ -3081,7 +3257,7 @@ fn test_prove_test_hide_u64_secret() {
     let s = &Store::<Fr>::default();
 
     let expr = "(hide 0u64 123)";
-    let error = s.cont_error();
+    let error = error_cont(s, "type-mismatch", "123");
 
     test_aux::<_, _, M1<'_, _>>(s, expr, None, None, Some(error), None, 3, &None);
 }
@@ -3091,7 +3267,7 @@ fn test_prove_test_mod_by_zero_error() {
     let s = &Store::<Fr>::default();
 
     let expr = "(% 0 0)";
-    let error = s.cont_error();
+    let error = error_cont(s, "type-mismatch", "0");
 
     test_aux::<_, _, M1<'_, _>>(s, expr, None, None, Some(error), None, 3, &None);
 }
@@ -3100,7 +3276,7 @@ fn test_prove_test_mod_by_zero_error() {
 fn test_prove_dotted_syntax_error() {
     let s = &Store::<Fr>::default();
     let expr = "(let ((a (lambda (x) (+ x 1)))) (a . 1))";
-    let error = s.cont_error();
+    let error = error_cont(s, "invalid-form", "(a . 1)");
 
     test_aux::<_, _, M1<'_, _>>(s, expr, None, None, Some(error), None, 3, &None);
 }
@@ -3136,7 +3312,14 @@ fn test_prove_call_literal_fun() {
 #[test]
 fn test_prove_lambda_body_syntax() {
     let s = &Store::<Fr>::default();
-    let error = s.cont_error();
+    let dummy = s.intern_lurk_symbol("dummy");
+    let closure = |body| {
+        let body = s.read_with_default_state(body).unwrap();
+        s.intern_fun(dummy, body, s.intern_nil())
+    };
+    let error = s.intern_cont_error("invalid-form", closure("nil"));
+    let error2 = s.intern_cont_error("invalid-form", closure("(1 2)"));
+    let error3 = error_cont(s, "invalid-form", "1");
 
     test_aux::<_, _, M1<'_, _>>(s, "((lambda ()))", None, None, Some(error), None, 2, &None);
     test_aux::<_, _, M1<'_, _>>(
@@ -3144,7 +3327,7 @@ fn test_prove_lambda_body_syntax() {
         "((lambda () 1 2))",
         None,
         None,
-        Some(error),
+        Some(error2),
         None,
         2,
         &None,
@@ -3154,7 +3337,7 @@ fn test_prove_lambda_body_syntax() {
         "((lambda (x)) 1)",
         None,
         None,
-        Some(error),
+        Some(error3),
         None,
         3,
         &None,
@@ -3164,7 +3347,7 @@ fn test_prove_lambda_body_syntax() {
         "((lambda (x) 1 2) 1)",
         None,
         None,
-        Some(error),
+        Some(error3),
         None,
         3,
         &None,
@@ -3175,16 +3358,17 @@ fn test_prove_lambda_body_syntax() {
 #[ignore]
 fn test_prove_non_symbol_binding_error() {
     let s = &Store::<Fr>::default();
-    let error = s.cont_error();
-
     let test = |x| {
         let expr = format!("(let (({x} 123)) {x})");
         let expr2 = format!("(letrec (({x} 123)) {x})");
         let expr3 = format!("(lambda ({x}) {x})");
+        let error = error_cont(s, "invalid-form", &expr);
+        let error2 = error_cont(s, "invalid-form", &expr2);
+        let error3 = error_cont(s, "invalid-form", &expr3);
 
         test_aux::<_, _, M1<'_, _>>(s, &expr, None, None, Some(error), None, 1, &None);
-        test_aux::<_, _, M1<'_, _>>(s, &expr2, None, None, Some(error), None, 1, &None);
-        test_aux::<_, _, M1<'_, _>>(s, &expr3, None, None, Some(error), None, 1, &None);
+        test_aux::<_, _, M1<'_, _>>(s, &expr2, None, None, Some(error2), None, 1, &None);
+        test_aux::<_, _, M1<'_, _>>(s, &expr3, None, None, Some(error3), None, 1, &None);
     };
 
     test(":a");
@@ -3197,8 +3381,6 @@ fn test_prove_non_symbol_binding_error() {
 #[test]
 fn test_prove_head_with_sym_mimicking_value() {
     let s = &Store::<Fr>::default();
-    let error = s.cont_error();
-
     let hash_num = |s: &Store<Fr>, state: Rc<RefCell<State>>, name| {
         let sym = s.read(state, name).unwrap();
         let z_ptr = s.hash_ptr(&sym);
@@ -3210,36 +3392,43 @@ fn test_prove_head_with_sym_mimicking_value() {
     {
         // binop
         let expr = format!("({} 1 1)", hash_num(s, state.clone(), "+"));
+        let error = error_cont(s, "not-a-function", &expr);
         test_aux::<_, _, M1<'_, _>>(s, &expr, None, None, Some(error), None, 1, &None);
     }
     {
         // unop
         let expr = format!("({} '(1 . 2))", hash_num(s, state.clone(), "car"));
+        let error = error_cont(s, "not-a-function", &expr);
         test_aux::<_, _, M1<'_, _>>(s, &expr, None, None, Some(error), None, 1, &None);
     }
     {
         // let_or_letrec
         let expr = format!("({} ((a 1)) a)", hash_num(s, state.clone(), "let"));
+        let error = error_cont(s, "not-a-function", &expr);
         test_aux::<_, _, M1<'_, _>>(s, &expr, None, None, Some(error), None, 1, &None);
     }
     {
         // current-env
         let expr = format!("({})", hash_num(s, state.clone(), "current-env"));
+        let error = error_cont(s, "not-a-function", &expr);
         test_aux::<_, _, M1<'_, _>>(s, &expr, None, None, Some(error), None, 1, &None);
     }
     {
         // lambda
         let expr = format!("({} (x) 123)", hash_num(s, state.clone(), "lambda"));
+        let error = error_cont(s, "not-a-function", &expr);
         test_aux::<_, _, M1<'_, _>>(s, &expr, None, None, Some(error), None, 1, &None);
     }
     {
         // quote
         let expr = format!("({} asdf)", hash_num(s, state.clone(), "quote"));
+        let error = error_cont(s, "not-a-function", &expr);
         test_aux::<_, _, M1<'_, _>>(s, &expr, None, None, Some(error), None, 1, &None);
     }
     {
         // if
         let expr = format!("({} t 123 456)", hash_num(s, state, "if"));
+        let error = error_cont(s, "not-a-function", &expr);
         test_aux::<_, _, M1<'_, _>>(s, &expr, None, None, Some(error), None, 1, &None);
     }
 }
@@ -3281,7 +3470,12 @@ fn test_dumb_lang() {
     let error6 = Ptr::char('x');
     let error7 = Ptr::char('y');
 
-    let error = s.cont_error();
+    // arity errors are caught by the step function, but type errors are up to
+    // the coprocessor
+    let arity_error4 = s.intern_cont_error("arity-mismatch", error4);
+    let arity_error5 = s.intern_cont_error("arity-mismatch", error5);
+    let type_error6 = s.intern_cont_error("type-mismatch", error6);
+    let type_error7 = s.intern_cont_error("type-mismatch", error7);
     let terminal = s.cont_terminal();
     let lang = Arc::new(lang);
 
@@ -3320,7 +3514,7 @@ fn test_dumb_lang() {
         expr4,
         Some(error4),
         None,
-        Some(error),
+        Some(arity_error4),
         None,
         4,
         &Some(lang.clone()),
@@ -3330,7 +3524,7 @@ fn test_dumb_lang() {
         expr5,
         Some(error5),
         None,
-        Some(error),
+        Some(arity_error5),
         None,
         2,
         &Some(lang.clone()),
@@ -3340,7 +3534,7 @@ fn test_dumb_lang() {
         expr6,
         Some(error6),
         None,
-        Some(error),
+        Some(type_error6),
         None,
         3,
         &Some(lang.clone()),
@@ -3350,7 +3544,7 @@ fn test_dumb_lang() {
         expr6_,
         Some(error6),
         None,
-        Some(error),
+        Some(type_error6),
         None,
         3,
         &Some(lang.clone()),
@@ -3360,7 +3554,7 @@ fn test_dumb_lang() {
         expr7,
         Some(error7),
         None,
-        Some(error),
+        Some(type_error7),
         None,
         3,
         &Some(lang),
@@ -3527,6 +3721,116 @@ fn test_trie_lang() {
         None,
         lang.clone(),
     );
+
+    let expr7 = "(.lurk.trie.remove 0x1b22dc5a394231c34e4529af674dc56a736fbd07508acfd1d12c0e67c8b4de27 123)";
+    let expr7 = s.read(state.clone(), expr7).unwrap();
+    let res7 = s
+        .read_with_default_state(
+            "0x1cc5b90039db85fd519af975afa1de9d2b92960a585a546637b653b115bc3b53",
+        )
+        .unwrap();
+    nova_test_full_aux2::<_, _, C1LEM<'_, _, TrieCoproc<_>>>(
+        s,
+        expr7,
+        Some(res7),
+        None,
+        Some(terminal),
+        None,
+        3,
+        DEFAULT_REDUCTION_COUNT,
+        false,
+        None,
+        lang.clone(),
+    );
+
+    let expr8 = "(.lurk.trie.lookup (.lurk.trie.update 0x1b22dc5a394231c34e4529af674dc56a736fbd07508acfd1d12c0e67c8b4de27 123 789) 123)";
+    let expr8 = s.read(state.clone(), expr8).unwrap();
+    let res8 = Ptr::comm(Fr::from(789));
+    nova_test_full_aux2::<_, _, C1LEM<'_, _, TrieCoproc<_>>>(
+        s,
+        expr8,
+        Some(res8),
+        None,
+        Some(terminal),
+        None,
+        6,
+        DEFAULT_REDUCTION_COUNT,
+        false,
+        None,
+        lang.clone(),
+    );
+
+    let expr9 = "(.lurk.trie.update 0x1cc5b90039db85fd519af975afa1de9d2b92960a585a546637b653b115bc3b53 123 789)";
+    let expr9 = s.read(state.clone(), expr9).unwrap();
+    let error = error_cont(s, "missing-key", "123");
+    nova_test_full_aux2::<_, _, C1LEM<'_, _, TrieCoproc<_>>>(
+        s,
+        expr9,
+        None,
+        None,
+        Some(error),
+        None,
+        4,
+        DEFAULT_REDUCTION_COUNT,
+        false,
+        None,
+        lang.clone(),
+    );
+}
+
+#[test]
+fn test_map_lang() {
+    use crate::coprocessor::trie::{install, TrieCoproc};
+
+    let s = &Store::<Fr>::default();
+    let state = State::init_lurk_state().rccell();
+    let mut lang = Lang::<Fr, TrieCoproc<Fr>>::new();
+
+    install(&state, &mut lang);
+
+    let lang = Arc::new(lang);
+
+    let terminal = s.cont_terminal();
+    let nil = s.intern_nil();
+    let map = s.intern_map(vec![(Ptr::num_u64(1), Ptr::num_u64(3))]);
+
+    let cases = [
+        ("(.lurk.map.get {1 2} 1)", Ptr::num_u64(2), 3),
+        ("(.lurk.map.get {1 2} 3)", nil, 3),
+        ("(.lurk.map.assoc {1 2} 1 3)", map, 4),
+    ];
+    for (expr, expected, iterations) in cases {
+        let expr = s.read(state.clone(), expr).unwrap();
+        nova_test_full_aux2::<_, _, C1LEM<'_, _, TrieCoproc<_>>>(
+            s,
+            expr,
+            Some(expected),
+            None,
+            Some(terminal),
+            None,
+            iterations,
+            DEFAULT_REDUCTION_COUNT,
+            false,
+            None,
+            lang.clone(),
+        );
+    }
+
+    let expr = s.read(state.clone(), "(.lurk.map.get 1 2)").unwrap();
+    let error = error_cont(s, "type-mismatch", "1");
+    nova_test_full_aux2::<_, _, C1LEM<'_, _, TrieCoproc<_>>>(
+        s,
+        expr,
+        None,
+        None,
+        Some(error),
+        None,
+        3,
+        DEFAULT_REDUCTION_COUNT,
+        false,
+        None,
+        lang,
+    );
 }
 
 // This is related to issue #426
@@ -3547,6 +3851,57 @@ fn test_prove_lambda_body_nil() {
     );
 }
 
+#[test]
+fn test_prove_variadic_lambda() {
+    let s = &Store::<Fr>::default();
+    let terminal = s.cont_terminal();
+    for (expr, res, iterations) in [
+        ("((lambda (&rest xs) xs) 1 2 3)", "(1 2 3)", 8),
+        ("((lambda (&rest xs) xs))", "nil", 3),
+        ("((lambda (a &rest xs) (cons a xs)) 1)", "(1)", 9),
+        ("((lambda (a &rest xs) (cons a xs)) 1 2 3)", "(1 2 3)", 12),
+        (
+            "(let ((vf (lambda (&rest xs) (lambda (y) (cons y xs))))) ((vf 1) 2))",
+            "(2 1)",
+            15,
+        ),
+    ] {
+        let res = s.read_with_default_state(res).unwrap();
+        test_aux::<_, _, M1<'_, _>>(
+            s,
+            expr,
+            Some(res),
+            None,
+            Some(terminal),
+            None,
+            iterations,
+            &None,
+        );
+    }
+}
+
+#[test]
+fn test_prove_apply() {
+    let s = &Store::<Fr>::default();
+    let terminal = s.cont_terminal();
+    for (expr, res, iterations) in [
+        ("(apply (lambda (a b) (+ a b)) '(1 2))", "3", 10),
+        ("(apply (lambda (&rest xs) xs) '(1 2))", "(1 2)", 4),
+    ] {
+        let res = s.read_with_default_state(res).unwrap();
+        test_aux::<_, _, M1<'_, _>>(
+            s,
+            expr,
+            Some(res),
+            None,
+            Some(terminal),
+            None,
+            iterations,
+            &None,
+        );
+    }
+}
+
 // The following 3 tests are related to issue #424
 #[test]
 fn test_letrec_let_nesting() {
@@ -3592,7 +3947,7 @@ fn test_letrec_sequencing() {
         None,
         Some(terminal),
         None,
-        8,
+        7,
         &None,
     );
 }
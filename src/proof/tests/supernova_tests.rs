@@ -0,0 +1,42 @@
+use pasta_curves::pallas::Scalar as Fr;
+use std::sync::Arc;
+
+use crate::{
+    coprocessor::test::DumbCoprocessor,
+    eval::lang::Lang,
+    lem::{multiframe::MultiFrame, store::Store},
+    proof::{
+        supernova::{public_params, SuperNovaProver},
+        Prover,
+    },
+    state::user_sym,
+};
+
+#[test]
+fn test_prove_and_compress_nivc() {
+    let s = &Store::<Fr>::default();
+
+    let mut lang = Lang::<Fr, DumbCoprocessor<Fr>>::new();
+    lang.add_coprocessor(user_sym("cproc-dumb"), DumbCoprocessor::new());
+    let lang = Arc::new(lang);
+
+    let expr = s.read_with_default_state("(cproc-dumb 9 8)").unwrap();
+    let env = s.intern_nil();
+
+    let reduction_count = 1;
+    let pp = public_params::<_, _, MultiFrame<'_, _, _>>(reduction_count, lang.clone()).unwrap();
+    let prover =
+        SuperNovaProver::<_, _, MultiFrame<'_, _, _>>::new(reduction_count, (*lang).clone());
+    let (proof, z0, zi, num_steps, last_circuit_index) = prover
+        .evaluate_and_prove(&pp, expr, env, s, 100, lang)
+        .unwrap();
+
+    assert!(proof
+        .verify(&pp, last_circuit_index, num_steps, &z0, &zi)
+        .unwrap());
+
+    let compressed = proof.compress(&pp).unwrap();
+    assert!(compressed
+        .verify(&pp, last_circuit_index, num_steps, &z0, &zi)
+        .unwrap());
+}
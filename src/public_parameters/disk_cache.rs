@@ -5,10 +5,11 @@ use std::marker::PhantomData;
 use abomonation::{encode, Abomonation};
 use camino::{Utf8Path, Utf8PathBuf};
 use nova::traits::Engine;
+use serde::{de::DeserializeOwned, Serialize};
 
 use crate::config::lurk_config;
 use crate::coprocessor::Coprocessor;
-use crate::proof::nova::{CurveCycleEquipped, PublicParams, E1, E2};
+use crate::proof::nova::{CurveCycleEquipped, E1, E2};
 use crate::proof::MultiFrameTrait;
 use crate::public_parameters::error::Error;
 
@@ -46,10 +47,10 @@ where
         })
     }
 
-    pub(crate) fn read(
+    pub(crate) fn read<V: DeserializeOwned>(
         &self,
         instance: &Instance<'a, F, C, M>,
-    ) -> Result<PublicParams<F, M>, Error> {
+    ) -> Result<V, Error> {
         let file = instance.open(&self.dir)?;
         let reader = BufReader::new(file);
         bincode::deserialize_from(reader)
@@ -67,10 +68,10 @@ where
         Ok(())
     }
 
-    pub(crate) fn write(
+    pub(crate) fn write<V: Serialize>(
         &self,
         instance: &Instance<'a, F, C, M>,
-        data: &PublicParams<F, M>,
+        data: &V,
     ) -> Result<(), Error> {
         let file = instance.create(&self.dir)?;
         let writer = BufWriter::new(&file);
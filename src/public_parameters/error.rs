@@ -1,3 +1,4 @@
+use nova::supernova::error::SuperNovaError;
 use std::io;
 use thiserror::Error;
 
@@ -10,4 +11,6 @@ pub enum Error {
     Cache(String),
     #[error("JSON error: {0}")]
     Json(#[from] serde_json::Error),
+    #[error("SuperNova error: {0}")]
+    SuperNova(#[from] SuperNovaError),
 }
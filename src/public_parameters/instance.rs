@@ -8,7 +8,8 @@
 //! The first kind corresponds to [::nova::PublicParams], which contain a full instance of public parameters
 //! required for a Nova proof. The second and third kinds correspond to [::nova::supernova::AuxParams] and
 //! [::nova::supernova::CircuitParams], which contain partial instances of public parameters required for
-//! a SuperNova proof.
+//! a SuperNova proof. On top of those, we cache the prover and verifier keys of the SuperNova
+//! [::nova::supernova::snark::CompressedSNARK], which are derived from the full set of SuperNova parameters.
 //!
 //! ## Hashing
 //!
@@ -19,6 +20,7 @@
 //! - For [::nova::supernova::AuxParams], this is the combine hash of all of the
 //!   primary circuit hashes of the SuperNova instance.
 //! - For [::nova::supernova::CircuitParams], this is the hash of the shape of its particular SuperNova circuit.
+//! - For the SuperNova compression keys, this is the same combined hash as for [::nova::supernova::AuxParams].
 //!
 //! Because the first primary circuit of Lurk will always be the universal Lurk circuit, which may get very large
 //! at high `rc`, we instead hash an "ad-hoc" circuit that still uniquely represents the universal Lurk circuit,
@@ -99,6 +101,8 @@ pub enum Kind {
     SuperNovaAuxParams,
     /// Tag for [supernova::SuperNovaCircuitParams] instances
     SuperNovaCircuitParams(usize),
+    /// Tag for [supernova::CompressionKeys] instances
+    SuperNovaCompressionKeys,
 }
 
 /// What we put into the cache
@@ -143,7 +147,7 @@ where
     pub fn new(rc: usize, lang: Arc<Lang<F, C>>, abomonated: bool, kind: Kind) -> Self {
         let cache_key = match kind {
             Kind::NovaPublicParams => nova::circuit_cache_key::<'a, F, C, M>(rc, lang.clone()),
-            Kind::SuperNovaAuxParams => {
+            Kind::SuperNovaAuxParams | Kind::SuperNovaCompressionKeys => {
                 supernova::circuit_cache_keys::<F, C, M>(rc, &lang).digest()
             }
             Kind::SuperNovaCircuitParams(circuit_index) => {
@@ -182,7 +186,9 @@ where
 
     pub fn reindex(&self, circuit_index: usize) -> Self {
         match self.kind {
-            Kind::SuperNovaAuxParams | Kind::SuperNovaCircuitParams(_) => Instance::new(
+            Kind::SuperNovaAuxParams
+            | Kind::SuperNovaCircuitParams(_)
+            | Kind::SuperNovaCompressionKeys => Instance::new(
                 self.rc,
                 self.lang(),
                 self.abomonated,
@@ -191,6 +197,21 @@ where
             _ => panic!(),
         }
     }
+
+    /// If this [Instance] is of [Kind::SuperNovaAuxParams], then generate the instance of the
+    /// compression keys that are derived from the same set of SuperNova parameters.
+    pub fn compression_keys_instance(&self) -> Self {
+        assert!(
+            matches!(self.kind, Kind::SuperNovaAuxParams),
+            "not a supernova instance"
+        );
+        Instance::new(
+            self.rc,
+            self.lang(),
+            self.abomonated,
+            Kind::SuperNovaCompressionKeys,
+        )
+    }
 }
 
 impl<'a, F: CurveCycleEquipped, C: Coprocessor<F>, M: MultiFrameTrait<'a, F, C>>
@@ -84,7 +84,7 @@ impl PublicParamMemCache {
             }
         } else {
             // read the file if it exists, otherwise initialize
-            if let Ok(pp) = disk_cache.read(instance) {
+            if let Ok(pp) = disk_cache.read::<PublicParams<F, M>>(instance) {
                 info!("loading abomonated {}", instance.key());
                 Ok(Arc::new(pp))
             } else {
@@ -12,7 +12,7 @@ mod error;
 pub mod instance;
 mod mem_cache;
 
-use crate::proof::supernova::{self, SuperNovaAuxParams, SuperNovaPublicParams};
+use crate::proof::supernova::{self, CompressionKeys, SuperNovaAuxParams, SuperNovaPublicParams};
 use crate::public_parameters::disk_cache::public_params_dir;
 use crate::public_parameters::error::Error;
 
@@ -161,21 +161,26 @@ where
 
     let maybe_aux_params = supernova_aux_params::<F, C, M>(instance_primary);
 
-    let pp = match (maybe_circuit_params_vec, maybe_aux_params) {
-        (Ok(circuit_params_vec), Ok(aux_params)) => {
+    let instance_keys = instance_primary.compression_keys_instance();
+    let maybe_keys = disk_cache.read::<CompressionKeys<F, M>>(&instance_keys);
+
+    let pp = match (maybe_circuit_params_vec, maybe_aux_params, maybe_keys) {
+        (Ok(circuit_params_vec), Ok(aux_params), Ok(CompressionKeys { pk, vk })) => {
             println!("generating public params");
             supernova::PublicParams {
                 pp: SuperNovaPublicParams::<F, M>::from_parts_unchecked(
                     circuit_params_vec,
                     aux_params,
                 ),
+                pk,
+                vk,
             }
         }
         _ => {
             println!("generating running claim params");
-            let pp = default(instance_primary);
+            let supernova::PublicParams { pp, pk, vk } = default(instance_primary)?;
 
-            let (circuit_params_vec, aux_params) = pp.pp.into_parts();
+            let (circuit_params_vec, aux_params) = pp.into_parts();
 
             disk_cache.write_abomonated(instance_primary, &aux_params)?;
 
@@ -183,11 +188,18 @@ where
                 let instance = instance_primary.reindex(circuit_index);
                 disk_cache.write_abomonated(&instance, circuit_params)?;
             }
+
+            let keys = CompressionKeys { pk, vk };
+            disk_cache.write(&instance_keys, &keys)?;
+            let CompressionKeys { pk, vk } = keys;
+
             supernova::PublicParams {
                 pp: SuperNovaPublicParams::<F, M>::from_parts_unchecked(
                     circuit_params_vec,
                     aux_params,
                 ),
+                pk,
+                vk,
             }
         }
     };
@@ -215,10 +215,12 @@ const LURK_PACKAGE_SYMBOL_NAME: &str = "lurk";
 const USER_PACKAGE_SYMBOL_NAME: &str = "user";
 const META_PACKAGE_SYMBOL_NAME: &str = "meta";
 
-const LURK_PACKAGE_SYMBOLS_NAMES: [&str; 36] = [
+const LURK_PACKAGE_SYMBOLS_NAMES: [&str; 67] = [
+    "apply",
     "atom",
     "begin",
     "car",
+    "catch",
     "cdr",
     "char",
     "comm",
@@ -228,19 +230,47 @@ const LURK_PACKAGE_SYMBOLS_NAMES: [&str; 36] = [
     "emit",
     "eval",
     "eq",
+    "fail",
     "hide",
     "if",
     "lambda",
     "let",
     "letrec",
+    "logand",
+    "logor",
+    "logxor",
     "nil",
     "num",
+    "u8",
+    "u16",
+    "u32",
     "u64",
+    "u128",
+    "i64",
     "open",
     "quote",
+    "quasiquote",
+    "unquote",
+    "unquote-splicing",
+    "rotl",
+    "rotr",
     "secret",
+    "shl",
+    "shr",
     "strcons",
+    "string-append",
+    "string-length",
+    "string<",
+    "string->symbol",
+    "substring",
+    "symbol->string",
+    "num->string",
     "t",
+    "vector",
+    "vector-length",
+    "vector-ref",
+    "vector-set",
+    "type-of",
     "+",
     "-",
     "*",
@@ -252,11 +282,13 @@ const LURK_PACKAGE_SYMBOLS_NAMES: [&str; 36] = [
     "<=",
     ">=",
     "_",
+    "&rest",
 ];
 
-const META_PACKAGE_SYMBOLS_NAMES: [&str; 23] = [
+const META_PACKAGE_SYMBOLS_NAMES: [&str; 24] = [
     "def",
     "defrec",
+    "defmacro",
     "load",
     "assert",
     "assert-eq",
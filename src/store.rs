@@ -38,6 +38,8 @@ pub struct Store<F: LurkField> {
 
     // Other sparse storage format without hashing is likely more efficient
     pub num_store: IndexSet<Box<Num<F>>>,
+    /// Holds the u128s, which don't fit in a raw pointer's index
+    pub u128_store: IndexSet<Box<u128>>,
 
     /// Holds a Str, which is a char head and a string tail
     pub str_store: IndexSet<Box<(Ptr<F>, Ptr<F>)>>,
@@ -90,6 +92,7 @@ impl<F: LurkField> Default for Store<F> {
             comm_store: Default::default(),
             sym_store: Default::default(),
             num_store: Default::default(),
+            u128_store: Default::default(),
             fun_store: Default::default(),
             str_store: Default::default(),
             thunk_store: Default::default(),
@@ -515,7 +518,14 @@ impl<F: LurkField> Store<F> {
 
     pub fn intern_uint(&self, n: UInt) -> Ptr<F> {
         match n {
+            UInt::U8(x) => Ptr::index(ExprTag::U8, x as usize),
+            UInt::U16(x) => Ptr::index(ExprTag::U16, x as usize),
+            UInt::U32(x) => Ptr::index(ExprTag::U32, x as usize),
             UInt::U64(x) => self.intern_u64(x),
+            UInt::U128(x) => {
+                let (ptr, _) = self.u128_store.insert_probe(Box::new(x));
+                Ptr::index(ExprTag::U128, ptr)
+            }
         }
     }
 
@@ -743,10 +753,15 @@ impl<F: LurkField> Store<F> {
     }
 
     pub fn fetch_uint(&self, ptr: &Ptr<F>) -> Option<UInt> {
-        // If more UInt variants are added, the following assertion should be relaxed to check for any of them.
-        debug_assert!(matches!(ptr.tag, ExprTag::U64));
         match ptr.tag {
+            ExprTag::U8 => Some(UInt::U8(ptr.raw.idx()? as u8)),
+            ExprTag::U16 => Some(UInt::U16(ptr.raw.idx()? as u16)),
+            ExprTag::U32 => Some(UInt::U32(ptr.raw.idx()? as u32)),
             ExprTag::U64 => Some(UInt::U64(ptr.raw.idx()? as u64)),
+            ExprTag::U128 => self
+                .u128_store
+                .get_index(ptr.raw.idx()?)
+                .map(|x| UInt::U128(**x)),
             _ => unreachable!(),
         }
     }
@@ -777,8 +792,12 @@ impl<F: LurkField> Store<F> {
                 .fetch_strcons(ptr)
                 .map(|(car, cdr)| Expression::Str(car, cdr)),
             ExprTag::Char => self.fetch_char(ptr).map(Expression::Char),
-            ExprTag::U64 => self.fetch_uint(ptr).map(Expression::UInt),
-            ExprTag::Cproc => unreachable!("Lurk Alpha doesn't produce such expressions"),
+            ExprTag::U8 | ExprTag::U16 | ExprTag::U32 | ExprTag::U64 | ExprTag::U128 => {
+                self.fetch_uint(ptr).map(Expression::UInt)
+            }
+            ExprTag::Cproc | ExprTag::I64 | ExprTag::Vec | ExprTag::Map => {
+                unreachable!("Lurk Alpha doesn't produce such expressions")
+            }
         }
     }
 
@@ -805,8 +824,9 @@ impl<F: LurkField> Store<F> {
 
     pub fn fetch_cont(&self, ptr: &ContPtr<F>) -> Option<Continuation<F>> {
         use ContTag::{
-            Binop, Binop2, Call, Call0, Call2, Cproc, Dummy, Emit, Error, If, Let, LetRec, Lookup,
-            Outermost, Tail, Terminal, Unop,
+            Apply, Binop, Binop2, Call, Call0, Call2, Catch, Cproc, Dummy, Emit, Error, Fail, If,
+            Let, LetRec, LetRecNext, Lookup, Outermost, Rest, StringOp, Tail, Terminal, Unop,
+            Unwind, Vector, VectorOp,
         };
         match ptr.tag {
             Outermost => Some(Continuation::Outermost),
@@ -907,7 +927,10 @@ impl<F: LurkField> Store<F> {
                 .map(|continuation| Continuation::Emit {
                     continuation: *continuation,
                 }),
-            Cproc => unreachable!("Lurk Alpha doesn't produce such continuations"),
+            Cproc | Fail | Catch | Unwind | LetRecNext | Rest | Apply | StringOp | Vector
+            | VectorOp => {
+                unreachable!("Lurk Alpha doesn't produce such continuations")
+            }
         }
     }
 
@@ -1464,7 +1487,10 @@ impl<F: LurkField> Store<F> {
                     Some(ptr)
                 }
                 (ExprTag::Char, Some(Char(x))) => Some(x.into()),
-                (ExprTag::U64, Some(UInt(x))) => Some(self.intern_uint(x)),
+                (
+                    ExprTag::U8 | ExprTag::U16 | ExprTag::U32 | ExprTag::U64 | ExprTag::U128,
+                    Some(UInt(x)),
+                ) => Some(self.intern_uint(x)),
                 (ExprTag::Thunk, Some(Thunk(value, continuation))) => {
                     let value = self.intern_z_expr_ptr(&value, z_store)?;
                     let continuation = self.intern_z_cont_ptr(&continuation, z_store)?;
@@ -2022,8 +2048,8 @@ pub mod test {
         let foo_list = list!(Fr, [symbol!(["foo"])]);
         let foo_sym = symbol!(Fr, ["foo"]);
 
-        let expr = store.intern_syntax(foo_list);
-        let sym = store.intern_syntax(foo_sym);
+        let expr = store.intern_syntax(foo_list).unwrap();
+        let sym = store.intern_syntax(foo_sym).unwrap();
         let sym1 = store.car(&expr).unwrap();
         let sss = store.fetch_sym(&sym);
         let hash = store.hash_expr(&sym);
@@ -2166,4 +2192,23 @@ pub mod test {
 
         assert!(store.open(comm3).is_none());
     }
+
+    #[test]
+    fn uint_z_store_roundtrip() {
+        let store = Store::<Fr>::default();
+        for n in [
+            UInt::U8(u8::MAX),
+            UInt::U16(u16::MAX),
+            UInt::U32(u32::MAX),
+            UInt::U64(u64::MAX),
+            UInt::U128(u128::MAX),
+        ] {
+            let ptr = store.intern_uint(n);
+            assert_eq!(store.fetch_uint(&ptr), Some(n));
+
+            let (z_store, z_ptr) = ZStore::new_with_expr(&store, &ptr);
+            let (new_store, new_ptr) = z_store.to_store_with_z_ptr(&z_ptr.unwrap()).unwrap();
+            assert_eq!(new_store.fetch_uint(&new_ptr), Some(n));
+        }
+    }
 }
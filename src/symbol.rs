@@ -14,7 +14,7 @@ use serde::{Deserialize, Serialize};
 pub(crate) const KEYWORD_MARKER: char = ':';
 pub(crate) const SYM_SEPARATOR: char = '.';
 pub(crate) const SYM_MARKER: char = '.';
-pub(crate) const ESCAPE_CHARS: &str = "|(){}[],.:'\\\"";
+pub(crate) const ESCAPE_CHARS: &str = "|(){}[],.:'`\\\"";
 
 #[derive(Clone, Debug, Eq, PartialEq, PartialOrd, Serialize, Deserialize, Hash)]
 #[cfg_attr(not(target_arch = "wasm32"), derive(Arbitrary))]
@@ -276,9 +276,10 @@ impl Symbol {
         use crate::syntax::Syntax;
         use nom::{sequence::preceded, Parser};
         use pasta_curves::pallas::Scalar;
+        let state = State::default().rccell();
         match preceded(
-            parse_space::<Scalar>,
-            parse_symbol(State::default().rccell(), true),
+            parse_space::<Scalar>(state.clone(), true),
+            parse_symbol(state, true),
         )
         .parse(Span::new(name))
         {
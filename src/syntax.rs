@@ -4,10 +4,13 @@ use crate::field::LurkField;
 use crate::lurk_sym_ptr;
 use crate::num::Num;
 use crate::package::SymbolRef;
-use crate::parser::position::Pos;
+use crate::parser::{
+    position::Pos,
+    string::{escape_char, escape_string},
+};
 use crate::ptr::Ptr;
-use crate::state::lurk_sym;
-use crate::store::Store;
+use crate::state::{lurk_sym, user_sym};
+use crate::store::{self, Store};
 use crate::uint::UInt;
 
 #[cfg(not(target_arch = "wasm32"))]
@@ -20,6 +23,8 @@ pub enum Syntax<F: LurkField> {
     Num(Pos, Num<F>),
     /// A u64 integer: 1u64, 0xffu64
     UInt(Pos, UInt),
+    /// A signed 64-bit integer: -1i64, 0xffi64
+    I64(Pos, i64),
     /// A hierarchical symbol: foo, foo.bar.baz or keyword :foo
     Symbol(Pos, SymbolRef),
     /// A string literal: "foobar", "foo\nbar"
@@ -28,10 +33,18 @@ pub enum Syntax<F: LurkField> {
     Char(Pos, char),
     /// A quoted expression: 'a, '(1 2)
     Quote(Pos, Box<Syntax<F>>),
+    /// A quasiquoted expression: `a, `(1 ,x)
+    Quasiquote(Pos, Box<Syntax<F>>),
+    /// An unquoted expression inside a quasiquote: ,x
+    Unquote(Pos, Box<Syntax<F>>),
+    /// A spliced expression inside a quasiquote: ,@xs
+    UnquoteSplicing(Pos, Box<Syntax<F>>),
     /// A nil-terminated cons-list of expressions: (1 2 3)
     List(Pos, Vec<Syntax<F>>),
     /// An improper cons-list of expressions: (1 2 . 3)
     Improper(Pos, Vec<Syntax<F>>, Box<Syntax<F>>),
+    /// A map from keys to values: {:a 1 :b 2}
+    Map(Pos, Vec<(Syntax<F>, Syntax<F>)>),
 }
 
 impl<F: LurkField> Syntax<F> {
@@ -40,14 +53,223 @@ impl<F: LurkField> Syntax<F> {
         match self {
             Self::Num(pos, _)
             | Self::UInt(pos, _)
+            | Self::I64(pos, _)
             | Self::Symbol(pos, _)
             | Self::String(pos, _)
             | Self::Char(pos, _)
             | Self::Quote(pos, _)
+            | Self::Quasiquote(pos, _)
+            | Self::Unquote(pos, _)
+            | Self::UnquoteSplicing(pos, _)
             | Self::List(pos, _)
-            | Self::Improper(pos, ..) => pos,
+            | Self::Improper(pos, ..)
+            | Self::Map(pos, _) => pos,
         }
     }
+
+    /// Whether an unquote or a splice that has to be evaluated occurs inside
+    /// `self`, which is nested in `depth` quasiquotes besides the one being
+    /// expanded
+    fn has_unquote(&self, depth: usize) -> bool {
+        match self {
+            Self::Unquote(_, x) | Self::UnquoteSplicing(_, x) => {
+                depth == 0 || x.has_unquote(depth - 1)
+            }
+            Self::Quasiquote(_, x) => x.has_unquote(depth + 1),
+            Self::Quote(_, x) => x.has_unquote(depth),
+            Self::List(_, xs) => xs.iter().any(|x| x.has_unquote(depth)),
+            Self::Improper(_, xs, end) => {
+                xs.iter().any(|x| x.has_unquote(depth)) || end.has_unquote(depth)
+            }
+            Self::Map(_, kvs) => kvs
+                .iter()
+                .any(|(k, v)| k.has_unquote(depth) || v.has_unquote(depth)),
+            _ => false,
+        }
+    }
+
+    /// Writes the quasiquotes, unquotes and splices inside `self` as the lists
+    /// `(quasiquote x)`, `(unquote x)` and `(unquote-splicing x)`, so that
+    /// `self` can be quoted as data
+    fn quasiquote_data(self) -> Syntax<F> {
+        let form = |pos, name, x: Box<Self>| {
+            let xs = vec![
+                Self::Symbol(pos, lurk_sym(name).into()),
+                x.quasiquote_data(),
+            ];
+            Self::List(pos, xs)
+        };
+        match self {
+            Self::Quote(pos, x) => Self::Quote(pos, Box::new(x.quasiquote_data())),
+            Self::Quasiquote(pos, x) => form(pos, "quasiquote", x),
+            Self::Unquote(pos, x) => form(pos, "unquote", x),
+            Self::UnquoteSplicing(pos, x) => form(pos, "unquote-splicing", x),
+            Self::List(pos, xs) => {
+                Self::List(pos, xs.into_iter().map(Self::quasiquote_data).collect())
+            }
+            Self::Improper(pos, xs, end) => Self::Improper(
+                pos,
+                xs.into_iter().map(Self::quasiquote_data).collect(),
+                Box::new(end.quasiquote_data()),
+            ),
+            Self::Map(pos, kvs) => Self::Map(
+                pos,
+                kvs.into_iter()
+                    .map(|(k, v)| (k.quasiquote_data(), v.quasiquote_data()))
+                    .collect(),
+            ),
+            x => x,
+        }
+    }
+
+    /// Expands the body of a quasiquote into an expression built with the
+    /// existing `quote`, `cons` and `letrec` forms:
+    ///
+    /// * parts without unquotes are quoted as a whole
+    /// * `,x` becomes `x`
+    /// * `,@xs` is appended to the rest of the list, or used directly as the
+    ///   tail when it's in the last position
+    /// * maps are built from `{}` with `.lurk.map.assoc`, so evaluating them
+    ///   needs the map functions
+    ///
+    /// Nested quasiquotes are kept as `(quasiquote x)` lists, in which only the
+    /// unquotes that are as deeply nested in unquotes as in quasiquotes are
+    /// evaluated. Splices in other positions than the last one share a single
+    /// `append` function, bound around the whole expansion.
+    pub fn expand_quasiquote(self) -> Syntax<F> {
+        let pos = *self.get_pos();
+        let mut appends = false;
+        let expanded = self.expand_quasiquote_at(0, &mut appends);
+        if !appends {
+            return expanded;
+        }
+        let list = |xs| Self::List(pos, xs);
+        let binding = list(vec![Self::append_sym(pos), Self::append(pos)]);
+        list(vec![
+            Self::Symbol(pos, lurk_sym("letrec").into()),
+            list(vec![binding]),
+            expanded,
+        ])
+    }
+
+    fn expand_quasiquote_at(self, depth: usize, appends: &mut bool) -> Syntax<F> {
+        if !self.has_unquote(depth) {
+            return match self {
+                Self::Num(..)
+                | Self::UInt(..)
+                | Self::I64(..)
+                | Self::String(..)
+                | Self::Char(..) => self,
+                _ => {
+                    let pos = *self.get_pos();
+                    Self::Quote(pos, Box::new(self.quasiquote_data()))
+                }
+            };
+        }
+        // `(name x)`, with `x` already expanded
+        let form = |pos, name, x| {
+            let sym = |name| Self::Symbol(pos, lurk_sym(name).into());
+            let nil = sym("nil");
+            let name = Self::Quote(pos, Box::new(sym(name)));
+            let tail = Self::List(pos, vec![sym("cons"), x, nil]);
+            Self::List(pos, vec![sym("cons"), name, tail])
+        };
+        match self {
+            Self::Unquote(_, x) | Self::UnquoteSplicing(_, x) if depth == 0 => *x,
+            Self::Unquote(pos, x) => {
+                form(pos, "unquote", x.expand_quasiquote_at(depth - 1, appends))
+            }
+            Self::UnquoteSplicing(pos, x) => form(
+                pos,
+                "unquote-splicing",
+                x.expand_quasiquote_at(depth - 1, appends),
+            ),
+            Self::Quasiquote(pos, x) => form(
+                pos,
+                "quasiquote",
+                x.expand_quasiquote_at(depth + 1, appends),
+            ),
+            Self::Quote(pos, x) => form(pos, "quote", x.expand_quasiquote_at(depth, appends)),
+            Self::List(pos, xs) => {
+                let nil = Self::Symbol(pos, lurk_sym("nil").into());
+                Self::expand_quasiquote_list(pos, xs, nil, depth, appends)
+            }
+            Self::Improper(pos, xs, end) => {
+                let end = end.expand_quasiquote_at(depth, appends);
+                Self::expand_quasiquote_list(pos, xs, end, depth, appends)
+            }
+            Self::Map(pos, kvs) => {
+                let assoc = lurk_sym("map").direct_child("assoc");
+                let assoc = Self::Symbol(pos, assoc.into());
+                kvs.into_iter().fold(Self::Map(pos, vec![]), |map, (k, v)| {
+                    let k = k.expand_quasiquote_at(depth, appends);
+                    let v = v.expand_quasiquote_at(depth, appends);
+                    Self::List(pos, vec![assoc.clone(), map, k, v])
+                })
+            }
+            _ => unreachable!("atoms have no unquotes"),
+        }
+    }
+
+    fn expand_quasiquote_list(
+        pos: Pos,
+        xs: Vec<Syntax<F>>,
+        end: Syntax<F>,
+        depth: usize,
+        appends: &mut bool,
+    ) -> Syntax<F> {
+        let sym = |name| Self::Symbol(pos, lurk_sym(name).into());
+        let mut tail = end;
+        let mut is_nil = true;
+        for x in xs.into_iter().rev() {
+            tail = match x {
+                Self::UnquoteSplicing(_, ys) if depth == 0 && is_nil => *ys,
+                Self::UnquoteSplicing(_, ys) if depth == 0 => {
+                    *appends = true;
+                    Self::List(pos, vec![Self::append_sym(pos), *ys, tail])
+                }
+                x => Self::List(
+                    pos,
+                    vec![sym("cons"), x.expand_quasiquote_at(depth, appends), tail],
+                ),
+            };
+            is_nil = false;
+        }
+        tail
+    }
+
+    /// The name of the function that splices lists. It's not in the user
+    /// package, so it doesn't shadow the user's own functions
+    fn append_sym(pos: Pos) -> Syntax<F> {
+        Self::Symbol(pos, lurk_sym("append").into())
+    }
+
+    /// `(lambda (xs ys) ...)`, which appends `ys` to `xs` by calling itself
+    /// through the name bound by `expand_quasiquote`
+    fn append(pos: Pos) -> Syntax<F> {
+        let lurk = |name| Self::Symbol(pos, lurk_sym(name).into());
+        let user = |name| Self::Symbol(pos, user_sym(name).into());
+        let list = |xs| Self::List(pos, xs);
+        let body = list(vec![
+            lurk("if"),
+            user("xs"),
+            list(vec![
+                lurk("cons"),
+                list(vec![lurk("car"), user("xs")]),
+                list(vec![
+                    Self::append_sym(pos),
+                    list(vec![lurk("cdr"), user("xs")]),
+                    user("ys"),
+                ]),
+            ]),
+            user("ys"),
+        ]);
+        list(vec![
+            lurk("lambda"),
+            list(vec![user("xs"), user("ys")]),
+            body,
+        ])
+    }
 }
 
 #[cfg(not(target_arch = "wasm32"))]
@@ -60,6 +282,7 @@ impl<Fr: LurkField> Arbitrary for Syntax<Fr> {
         let leaf = prop_oneof![
             any::<Num<Fr>>().prop_map(|x| Syntax::Num(Pos::No, x)),
             any::<UInt>().prop_map(|x| Syntax::UInt(Pos::No, x)),
+            any::<i64>().prop_map(|x| Syntax::I64(Pos::No, x)),
             any::<Symbol>().prop_map(|x| Syntax::Symbol(Pos::No, x.into())),
             any::<String>().prop_map(|x| Syntax::String(Pos::No, x)),
             any::<char>().prop_map(|x| Syntax::Char(Pos::No, x))
@@ -69,11 +292,22 @@ impl<Fr: LurkField> Arbitrary for Syntax<Fr> {
                 inner
                     .clone()
                     .prop_map(|x| Syntax::Quote(Pos::No, Box::new(x))),
+                inner
+                    .clone()
+                    .prop_map(|x| Syntax::Quasiquote(Pos::No, Box::new(x))),
+                inner
+                    .clone()
+                    .prop_map(|x| Syntax::Unquote(Pos::No, Box::new(x))),
+                inner
+                    .clone()
+                    .prop_map(|x| Syntax::UnquoteSplicing(Pos::No, Box::new(x))),
                 prop::collection::vec(inner.clone(), 0..10).prop_map(|x| Syntax::List(Pos::No, x)),
-                prop::collection::vec(inner, 2..12).prop_map(|mut xs| {
+                prop::collection::vec(inner.clone(), 2..12).prop_map(|mut xs| {
                     let x = xs.pop().unwrap();
                     Syntax::Improper(Pos::No, xs, Box::new(x))
-                })
+                }),
+                prop::collection::vec((inner.clone(), inner), 0..6)
+                    .prop_map(|kvs| Syntax::Map(Pos::No, kvs))
             ]
         })
         .boxed()
@@ -84,17 +318,23 @@ impl<F: LurkField> fmt::Display for Syntax<F> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             Self::Num(_, x) => write!(f, "{x}"),
-            Self::UInt(_, x) => write!(f, "{x}u64"),
+            Self::UInt(_, x) => write!(f, "{x}{}", x.suffix()),
+            Self::I64(_, x) => write!(f, "{x}i64"),
             Self::Symbol(_, x) => write!(f, "{x}"),
-            Self::String(_, x) => write!(f, "\"{}\"", x.escape_default()),
-            Self::Char(_, x) => {
-                if *x == '(' || *x == ')' {
-                    write!(f, "'\\{x}'")
+            Self::String(_, x) => write!(f, "\"{}\"", escape_string(x)),
+            Self::Char(_, x) => write!(f, "'{}'", escape_char(*x)),
+            Self::Quote(_, x) => write!(f, "'{x}"),
+            Self::Quasiquote(_, x) => write!(f, "`{x}"),
+            Self::Unquote(_, x) => {
+                // keeps `, @x` from being read back as a splice
+                let x = x.to_string();
+                if x.starts_with('@') {
+                    write!(f, ", {x}")
                 } else {
-                    write!(f, "'{}'", x.escape_default())
+                    write!(f, ",{x}")
                 }
             }
-            Self::Quote(_, x) => write!(f, "'{x}"),
+            Self::UnquoteSplicing(_, x) => write!(f, ",@{x}"),
             Self::List(_, xs) => {
                 let mut iter = xs.iter().peekable();
                 write!(f, "(")?;
@@ -117,37 +357,58 @@ impl<F: LurkField> fmt::Display for Syntax<F> {
                 }
                 write!(f, ")")
             }
+            Self::Map(_, kvs) => {
+                let kvs = kvs
+                    .iter()
+                    .map(|(k, v)| format!("{k} {v}"))
+                    .collect::<Vec<_>>();
+                write!(f, "{{{}}}", kvs.join(" "))
+            }
         }
     }
 }
 
 impl<F: LurkField> Store<F> {
-    pub fn intern_syntax(&self, syn: Syntax<F>) -> Ptr<F> {
+    /// Interns `syn`, failing on the syntax that Lurk Alpha doesn't support
+    pub fn intern_syntax(&self, syn: Syntax<F>) -> Result<Ptr<F>, store::Error> {
         match syn {
-            Syntax::Num(_, x) => self.intern_num(x),
-            Syntax::UInt(_, x) => self.intern_uint(x),
-            Syntax::Char(_, x) => self.intern_char(x),
-            Syntax::Symbol(_, symbol) => self.intern_symbol(&symbol),
-            Syntax::String(_, x) => self.intern_string(&x),
+            Syntax::Num(_, x) => Ok(self.intern_num(x)),
+            Syntax::UInt(_, x) => Ok(self.intern_uint(x)),
+            Syntax::I64(_, x) => Err(store::Error(format!(
+                "Lurk Alpha doesn't support i64: {x}i64"
+            ))),
+            Syntax::Map(..) => Err(store::Error("Lurk Alpha doesn't support maps".into())),
+            Syntax::Char(_, x) => Ok(self.intern_char(x)),
+            Syntax::Symbol(_, symbol) => Ok(self.intern_symbol(&symbol)),
+            Syntax::String(_, x) => Ok(self.intern_string(&x)),
             Syntax::Quote(pos, x) => {
                 let xs = vec![Syntax::Symbol(pos, lurk_sym("quote").into()), *x];
                 self.intern_syntax(Syntax::List(pos, xs))
             }
+            Syntax::Quasiquote(_, x) => self.intern_syntax(x.expand_quasiquote()),
+            Syntax::Unquote(pos, x) => {
+                let xs = vec![Syntax::Symbol(pos, lurk_sym("unquote").into()), *x];
+                self.intern_syntax(Syntax::List(pos, xs))
+            }
+            Syntax::UnquoteSplicing(pos, x) => {
+                let xs = vec![Syntax::Symbol(pos, lurk_sym("unquote-splicing").into()), *x];
+                self.intern_syntax(Syntax::List(pos, xs))
+            }
             Syntax::List(_, xs) => {
                 let mut cdr = lurk_sym_ptr!(self, nil);
                 for x in xs.into_iter().rev() {
-                    let car = self.intern_syntax(x);
+                    let car = self.intern_syntax(x)?;
                     cdr = self.intern_cons(car, cdr);
                 }
-                cdr
+                Ok(cdr)
             }
             Syntax::Improper(_, xs, end) => {
-                let mut cdr = self.intern_syntax(*end);
+                let mut cdr = self.intern_syntax(*end)?;
                 for x in xs.into_iter().rev() {
-                    let car = self.intern_syntax(x);
+                    let car = self.intern_syntax(x)?;
                     cdr = self.intern_cons(car, cdr);
                 }
-                cdr
+                Ok(cdr)
             }
         }
     }
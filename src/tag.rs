@@ -40,6 +40,13 @@ pub enum ExprTag {
     U64,
     Key,
     Cproc,
+    U8,
+    U16,
+    U32,
+    U128,
+    I64,
+    Vec,
+    Map,
 }
 
 impl From<ExprTag> for u16 {
@@ -69,6 +76,13 @@ impl fmt::Display for ExprTag {
             ExprTag::Comm => write!(f, "comm#"),
             ExprTag::U64 => write!(f, "u64#"),
             ExprTag::Cproc => write!(f, "cproc#"),
+            ExprTag::U8 => write!(f, "u8#"),
+            ExprTag::U16 => write!(f, "u16#"),
+            ExprTag::U32 => write!(f, "u32#"),
+            ExprTag::U128 => write!(f, "u128#"),
+            ExprTag::I64 => write!(f, "i64#"),
+            ExprTag::Vec => write!(f, "vec#"),
+            ExprTag::Map => write!(f, "map#"),
         }
     }
 }
@@ -86,7 +100,14 @@ impl TypePredicates for ExprTag {
             | Self::Str
             | Self::Char
             | Self::Comm
+            | Self::U8
+            | Self::U16
+            | Self::U32
             | Self::U64
+            | Self::U128
+            | Self::I64
+            | Self::Vec
+            | Self::Map
             | Self::Key
             | Self::Cproc => true,
         }
@@ -138,6 +159,15 @@ pub enum ContTag {
     Terminal,
     Emit,
     Cproc,
+    Fail,
+    Catch,
+    Unwind,
+    LetRecNext,
+    Rest,
+    Apply,
+    StringOp,
+    Vector,
+    VectorOp,
 }
 
 impl From<ContTag> for u16 {
@@ -189,6 +219,15 @@ impl fmt::Display for ContTag {
             ContTag::Terminal => write!(f, "terminal#"),
             ContTag::Emit => write!(f, "emit#"),
             ContTag::Cproc => write!(f, "cproc#"),
+            ContTag::Fail => write!(f, "fail#"),
+            ContTag::Catch => write!(f, "catch#"),
+            ContTag::Unwind => write!(f, "unwind#"),
+            ContTag::LetRecNext => write!(f, "letrec-next#"),
+            ContTag::Rest => write!(f, "rest#"),
+            ContTag::Apply => write!(f, "apply#"),
+            ContTag::StringOp => write!(f, "string-op#"),
+            ContTag::Vector => write!(f, "vector#"),
+            ContTag::VectorOp => write!(f, "vector-op#"),
         }
     }
 }
@@ -220,6 +259,17 @@ pub enum Op1 {
     Char,
     Eval,
     U64,
+    U8,
+    U16,
+    U32,
+    U128,
+    I64,
+    StringLength,
+    SymbolToString,
+    StringToSymbol,
+    NumToString,
+    VectorLength,
+    TypeOf,
 }
 
 impl From<Op1> for u16 {
@@ -278,6 +328,17 @@ impl Op for Op1 {
             Op1::Char => "char",
             Op1::Eval => "eval",
             Op1::U64 => "u64",
+            Op1::U8 => "u8",
+            Op1::U16 => "u16",
+            Op1::U32 => "u32",
+            Op1::U128 => "u128",
+            Op1::I64 => "i64",
+            Op1::StringLength => "string-length",
+            Op1::SymbolToString => "symbol->string",
+            Op1::StringToSymbol => "string->symbol",
+            Op1::NumToString => "num->string",
+            Op1::VectorLength => "vector-length",
+            Op1::TypeOf => "type-of",
         }
     }
 
@@ -295,6 +356,17 @@ impl Op for Op1 {
             &Op1::Char,
             &Op1::Eval,
             &Op1::U64,
+            &Op1::U8,
+            &Op1::U16,
+            &Op1::U32,
+            &Op1::U128,
+            &Op1::I64,
+            &Op1::StringLength,
+            &Op1::SymbolToString,
+            &Op1::StringToSymbol,
+            &Op1::NumToString,
+            &Op1::VectorLength,
+            &Op1::TypeOf,
         ]
     }
 
@@ -318,6 +390,17 @@ impl fmt::Display for Op1 {
             Op1::Char => write!(f, "char#"),
             Op1::Eval => write!(f, "eval#"),
             Op1::U64 => write!(f, "u64#"),
+            Op1::U8 => write!(f, "u8#"),
+            Op1::U16 => write!(f, "u16#"),
+            Op1::U32 => write!(f, "u32#"),
+            Op1::U128 => write!(f, "u128#"),
+            Op1::I64 => write!(f, "i64#"),
+            Op1::StringLength => write!(f, "string-length#"),
+            Op1::SymbolToString => write!(f, "symbol->string#"),
+            Op1::StringToSymbol => write!(f, "string->symbol#"),
+            Op1::NumToString => write!(f, "num->string#"),
+            Op1::VectorLength => write!(f, "vector-length#"),
+            Op1::TypeOf => write!(f, "type-of#"),
         }
     }
 }
@@ -353,6 +436,19 @@ pub enum Op2 {
     Hide,
     Modulo,
     Eval,
+    LogAnd,
+    LogOr,
+    LogXor,
+    Shl,
+    Shr,
+    Rotl,
+    Rotr,
+    Apply,
+    StringAppend,
+    Substring,
+    StringLess,
+    VectorRef,
+    VectorSet,
 }
 
 impl From<Op2> for u16 {
@@ -398,6 +494,13 @@ impl Op2 {
                 | Op2::GreaterEqual
                 | Op2::NumEqual
                 | Op2::Modulo
+                | Op2::LogAnd
+                | Op2::LogOr
+                | Op2::LogXor
+                | Op2::Shl
+                | Op2::Shr
+                | Op2::Rotl
+                | Op2::Rotr
         )
     }
 }
@@ -421,6 +524,19 @@ impl Op for Op2 {
             Op2::Hide => "hide",
             Op2::Modulo => "%",
             Op2::Eval => "eval",
+            Op2::LogAnd => "logand",
+            Op2::LogOr => "logor",
+            Op2::LogXor => "logxor",
+            Op2::Shl => "shl",
+            Op2::Shr => "shr",
+            Op2::Rotl => "rotl",
+            Op2::Rotr => "rotr",
+            Op2::Apply => "apply",
+            Op2::StringAppend => "string-append",
+            Op2::Substring => "substring",
+            Op2::StringLess => "string<",
+            Op2::VectorRef => "vector-ref",
+            Op2::VectorSet => "vector-set",
         }
     }
 
@@ -442,11 +558,29 @@ impl Op for Op2 {
             &Op2::Hide,
             &Op2::Modulo,
             &Op2::Eval,
+            &Op2::LogAnd,
+            &Op2::LogOr,
+            &Op2::LogXor,
+            &Op2::Shl,
+            &Op2::Shr,
+            &Op2::Rotl,
+            &Op2::Rotr,
+            &Op2::Apply,
+            &Op2::StringAppend,
+            &Op2::Substring,
+            &Op2::StringLess,
+            &Op2::VectorRef,
+            &Op2::VectorSet,
         ]
     }
 
     fn supports_arity(&self, n: usize) -> bool {
-        matches!((self, n), (Op2::Begin, _) | (Op2::Eval, 1 | 2) | (_, 2))
+        match self {
+            Op2::Begin => true,
+            Op2::Eval => matches!(n, 1 | 2),
+            Op2::Substring | Op2::VectorSet => n == 3,
+            _ => n == 2,
+        }
     }
 }
 
@@ -469,6 +603,19 @@ impl fmt::Display for Op2 {
             Op2::Hide => write!(f, "hide"),
             Op2::Modulo => write!(f, "modulo"),
             Op2::Eval => write!(f, "eval#"),
+            Op2::LogAnd => write!(f, "logand#"),
+            Op2::LogOr => write!(f, "logor#"),
+            Op2::LogXor => write!(f, "logxor#"),
+            Op2::Shl => write!(f, "shl#"),
+            Op2::Shr => write!(f, "shr#"),
+            Op2::Rotl => write!(f, "rotl#"),
+            Op2::Rotr => write!(f, "rotr#"),
+            Op2::Apply => write!(f, "apply#"),
+            Op2::StringAppend => write!(f, "string-append#"),
+            Op2::Substring => write!(f, "substring#"),
+            Op2::StringLess => write!(f, "string-less#"),
+            Op2::VectorRef => write!(f, "vector-ref#"),
+            Op2::VectorSet => write!(f, "vector-set#"),
         }
     }
 }
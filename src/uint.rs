@@ -7,21 +7,100 @@ use std::{
     fmt::Display,
     ops::{Add, Div, Mul, Rem, Sub},
 };
+use thiserror::Error;
+
+use crate::tag::ExprTag;
 
 /// Unsigned fixed-width integer type for Lurk.
+///
+/// Arithmetic is only defined between integers of the same width. The operators
+/// panic on mismatched widths, while the `checked_*` methods return a
+/// `UIntError`.
 #[derive(Debug, Copy, Clone, PartialEq, PartialOrd, Eq, Serialize, Deserialize)]
 #[cfg_attr(not(target_arch = "wasm32"), derive(Arbitrary))]
 #[cfg_attr(not(target_arch = "wasm32"), serde_test)]
 pub enum UInt {
+    U8(u8),
+    U16(u16),
+    U32(u32),
     U64(u64),
+    U128(u128),
 }
 
 impl UInt {
     pub fn is_zero(&self) -> bool {
         match self {
+            UInt::U8(n) => *n == 0,
+            UInt::U16(n) => *n == 0,
+            UInt::U32(n) => *n == 0,
             UInt::U64(n) => *n == 0,
+            UInt::U128(n) => *n == 0,
+        }
+    }
+
+    /// The number of bits of the integer type
+    pub const fn bits(&self) -> u32 {
+        match self {
+            UInt::U8(_) => 8,
+            UInt::U16(_) => 16,
+            UInt::U32(_) => 32,
+            UInt::U64(_) => 64,
+            UInt::U128(_) => 128,
+        }
+    }
+
+    /// The suffix used to write literals of the integer type: `u8`, `u16` etc
+    pub const fn suffix(&self) -> &'static str {
+        match self {
+            UInt::U8(_) => "u8",
+            UInt::U16(_) => "u16",
+            UInt::U32(_) => "u32",
+            UInt::U64(_) => "u64",
+            UInt::U128(_) => "u128",
+        }
+    }
+
+    /// The expression tag of the integer type
+    pub const fn tag(&self) -> ExprTag {
+        match self {
+            UInt::U8(_) => ExprTag::U8,
+            UInt::U16(_) => ExprTag::U16,
+            UInt::U32(_) => ExprTag::U32,
+            UInt::U64(_) => ExprTag::U64,
+            UInt::U128(_) => ExprTag::U128,
         }
     }
+
+    /// Builds the integer of the type tagged by `tag`. Returns `None` if `tag`
+    /// isn't the tag of an integer type or if `n` doesn't fit in it.
+    pub fn from_tag(tag: ExprTag, n: u128) -> Option<Self> {
+        match tag {
+            ExprTag::U8 => n.try_into().ok().map(UInt::U8),
+            ExprTag::U16 => n.try_into().ok().map(UInt::U16),
+            ExprTag::U32 => n.try_into().ok().map(UInt::U32),
+            ExprTag::U64 => n.try_into().ok().map(UInt::U64),
+            ExprTag::U128 => Some(UInt::U128(n)),
+            _ => None,
+        }
+    }
+}
+
+impl From<u8> for UInt {
+    fn from(n: u8) -> Self {
+        Self::U8(n)
+    }
+}
+
+impl From<u16> for UInt {
+    fn from(n: u16) -> Self {
+        Self::U16(n)
+    }
+}
+
+impl From<u32> for UInt {
+    fn from(n: u32) -> Self {
+        Self::U32(n)
+    }
 }
 
 impl From<u64> for UInt {
@@ -30,10 +109,20 @@ impl From<u64> for UInt {
     }
 }
 
-impl From<UInt> for u64 {
-    fn from(u: UInt) -> u64 {
+impl From<u128> for UInt {
+    fn from(n: u128) -> Self {
+        Self::U128(n)
+    }
+}
+
+impl From<UInt> for u128 {
+    fn from(u: UInt) -> u128 {
         match u {
-            UInt::U64(n) => n,
+            UInt::U8(n) => n.into(),
+            UInt::U16(n) => n.into(),
+            UInt::U32(n) => n.into(),
+            UInt::U64(n) => n.into(),
+            UInt::U128(n) => n,
         }
     }
 }
@@ -41,49 +130,92 @@ impl From<UInt> for u64 {
 impl Display for UInt {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
+            UInt::U8(n) => write!(f, "{n}"),
+            UInt::U16(n) => write!(f, "{n}"),
+            UInt::U32(n) => write!(f, "{n}"),
             UInt::U64(n) => write!(f, "{n}"),
+            UInt::U128(n) => write!(f, "{n}"),
         }
     }
 }
 
+#[derive(Error, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UIntError {
+    #[error("mismatched integer types {0} and {1}")]
+    MismatchedTypes(&'static str, &'static str),
+}
+
+macro_rules! uint_binop {
+    ($self:ident, $other:ident, $a:ident, $b:ident, $e:expr) => {
+        match ($self, $other) {
+            (UInt::U8($a), UInt::U8($b)) => Ok(UInt::U8($e)),
+            (UInt::U16($a), UInt::U16($b)) => Ok(UInt::U16($e)),
+            (UInt::U32($a), UInt::U32($b)) => Ok(UInt::U32($e)),
+            (UInt::U64($a), UInt::U64($b)) => Ok(UInt::U64($e)),
+            (UInt::U128($a), UInt::U128($b)) => Ok(UInt::U128($e)),
+            (a, b) => Err(UIntError::MismatchedTypes(a.suffix(), b.suffix())),
+        }
+    };
+}
+
+impl UInt {
+    /// Wrapping addition. Returns an error if the widths of `self` and `other`
+    /// differ
+    pub fn checked_add(self, other: Self) -> Result<Self, UIntError> {
+        uint_binop!(self, other, a, b, a.wrapping_add(b))
+    }
+
+    /// Wrapping subtraction. Returns an error if the widths of `self` and
+    /// `other` differ
+    pub fn checked_sub(self, other: Self) -> Result<Self, UIntError> {
+        uint_binop!(self, other, a, b, a.wrapping_sub(b))
+    }
+
+    /// Wrapping multiplication. Returns an error if the widths of `self` and
+    /// `other` differ
+    pub fn checked_mul(self, other: Self) -> Result<Self, UIntError> {
+        uint_binop!(self, other, a, b, a.wrapping_mul(b))
+    }
+
+    /// Division. Returns an error if the widths of `self` and `other` differ
+    pub fn checked_div(self, other: Self) -> Result<Self, UIntError> {
+        uint_binop!(self, other, a, b, a / b)
+    }
+
+    /// Remainder. Returns an error if the widths of `self` and `other` differ
+    pub fn checked_rem(self, other: Self) -> Result<Self, UIntError> {
+        uint_binop!(self, other, a, b, a % b)
+    }
+}
+
 impl Add for UInt {
     type Output = Self;
     fn add(self, other: Self) -> Self {
-        match (self, other) {
-            (UInt::U64(a), UInt::U64(b)) => UInt::U64(a.wrapping_add(b)),
-        }
+        self.checked_add(other).unwrap_or_else(|e| panic!("{e}"))
     }
 }
 
 impl Sub for UInt {
     type Output = Self;
     fn sub(self, other: Self) -> Self {
-        match (self, other) {
-            (UInt::U64(a), UInt::U64(b)) => UInt::U64(a.wrapping_sub(b)),
-        }
+        self.checked_sub(other).unwrap_or_else(|e| panic!("{e}"))
     }
 }
 impl Div for UInt {
     type Output = Self;
     fn div(self, other: Self) -> Self {
-        match (self, other) {
-            (UInt::U64(a), UInt::U64(b)) => UInt::U64(a / b),
-        }
+        self.checked_div(other).unwrap_or_else(|e| panic!("{e}"))
     }
 }
 impl Mul for UInt {
     type Output = Self;
     fn mul(self, other: Self) -> Self {
-        match (self, other) {
-            (UInt::U64(a), UInt::U64(b)) => UInt::U64(a.wrapping_mul(b)),
-        }
+        self.checked_mul(other).unwrap_or_else(|e| panic!("{e}"))
     }
 }
 impl Rem for UInt {
     type Output = Self;
     fn rem(self, other: Self) -> Self {
-        match (self, other) {
-            (UInt::U64(a), UInt::U64(b)) => UInt::U64(a % b),
-        }
+        self.checked_rem(other).unwrap_or_else(|e| panic!("{e}"))
     }
 }
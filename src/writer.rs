@@ -3,6 +3,7 @@ use crate::expr::Expression;
 use crate::field::LurkField;
 use crate::lurk_sym_ptr;
 use crate::package::SymbolRef;
+use crate::parser::string::{escape_char, escape_string};
 use crate::ptr::{ContPtr, Ptr};
 use crate::state::initial_lurk_state;
 use crate::state::State;
@@ -90,7 +91,7 @@ impl<F: LurkField> Write<F> for Expression<F> {
             Str(car, cdr) => {
                 let head = store.fetch_char(car).expect("missing string head");
                 let tail = store.fetch_string(cdr).expect("missing string tail");
-                write!(w, "\"{head}{tail}\"")
+                write!(w, "\"{}\"", escape_string(&format!("{head}{tail}")))
             }
             Fun(arg, body, _closed_env) => {
                 let is_zero_arg = *arg == lurk_sym_ptr!(store, dummy);
@@ -138,9 +139,9 @@ impl<F: LurkField> Write<F> for Expression<F> {
                 write!(w, ")")
             }
             Char(c) => {
-                write!(w, "'{c}'")
+                write!(w, "'{}'", escape_char(*c))
             }
-            UInt(n) => write!(w, "{n}u64"),
+            UInt(n) => write!(w, "{n}{}", n.suffix()),
         }
     }
 }
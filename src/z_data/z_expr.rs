@@ -49,6 +49,13 @@ pub enum ZExpr<F: LurkField> {
     Thunk(ZExprPtr<F>, ZContPtr<F>),
     Char(char),
     UInt(UInt),
+    /// A vector with its length, the number of leaves of its tree and the
+    /// root of the tree
+    Vec {
+        len: ZExprPtr<F>,
+        span: ZExprPtr<F>,
+        root: ZExprPtr<F>,
+    },
 }
 
 impl<F: LurkField> std::fmt::Display for ZExpr<F> {
@@ -74,6 +81,7 @@ impl<F: LurkField> std::fmt::Display for ZExpr<F> {
             ZExpr::Char(x) => write!(f, "(char {x})"),
             ZExpr::Num(x) => write!(f, "(num  {x:?})"),
             ZExpr::UInt(x) => write!(f, "(uint {x})"),
+            ZExpr::Vec { len, span, root } => write!(f, "(vec {len} {span} {root})"),
         }
     }
 }
@@ -126,8 +134,23 @@ impl<F: LurkField> ZExpr<F> {
             ),
             ZExpr::Char(f) => ZPtr(ExprTag::Char, F::from_char(*f)),
             ZExpr::UInt(x) => match x {
+                UInt::U8(x) => ZPtr(ExprTag::U8, F::from_u64((*x).into())),
+                UInt::U16(x) => ZPtr(ExprTag::U16, F::from_u16(*x)),
+                UInt::U32(x) => ZPtr(ExprTag::U32, F::from_u32(*x)),
                 UInt::U64(x) => ZPtr(ExprTag::U64, F::from_u64(*x)),
+                UInt::U128(x) => ZPtr(ExprTag::U128, F::from_u128(*x)),
             },
+            ZExpr::Vec { len, span, root } => ZPtr(
+                ExprTag::Vec,
+                cache.hash6(&[
+                    len.0.to_field(),
+                    len.1,
+                    span.0.to_field(),
+                    span.1,
+                    root.0.to_field(),
+                    root.1,
+                ]),
+            ),
         }
     }
 
@@ -164,14 +187,18 @@ impl<F: LurkField> ZExpr<F> {
                 Some(ZExpr::Str(store.hash_expr(&tag)?, store.hash_expr(&val)?))
             }),
             ExprTag::Char => store.fetch_char(ptr).map(ZExpr::Char),
-            ExprTag::U64 => store.fetch_uint(ptr).map(ZExpr::UInt),
+            ExprTag::U8 | ExprTag::U16 | ExprTag::U32 | ExprTag::U64 | ExprTag::U128 => {
+                store.fetch_uint(ptr).map(ZExpr::UInt)
+            }
             ExprTag::Thunk => store.fetch_thunk(ptr).and_then(|thunk| {
                 Some(ZExpr::Thunk(
                     store.hash_expr(&thunk.value)?,
                     store.hash_cont(&thunk.continuation)?,
                 ))
             }),
-            ExprTag::Cproc => unreachable!("Lurk Alpha doesn't produce such expressions"),
+            ExprTag::Cproc | ExprTag::I64 | ExprTag::Vec | ExprTag::Map => {
+                unreachable!("Lurk Alpha doesn't produce such expressions")
+            }
         }
     }
 }
@@ -202,6 +229,8 @@ impl<F: LurkField> Arbitrary for ZExpr<F> {
             any::<(ZExprPtr<F>, ZContPtr<F>)>().prop_map(|(x, y)| ZExpr::Thunk(x, y)),
             any::<char>().prop_map(|x| Self::Char(x)),
             any::<u64>().prop_map(|x| Self::UInt(UInt::U64(x))),
+            any::<(ZExprPtr<F>, ZExprPtr<F>, ZExprPtr<F>)>()
+                .prop_map(|(len, span, root)| ZExpr::Vec { len, span, root }),
         ]
         .boxed()
     }
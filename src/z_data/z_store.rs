@@ -68,9 +68,12 @@ impl<F: LurkField> ZStore<F> {
     /// we can infer that this pointer refers to a `ZExpr::UInt(UInt::U64(0u64)))` and return it.
     pub fn immediate_z_expr(ptr: &ZExprPtr<F>) -> Option<ZExpr<F>> {
         match ptr {
-            ZPtr(ExprTag::U64, val) => {
-                let x = F::to_u64(val)?;
-                Some(ZExpr::UInt(UInt::U64(x)))
+            ZPtr(
+                tag @ (ExprTag::U8 | ExprTag::U16 | ExprTag::U32 | ExprTag::U64 | ExprTag::U128),
+                val,
+            ) => {
+                let x = UInt::from_tag(*tag, F::to_u128(val)?)?;
+                Some(ZExpr::UInt(x))
             }
             ZPtr(ExprTag::Char, val) => {
                 let x = F::to_char(val)?;
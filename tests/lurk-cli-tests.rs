@@ -1,5 +1,5 @@
 use assert_cmd::prelude::*;
-use camino::Utf8Path;
+use camino::{Utf8Path, Utf8PathBuf};
 use std::fs::File;
 use std::io::prelude::*;
 use std::process::Command;
@@ -71,6 +71,181 @@ fn test_prove_and_verify() {
     cmd.assert().success();
 }
 
+#[test]
+fn test_prove_and_verify_supernova() {
+    let tmp_dir = Builder::new().prefix("tmp").tempdir().unwrap();
+    let tmp_dir = Utf8Path::from_path(tmp_dir.path()).unwrap();
+    let public_param_dir = tmp_dir.join("public_params");
+    let proof_dir = tmp_dir.join("proofs");
+    let commit_dir = tmp_dir.join("commits");
+    let lurk_file = tmp_dir.join("prove_verify.lurk");
+
+    let mut file = File::create(lurk_file.clone()).unwrap();
+    file.write_all(b"!(prove (+ 1 1))\n").unwrap();
+    file.write_all(b"!(verify \"SuperNova_Pallas_10_3f2526abf20fc9006dd93c0d3ff49954ef070ef52d2e88426974de42cc27bdb2\")\n").unwrap();
+
+    let mut cmd = lurk_cmd();
+    cmd.env("LURK_PERF", "max-parallel-simple");
+    cmd.arg("load");
+    cmd.arg(lurk_file.into_string());
+    cmd.arg("--backend");
+    cmd.arg("supernova");
+    cmd.arg("--public-params-dir");
+    cmd.arg(public_param_dir);
+    cmd.arg("--proofs-dir");
+    cmd.arg(proof_dir);
+    cmd.arg("--commits-dir");
+    cmd.arg(commit_dir);
+
+    cmd.assert().success();
+}
+
+#[test]
+fn test_prove_and_verify_bn256() {
+    let tmp_dir = Builder::new().prefix("tmp").tempdir().unwrap();
+    let tmp_dir = Utf8Path::from_path(tmp_dir.path()).unwrap();
+    let public_param_dir = tmp_dir.join("public_params");
+    let proof_dir = tmp_dir.join("proofs");
+    let commit_dir = tmp_dir.join("commits");
+    let lurk_file = tmp_dir.join("prove.lurk");
+
+    let mut file = File::create(lurk_file.clone()).unwrap();
+    file.write_all(b"!(prove (+ 1 1))\n").unwrap();
+
+    let mut cmd = lurk_cmd();
+    cmd.env("LURK_PERF", "max-parallel-simple");
+    cmd.arg("load");
+    cmd.arg(lurk_file.into_string());
+    cmd.arg("--field");
+    cmd.arg("bn256");
+    cmd.arg("--public-params-dir");
+    cmd.arg(&public_param_dir);
+    cmd.arg("--proofs-dir");
+    cmd.arg(&proof_dir);
+    cmd.arg("--commits-dir");
+    cmd.arg(commit_dir);
+    cmd.assert().success();
+
+    // the proof key depends on the field, so we look it up on the proofs dir
+    let proof_key = std::fs::read_dir(&proof_dir)
+        .unwrap()
+        .map(|entry| Utf8PathBuf::from_path_buf(entry.unwrap().path()).unwrap())
+        .find(|path| path.extension() == Some("proof"))
+        .unwrap()
+        .file_stem()
+        .unwrap()
+        .to_owned();
+    assert!(proof_key.starts_with("Nova_BN256_10_"));
+
+    let mut cmd = lurk_cmd();
+    cmd.arg("verify");
+    cmd.arg(proof_key);
+    cmd.arg("--public-params-dir");
+    cmd.arg(public_param_dir);
+    cmd.arg("--proofs-dir");
+    cmd.arg(proof_dir);
+    cmd.assert().success();
+}
+
+#[test]
+fn test_defmacro() {
+    let tmp_dir = Builder::new().prefix("tmp").tempdir().unwrap();
+    let tmp_dir = Utf8Path::from_path(tmp_dir.path()).unwrap();
+    let lurk_file = tmp_dir.join("defmacro.lurk");
+
+    let mut file = File::create(lurk_file.clone()).unwrap();
+    file.write_all(b"!(defmacro when (c x) `(if ,c ,x nil))\n")
+        .unwrap();
+    file.write_all(b"!(defmacro unless (c x) `(when (eq ,c nil) ,x))\n")
+        .unwrap();
+    file.write_all(b"!(assert-eq 42 (when (= 1 1) 42))\n")
+        .unwrap();
+    file.write_all(b"!(assert-eq nil (unless t 42))\n").unwrap();
+    file.write_all(b"!(assert-eq '(when t 1) '(when t 1))\n")
+        .unwrap();
+    file.write_all(b"(let ((f (lambda (x) (when x (+ x 1))))) (f 1))\n")
+        .unwrap();
+    // bound variables shadow macros and binding lists aren't expanded
+    file.write_all(b"!(assert-eq 3 (let ((when (lambda (c x) (+ c x)))) (when 1 2)))\n")
+        .unwrap();
+    file.write_all(b"!(assert-eq 3 ((lambda (when) (when 1 2)) +))\n")
+        .unwrap();
+    file.write_all(b"!(assert-eq 1 (let ((x 1) (unless 2)) x))\n")
+        .unwrap();
+    // definitions keep their names and parameters
+    file.write_all(b"!(defmacro when (c x) `(if ,c ,x 0))\n")
+        .unwrap();
+    file.write_all(b"!(assert-eq 0 (when nil 42))\n").unwrap();
+    file.write_all(b"!(defmacro swap (when unless) `(cons ,unless ,when))\n")
+        .unwrap();
+    file.write_all(b"!(assert-eq '(2 . 1) (swap 1 2))\n")
+        .unwrap();
+    // definitions replace macros of the same name
+    file.write_all(b"!(def when 1)\n").unwrap();
+    file.write_all(b"!(assert-eq 1 when)\n").unwrap();
+    file.write_all(b"!(defmacro twice (x) `(+ ,x ,x))\n")
+        .unwrap();
+    file.write_all(b"!(assert-eq 4 (twice 2))\n").unwrap();
+    file.write_all(b"!(def twice (lambda (x) (* x 3)))\n")
+        .unwrap();
+    file.write_all(b"!(assert-eq 6 (twice 2))\n").unwrap();
+    file.write_all(b"!(defmacro sum (xs) 0)\n").unwrap();
+    file.write_all(b"!(defrec sum (lambda (xs) (if (eq xs nil) 0 (+ (car xs) (sum (cdr xs))))))\n")
+        .unwrap();
+    file.write_all(b"!(assert-eq 6 (sum '(1 2 3)))\n").unwrap();
+    // the arguments of `call` are expanded, including the expression of the commitment
+    file.write_all(b"!(defmacro id (x) x)\n").unwrap();
+    file.write_all(b"!(call ((id (commit (lambda (x) (+ x 1))))) (id 1))\n")
+        .unwrap();
+
+    let mut cmd = lurk_cmd();
+    cmd.arg("load");
+    cmd.arg(lurk_file.into_string());
+    cmd.assert().success();
+}
+
+#[test]
+fn test_defrec_mutual_recursion() {
+    let tmp_dir = Builder::new().prefix("tmp").tempdir().unwrap();
+    let tmp_dir = Utf8Path::from_path(tmp_dir.path()).unwrap();
+    let lurk_file = tmp_dir.join("defrec.lurk");
+
+    let mut file = File::create(lurk_file.clone()).unwrap();
+    file.write_all(b"!(defrec (even? (lambda (n) (if (= n 0) t (odd? (- n 1)))))\n")
+        .unwrap();
+    file.write_all(b"         (odd? (lambda (n) (if (= n 0) nil (even? (- n 1))))))\n")
+        .unwrap();
+    file.write_all(b"!(assert-eq t (even? 10))\n").unwrap();
+    file.write_all(b"!(assert-eq nil (odd? 10))\n").unwrap();
+
+    let mut cmd = lurk_cmd();
+    cmd.arg("load");
+    cmd.arg(lurk_file.into_string());
+    cmd.assert().success();
+}
+
+#[test]
+fn test_map_functions() {
+    let tmp_dir = Builder::new().prefix("tmp").tempdir().unwrap();
+    let tmp_dir = Utf8Path::from_path(tmp_dir.path()).unwrap();
+    let lurk_file = tmp_dir.join("map.lurk");
+
+    let mut file = File::create(lurk_file.clone()).unwrap();
+    file.write_all(b"!(def m (.lurk.map.assoc {1 2} 3 4))\n")
+        .unwrap();
+    file.write_all(b"!(assert-eq 2 (.lurk.map.get m 1))\n")
+        .unwrap();
+    file.write_all(b"!(assert-eq 4 (.lurk.map.get m 3))\n")
+        .unwrap();
+    file.write_all(b"!(assert-eq nil (.lurk.map.get m 5))\n")
+        .unwrap();
+
+    let mut cmd = lurk_cmd();
+    cmd.arg("load");
+    cmd.arg(lurk_file.into_string());
+    cmd.assert().success();
+}
+
 #[test]
 fn test_repl_panic() {
     let tmp_dir = Builder::new().prefix("tmp").tempdir().unwrap();
@@ -86,3 +261,82 @@ fn test_repl_panic() {
     cmd.arg(lurk_file.into_string());
     cmd.assert().failure();
 }
+
+#[test]
+fn test_profile_command() {
+    let mut cmd = lurk_cmd();
+    cmd.arg("profile");
+    cmd.arg("--depth");
+    cmd.arg("1");
+    let output = cmd.output().unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("constraints:"));
+    assert!(stdout.contains("step"));
+}
+
+#[test]
+fn test_profile_lem_file() {
+    let tmp_dir = Builder::new().prefix("tmp").tempdir().unwrap();
+    let tmp_dir = Utf8Path::from_path(tmp_dir.path()).unwrap();
+    let lem_file = tmp_dir.join("pair.lem");
+
+    let mut file = File::create(lem_file.clone()).unwrap();
+    file.write_all(b"fn pair(x): 1 {\n").unwrap();
+    file.write_all(b"    let y: Expr::Cons = cons2(x, x);\n")
+        .unwrap();
+    file.write_all(b"    return (y)\n").unwrap();
+    file.write_all(b"}\n").unwrap();
+
+    for field in ["pallas", "vesta", "bn256"] {
+        let mut cmd = lurk_cmd();
+        cmd.arg("profile");
+        cmd.arg(lem_file.as_str());
+        cmd.arg("--field");
+        cmd.arg(field);
+        cmd.arg("--json");
+        let output = cmd.output().unwrap();
+        assert!(output.status.success());
+        let stdout = String::from_utf8(output.stdout).unwrap();
+        assert!(stdout.contains("\"total\""));
+    }
+
+    let mut cmd = lurk_cmd();
+    cmd.arg("profile");
+    cmd.arg(lem_file.into_string());
+    cmd.arg("--field");
+    cmd.arg("grumpkin");
+    cmd.assert().failure();
+}
+
+#[test]
+fn test_load_with_coverage() {
+    let tmp_dir = Builder::new().prefix("tmp").tempdir().unwrap();
+    let tmp_dir = Utf8Path::from_path(tmp_dir.path()).unwrap();
+    let lurk_file = tmp_dir.join("coverage.lurk");
+
+    let mut file = File::create(lurk_file.clone()).unwrap();
+    file.write_all(b"(+ 1 1)\n").unwrap();
+    file.write_all(b"(let ((x 1)) (if (= x 1) :yes :no))\n")
+        .unwrap();
+
+    let mut cmd = lurk_cmd();
+    cmd.arg("load");
+    cmd.arg(lurk_file.as_str());
+    cmd.arg("--coverage");
+    let output = cmd.output().unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(stdout.contains("Coverage: "));
+    assert!(stdout.contains("branches of `step` taken"));
+    assert!(stdout.contains("never taken: "));
+
+    // without the flag, no report is printed
+    let mut cmd = lurk_cmd();
+    cmd.arg("load");
+    cmd.arg(lurk_file.into_string());
+    let output = cmd.output().unwrap();
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert!(!stdout.contains("Coverage: "));
+}